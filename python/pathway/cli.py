@@ -1,5 +1,6 @@
 # Copyright © 2024 Pathway
 
+import json
 import logging
 import os
 import pathlib
@@ -290,6 +291,212 @@ def spawn_from_env():
         logging.warning("PATHWAY_SPAWN_ARGS variable is unspecified, exiting...")
 
 
+def persistence_backend(
+    backend,
+    path,
+    connection_string,
+    bucket,
+    region,
+    endpoint,
+    access_key,
+    secret_access_key,
+) -> pw.persistence.Backend:
+    if backend in ("azure", "postgres") and connection_string is None:
+        raise click.UsageError(f"--connection-string is required for {backend}")
+    if backend in ("s3", "gcs") and bucket is None:
+        raise click.UsageError(f"--bucket is required for {backend}")
+    if backend in ("fs", "sqlite") and not path:
+        raise click.UsageError(f"--path is required for {backend}")
+
+    if backend == "fs":
+        if not os.path.isdir(path):
+            raise click.BadParameter(f"{path} is not a directory", param_hint="--path")
+        return pw.persistence.Backend.filesystem(path)
+    if backend == "sqlite":
+        if not os.path.isfile(path):
+            raise click.BadParameter(f"{path} is not a file", param_hint="--path")
+        return pw.persistence.Backend.sqlite(path)
+    if backend == "azure":
+        return pw.persistence.Backend.azure(connection_string, root_path=path)
+    if backend == "postgres":
+        return pw.persistence.Backend.postgres(connection_string, root_path=path)
+    bucket_settings = pw.io.s3.AwsS3Settings(
+        bucket_name=bucket,
+        access_key=access_key,
+        secret_access_key=secret_access_key,
+        region=region,
+        endpoint=endpoint,
+    )
+    if backend == "gcs":
+        return pw.persistence.Backend.gcs(path, bucket_settings)
+    return pw.persistence.Backend.s3(path, bucket_settings)
+
+
+@cli.group(context_settings={"show_default": True})
+@click.option(
+    "--backend",
+    type=click.Choice(
+        ["fs", "s3", "gcs", "azure", "sqlite", "postgres"], case_sensitive=False
+    ),
+    default="fs",
+    help="persistence backend",
+)
+@click.option(
+    "--path",
+    type=str,
+    default="",
+    help="root directory of the filesystem backend, path to the SQLite database or "
+    "root path in the bucket, the container or the Postgres table",
+)
+@click.option(
+    "--connection-string",
+    type=str,
+    envvar="PATHWAY_PERSISTENCE_CONNECTION_STRING",
+    default=None,
+    help="container URL with a SAS token for Azure, connection string for Postgres",
+)
+@click.option("--bucket", type=str, default=None, help="bucket for S3 and GCS")
+@click.option("--region", type=str, default=None, help="region of the bucket")
+@click.option(
+    "--endpoint", type=str, default=None, help="endpoint of the S3-compatible storage"
+)
+@click.option(
+    "--access-key",
+    type=str,
+    envvar="AWS_ACCESS_KEY_ID",
+    default=None,
+    help="access key for the bucket",
+)
+@click.option(
+    "--secret-access-key",
+    type=str,
+    envvar="AWS_SECRET_ACCESS_KEY",
+    default=None,
+    help="secret access key for the bucket",
+)
+@click.option(
+    "--encryption-key-file",
+    type=click.Path(exists=True, dir_okay=False),
+    default=None,
+    help="file with the keys the persisted state is encrypted with",
+)
+@click.option(
+    "--force",
+    is_flag=True,
+    help="modify the state even if it is locked, e.g. by a program that has crashed",
+)
+@click.pass_context
+def persistence(
+    ctx: click.Context,
+    backend,
+    path,
+    connection_string,
+    bucket,
+    region,
+    endpoint,
+    access_key,
+    secret_access_key,
+    encryption_key_file,
+    force,
+) -> None:
+    """Inspect and repair the persisted state offline"""
+    ctx.obj = pw.persistence.Inspector(
+        persistence_backend(
+            backend.lower(),
+            path,
+            connection_string,
+            bucket,
+            region,
+            endpoint,
+            access_key,
+            secret_access_key,
+        ),
+        encryption_key_file=encryption_key_file,
+        force=force,
+    )
+
+
+internal_id_option = click.option(
+    "--internal-id",
+    is_flag=True,
+    help="the persistent id is the internal id, as listed by the streams command, "
+    "instead of the name given to the data source",
+)
+
+
+def parse_persistent_id(persistent_id: str, internal_id: bool) -> str | int:
+    if not internal_id:
+        return persistent_id
+    try:
+        return int(persistent_id)
+    except ValueError:
+        raise click.BadParameter(
+            f"{persistent_id} is not an internal id", param_hint="PERSISTENT_ID"
+        ) from None
+
+
+@persistence.command()
+@click.pass_obj
+def streams(inspector) -> None:
+    """List the snapshot streams as persistent id and worker id pairs"""
+    for persistent_id, worker_id in inspector.streams():
+        click.echo(f"{persistent_id}\t{worker_id}")
+
+
+@persistence.command()
+@click.pass_obj
+def metadata(inspector) -> None:
+    """Show the latest metadata blocks and the finalized times of the workers"""
+    click.echo(json.dumps(inspector.metadata(), indent=2))
+
+
+@persistence.command()
+@click.argument("persistent_id")
+@click.argument("worker_id", type=int)
+@internal_id_option
+@click.pass_obj
+def dump(inspector, persistent_id, worker_id, internal_id) -> None:
+    """Dump the events of a snapshot stream as JSON lines"""
+    for event in inspector.events(
+        parse_persistent_id(persistent_id, internal_id), worker_id
+    ):
+        click.echo(json.dumps(event))
+
+
+@persistence.command()
+@click.argument("persistent_id")
+@click.argument("time", type=int)
+@click.option(
+    "--worker-id",
+    type=int,
+    default=None,
+    help="truncate only the stream of this worker",
+)
+@internal_id_option
+@click.pass_obj
+def truncate(inspector, persistent_id, time, worker_id, internal_id) -> None:
+    """Roll the snapshot streams and the finalized times back to the given time"""
+    truncated = inspector.truncate(
+        parse_persistent_id(persistent_id, internal_id), time, worker_id
+    )
+    for worker_id, n_events_left in truncated.items():
+        click.echo(
+            f"worker {worker_id}: {plural(n_events_left, 'event', 'events')} left"
+        )
+
+
+@persistence.command()
+@click.pass_obj
+def migrate(inspector) -> None:
+    """Rewrite the snapshot chunks of older versions in the current format"""
+    migrated = inspector.migrate()
+    for (persistent_id, worker_id), n_migrated in migrated.items():
+        click.echo(
+            f"{persistent_id} (worker {worker_id}): "
+            f"{plural(n_migrated, 'chunk', 'chunks')} migrated"
+        )
+
+
 @cli.group()
 def airbyte() -> None:
     pass
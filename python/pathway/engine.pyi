@@ -6,6 +6,7 @@ from __future__ import annotations
 
 import asyncio
 import dataclasses
+import os
 from collections.abc import Callable, Iterable
 from enum import Enum
 from typing import Any, Generic, TypeVar, Union, final
@@ -92,11 +93,21 @@ class TableProperties:
         column_properties: Iterable[tuple[ColumnPath, ColumnProperties]]
     ) -> TableProperties: ...
 
+@dataclasses.dataclass(frozen=True)
+class ErrorBudget:
+    max_error_rate: float
+    window_ms: int = 60_000
+    min_entries: int = 100
+    action: str = "fail"
+    initial_backoff_ms: int = 1_000
+    max_backoff_ms: int = 60_000
+
 @dataclasses.dataclass(frozen=True)
 class ConnectorProperties:
     commit_duration_ms: int | None = None
     unsafe_trusted_ids: bool | None = False
     column_properties: list[ColumnProperties] = []
+    error_budget: ErrorBudget | None = None
 
 class Column:
     """A Column holds data and conceptually is a Dict[Universe elems, dt]
@@ -175,6 +186,30 @@ class Reducer:
     def stateful_many(combine_many: CombineMany[S]) -> Reducer: ...
     EARLIEST: Reducer
     LATEST: Reducer
+    MEAN: Reducer
+    @staticmethod
+    def variance(sample: bool) -> Reducer: ...
+    @staticmethod
+    def stddev(sample: bool) -> Reducer: ...
+    COUNT_DISTINCT: Reducer
+    @staticmethod
+    def count_distinct_approx(precision: int) -> Reducer: ...
+    @staticmethod
+    def quantile(quantile: float) -> Reducer: ...
+    @staticmethod
+    def quantiles(quantiles: list[float]) -> Reducer: ...
+    @staticmethod
+    def top_k(
+        k: int, descending: bool = False, order_column: int | None = None
+    ) -> Reducer: ...
+
+class Window:
+    @staticmethod
+    def tumbling(duration: Any, origin: Any | None = None) -> Window: ...
+    @staticmethod
+    def sliding(hop: Any, duration: Any, origin: Any | None = None) -> Window: ...
+    @staticmethod
+    def session(max_gap: Any) -> Window: ...
 
 @dataclasses.dataclass
 class ReducerData:
@@ -514,6 +549,18 @@ class Scope:
         mark_forgetting_records: bool,
         table_properties: TableProperties,
     ) -> Table: ...
+    def window_table(
+        self,
+        table: Table,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        table_properties: TableProperties,
+        *,
+        delay: Any | None = None,
+        cutoff: Any | None = None,
+        keep_results: bool = True,
+    ) -> Table: ...
     def forget_immediately(
         self,
         table: Table,
@@ -611,6 +658,14 @@ class Scope:
         assign_id: bool = False,
         left_ear: bool = False,
         right_ear: bool = False,
+        semi: bool = False,
+        anti: bool = False,
+        left_time_column_path: ColumnPath | None = None,
+        right_time_column_path: ColumnPath | None = None,
+        asof_direction: str | None = None,
+        tolerance: Any | None = None,
+        interval: tuple[Any, Any] | None = None,
+        cutoff: Any | None = None,
     ) -> Table: ...
     def use_external_index_as_of_now(
         self,
@@ -652,7 +707,19 @@ class Scope:
         instance_column_path: ColumnPath,
         table_properties: TableProperties,
     ) -> Table: ...
+    def top_n_table(
+        self,
+        table: Table,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: int,
+        table_properties: TableProperties,
+        *,
+        offset: int = 0,
+        descending: bool = False,
+    ) -> Table: ...
     def probe_table(self, table: Table, operator_id: int): ...
+    def arrangement_stats(self) -> ArrangementStatsRegistry: ...
     def subscribe_table(
         self,
         table: Table,
@@ -676,7 +743,9 @@ class Scope:
     def import_table(self, table: ExportedTable) -> Table: ...
     def error_log(self, properties: ConnectorProperties) -> tuple[Table, ErrorLog]: ...
     def set_error_log(self, error_log: ErrorLog | None) -> None: ...
-    def set_operator_properties(self, id: int, depends_on_error_log: bool) -> None: ...
+    def set_operator_properties(
+        self, id: int, depends_on_error_log: bool, trace: Trace | None = None
+    ) -> None: ...
     def remove_errors_from_table(
         self,
         table: Table,
@@ -708,6 +777,23 @@ class ExportedTable:
     def frontier(self) -> Frontier: ...
     def snapshot_at(self, frontier: Frontier) -> list[tuple[Pointer, list[Value]]]: ...
 
+class ArrangementStats:
+    num_batches: int
+    num_records: int
+    approximate_bytes: int
+
+class OperatorArrangementStats:
+    operator_id: int | None
+    trace: Trace | None
+    num_arrangements: int
+    stats: ArrangementStats
+
+class ArrangementStatsRegistry:
+    def per_worker(self) -> dict[int, ArrangementStats]: ...
+    def per_operator(
+        self, limit: int | None = None
+    ) -> list[OperatorArrangementStats]: ...
+
 def run_with_new_graph(
     logic: Callable[[Scope], Iterable[tuple[Table, list[ColumnPath]]]],
     event_loop: asyncio.AbstractEventLoop,
@@ -716,12 +802,15 @@ def run_with_new_graph(
     ignore_asserts: bool = False,
     monitoring_level: MonitoringLevel = MonitoringLevel.NONE,
     with_http_server: bool = False,
+    monitoring_http_host: str | None = None,
+    monitoring_http_port: int | None = None,
     persistence_config: PersistenceConfig | None = None,
     license_key: str | None = None,
     monitoring_server: str | None = None,
     trace_parent: str | None = None,
     run_id: str | None = None,
     terminate_on_error: bool = True,
+    dead_letter_sink: tuple[DataStorage, DataFormat] | None = None,
 ) -> list[CapturedStream]: ...
 def unsafe_make_pointer(arg) -> Pointer: ...
 
@@ -773,6 +862,25 @@ class ElasticSearchParams:
 class PersistenceConfig:
     def __init__(self, *args, **kwargs): ...
 
+class PersistenceInspector:
+    def __init__(
+        self,
+        *,
+        metadata_storage: DataStorage,
+        stream_storage: DataStorage,
+        encryption_key_file: str | os.PathLike | None = None,
+        force: bool = False,
+    ) -> None: ...
+    @staticmethod
+    def internal_persistent_id(external_persistent_id: str) -> int: ...
+    def snapshot_streams(self) -> list[tuple[int, int]]: ...
+    def metadata(self) -> dict[int, str]: ...
+    def finalized_timestamps(self) -> dict[int, Frontier]: ...
+    def dump_events(self, persistent_id: int, worker_id: int) -> list[str]: ...
+    def truncate_stream(self, persistent_id: int, worker_id: int, time: int) -> int: ...
+    def migrate_stream(self, persistent_id: int, worker_id: int) -> int: ...
+    def migrate_metadata(self) -> int: ...
+
 class PersistenceMode(Enum):
     BATCH: PersistenceMode
     SPEEDRUN_REPLAY: PersistenceMode
@@ -838,6 +946,8 @@ class ExternalIndexFactory:
         connectivity: int,
         expansion_add: int,
         expansion_search: int,
+        quantization: USearchScalarKind = USearchScalarKind.F16,
+        multi: bool = False,
     ) -> ExternalIndexFactory: ...
     @staticmethod
     def tantivy_factory(
@@ -878,9 +988,19 @@ class USearchMetricKind(Enum):
     TANIMOTO: USearchMetricKind
     SORENSEN: USearchMetricKind
 
+class USearchScalarKind(Enum):
+    F64: USearchScalarKind
+    F32: USearchScalarKind
+    F16: USearchScalarKind
+    I8: USearchScalarKind
+    B1: USearchScalarKind
+
 class BruteForceKnnMetricKind(Enum):
     L2SQ: BruteForceKnnMetricKind
     COS: BruteForceKnnMetricKind
+    IP: BruteForceKnnMetricKind
+    L1: BruteForceKnnMetricKind
+    HAMMING: BruteForceKnnMetricKind
 
 def check_entitlements(
     *,
@@ -5,7 +5,7 @@ from __future__ import annotations
 from abc import ABC, abstractmethod
 from collections.abc import Callable
 from dataclasses import dataclass
-from typing import Any
+from typing import Any, Literal
 
 import pandas as pd
 
@@ -13,10 +13,57 @@ from pathway.internals import api
 from pathway.internals.schema import Schema, schema_from_pandas
 
 
+@dataclass(frozen=True)
+class ErrorBudget:
+    """The fraction of the entries of an input connector that may be erroneous within
+    a sliding window, counting both the errors returned by the source and the entries
+    that could not be parsed, and what happens once it is exceeded.
+
+    Args:
+        max_error_rate: Maximum fraction of erroneous entries within the window,
+            between 0 and 1.
+        window_ms: Length of the sliding window, in milliseconds.
+        min_entries: Minimum number of entries within the window for the rate to be
+            checked, so that a few errors among the first entries don't exceed it.
+        action: What to do once the budget is exceeded. ``"fail"`` terminates
+            the computation, ``"pause"`` stops reading and parsing the entries for
+            ``initial_backoff_ms``, doubling the pause up to ``max_backoff_ms``
+            each time the budget is exceeded again within the window, and ``"skip"``
+            keeps skipping the erroneous entries, logging an alert once per window.
+        initial_backoff_ms: Length of the first pause, in milliseconds. Only used
+            by the ``"pause"`` action.
+        max_backoff_ms: Maximum length of a pause, in milliseconds. Only used by
+            the ``"pause"`` action.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> budget = pw.io.ErrorBudget(max_error_rate=0.05, window_ms=60_000, action="pause")
+    """
+
+    max_error_rate: float
+    window_ms: int = 60_000
+    min_entries: int = 100
+    action: Literal["fail", "pause", "skip"] = "fail"
+    initial_backoff_ms: int = 1_000
+    max_backoff_ms: int = 60_000
+
+    def to_engine(self) -> api.ErrorBudget:
+        return api.ErrorBudget(
+            max_error_rate=self.max_error_rate,
+            window_ms=self.window_ms,
+            min_entries=self.min_entries,
+            action=self.action,
+            initial_backoff_ms=self.initial_backoff_ms,
+            max_backoff_ms=self.max_backoff_ms,
+        )
+
+
 @dataclass(frozen=True)
 class DataSourceOptions:
     commit_duration_ms: int | None = None
     unsafe_trusted_ids: bool | None = False
+    error_budget: ErrorBudget | None = None
 
 
 @dataclass(frozen=True, kw_only=True)
@@ -39,6 +86,11 @@ class DataSource(ABC):
             commit_duration_ms=self.data_source_options.commit_duration_ms,
             unsafe_trusted_ids=self.data_source_options.unsafe_trusted_ids,
             column_properties=columns,
+            error_budget=(
+                self.data_source_options.error_budget.to_engine()
+                if self.data_source_options.error_budget is not None
+                else None
+            ),
         )
 
     def get_effective_schema(self) -> type[Schema]:
@@ -0,0 +1,35 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+
+from pathway.internals import api
+
+# The columns of the dead-letter records, in the order used by the engine.
+DEAD_LETTER_VALUE_FIELDS = [
+    ("source", api.PathwayType.optional(api.PathwayType.STRING)),
+    ("offset", api.PathwayType.optional(api.PathwayType.JSON)),
+    ("payload", api.PathwayType.ANY),
+    ("operator_id", api.PathwayType.optional(api.PathwayType.INT)),
+    ("trace", api.PathwayType.optional(api.PathwayType.STRING)),
+    ("message", api.PathwayType.STRING),
+]
+
+
+def dead_letter_value_fields() -> list[api.ValueField]:
+    return [api.ValueField(name, type_) for name, type_ in DEAD_LETTER_VALUE_FIELDS]
+
+
+@dataclass(frozen=True)
+class DeadLetterSink:
+    """Destination of the input rows that could not be parsed and of the data
+    errors raised by the operators. Create it with one of the functions from
+    ``pw.io.dead_letter``.
+    """
+
+    data_storage: api.DataStorage
+    data_format: api.DataFormat
+
+    def to_engine(self) -> tuple[api.DataStorage, api.DataFormat]:
+        return (self.data_storage, self.data_format)
@@ -13,6 +13,7 @@ import pathway.internals.graph_runner.telemetry as telemetry
 from pathway.internals import api, parse_graph as graph, table, trace
 from pathway.internals.column_path import ColumnPath
 from pathway.internals.config import get_pathway_config
+from pathway.internals.dead_letter import DeadLetterSink
 from pathway.internals.graph_runner.async_utils import new_event_loop
 from pathway.internals.graph_runner.row_transformer_operator_handler import (  # noqa: registers handler for RowTransformerOperator
     RowTransformerOperatorHandler,
@@ -50,11 +51,14 @@ class GraphRunner:
         ignore_asserts: bool | None = None,
         monitoring_level: MonitoringLevel = MonitoringLevel.AUTO,
         with_http_server: bool = False,
+        monitoring_http_host: str | None = None,
+        monitoring_http_port: int | None = None,
         default_logging: bool = True,
         persistence_config: PersistenceConfig | None = None,
         runtime_typechecking: bool | None = None,
         license_key: str | None = None,
         terminate_on_error: bool | None = None,
+        dead_letter_sink: DeadLetterSink | None = None,
         _stacklevel: int = 1,
     ) -> None:
         pathway_config = get_pathway_config()
@@ -65,6 +69,9 @@ class GraphRunner:
         self.ignore_asserts = ignore_asserts
         self.monitoring_level = monitoring_level
         self.with_http_server = with_http_server
+        self.monitoring_http_host = monitoring_http_host
+        self.monitoring_http_port = monitoring_http_port
+        self.dead_letter_sink = dead_letter_sink
         self.default_logging = default_logging
         self.persistence_config = persistence_config or pathway_config.replay_config
         if runtime_typechecking is None:
@@ -207,12 +214,19 @@ class GraphRunner:
                         stats_monitor=stats_monitor,
                         monitoring_level=monitoring_level,
                         with_http_server=self.with_http_server,
+                        monitoring_http_host=self.monitoring_http_host,
+                        monitoring_http_port=self.monitoring_http_port,
                         persistence_config=persistence_engine_config,
                         license_key=self.license_key,
                         monitoring_server=pathway_config.monitoring_server,
                         trace_parent=trace_parent,
                         run_id=run_id,
                         terminate_on_error=self.terminate_on_error,
+                        dead_letter_sink=(
+                            self.dead_letter_sink.to_engine()
+                            if self.dead_letter_sink is not None
+                            else None
+                        ),
                     )
                 except api.EngineErrorWithTrace as e:
                     error, frame = e.args
@@ -81,7 +81,9 @@ class OperatorHandler(ABC, Generic[T]):
     ):
         with trace.custom_trace(operator.trace):
             self.scope.set_operator_properties(
-                self.operator_id, operator.depends_on_error_log
+                self.operator_id,
+                operator.depends_on_error_log,
+                operator.trace.to_engine(),
             )
             if operator.error_log and not self.scope_context.inside_iterate:
                 self.scope.set_error_log(self.state.get_error_log(operator.error_log))
@@ -53,6 +53,15 @@ class LogsOutput:
                 yield new_line
 
 
+def _format_bytes(num_bytes: int) -> str:
+    size = float(num_bytes)
+    for unit in ["B", "KiB", "MiB", "GiB"]:
+        if size < 1024:
+            return f"{size:.1f} {unit}" if unit != "B" else f"{num_bytes} B"
+        size /= 1024
+    return f"{size:.1f} TiB"
+
+
 class MonitoringOutput:
     def __init__(
         self,
@@ -92,17 +101,52 @@ class MonitoringOutput:
         table.add_column("no. messages in the last minibatch", justify="right")
         table.add_column("in the last minute", justify="right")
         table.add_column("since start", justify="right")
+        table.add_column("errors", justify="right")
 
         for name, entry in self.data.connector_stats:
+            if entry.finished:
+                status = "finished"
+            elif entry.paused:
+                status = "paused"
+            else:
+                status = f"{entry.num_messages_recently_committed}"
             table.add_row(
                 name,
-                (
-                    "finished"
-                    if entry.finished
-                    else f"{entry.num_messages_recently_committed}"
-                ),
+                status,
                 f"{entry.num_messages_in_last_minute}",
                 f"{entry.num_messages_from_start}",
+                f"{entry.num_errors_from_start}",
+            )
+        return table
+
+    def get_operator_name(self, operator_id: int | None, trace: Any) -> str:
+        if operator_id is None:
+            return "internal"
+        for id_, node_name in self.node_names:
+            if id_ == operator_id:
+                return node_name
+        if trace is not None:
+            return f"{os.path.basename(trace.file_name)}:{trace.line_number}"
+        return f"operator {operator_id}"
+
+    def get_memory_table(self, max_height) -> Table:
+        table = Table(
+            caption="The biggest arrangements, grouped by the operator creating them.",
+            box=box.SIMPLE,
+        )
+        table.add_column("operator", justify="left")
+        table.add_column("no. records", justify="right")
+        table.add_column("no. batches", justify="right")
+        table.add_column("approx. records size", justify="right")
+
+        max_rows_to_print = max(max_height - 4, 1)
+        # 2 lines for header, 2 lines for caption
+        for entry in self.data.operator_arrangement_stats[:max_rows_to_print]:
+            table.add_row(
+                self.get_operator_name(entry.operator_id, entry.trace),
+                f"{entry.stats.num_records}",
+                f"{entry.stats.num_batches}",
+                _format_bytes(entry.stats.approximate_bytes),
             )
         return table
 
@@ -154,7 +198,13 @@ class MonitoringOutput:
     ) -> RenderResult:
         layout = Layout(name="monitoring_inner")
         layout.split_row(Layout(name="connectors"), Layout(name="operators"))
-        layout["connectors"].update(Align.center(self.get_connectors_table()))
+        layout["connectors"].split_column(
+            Layout(name="connector_stats"), Layout(name="memory")
+        )
+        layout["connector_stats"].update(Align.center(self.get_connectors_table()))
+        layout["memory"].update(
+            Align.center(self.get_memory_table((options.max_height - 2) // 2))
+        )
         layout["operators"].update(
             Align.center(self.get_operators_table(options.max_height - 2))
         )
@@ -122,6 +122,49 @@ class SumReducer(UnaryReducer):
             return api.Reducer.FLOAT_SUM
 
 
+class MomentsReducer(UnaryReducerWithDefault):
+    _optional_result: bool
+
+    def __init__(
+        self, *, name: str, engine_reducer: api.Reducer, optional_result: bool = False
+    ):
+        super().__init__(name=name, engine_reducer=engine_reducer)
+        self._optional_result = optional_result
+
+    def return_type_unary(self, arg_type: dt.DType, id_type: dt.DType) -> dt.DType:
+        if not dt.dtype_issubclass(arg_type, dt.FLOAT):
+            raise TypeError(
+                f"Pathway does not support using reducer {self}"
+                + f" on column of type {arg_type}.\n"
+            )
+        if self._optional_result:
+            return dt.Optional(dt.FLOAT)
+        return dt.FLOAT
+
+
+class CountDistinctReducer(UnaryReducerWithDefault):
+    def return_type_unary(self, arg_type: dt.DType, id_type: dt.DType) -> dt.DType:
+        return dt.INT
+
+
+class QuantilesReducer(UnaryReducerWithDefault):
+    _multiple: bool
+
+    def __init__(self, *, name: str, engine_reducer: api.Reducer, multiple: bool):
+        super().__init__(name=name, engine_reducer=engine_reducer)
+        self._multiple = multiple
+
+    def return_type_unary(self, arg_type: dt.DType, id_type: dt.DType) -> dt.DType:
+        if not dt.dtype_issubclass(arg_type, dt.FLOAT):
+            raise TypeError(
+                f"Pathway does not support using reducer {self}"
+                + f" on column of type {arg_type}.\n"
+            )
+        if self._multiple:
+            return dt.List(dt.FLOAT)
+        return dt.FLOAT
+
+
 class SortedTupleWrappingReducer(UnaryReducerWithDefault):
     _skip_nones: bool
 
@@ -202,6 +245,20 @@ class TupleConvertibleToNDArrayWrappingReducer(TupleWrappingReducer):
         )
 
 
+class TopKReducer(Reducer):
+    _engine_reducer: api.Reducer
+
+    def __init__(self, *, name: str, engine_reducer: api.Reducer):
+        super().__init__(name=name)
+        self._engine_reducer = engine_reducer
+
+    def return_type(self, arg_types: list[dt.DType], id_type: dt.DType) -> dt.DType:
+        return dt.List(dt.Tuple(arg_types[0], id_type))
+
+    def engine_reducer(self, arg_types: list[dt.DType]) -> api.Reducer:
+        return self._engine_reducer
+
+
 class StatefulManyReducer(Reducer):
     name = "stateful_many"
     combine_many: api.CombineMany
@@ -257,6 +314,36 @@ _latest = TimeBasedTypePreservingUnaryReducer(
     name="latest", engine_reducer=api.Reducer.LATEST, alternative="max"
 )
 
+_mean = MomentsReducer(name="mean", engine_reducer=api.Reducer.MEAN)
+
+
+def _variance(sample: bool):
+    # the sample variance of a single value is undefined
+    return MomentsReducer(
+        name="variance",
+        engine_reducer=api.Reducer.variance(sample),
+        optional_result=sample,
+    )
+
+
+def _stddev(sample: bool):
+    return MomentsReducer(
+        name="stddev",
+        engine_reducer=api.Reducer.stddev(sample),
+        optional_result=sample,
+    )
+
+_count_distinct = CountDistinctReducer(
+    name="count_distinct", engine_reducer=api.Reducer.COUNT_DISTINCT
+)
+
+
+def _count_distinct_approximate(precision: int):
+    return CountDistinctReducer(
+        name="count_distinct_approximate",
+        engine_reducer=api.Reducer.count_distinct_approx(precision),
+    )
+
 
 def _apply_unary_reducer(
     reducer: UnaryReducer, arg: expr.ColumnExpression, **kwargs
@@ -721,3 +808,242 @@ def latest(expression: expr.ColumnExpression) -> expr.ColumnExpression:
     2 | 2
     """
     return _apply_unary_reducer(_latest, expression)
+
+
+def mean(expression: expr.ColumnExpression) -> expr.ColumnExpression:
+    """
+    Returns the mean of the aggregated values. Unlike :py:func:`avg`, it is computed
+    in a single pass from an exact sum, so it doesn't depend on the order of updates.
+    Infinite and NaN values, as well as values whose square overflows, are errors.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA |  3
+    ... valB |  4
+    ... valB |  4
+    ... valB |  7
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(mean=pw.reducers.mean(t.colB))
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    mean
+    1.0
+    5.0
+    """
+    return _apply_unary_reducer(_mean, expression)
+
+
+def variance(
+    expression: expr.ColumnExpression, *, sample: bool = True
+) -> expr.ColumnExpression:
+    """
+    Returns the variance of the aggregated values. By default, the sample variance is
+    computed (divided by ``n - 1``), which is ``None`` for a group with a single value.
+    With ``sample=False``, the population variance (divided by ``n``) is computed.
+    It is computed from exact sums of the values and of their squares, so updates
+    and retractions cancel out regardless of their order.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA |  3
+    ... valB |  4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA,
+    ...     variance=pw.reducers.variance(t.colB),
+    ...     population_variance=pw.reducers.variance(t.colB, sample=False),
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    colA | variance | population_variance
+    valA | 4.0      | 2.6666666666666665
+    valB |          | 0.0
+    """
+    return _apply_unary_reducer(_variance(sample), expression)
+
+
+def stddev(
+    expression: expr.ColumnExpression, *, sample: bool = True
+) -> expr.ColumnExpression:
+    """
+    Returns the standard deviation of the aggregated values, i.e. the square root of
+    their :py:func:`variance`. By default, the sample standard deviation is computed,
+    which is ``None`` for a group with a single value.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA |  3
+    ... valB |  4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA,
+    ...     stddev=pw.reducers.stddev(t.colB),
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    colA | stddev
+    valA | 2.0
+    valB |
+    """
+    return _apply_unary_reducer(_stddev(sample), expression)
+
+
+def count_distinct(expression: expr.ColumnExpression) -> expr.ColumnExpression:
+    """
+    Returns the number of distinct aggregated values.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA | -1
+    ... valB |  4
+    ... valB |  4
+    ... valB |  4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA, count=pw.reducers.count_distinct(t.colB)
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    colA | count
+    valA | 2
+    valB | 1
+    """
+    return _apply_unary_reducer(_count_distinct, expression)
+
+
+def count_distinct_approximate(
+    expression: expr.ColumnExpression, *, precision: int = 12
+) -> expr.ColumnExpression:
+    """
+    Returns the approximate number of distinct aggregated values, estimated with
+    HyperLogLog. The state of the reducer is bounded by ``2 ** precision`` registers,
+    regardless of the number of distinct values. The relative error of the estimate
+    is about ``1.04 / sqrt(2 ** precision)``. ``precision`` has to be between 4 and 18.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA | -1
+    ... valB |  4
+    ... valB |  4
+    ... valB |  4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA, count=pw.reducers.count_distinct_approximate(t.colB)
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    colA | count
+    valA | 2
+    valB | 1
+    """
+    return _apply_unary_reducer(_count_distinct_approximate(precision), expression)
+
+
+def quantile(
+    expression: expr.ColumnExpression, q: float | builtins.list[float]
+) -> expr.ColumnExpression:
+    """
+    Returns the ``q``-th quantile of the aggregated values, linearly interpolated between
+    the closest values, like ``numpy.quantile``. If ``q`` is a list, a list with
+    the quantiles is returned. ``q`` has to be between 0 and 1. The quantiles are exact
+    for groups with up to about 1700 distinct values. Larger groups are summarized with
+    a sketch of bounded size, so their quantiles are exact only if no two distinct
+    values differ by less than 1% of their magnitude. Otherwise, the relative error
+    is at most 1%.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ... colA | colB
+    ... valA | -1
+    ... valA |  1
+    ... valA |  2
+    ... valA | 10
+    ... valB |  4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA,
+    ...     median=pw.reducers.quantile(t.colB, 0.5),
+    ...     extremes=pw.reducers.quantile(t.colB, [0.0, 1.0]),
+    ... )
+    >>> pw.debug.compute_and_print(result, include_id=False)
+    colA | median | extremes
+    valA | 1.5    | (-1.0, 10.0)
+    valB | 4.0    | (4.0, 4.0)
+    """
+    if isinstance(q, builtins.list):
+        reducer = QuantilesReducer(
+            name="quantile", engine_reducer=api.Reducer.quantiles(q), multiple=True
+        )
+    else:
+        reducer = QuantilesReducer(
+            name="quantile", engine_reducer=api.Reducer.quantile(q), multiple=False
+        )
+    return _apply_unary_reducer(reducer, expression)
+
+
+def top_k(
+    expression: expr.ColumnExpression,
+    k: int,
+    *,
+    order_by: expr.ColumnExpression | None = None,
+    descending: bool = False,
+) -> expr.ColumnExpression:
+    """
+    Returns the ``k`` smallest (or largest, if ``descending`` is set) aggregated values,
+    ordered by ``order_by`` or by the values themselves. Each value is returned
+    together with the id of its row, which is also used to break ties.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> t = pw.debug.table_from_markdown('''
+    ...   | colA | colB | colC
+    ... 1 | valA | a    | 3
+    ... 2 | valA | b    | 1
+    ... 3 | valA | c    | 2
+    ... 4 | valB | d    | 4
+    ... ''')
+    >>> result = t.groupby(t.colA).reduce(
+    ...     pw.this.colA,
+    ...     top=pw.reducers.top_k(t.colB, 2, order_by=t.colC, descending=True),
+    ... )
+    >>> values = result.select(
+    ...     pw.this.colA,
+    ...     top=pw.apply(lambda top: ",".join(value for value, _ in top), pw.this.top),
+    ... )
+    >>> pw.debug.compute_and_print(values, include_id=False)
+    colA | top
+    valA | a,c
+    valB | d
+    """
+    reducer = TopKReducer(
+        name="top_k",
+        engine_reducer=api.Reducer.top_k(
+            k, descending, order_column=None if order_by is None else 1
+        ),
+    )
+    if order_by is None:
+        return expr.ReducerExpression(reducer, expression)
+    return expr.ReducerExpression(reducer, expression, order_by)
@@ -2,6 +2,7 @@
 
 
 from pathway.internals import parse_graph
+from pathway.internals.dead_letter import DeadLetterSink
 from pathway.internals.graph_runner import GraphRunner
 from pathway.internals.monitoring import MonitoringLevel
 from pathway.internals.runtime_type_check import check_arg_types
@@ -14,11 +15,14 @@ def run(
     debug: bool = False,
     monitoring_level: MonitoringLevel = MonitoringLevel.AUTO,
     with_http_server: bool = False,
+    monitoring_http_host: str | None = None,
+    monitoring_http_port: int | None = None,
     default_logging: bool = True,
     persistence_config: PersistenceConfig | None = None,
     runtime_typechecking: bool | None = None,
     license_key: str | None = None,
     terminate_on_error: bool | None = None,
+    dead_letter_sink: DeadLetterSink | None = None,
 ) -> None:
     """Runs the computation graph.
 
@@ -31,23 +35,36 @@ def run(
         with_http_server: whether to start a http server with runtime metrics. [will be deprecated soon]
             Learn more about Pathway monitoring in a
             `tutorial </developers/user-guide/deployment/pathway-monitoring/>`_ .
+        monitoring_http_host: the IP address the http server listens on. If unset, it is
+            taken from the ``PATHWAY_MONITORING_HTTP_HOST`` environment variable or defaults
+            to ``127.0.0.1``. Use ``0.0.0.0`` to expose the server on all interfaces.
+        monitoring_http_port: the port of the http server. The server of the process with
+            id ``i`` listens on ``monitoring_http_port + i``. If unset, the port is taken
+            from the ``PATHWAY_MONITORING_HTTP_PORT`` environment variable or defaults to 20000.
         default_logging: whether to allow pathway to set its own logging handler. Set
             it to False if you want to set your own logging handler.
         persistence_config: the config for persisting the state in case this
             persistence is required.
         runtime_typechecking: enables additional strict type checking at runtime
         terminate_on_error: whether to terminate the computation if the data/user-logic error occurs
+        dead_letter_sink: where to write the input rows that could not be parsed and the
+            data errors raised by the operators, created with one of the functions from
+            ``pw.io.dead_letter``. If ``terminate_on_error`` is True, the data error
+            that terminates the computation is written before it stops.
     """
     GraphRunner(
         parse_graph.G,
         debug=debug,
         monitoring_level=monitoring_level,
         with_http_server=with_http_server,
+        monitoring_http_host=monitoring_http_host,
+        monitoring_http_port=monitoring_http_port,
         default_logging=default_logging,
         persistence_config=persistence_config,
         license_key=license_key,
         runtime_typechecking=runtime_typechecking,
         terminate_on_error=terminate_on_error,
+        dead_letter_sink=dead_letter_sink,
         _stacklevel=4,
     ).run_outputs()
 
@@ -58,11 +75,14 @@ def run_all(
     debug: bool = False,
     monitoring_level: MonitoringLevel = MonitoringLevel.AUTO,
     with_http_server: bool = False,
+    monitoring_http_host: str | None = None,
+    monitoring_http_port: int | None = None,
     default_logging: bool = True,
     persistence_config: PersistenceConfig | None = None,
     runtime_typechecking: bool | None = None,
     license_key: str | None = None,
     terminate_on_error: bool | None = None,
+    dead_letter_sink: DeadLetterSink | None = None,
 ) -> None:
     """Runs the computation graph with disabled tree-shaking optimization.
 
@@ -75,22 +95,35 @@ def run_all(
         with_http_server: whether to start a http server with runtime metrics. [will be deprecated soon]
             Learn more about Pathway monitoring in a
             `tutorial </developers/user-guide/deployment/pathway-monitoring/>`_ .
+        monitoring_http_host: the IP address the http server listens on. If unset, it is
+            taken from the ``PATHWAY_MONITORING_HTTP_HOST`` environment variable or defaults
+            to ``127.0.0.1``. Use ``0.0.0.0`` to expose the server on all interfaces.
+        monitoring_http_port: the port of the http server. The server of the process with
+            id ``i`` listens on ``monitoring_http_port + i``. If unset, the port is taken
+            from the ``PATHWAY_MONITORING_HTTP_PORT`` environment variable or defaults to 20000.
         default_logging: whether to allow pathway to set its own logging handler. Set
             it to False if you want to set your own logging handler.
         persistence_config: the config for persisting the state in case this
             persistence is required.
         runtime_typechecking: enables additional strict type checking at runtime
         terminate_on_error: whether to terminate the computation if the data/user-logic error occurs
+        dead_letter_sink: where to write the input rows that could not be parsed and the
+            data errors raised by the operators, created with one of the functions from
+            ``pw.io.dead_letter``. If ``terminate_on_error`` is True, the data error
+            that terminates the computation is written before it stops.
     """
     GraphRunner(
         parse_graph.G,
         debug=debug,
         monitoring_level=monitoring_level,
         with_http_server=with_http_server,
+        monitoring_http_host=monitoring_http_host,
+        monitoring_http_port=monitoring_http_port,
         default_logging=default_logging,
         persistence_config=persistence_config,
         runtime_typechecking=runtime_typechecking,
         license_key=license_key,
         terminate_on_error=terminate_on_error,
+        dead_letter_sink=dead_letter_sink,
         _stacklevel=4,
     ).run_all()
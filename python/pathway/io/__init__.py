@@ -1,9 +1,11 @@
 # Copyright © 2024 Pathway
 
+from pathway.internals.datasource import ErrorBudget
 from pathway.io import (
     airbyte,
     bigquery,
     csv,
+    dead_letter,
     debezium,
     deltalake,
     elasticsearch,
@@ -34,8 +36,10 @@ __all__ = [
     "bigquery",
     "csv",
     "CsvParserSettings",
+    "dead_letter",
     "debezium",
     "elasticsearch",
+    "ErrorBudget",
     "fs",
     "http",
     "jsonlines",
@@ -10,6 +10,7 @@ from typing import Any
 import requests
 import yaml
 
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.schema import Schema
 from pathway.io.python import read as python_connector_read
 from pathway.optional_import import optional_imports
@@ -117,6 +118,7 @@ def read(
     enforce_method: str | None = None,
     refresh_interval_ms: int = 60000,
     persistent_id: int | None = None,
+    error_budget: ErrorBudget | None = None,
 ):
     """
     Reads a table with an Airbyte connector that supports the \
@@ -164,6 +166,9 @@ mode.
             ``"pypi"``, Pathway will prefer the usage of the latest image available on
             PyPI. Use this option when you need to ensure certain behaviour on the local
             run.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
 
@@ -336,6 +341,7 @@ mode.
         subject=subject,
         schema=_AirbyteRecordSchema,
         autocommit_duration_ms=max(refresh_interval_ms, 1),
+        error_budget=error_budget,
         name="airbyte",
         persistent_id=persistent_id,
     )
@@ -7,6 +7,7 @@ from typing import Any
 
 import pathway as pw
 from pathway.internals.api import PathwayType
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
@@ -25,6 +26,7 @@ def read(
     object_pattern: str = "*",
     with_metadata: bool = False,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: ErrorBudget | None = None,
     persistent_id: str | None = None,
     debug_data=None,
     id_columns: list[str] | None = None,
@@ -76,6 +78,9 @@ named ``path`` that will show the full path to the file from where a row was fil
         autocommit_duration_ms: the maximum time between two commits. Every
           autocommit_duration_ms milliseconds, the updates received by the connector are
           committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+          sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+          By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         persistent_id: (unstable) An identifier, under which the state of the table
             will be persisted or ``None``, if there is no need to persist the state of this table.
             When a program restarts, it restores the state for all input tables according to what
@@ -170,6 +175,7 @@ named ``path`` that will show the full path to the file from where a row was fil
         with_metadata=with_metadata,
         csv_settings=csv_settings,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         json_field_paths=None,
         persistent_id=persistent_id,
         debug_data=debug_data,
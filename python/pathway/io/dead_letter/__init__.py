@@ -0,0 +1,106 @@
+# Copyright © 2024 Pathway
+
+from __future__ import annotations
+
+from os import PathLike, fspath
+
+from pathway.internals import api
+from pathway.internals.dead_letter import DeadLetterSink, dead_letter_value_fields
+from pathway.internals.runtime_type_check import check_arg_types
+from pathway.io.postgres import _connection_string_from_settings
+
+__all__ = ["DeadLetterSink", "jsonlines", "kafka", "postgres"]
+
+
+@check_arg_types
+def jsonlines(filename: str | PathLike) -> DeadLetterSink:
+    """Writes the rejected input rows and the data errors to a file in jsonlines format.
+
+    Each record has the fields ``source`` (the name of the input connector),
+    ``offset`` (the position of the row in the source), ``payload`` (the raw data,
+    as read from the source), ``operator_id`` and ``trace`` (the operator that raised
+    the error), and ``message``. Input rows have no ``operator_id`` and data errors
+    raised by the operators have no ``source``, ``offset`` or ``payload``. A row with
+    several invalid fields is written once, with the messages separated by newlines.
+
+    If the computation runs in multiple processes, each of them writes to its own file,
+    named after ``filename`` with the process id appended, e.g. ``dead_letters-1.jsonl``.
+
+    Args:
+        filename: Path to the target output file.
+
+    Returns:
+        The sink to be passed as ``dead_letter_sink`` to ``pw.run``.
+
+    Example:
+
+    >>> import pathway as pw
+    >>> sink = pw.io.dead_letter.jsonlines("dead_letters.jsonl")
+
+    >>> pw.run(terminate_on_error=False, dead_letter_sink=sink)  # doctest: +SKIP
+    """
+    return DeadLetterSink(
+        api.DataStorage(storage_type="fs", path=fspath(filename)),
+        api.DataFormat(
+            format_type="jsonlines",
+            key_field_names=[],
+            value_fields=dead_letter_value_fields(),
+        ),
+    )
+
+
+@check_arg_types
+def kafka(rdkafka_settings: dict, topic_name: str) -> DeadLetterSink:
+    """Writes the rejected input rows and the data errors to a Kafka topic, one
+    JSON message per record. The fields of the records are described in
+    ``pw.io.dead_letter.jsonlines``.
+
+    Args:
+        rdkafka_settings: Connection settings in the format of
+            `librdkafka <https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md>`_.
+        topic_name: Name of the topic the records are sent to.
+
+    Returns:
+        The sink to be passed as ``dead_letter_sink`` to ``pw.run``.
+    """
+    return DeadLetterSink(
+        api.DataStorage(
+            storage_type="kafka",
+            rdkafka_settings=rdkafka_settings,
+            topic=topic_name,
+            header_fields=[],
+        ),
+        api.DataFormat(
+            format_type="jsonlines",
+            key_field_names=[],
+            value_fields=dead_letter_value_fields(),
+        ),
+    )
+
+
+@check_arg_types
+def postgres(postgres_settings: dict, table_name: str) -> DeadLetterSink:
+    """Inserts the rejected input rows and the data errors into a Postgres table.
+    The table must have the columns ``source``, ``offset``, ``payload``,
+    ``operator_id``, ``trace`` and ``message``, described in
+    ``pw.io.dead_letter.jsonlines``, and the integer columns ``time`` and ``diff``.
+
+    Args:
+        postgres_settings: Components for the connection string for Postgres.
+        table_name: Name of the target table.
+
+    Returns:
+        The sink to be passed as ``dead_letter_sink`` to ``pw.run``.
+    """
+    return DeadLetterSink(
+        api.DataStorage(
+            storage_type="postgres",
+            connection_string=_connection_string_from_settings(postgres_settings),
+        ),
+        api.DataFormat(
+            format_type="sql",
+            key_field_names=[],
+            value_fields=dead_letter_value_fields(),
+            table_name=table_name,
+        ),
+    )
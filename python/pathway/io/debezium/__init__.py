@@ -25,6 +25,7 @@ def read(
     schema: type[Schema] | None = None,
     debug_data=None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     persistent_id: str | None = None,
     value_columns: list[str] | None = None,
     primary_key: list[str] | None = None,
@@ -46,6 +47,9 @@ def read(
         autocommit_duration_ms:the maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         persistent_id: (unstable) An identifier, under which the state of the table
             will be persisted or ``None``, if there is no need to persist the state of this table.
             When a program restarts, it restores the state for all input tables according to what
@@ -136,7 +140,8 @@ def read(
         default_values=default_values,
     )
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     data_format = api.DataFormat(
         format_type="debezium", debezium_db_type=db_type, **data_format_definition
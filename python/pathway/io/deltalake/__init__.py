@@ -44,6 +44,7 @@ def read(
         AwsS3Settings | MinIOSettings | WasabiS3Settings | DigitalOceanS3Settings | None
     ) = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     persistent_id: str | None = None,
     debug_data: Any = None,
 ) -> Table:
@@ -73,6 +74,9 @@ def read(
         autocommit_duration_ms: The maximum time between two commits. Every
             ``autocommit_duration_ms`` milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         debug_data: Static data replacing original one when debug mode is active.
 
     Examples:
@@ -150,7 +154,8 @@ def read(
     )
 
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
@@ -45,6 +45,7 @@ def read(
     primary_key: list[str] | None = None,
     types: dict[str, PathwayType] | None = None,
     default_values: dict[str, Any] | None = None,
+    error_budget: datasource.ErrorBudget | None = None,
     _stacklevel: int = 1,
 ) -> Table:
     """Reads a table from one or several files with the specified format.
@@ -98,6 +99,9 @@ named ``path`` that will show the full path to the file from where a row was fil
             was saved for their ``persistent_id``. This way it's possible to configure the start of
             computations from the moment they were terminated last time.
         debug_data: Static data replacing original one when debug mode is active.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         value_columns: Names of the columns to be extracted from the files. [will be deprecated soon]
         primary_key: In case the table should have a primary key generated according to
             a subset of its columns, the set of columns should be specified in this field.
@@ -262,7 +266,8 @@ named ``path`` that will show the full path to the file from where a row was fil
     )
 
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
@@ -20,6 +20,7 @@ from googleapiclient.http import MediaIoBaseDownload
 import pathway as pw
 from pathway.internals import api
 from pathway.internals.api import SessionType
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.io._utils import (
     STATUS_DOWNLOADED,
@@ -342,6 +343,7 @@ def read(
     service_user_credentials_file: str,
     with_metadata: bool = False,
     file_name_pattern: list | str | None = None,
+    error_budget: ErrorBudget | None = None,
 ) -> pw.Table:
     """Reads a table from a Google Drive directory or file.
 
@@ -367,6 +369,9 @@ def read(
         file_name_pattern: glob pattern (or list of patterns) to be used to filter files based on their names.
             Defaults to `None` which doesn't filter anything. Doesn't apply to folder names.
             For example, `*.pdf` will only return files that has `.pdf` extension.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
     Returns:
         The table read.
 
@@ -398,4 +403,6 @@ def read(
         file_name_pattern=file_name_pattern,
     )
 
-    return pw.io.python.read(subject, format="binary", name="gdrive")
+    return pw.io.python.read(
+        subject, format="binary", name="gdrive", error_budget=error_budget
+    )
@@ -6,6 +6,7 @@ from collections.abc import Callable
 from typing import Any
 
 from pathway.internals.api import PathwayType, Pointer
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
@@ -42,6 +43,7 @@ def read(
     allow_redirects: bool = True,
     retry_codes: tuple | None = (429, 500, 502, 503, 504),
     autocommit_duration_ms: int = 10000,
+    error_budget: ErrorBudget | None = None,
     debug_data=None,
     value_columns: list[str] | None = None,
     primary_key: list[str] | None = None,
@@ -77,6 +79,9 @@ def read(
         autocommit_duration_ms: the maximum time between two commits. Every
           autocommit_duration_ms milliseconds, the updates received by the connector are
           committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+          sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+          By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         debug_data: static data replacing original one when debug mode is active.
         value_columns: columns to extract for a table. [will be deprecated soon]
         primary_key: in case the table should have a primary key generated according to
@@ -148,6 +153,7 @@ def read(
         types=types,
         default_values=default_values,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         debug_data=debug_data,
         name="http",
     )
@@ -44,6 +44,7 @@ def read(
     primary_key: list[str] | None = None,
     types: dict[str, PathwayType] | None = None,
     default_values: dict[str, Any] | None = None,
+    error_budget: datasource.ErrorBudget | None = None,
     _stacklevel: int = 1,
     **kwargs,
 ) -> Table:
@@ -90,6 +91,9 @@ def read(
             computations from the moment they were terminated last time.
         value_columns: Columns to extract for a table, required for format other than
             "raw". [will be deprecated soon]
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         primary_key: In case the table should have a primary key generated according to
             a subset of its columns, the set of columns should be specified in this field.
             Otherwise, the primary key will be generated randomly. [will be deprecated soon]
@@ -272,7 +276,8 @@ def read(
         _stacklevel=5,
     )
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
@@ -297,6 +302,7 @@ def simple_read(
     format="raw",
     debug_data=None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     json_field_paths: dict[str, str] | None = None,
     parallel_readers: int | None = None,
     persistent_id: str | None = None,
@@ -331,6 +337,9 @@ topic.
         autocommit_duration_ms: The maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         json_field_paths: If the format is JSON, this field allows to map field names
             into path in the field. For the fields which require such mapping, it should be
             given in the format ``<field_name>: <path to be mapped>``, where the path to
@@ -377,6 +386,7 @@ topic.
         format=format,
         debug_data=debug_data,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         json_field_paths=json_field_paths,
         parallel_readers=parallel_readers,
         persistent_id=persistent_id,
@@ -396,6 +406,7 @@ def read_from_upstash(
     format="raw",
     debug_data=None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     json_field_paths: dict[str, str] | None = None,
     parallel_readers: int | None = None,
     persistent_id: str | None = None,
@@ -433,6 +444,9 @@ topic.
         autocommit_duration_ms: The maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         json_field_paths: If the format is JSON, this field allows to map field names
             into path in the field. For the fields which require such mapping, it should be
             given in the format ``<field_name>: <path to be mapped>``, where the path to
@@ -490,6 +504,7 @@ topic.
         format=format,
         debug_data=debug_data,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         json_field_paths=json_field_paths,
         parallel_readers=parallel_readers,
         persistent_id=persistent_id,
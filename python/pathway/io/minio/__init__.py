@@ -4,6 +4,7 @@ from __future__ import annotations
 
 from typing import Any
 
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
@@ -67,6 +68,7 @@ def read(
     json_field_paths: dict[str, str] | None = None,
     persistent_id: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: ErrorBudget | None = None,
     debug_data: Any = None,
 ) -> Table:
     """Reads a table from one or several objects from S3 bucket in MinIO.
@@ -104,6 +106,9 @@ def read(
             was saved for their ``persistent_id``. This way it's possible to configure the start of
             computations from the moment they were terminated last time.
         debug_data: Static data replacing original one when debug mode is active.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
         Table: The table read.
@@ -145,6 +150,7 @@ def read(
         csv_settings=csv_settings,
         mode=mode,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         persistent_id=persistent_id,
         json_field_paths=json_field_paths,
         debug_data=debug_data,
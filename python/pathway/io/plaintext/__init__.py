@@ -5,6 +5,7 @@ from __future__ import annotations
 from os import PathLike
 
 import pathway as pw
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
@@ -20,6 +21,7 @@ def read(
     with_metadata: bool = False,
     persistent_id: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: ErrorBudget | None = None,
     debug_data=None,
 ) -> Table:
     """Reads a table from a text file or a directory of text files. The resulting table
@@ -60,6 +62,9 @@ computations from the moment they were terminated last time.
         autocommit_duration_ms: the maximum time between two commits. Every
           autocommit_duration_ms milliseconds, the updates received by the connector are
           committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+          sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+          By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         debug_data: Static data replacing original one when debug mode is active.
 
     Returns:
@@ -79,6 +84,7 @@ computations from the moment they were terminated last time.
         with_metadata=with_metadata,
         persistent_id=persistent_id,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         debug_data=debug_data,
         _stacklevel=5,
     )
@@ -12,6 +12,7 @@ from fs.errors import ResourceNotFound as FSResourceNotFound
 from fs.walk import Walker
 
 from pathway.internals import api
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.table import Table
 from pathway.internals.trace import trace_user_frame
@@ -146,6 +147,7 @@ def read(
     refresh_interval: float = 30,
     mode: str = "streaming",
     with_metadata: bool = False,
+    error_budget: ErrorBudget | None = None,
 ) -> Table:
     """Reads a table from \
 `PyFilesystem <https://docs.pyfilesystem.org/en/latest/introduction.html>_` source.
@@ -168,6 +170,9 @@ set to "streaming".
         with_metadata: when set to True, the connector will add column \
 named `_metadata` to the table. This column will contain file metadata, such as: \
 `path`, `name`, `owner`, `created_at`, `modified_at`, `accessed_at`, `size`.
+        error_budget: the fraction of erroneous entries the connector may produce \
+within a sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``. \
+By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
         The table read.
@@ -217,5 +222,6 @@ connector. It can be done with the usage of `FTP` file source with the code as f
         subject,
         format="binary",
         autocommit_duration_ms=None,
+        error_budget=error_budget,
         name="pyfilesystem",
     )
@@ -352,6 +352,7 @@ def read(
     schema: type[Schema] | None = None,
     format: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     debug_data=None,
     value_columns: list[str] | None = None,
     primary_key: list[str] | None = None,
@@ -372,6 +373,9 @@ def read(
         autocommit_duration_ms: the maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph
+        error_budget: the fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         value_columns: Columns to extract for a table. [will be deprecated soon]
         primary_key: In case the table should have a primary key generated according to
             a subset of its columns, the set of columns should be specified in this field.
@@ -455,7 +459,8 @@ computations from the moment they were terminated last time.
         mode=mode,
     )
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
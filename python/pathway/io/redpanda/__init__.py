@@ -5,6 +5,7 @@ from __future__ import annotations
 from typing import Any
 
 from pathway.internals.api import PathwayType
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
@@ -23,6 +24,7 @@ def read(
     format="raw",
     debug_data=None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: ErrorBudget | None = None,
     json_field_paths: dict[str, str] | None = None,
     parallel_readers: int | None = None,
     persistent_id: str | None = None,
@@ -45,6 +47,9 @@ def read(
         autocommit_duration_ms:the maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         json_field_paths: If the format is JSON, this field allows to map field names
             into path in the field. For the field which require such mapping, it should be
             given in the format `<field_name>: <path to be mapped>`, where the path to
@@ -216,6 +221,7 @@ def read(
         default_values=default_values,
         debug_data=debug_data,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         json_field_paths=json_field_paths,
         parallel_readers=parallel_readers,
         persistent_id=persistent_id,
@@ -102,6 +102,7 @@ def read(
     json_field_paths: dict[str, str] | None = None,
     persistent_id: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     debug_data: Any = None,
 ) -> Table:
     """Reads a table from one or several objects in Amazon S3 bucket in the given
@@ -141,6 +142,9 @@ def read(
             was saved for their ``persistent_id``. This way it's possible to configure the start of
             computations from the moment they were terminated last time.
         debug_data: Static data replacing original one when debug mode is active.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
         Table: The table read.
@@ -271,7 +275,8 @@ def read(
         _stacklevel=5,
     )
     data_source_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
@@ -298,6 +303,7 @@ def read_from_digital_ocean(
     json_field_paths: dict[str, str] | None = None,
     persistent_id: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     debug_data: Any = None,
 ) -> Table:
     """Reads a table from one or several objects in Digital Ocean S3 bucket.
@@ -336,6 +342,9 @@ def read_from_digital_ocean(
             was saved for their ``persistent_id``. This way it's possible to configure the start of
             computations from the moment they were terminated last time.
         debug_data: Static data replacing original one when debug mode is active.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
         Table: The table read.
@@ -388,7 +397,8 @@ def read_from_digital_ocean(
         _stacklevel=5,
     )
     datasource_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
@@ -415,6 +425,7 @@ def read_from_wasabi(
     json_field_paths: dict[str, str] | None = None,
     persistent_id: str | None = None,
     autocommit_duration_ms: int | None = 1500,
+    error_budget: datasource.ErrorBudget | None = None,
     debug_data: Any = None,
 ) -> Table:
     """Reads a table from one or several objects in Wasabi S3 bucket.
@@ -452,6 +463,9 @@ def read_from_wasabi(
             was saved for their ``persistent_id``. This way it's possible to configure the start of
             computations from the moment they were terminated last time.
         debug_data: Static data replacing original one when debug mode is active.
+        error_budget: The fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
 
     Returns:
         Table: The table read.
@@ -503,7 +517,8 @@ def read_from_wasabi(
         _stacklevel=5,
     )
     datasource_options = datasource.DataSourceOptions(
-        commit_duration_ms=autocommit_duration_ms
+        commit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
     )
     return table_from_datasource(
         datasource.GenericDataSource(
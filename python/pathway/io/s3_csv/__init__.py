@@ -5,6 +5,7 @@ from __future__ import annotations
 from typing import Any
 
 from pathway.internals.api import PathwayType
+from pathway.internals.datasource import ErrorBudget
 from pathway.internals.runtime_type_check import check_arg_types
 from pathway.internals.schema import Schema
 from pathway.internals.table import Table
@@ -23,6 +24,7 @@ def read(
     csv_settings: CsvParserSettings | None = None,
     mode: str = "streaming",
     autocommit_duration_ms: int | None = 1500,
+    error_budget: ErrorBudget | None = None,
     persistent_id: str | None = None,
     debug_data=None,
     value_columns: list[str] | None = None,
@@ -50,6 +52,9 @@ def read(
         autocommit_duration_ms: the maximum time between two commits. Every
             autocommit_duration_ms milliseconds, the updates received by the connector are
             committed and pushed into Pathway's computation graph.
+        error_budget: the fraction of erroneous entries the connector may produce within a
+            sliding window, and what to do once it is exceeded. See ``pw.io.ErrorBudget``.
+            By default, the errors are handled according to ``terminate_on_error`` of ``pw.run``.
         persistent_id: (unstable) An identifier, under which the state of the table
             will be persisted or ``None``, if there is no need to persist the state of this table.
             When a program restarts, it restores the state for all input tables according to what
@@ -161,6 +166,7 @@ def read(
         csv_settings=csv_settings,
         persistent_id=persistent_id,
         autocommit_duration_ms=autocommit_duration_ms,
+        error_budget=error_budget,
         debug_data=debug_data,
         **kwargs,
     )
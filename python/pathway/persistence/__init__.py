@@ -1,6 +1,7 @@
 # Copyright © 2024 Pathway
 
 import contextlib
+import json
 import os
 from collections.abc import Generator
 from dataclasses import KW_ONLY, dataclass
@@ -68,6 +69,98 @@ as they are used by S3 connectors.
             ),
         )
 
+    @classmethod
+    def gcs(cls, root_path: str, bucket_settings: AwsS3Settings):
+        """
+        Configure the Google Cloud Storage backend. The bucket is accessed via its \
+S3-compatible API.
+
+        Args:
+            root_path: path to the root in the bucket, which will be used to store \
+persisted data;
+            bucket_settings: the settings for the bucket connection. The endpoint \
+should be ``https://storage.googleapis.com`` and the keys should be the HMAC keys \
+of the service account.
+
+        Returns:
+            Class instance denoting the GCS storage backend with root directory as
+            ``root_path`` and connection settings given by ``bucket_settings``.
+        """
+        return cls(
+            api.DataStorage(
+                storage_type="gcs",
+                aws_s3_settings=bucket_settings.settings,
+                path=root_path,
+            ),
+        )
+
+    @classmethod
+    def azure(cls, container_sas_url: str, root_path: str = ""):
+        """
+        Configure the Azure Blob Storage backend.
+
+        Args:
+            container_sas_url: the URL of the container with a SAS token, granting \
+the permissions to list, read, write and delete the blobs;
+            root_path: path to the root in the container, which will be used to store \
+persisted data.
+
+        Returns:
+            Class instance denoting the Azure Blob Storage backend.
+        """
+        return cls(
+            api.DataStorage(
+                storage_type="azure",
+                connection_string=container_sas_url,
+                path=root_path,
+            ),
+        )
+
+    @classmethod
+    def sqlite(cls, path: str | os.PathLike[str]):
+        """
+        Configure the SQLite backend. The persisted data is kept in a table of the \
+database, which can be safely shared by several processes.
+
+        Args:
+            path: the path to the database file.
+
+        Returns:
+            Class instance denoting the SQLite storage backend.
+        """
+        return cls(
+            api.DataStorage(
+                storage_type="sqlite",
+                path=os.fspath(path),
+            ),
+        )
+
+    @classmethod
+    def postgres(cls, connection_string: str, root_path: str = ""):
+        """
+        Configure the Postgres backend. The persisted data is kept in a table of the \
+database and each update is done in a transaction, so the database can be safely \
+shared by several processes.
+
+        Args:
+            connection_string: the connection string of the database. The connection \
+is encrypted with TLS if the server supports it, and the server certificate is checked \
+against the system certificates; use ``sslmode=require`` to make TLS mandatory or \
+``sslmode=disable`` to turn it off;
+            root_path: the prefix of the keys of the persisted data, which allows \
+several programs to share the database.
+
+        Returns:
+            Class instance denoting the Postgres storage backend.
+        """
+        return cls(
+            api.DataStorage(
+                storage_type="postgres",
+                connection_string=connection_string,
+                path=root_path,
+            ),
+        )
+
     @classmethod
     def mock(cls, events: dict[tuple[str, int], list[api.SnapshotEvent]]):
         return cls(api.DataStorage(storage_type="mock", mock_events=events))
@@ -99,6 +192,32 @@ class Config:
         snapshot_storage: snapshots backend configuration;
         snapshot_interval_ms: the desired duration between snapshot updates in \
 milliseconds;
+        snapshot_compaction_interval_ms: if set, the input snapshots are periodically \
+compacted into the net set of rows, so that the restarts don't replay the whole history. \
+The value is the desired duration between compactions in milliseconds;
+        checkpoint_operator_state: if set, the arranged inputs of stateful operators, such \
+as groupbys and joins, are checkpointed, so that after a restart their state is loaded \
+and the input connectors continue from the stored offsets instead of replaying the whole \
+input history. The checkpoints are compacted into the consolidated state periodically, \
+every ``snapshot_compaction_interval_ms`` or every minute if it's not set. Temporal operators, such as windows and buffers, and iterations are not \
+supported in this mode;
+        encryption_key_file: the file with the hexadecimal AES-256 keys, one per line, \
+used to encrypt the snapshots and the metadata. The first key is used for encryption, \
+the others are the previous keys, only needed to decrypt the data that hasn't been \
+re-encrypted yet, which happens on compaction. If not given, the keys are taken from \
+the ``PATHWAY_PERSISTENCE_ENCRYPTION_KEY`` environment variable, separated by commas, \
+or from the file given by ``PATHWAY_PERSISTENCE_ENCRYPTION_KEY_FILE``. If there are no \
+keys, the persisted data is not encrypted. The data persisted before the encryption \
+was enabled is rejected until it's encrypted with :py:meth:`PersistenceInspector.migrate`;
+        rollback_to_timestamp: if set, the computation is restarted as of this earlier \
+finalized time instead of the latest one: the snapshots are truncated at it and the \
+data following it is processed and output again. The finalized times are listed by \
+``Inspector.finalized_timestamps``. The time can't be earlier than the time the \
+snapshots were compacted up to. The applied rollback is recorded in the metadata, so \
+the later starts with the same setting continue from the latest finalized time;
+        rollback_generation: the identifier of the rollback given by \
+``rollback_to_timestamp``. Each rollback is applied once, so rolling back to the same \
+time again requires a new generation.
     """
 
     _: KW_ONLY
@@ -108,6 +227,11 @@ milliseconds;
     snapshot_access: api.SnapshotAccess
     persistence_mode: api.PersistenceMode
     continue_after_replay: bool
+    snapshot_compaction_interval_ms: int | None = None
+    checkpoint_operator_state: bool = False
+    encryption_key_file: str | os.PathLike | None = None
+    rollback_to_timestamp: int | None = None
+    rollback_generation: int = 0
 
     @classmethod
     def simple_config(
@@ -117,6 +241,11 @@ milliseconds;
         snapshot_access=api.SnapshotAccess.FULL,
         persistence_mode=api.PersistenceMode.PERSISTING,
         continue_after_replay=True,
+        snapshot_compaction_interval_ms=None,
+        checkpoint_operator_state=False,
+        encryption_key_file=None,
+        rollback_to_timestamp=None,
+        rollback_generation=0,
     ):
         """
         Construct config from a single instance of the \
@@ -140,6 +269,11 @@ may fall behind, and the less computational resources are required.
             snapshot_access=snapshot_access,
             persistence_mode=persistence_mode,
             continue_after_replay=continue_after_replay,
+            snapshot_compaction_interval_ms=snapshot_compaction_interval_ms,
+            checkpoint_operator_state=checkpoint_operator_state,
+            encryption_key_file=encryption_key_file,
+            rollback_to_timestamp=rollback_to_timestamp,
+            rollback_generation=rollback_generation,
         )
 
     @property
@@ -151,6 +285,11 @@ may fall behind, and the less computational resources are required.
             snapshot_access=self.snapshot_access,
             persistence_mode=self.persistence_mode,
             continue_after_replay=self.continue_after_replay,
+            snapshot_compaction_interval_ms=self.snapshot_compaction_interval_ms,
+            checkpoint_operator_state=self.checkpoint_operator_state,
+            encryption_key_file=self.encryption_key_file,
+            rollback_to_timestamp=self.rollback_to_timestamp,
+            rollback_generation=self.rollback_generation,
         )
 
     def on_before_run(self):
@@ -160,6 +299,128 @@ may fall behind, and the less computational resources are required.
         self.snapshot_storage.remove_path_from_env_variable()
 
 
+class Inspector:
+    """
+    Offline access to the persisted state, which helps to investigate the problems with
+    persistence. It lists the snapshot streams, dumps their events and the metadata, and
+    rolls the streams back to an earlier time, for example, to undo a bad deploy.
+
+    The state can't be modified while a program with the same persistence config is
+    running: each running worker keeps a lock in the metadata storage, which is removed
+    when the program stops.
+
+    Args:
+        metadata_storage: metadata backend configuration;
+        snapshot_storage: snapshots backend configuration. If not given, the metadata \
+backend is used.
+        encryption_key_file: the file with the encryption keys, the same as in \
+``Config``. If not given, the keys are taken from the environment.
+        force: whether to modify the state in spite of the locks. A program that has \
+crashed leaves its locks behind, so this is needed to repair its state.
+    """
+
+    def __init__(
+        self,
+        metadata_storage: Backend,
+        snapshot_storage: Backend | None = None,
+        encryption_key_file: str | os.PathLike | None = None,
+        force: bool = False,
+    ):
+        if snapshot_storage is None:
+            snapshot_storage = metadata_storage
+        self._inspector = api.PersistenceInspector(
+            metadata_storage=metadata_storage.engine_data_storage,
+            stream_storage=snapshot_storage.engine_data_storage,
+            encryption_key_file=encryption_key_file,
+            force=force,
+        )
+
+    @staticmethod
+    def _internal_persistent_id(persistent_id: str | int) -> int:
+        if isinstance(persistent_id, str):
+            return api.PersistenceInspector.internal_persistent_id(persistent_id)
+        return persistent_id
+
+    def streams(self) -> list[tuple[int, int]]:
+        """
+        Returns the persisted snapshot streams as pairs of the internal persistent id
+        and the id of the worker that has written the stream.
+        """
+        return self._inspector.snapshot_streams()
+
+    def metadata(self) -> dict[int, dict]:
+        """Returns the latest metadata block of each worker."""
+        return {
+            worker_id: json.loads(block)
+            for worker_id, block in self._inspector.metadata().items()
+        }
+
+    def finalized_timestamps(self) -> dict[int, int | api.Done]:
+        """Returns the time finalized by each worker, according to the metadata."""
+        return self._inspector.finalized_timestamps()
+
+    def events(self, persistent_id: str | int, worker_id: int) -> list[dict]:
+        """
+        Returns the events of the snapshot stream. The persistent id can be given
+        either as the name of the data source or as the internal id.
+        """
+        return [
+            json.loads(event)
+            for event in self._inspector.dump_events(
+                self._internal_persistent_id(persistent_id), worker_id
+            )
+        ]
+
+    def truncate(
+        self, persistent_id: str | int, time: int, worker_id: int | None = None
+    ) -> dict[int, int]:
+        """
+        Removes the events following the first time advancement not earlier than
+        ``time`` from the snapshot streams of the data source. If ``worker_id`` is not
+        given, the streams of all workers are truncated. The finalized times in the
+        metadata are rolled back to ``time`` too, so the next run processes the data
+        and produces the outputs after it again.
+
+        Returns:
+            The number of events left in each of the truncated streams.
+        """
+        internal_id = self._internal_persistent_id(persistent_id)
+        worker_ids = (
+            [worker_id]
+            if worker_id is not None
+            else [
+                stream_worker_id
+                for stream_id, stream_worker_id in self.streams()
+                if stream_id == internal_id
+            ]
+        )
+        return {
+            worker_id: self._inspector.truncate_stream(internal_id, worker_id, time)
+            for worker_id in worker_ids
+        }
+
+    def migrate(self) -> dict[tuple[int, int], int]:
+        """
+        Rewrites the snapshot chunks written before the framed format with checksums
+        was introduced. The old chunks are readable as they are, but they have no
+        protection against damaged or partially written data. If the encryption is
+        enabled, the chunks that aren't encrypted with the current key are rewritten
+        as well, and the metadata stored unencrypted is encrypted. Until then, the
+        unencrypted data is rejected.
+
+        Returns:
+            The number of rewritten chunks for each pair of the internal persistent id
+            and the worker id.
+        """
+        self._inspector.migrate_metadata()
+        return {
+            (persistent_id, worker_id): self._inspector.migrate_stream(
+                persistent_id, worker_id
+            )
+            for persistent_id, worker_id in self.streams()
+        }
+
+
 @contextlib.contextmanager
 def get_persistence_engine_config(
     persistence_config: Config | None,
@@ -31,17 +31,24 @@ from pathway.internals.reducers import (
     argmin,
     avg,
     count,
+    count_distinct,
+    count_distinct_approximate,
     earliest,
     int_sum,
     latest,
     max,
+    mean,
     min,
     ndarray,
     npsum,
+    quantile,
     sorted_tuple,
+    stddev,
     sum,
+    top_k,
     tuple,
     unique,
+    variance,
 )
 
 __all__ = [
@@ -50,18 +57,25 @@ __all__ = [
     "argmin",
     "avg",
     "count",
+    "count_distinct",
+    "count_distinct_approximate",
     "earliest",
     "int_sum",
     "latest",
     "max",
+    "mean",
     "min",
     "ndarray",
     "npsum",
+    "quantile",
     "sorted_tuple",
     "stateful_many",
     "stateful_single",
+    "stddev",
     "sum",
+    "top_k",
     "tuple",
     "udf_reducer",
     "unique",
+    "variance",
 ]
@@ -4,7 +4,11 @@
 
 from __future__ import annotations
 
-from pathway.engine import BruteForceKnnMetricKind, USearchMetricKind
+from pathway.engine import (
+    BruteForceKnnMetricKind,
+    USearchMetricKind,
+    USearchScalarKind,
+)
 
 from .bm25 import TantivyBM25, TantivyBM25Factory
 from .data_index import DataIndex
@@ -39,6 +43,7 @@ __all__ = [
     "USearchKnn",
     "UsearchKnnFactory",
     "USearchMetricKind",
+    "USearchScalarKind",
     "BruteForceKnn",
     "BruteForceKnnFactory",
     "BruteForceKnnMetricKind",
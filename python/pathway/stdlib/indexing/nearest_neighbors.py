@@ -9,9 +9,11 @@ from pathway.engine import (
     BruteForceKnnMetricKind,
     ExternalIndexFactory,
     USearchMetricKind,
+    USearchScalarKind,
 )
 from pathway.internals import dtype as dt
 from pathway.internals.runtime_type_check import check_arg_types
+from pathway.internals.type_interpreter import eval_type
 from pathway.internals.udfs.utils import _coerce_sync
 from pathway.stdlib.indexing.colnames import _INDEX_REPLY, _NO_OF_MATCHES, _QUERY_ID
 from pathway.stdlib.indexing.data_index import InnerIndex
@@ -25,11 +27,19 @@ from pathway.stdlib.ml.utils import _predict_asof_now
 
 
 def check_default_knn_column_types(
-    data_column, query_column, number_of_matches, metadata_column, metadata_filter
+    data_column,
+    query_column,
+    number_of_matches,
+    metadata_column,
+    metadata_filter,
+    *,
+    vector_type: dt.DType = dt.List(dt.FLOAT),
+    multi: bool = False,
 ):
+    data_type = dt.List(vector_type) if multi else vector_type
     typecheck_list: list[Tuple[str, Tuple[pw.ColumnExpression, dt.DType]]] = [
-        ("data column", (data_column, dt.List(dt.FLOAT))),
-        ("query column", (query_column, dt.List(dt.FLOAT))),
+        ("data column", (data_column, data_type)),
+        ("query column", (query_column, vector_type)),
     ]
 
     if metadata_column is not None:
@@ -87,6 +97,12 @@ class USearchKnn(InnerIndex):
             0 tells usearch to configure it on its own
         embedder: :py:class:`~pathway.UDF` used for calculating embeddings of string. It is needed, if index
             is used for indexing texts.
+        quantization (USearchScalarKind): scalar type used to store vectors in the index.
+            With ``USearchScalarKind.B1`` the data and queries are binary embeddings
+            (``bytes``), and ``dimensions`` is the number of bits.
+        multi (bool): if set to True, each entry of the data column is a list of vectors
+            (e.g. embeddings of document chunks); an entry is returned at most once, with
+            the score of its best matching vector.
 
     """
 
@@ -98,6 +114,8 @@ class USearchKnn(InnerIndex):
     expansion_add: int = 0
     expansion_search: int = 0
     embedder: pw.UDF | None = None
+    quantization: USearchScalarKind = USearchScalarKind.F16
+    multi: bool = False
 
     # data column after applying embeddings. It is calculated during initialization and
     # cannot be set in the constructor.
@@ -136,6 +154,12 @@ class USearchKnn(InnerIndex):
             number_of_matches,
             self.metadata_column,
             metadata_filter,
+            vector_type=(
+                dt.BYTES
+                if self.quantization == USearchScalarKind.B1
+                else dt.List(dt.FLOAT)
+            ),
+            multi=self.multi,
         )
 
         index_factory = ExternalIndexFactory.usearch_knn_factory(
@@ -145,6 +169,8 @@ class USearchKnn(InnerIndex):
             connectivity=self.connectivity,
             expansion_add=self.expansion_add,
             expansion_search=self.expansion_search,
+            quantization=self.quantization,
+            multi=self.multi,
         )
 
         number_of_matches_ref = number_of_matches
@@ -181,7 +207,9 @@ class BruteForceKnn(InnerIndex):
             case ``auxiliary_space`` is set to a value smaller than the current number
             of entries in the index, it is still proportional to
             the size of the index (the value given in this parameter is ignored)
-        metric (BruteForceKnnMetricKind): metric kind that is used to determine distance
+        metric (BruteForceKnnMetricKind): metric kind that is used to determine distance.
+            With ``BruteForceKnnMetricKind.HAMMING`` the data and queries can also be
+            binary embeddings (``bytes``), with ``dimensions`` being the number of bits.
         embedder: :py:class:`~pathway.UDF` used for calculating embeddings of string. It is needed, if index
             is used for indexing texts.
 
@@ -230,6 +258,12 @@ class BruteForceKnn(InnerIndex):
             number_of_matches,
             self.metadata_column,
             metadata_filter,
+            vector_type=(
+                dt.BYTES
+                if self.metric == BruteForceKnnMetricKind.HAMMING
+                and eval_type(self._data_column) == dt.BYTES
+                else dt.List(dt.FLOAT)
+            ),
         )
 
         index_factory = ExternalIndexFactory.brute_force_knn_factory(
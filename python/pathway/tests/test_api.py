@@ -528,6 +528,261 @@ def test_join_inner_simple(event_loop):
     assert_equal_tables_wo_index(ret, expected)
 
 
+def _semi_anti_join(s, *, semi=False, anti=False):
+    left = static_table_from_md(
+        s,
+        """
+        k   a
+        1  1  10
+        2  2  20
+        3  3  30
+        4  3  40
+        """,
+        legacy=False,
+    )
+    right = static_table_from_md(
+        s,
+        """
+        k    b
+        11  1  100
+        12  1  200
+        13  3  300
+        14  5  500
+        """,
+        legacy=False,
+    )
+    return s.join_tables(
+        left,
+        right,
+        [column_path.ColumnPath((0,))],
+        [column_path.ColumnPath((0,))],
+        last_column_is_instance=False,
+        table_properties=api.TableProperties.column(
+            api.ColumnProperties(dtype=api.PathwayType.ANY)
+        ),
+        semi=semi,
+        anti=anti,
+    )
+
+
+def test_join_semi(event_loop):
+    def build(s):
+        ret = _semi_anti_join(s, semi=True)
+        expected = static_table_from_md(
+            s,
+            """
+            k   a
+            1  1  10
+            3  3  30
+            4  3  40
+            """,
+            legacy=False,
+        )
+        return [
+            (ret, [column_path.ColumnPath((1, 0)), column_path.ColumnPath((1, 1))]),
+            (expected, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+        ]
+
+    ret, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables_wo_index(ret, expected)
+
+
+def test_join_anti(event_loop):
+    def build(s):
+        ret = _semi_anti_join(s, anti=True)
+        expected = static_table_from_md(
+            s,
+            """
+            k   a
+            2  2  20
+            """,
+            legacy=False,
+        )
+        return [
+            (ret, [column_path.ColumnPath((1, 0)), column_path.ColumnPath((1, 1))]),
+            (expected, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+        ]
+
+    ret, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables_wo_index(ret, expected)
+
+
+def test_join_semi_keeps_left_ids(event_loop):
+    def build(s):
+        ret = _semi_anti_join(s, semi=True)
+        return [(ret, [column_path.ColumnPath((0,)), column_path.ColumnPath((2,))])]
+
+    [ret] = api.run_with_new_graph(build, event_loop)
+
+    for key, (left_id, right_id) in api.squash_updates(ret).items():
+        assert left_id == key
+        assert right_id is None
+
+
+def test_top_n_table(event_loop):
+    def build(s):
+        table = static_table_from_md(
+            s,
+            """
+            g   v
+            1  1  5
+            2  1  3
+            3  1  8
+            4  1  9
+            5  2  7
+            6  2  4
+            """,
+            legacy=False,
+        )
+        ret = s.top_n_table(
+            table,
+            column_path.ColumnPath((1,)),
+            column_path.ColumnPath((0,)),
+            2,
+            api.TableProperties.column(
+                api.ColumnProperties(dtype=api.PathwayType.ANY)
+            ),
+            offset=1,
+            descending=True,
+        )
+        expected = static_table_from_md(
+            s,
+            """
+            g   v
+            1  1  5
+            3  1  8
+            6  2  4
+            """,
+            legacy=False,
+        )
+        return [
+            (ret, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+            (expected, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+        ]
+
+    ret, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables(ret, expected)
+
+
+def _temporal_join(s, left_md, right_md, **kwargs):
+    left = static_table_from_md(s, left_md, legacy=False)
+    right = static_table_from_md(s, right_md, legacy=False)
+    return s.join_tables(
+        left,
+        right,
+        [column_path.ColumnPath((0,))],
+        [column_path.ColumnPath((0,))],
+        last_column_is_instance=False,
+        table_properties=api.TableProperties.column(
+            api.ColumnProperties(dtype=api.PathwayType.ANY)
+        ),
+        left_time_column_path=column_path.ColumnPath((1,)),
+        right_time_column_path=column_path.ColumnPath((1,)),
+        **kwargs,
+    )
+
+
+def test_join_asof_backward(event_loop):
+    def build(s):
+        ret = _temporal_join(
+            s,
+            """
+            k  t   a
+            1  1  3  10
+            2  1  6  20
+            3  2  8  30
+            """,
+            """
+            k  t    b
+            11  1  1  100
+            12  1  5  200
+            13  2  7  300
+            14  2  9  400
+            """,
+            asof_direction="backward",
+        )
+        expected = static_table_from_md(
+            s,
+            """
+            a    b
+            1  10  100
+            2  20  200
+            3  30  300
+            """,
+            legacy=False,
+        )
+        return [
+            (ret, [column_path.ColumnPath((1, 2)), column_path.ColumnPath((3, 2))]),
+            (expected, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+        ]
+
+    ret, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables_wo_index(ret, expected)
+
+
+def test_join_interval(event_loop):
+    def build(s):
+        ret = _temporal_join(
+            s,
+            """
+            k  t   a
+            1  1  3  10
+            2  1  6  20
+            3  2  3  30
+            """,
+            """
+            k  t    b
+            11  1  2  100
+            12  1  4  200
+            13  1  9  300
+            14  2  5  400
+            """,
+            interval=(-1, 1),
+        )
+        expected = static_table_from_md(
+            s,
+            """
+            a    b
+            1  10  100
+            2  10  200
+            """,
+            legacy=False,
+        )
+        return [
+            (ret, [column_path.ColumnPath((1, 2)), column_path.ColumnPath((3, 2))]),
+            (expected, [column_path.ColumnPath((0,)), column_path.ColumnPath((1,))]),
+        ]
+
+    ret, expected = api.run_with_new_graph(build, event_loop)
+
+    assert_equal_tables_wo_index(ret, expected)
+
+
+def test_join_temporal_rejects_regular_join_options(event_loop):
+    def build(s):
+        _temporal_join(
+            s,
+            """
+            k  t
+            1  1  3
+            """,
+            """
+            k  t
+            11  1  2
+            """,
+            asof_direction="backward",
+            left_ear=True,
+        )
+        return []
+
+    with pytest.raises(ValueError, match="left_ear"):
+        api.run_with_new_graph(build, event_loop)
+
+
 @pytest.mark.xfail(reason="needs to be adjusted to new API")
 def test_join_on_id(event_loop):
     def build(s):
@@ -9,7 +9,7 @@ import urllib
 import pytest
 
 import pathway as pw
-from pathway.internals import graph_runner
+from pathway.internals import api, graph_runner
 from pathway.internals.parse_graph import G
 from pathway.tests.utils import T
 
@@ -64,3 +64,20 @@ def test_http_server_doesnt_run_when_disabled():
         G, with_http_server=False, monitoring_level=pw.MonitoringLevel.NONE
     ).run_tables(response_code)[0]
     assert updates_stream[0].values[0] == -1
+
+
+def test_http_server_rejects_invalid_host():
+    table = T(
+        """
+            | foo
+        1   | 42
+        """
+    )
+
+    with pytest.raises(api.EngineError, match="invalid monitoring http host"):
+        graph_runner.GraphRunner(
+            G,
+            with_http_server=True,
+            monitoring_http_host="localhost:20000",
+            monitoring_level=pw.MonitoringLevel.NONE,
+        ).run_tables(table)
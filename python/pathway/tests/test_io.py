@@ -1000,7 +1000,10 @@ def test_table_from_pandas_modify_dataframe():
     )
 
 
-def test_python_connector_persistence(tmp_path: pathlib.Path):
+@pytest.mark.parametrize("checkpoint_operator_state", [False, True])
+def test_python_connector_persistence(
+    tmp_path: pathlib.Path, checkpoint_operator_state: bool
+):
     persistent_storage_path = tmp_path / "PStorage"
     input_path = tmp_path / "input.txt"
     output_path = tmp_path / "output.txt"
@@ -1028,6 +1031,7 @@ def test_python_connector_persistence(tmp_path: pathlib.Path):
         run(
             persistence_config=pw.persistence.Config.simple_config(
                 pw.persistence.Backend.filesystem(persistent_storage_path),
+                checkpoint_operator_state=checkpoint_operator_state,
             )
         )
 
@@ -1060,6 +1064,71 @@ def test_python_connector_persistence(tmp_path: pathlib.Path):
     assert set(result["data"]) == {"three", "four"}
 
 
+def test_operator_state_checkpointing(tmp_path: pathlib.Path):
+    persistent_storage_path = tmp_path / "PStorage"
+    output_path = tmp_path / "output.csv"
+
+    class TestSubject(pw.io.python.ConnectorSubject):
+        def __init__(self, items):
+            super().__init__()
+            self.items = items
+
+        def run(self):
+            for item in self.items:
+                self.next_str(item)
+
+    def run_computation(items):
+        G.clear()
+        table = pw.io.python.read(TestSubject(items), format="raw", persistent_id="1")
+        counts = table.groupby(pw.this.data).reduce(
+            pw.this.data, count=pw.reducers.count()
+        )
+        pw.io.csv.write(counts, output_path)
+        run(
+            persistence_config=pw.persistence.Config.simple_config(
+                pw.persistence.Backend.filesystem(persistent_storage_path),
+                checkpoint_operator_state=True,
+            )
+        )
+
+    def inserted_rows():
+        result = pd.read_csv(output_path)
+        result = result[result["diff"] == 1]
+        return set(zip(result["data"], result["count"]))
+
+    run_computation(["a", "b", "a"])
+    assert inserted_rows() == {("a", 2), ("b", 1)}
+
+    # The rows of the first run are not replayed by the connector, the state
+    # of the groupby is loaded from its checkpoint instead.
+    run_computation(["a"])
+    assert inserted_rows() == {("a", 3)}
+
+    run_computation([])
+    assert inserted_rows() == set()
+
+
+def test_operator_state_checkpointing_rejects_buffer(tmp_path: pathlib.Path):
+    table = T(
+        """
+        t
+        1
+        """
+    )
+    result = table._buffer(pw.this.t + 1, pw.this.t)
+    pw.io.csv.write(result, tmp_path / "output.csv")
+    with pytest.raises(
+        api.EngineError,
+        match="buffer is not supported when operator state is checkpointed",
+    ):
+        run(
+            persistence_config=pw.persistence.Config.simple_config(
+                pw.persistence.Backend.filesystem(tmp_path / "PStorage"),
+                checkpoint_operator_state=True,
+            )
+        )
+
+
 def test_no_pstorage(tmp_path: pathlib.Path):
     input_path = tmp_path / "input.txt"
     output_path = tmp_path / "input.txt"
@@ -1161,6 +1230,26 @@ def test_immediate_connector_errors():
     assert not subject.timed_out
 
 
+def test_error_budget_terminates_on_too_many_errors(tmp_path: pathlib.Path):
+    input_path = tmp_path / "input.jsonl"
+    write_lines(
+        input_path, ['{"a": 1}', "not json", "not json", '{"a": 2}', "not json"]
+    )
+
+    class InputSchema(pw.Schema):
+        a: int
+
+    table = pw.io.jsonlines.read(
+        input_path,
+        schema=InputSchema,
+        mode="static",
+        error_budget=pw.io.ErrorBudget(max_error_rate=0.5, min_entries=4),
+    )
+    pw.io.null.write(table)
+    with pytest.raises(api.EngineError, match="exceeded its error budget"):
+        run_all(terminate_on_error=False)
+
+
 def run_replacement_test(
     streaming_target,
     input_format,
@@ -75,6 +75,37 @@ impl ParsedEventWithErrors {
             }
         }
     }
+    fn key_and_values(&self) -> (Option<&KeyFieldsWithErrors>, Option<&ValueFieldsWithErrors>) {
+        match self {
+            Self::AdvanceTime => (None, None),
+            Self::Insert((key, values)) | Self::Delete((key, values)) => (Some(key), Some(values)),
+            Self::Upsert((key, values)) => (Some(key), values.as_ref()),
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        let (key, values) = self.key_and_values();
+        matches!(key, Some(Some(Err(_))))
+            || values.is_some_and(|values| values.iter().any(Result::is_err))
+    }
+
+    /// Returns the messages of the errors in the key and in the values.
+    pub fn error_messages(&self) -> Vec<String> {
+        let (key, values) = self.key_and_values();
+        let key_error = key
+            .and_then(Option::as_ref)
+            .and_then(|key| key.as_ref().err());
+        let value_errors = values
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_ref().err());
+        key_error
+            .into_iter()
+            .chain(value_errors)
+            .map(ToString::to_string)
+            .collect()
+    }
+
     pub fn remove_errors(self, logic: &ErrorRemovalLogic) -> DynResult<ParsedEvent> {
         match self {
             Self::AdvanceTime => Ok(ParsedEvent::AdvanceTime),
@@ -264,7 +295,7 @@ pub trait Parser: Send {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FormatterContext {
     pub payloads: Vec<Vec<u8>>,
     pub key: Key,
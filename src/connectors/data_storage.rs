@@ -51,7 +51,9 @@ use crate::engine::Type;
 use crate::engine::Value;
 use crate::engine::{DateTimeNaive, DateTimeUtc, Duration as EngineDuration};
 use crate::fs_helpers::ensure_directory;
+use crate::persistence::encryption::Error as EncryptionError;
 use crate::persistence::frontier::OffsetAntichain;
+use crate::persistence::object_store::Error as ObjectStoreError;
 use crate::persistence::{ExternalPersistentId, PersistentId};
 use crate::python_api::extract_value;
 use crate::python_api::threads::PythonThreadState;
@@ -259,6 +261,9 @@ pub enum ReadError {
     #[error("failed to perform Sqlite request: {0}")]
     Sqlite(#[from] SqliteError),
 
+    #[error("failed to perform object store request: {0}")]
+    ObjectStore(#[from] ObjectStoreError),
+
     #[error(transparent)]
     DeltaTable(#[from] DeltaTableError),
 
@@ -277,6 +282,21 @@ pub enum ReadError {
     #[error("malformed data")]
     MalformedData,
 
+    #[error("snapshot chunk ends with an incomplete entry")]
+    TruncatedSnapshot,
+
+    #[error("snapshot entry checksum mismatch")]
+    SnapshotChecksumMismatch,
+
+    #[error("unsupported snapshot format version: {0}")]
+    UnsupportedSnapshotVersion(u32),
+
+    #[error("snapshot schema fingerprint {found:#x} doesn't match the expected {expected:#x}")]
+    IncompatibleSnapshotSchema { found: u64, expected: u64 },
+
+    #[error("failed to decrypt persisted data: {0}")]
+    Encryption(#[from] EncryptionError),
+
     #[error("no objects to read")]
     NoObjectsToRead,
 
@@ -488,6 +508,9 @@ pub enum WriteError {
     #[error("failed to perform write in postgres: {0}")]
     Postgres(#[from] postgres::Error),
 
+    #[error("failed to perform object store request: {0}")]
+    ObjectStore(#[from] ObjectStoreError),
+
     #[error(transparent)]
     Utf8(#[from] Utf8Error),
 
@@ -0,0 +1,257 @@
+// Copyright © 2024 Pathway
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{Builder, JoinHandle};
+
+use crossbeam_channel::{bounded, Receiver, Sender, TryRecvError};
+use itertools::Itertools;
+use log::error;
+
+use crate::connectors::data_format::Formatter;
+use crate::connectors::data_storage::{ReaderContext, Writer};
+use crate::connectors::Offset;
+use crate::engine::error::DynError;
+use crate::engine::{Key, Timestamp, Value};
+use crate::retry::{execute_with_retries, RetryConfig};
+use crate::timestamp::current_unix_timestamp_ms;
+
+const WRITE_RETRIES: usize = 5;
+
+/// The number of records waiting to be written, above which sending
+/// more records blocks until the writer catches up.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Separates the messages of the errors found in a single row.
+const MESSAGE_SEPARATOR: &str = "\n";
+
+/// Names of the columns of the dead-letter records, in the order in which
+/// their values are passed to the formatter.
+pub const DEAD_LETTER_COLUMNS: [&str; 6] = [
+    "source",
+    "offset",
+    "payload",
+    "operator_id",
+    "trace",
+    "message",
+];
+
+/// A rejected input row or a data error raised by an operator.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct DeadLetterRecord {
+    /// Name of the input connector, or `None` for the errors raised by operators.
+    pub source: Option<String>,
+    pub offset: Option<Offset>,
+    /// The raw data, as read from the source, so that the record can be replayed.
+    pub payload: Value,
+    pub operator_id: Option<i64>,
+    pub trace: Option<String>,
+    pub message: String,
+}
+
+impl DeadLetterRecord {
+    pub fn rejected_input(
+        source: String,
+        reader_context: Option<&ReaderContext>,
+        offset: Option<&Offset>,
+        message: String,
+    ) -> Self {
+        Self {
+            source: Some(source),
+            offset: offset.cloned(),
+            payload: reader_context.map_or(Value::None, raw_payload),
+            operator_id: None,
+            trace: None,
+            message,
+        }
+    }
+
+    pub fn data_error(operator_id: Option<i64>, trace: Option<String>, message: String) -> Self {
+        Self {
+            source: None,
+            offset: None,
+            payload: Value::None,
+            operator_id,
+            trace,
+            message,
+        }
+    }
+
+    fn values(self) -> Vec<Value> {
+        let offset = self.offset.map_or(Value::None, |offset| {
+            serde_json::to_value(offset).map_or(Value::None, Value::from)
+        });
+        vec![
+            self.source
+                .map_or(Value::None, |source| Value::from(source.as_str())),
+            offset,
+            self.payload,
+            self.operator_id.map_or(Value::None, Value::from),
+            self.trace
+                .map_or(Value::None, |trace| Value::from(trace.as_str())),
+            Value::from(self.message.as_str()),
+        ]
+    }
+}
+
+fn raw_payload(reader_context: &ReaderContext) -> Value {
+    match reader_context {
+        ReaderContext::RawBytes(_, bytes) => Value::from(bytes.as_slice()),
+        ReaderContext::TokenizedEntries(_, tokens) => tokens
+            .iter()
+            .map(|token| Value::from(token.as_str()))
+            .collect::<Vec<_>>()
+            .into(),
+        ReaderContext::KeyValue((key, value)) => vec![
+            key.as_deref().map_or(Value::None, Value::from),
+            value.as_deref().map_or(Value::None, Value::from),
+        ]
+        .into(),
+        ReaderContext::Diff((_, _, values)) => Value::from(format!("{values:?}").as_str()),
+        ReaderContext::Empty => Value::None,
+    }
+}
+
+/// Returns the path of the file written by the process `process_id` when
+/// the computation runs in multiple processes, e.g. `errors-1.jsonl`
+/// for `errors.jsonl`.
+pub fn process_file_path(path: &Path, process_id: usize) -> PathBuf {
+    let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(format!("-{process_id}"));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name)
+}
+
+/// The records that could not be processed, sent to a configured `Writer`.
+///
+/// The records are formatted and written in a separate thread, so that a slow
+/// destination doesn't block the workers for long. The queue is bounded,
+/// so sending blocks once the writer falls behind by [`QUEUE_CAPACITY`] records.
+/// Its columns are listed in [`DEAD_LETTER_COLUMNS`].
+#[allow(clippy::module_name_repetitions)]
+pub struct DeadLetterQueue {
+    sender: Mutex<Option<Sender<DeadLetterRecord>>>,
+    writer_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(writer: Box<dyn Writer>, formatter: Box<dyn Formatter>) -> Self {
+        let (sender, receiver) = bounded(QUEUE_CAPACITY);
+        let writer_thread = Builder::new()
+            .name(format!(
+                "pathway:dead_letter-{}",
+                writer.short_description()
+            ))
+            .spawn(move || Self::run_writer(writer, formatter, &receiver))
+            .expect("dead-letter writer thread creation failed");
+        Self {
+            sender: Mutex::new(Some(sender)),
+            writer_thread: Mutex::new(Some(writer_thread)),
+        }
+    }
+
+    fn run_writer(
+        mut writer: Box<dyn Writer>,
+        mut formatter: Box<dyn Formatter>,
+        receiver: &Receiver<DeadLetterRecord>,
+    ) {
+        let retries = if writer.retriable() { WRITE_RETRIES } else { 1 };
+        loop {
+            let record = match receiver.try_recv() {
+                Ok(record) => record,
+                Err(TryRecvError::Empty) => {
+                    // flush once the queue is drained, then wait for more records
+                    if let Err(e) = writer.flush(false) {
+                        error!("Failed to flush the dead-letter sink: {e}");
+                    }
+                    match receiver.recv() {
+                        Ok(record) => record,
+                        Err(_) => break,
+                    }
+                }
+                Err(TryRecvError::Disconnected) => break,
+            };
+            let time = Timestamp(
+                u64::try_from(current_unix_timestamp_ms())
+                    .expect("number of milliseconds should fit in 64 bits"),
+            );
+            let output = match formatter.format(&Key::random(), &record.values(), time, 1) {
+                Ok(output) => output,
+                Err(e) => {
+                    error!("Failed to format a record for the dead-letter sink: {e}");
+                    continue;
+                }
+            };
+            // only the writes can succeed when repeated
+            let result = execute_with_retries(
+                || writer.write(output.clone()).map_err(DynError::from),
+                RetryConfig::default(),
+                retries,
+            );
+            if let Err(e) = result {
+                error!("Failed to write a record to the dead-letter sink: {e}");
+            }
+        }
+        if let Err(e) = writer.flush(true) {
+            error!("Failed to flush the dead-letter sink: {e}");
+        }
+    }
+
+    /// Sends the record to the writer thread, waiting while the queue is full.
+    pub fn send(&self, record: DeadLetterRecord) {
+        // the lock isn't held while waiting, so that the other senders aren't blocked on it
+        let sender = self.sender.lock().unwrap().clone();
+        if let Some(sender) = sender {
+            if let Err(e) = sender.send(record) {
+                error!("Failed to send a record to the dead-letter sink: {e}");
+            }
+        }
+    }
+
+    /// Writes the remaining records and stops the writer thread.
+    pub fn close(&self) {
+        drop(self.sender.lock().unwrap().take());
+        if let Some(writer_thread) = self.writer_thread.lock().unwrap().take() {
+            writer_thread
+                .join()
+                .expect("dead-letter writer thread should not panic");
+        }
+    }
+}
+
+/// Sends the rejected parts of a single input entry to the dead-letter queue.
+#[derive(Clone)]
+pub struct RejectedInput {
+    queue: Arc<DeadLetterQueue>,
+    record: DeadLetterRecord,
+}
+
+impl RejectedInput {
+    pub fn new(
+        queue: Arc<DeadLetterQueue>,
+        source: String,
+        reader_context: Option<&ReaderContext>,
+        offset: Option<&Offset>,
+    ) -> Self {
+        let record =
+            DeadLetterRecord::rejected_input(source, reader_context, offset, String::new());
+        Self { queue, record }
+    }
+
+    pub fn reject(&self, error: impl Display) {
+        self.reject_all([error]);
+    }
+
+    /// Sends a single record with the messages of all errors found in the entry.
+    pub fn reject_all(&self, errors: impl IntoIterator<Item = impl Display>) {
+        self.queue.send(DeadLetterRecord {
+            message: errors.into_iter().join(MESSAGE_SEPARATOR),
+            ..self.record.clone()
+        });
+    }
+}
@@ -3,7 +3,9 @@
 use differential_dataflow::input::InputSession;
 use itertools::Itertools;
 use log::{error, info, warn};
+use opentelemetry::KeyValue;
 use std::cell::RefCell;
+use std::cmp::min;
 use std::env;
 use std::ops::ControlFlow;
 use std::rc::Rc;
@@ -19,20 +21,26 @@ use timely::dataflow::operators::probe::Handle;
 pub mod adaptors;
 pub mod data_format;
 pub mod data_storage;
+pub mod dead_letter;
 pub mod metadata;
 pub mod monitoring;
 pub mod offset;
 pub mod snapshot;
+pub mod snapshot_format;
 
-use crate::connectors::monitoring::ConnectorMonitor;
+use crate::connectors::dead_letter::{DeadLetterQueue, RejectedInput};
+use crate::connectors::monitoring::{
+    ConnectorMonitor, ErrorBudget, ErrorBudgetDecision, ErrorBudgetTracker,
+};
 use crate::engine::error::{DynError, Trace};
 use crate::engine::report_error::{
-    LogError, ReportError, SpawnWithReporter, UnwrapWithErrorLogger,
+    LogError, ReportError, ReportErrorExt, SpawnWithReporter, UnwrapWithErrorLogger,
 };
 use crate::engine::{DataError, Key, Value};
 
 use crate::connectors::adaptors::InputAdaptor;
 use crate::connectors::snapshot::Event as SnapshotEvent;
+use crate::engine::telemetry::{timestamp_attribute, SpanGuard};
 use crate::engine::Error as EngineError;
 use crate::engine::Timestamp;
 use crate::persistence::config::ReadersQueryPurpose;
@@ -97,6 +105,10 @@ pub struct Connector {
     current_frontier: OffsetAntichain,
     skip_all_errors: bool,
     error_logger: Rc<dyn LogError>,
+    name: String,
+    dead_letter_queue: Option<Arc<DeadLetterQueue>>,
+    error_budget: Arc<ErrorBudgetTracker>,
+    error_reporter: Option<Box<dyn ReportError>>,
 }
 
 #[derive(Debug)]
@@ -203,9 +215,47 @@ impl Connector {
             current_frontier: OffsetAntichain::new(),
             skip_all_errors,
             error_logger,
+            name: String::new(),
+            dead_letter_queue: None,
+            error_budget: Arc::new(ErrorBudgetTracker::new(None)),
+            error_reporter: None,
         }
     }
 
+    /// Limits the rate of errors, returned by the reader or found while parsing
+    /// the entries, that the connector accepts within a window.
+    #[must_use]
+    pub fn with_error_budget(mut self, error_budget: Option<ErrorBudget>) -> Self {
+        self.error_budget = Arc::new(ErrorBudgetTracker::new(error_budget));
+        self
+    }
+
+    /// Sends the rejected input entries, together with their raw payloads, to
+    /// the dead-letter queue.
+    #[must_use]
+    pub fn with_dead_letter_queue(
+        mut self,
+        dead_letter_queue: Option<Arc<DeadLetterQueue>>,
+    ) -> Self {
+        self.dead_letter_queue = dead_letter_queue;
+        self
+    }
+
+    fn rejected_input(
+        &self,
+        reader_context: Option<&ReaderContext>,
+        offset: Option<&Offset>,
+    ) -> Option<RejectedInput> {
+        self.dead_letter_queue.as_ref().map(|dead_letter_queue| {
+            RejectedInput::new(
+                dead_letter_queue.clone(),
+                self.name.clone(),
+                reader_context,
+                offset,
+            )
+        })
+    }
+
     fn advance_time(&mut self, input_session: &mut dyn InputAdaptor<Timestamp>) -> Timestamp {
         let new_timestamp = u64::try_from(current_unix_timestamp_ms())
             .expect("number of milliseconds should fit in 64 bits");
@@ -304,11 +354,17 @@ impl Connector {
         sender: &Sender<Entry>,
         main_thread: &Thread,
         error_reporter: &(impl ReportError + 'static),
+        reader_name: &str,
+        error_budget: &ErrorBudgetTracker,
     ) {
         let use_rare_wakeup = env::var("PATHWAY_YOLO_RARE_WAKEUPS") == Ok("1".to_string());
         let mut amt_send = 0;
         let mut consecutive_errors = 0;
         loop {
+            if let Some(pause) = error_budget.remaining_pause() {
+                thread::sleep(pause);
+            }
+
             let row_read_result = reader.read();
             let finished = matches!(row_read_result, Ok(ReadResult::Finished));
 
@@ -322,9 +378,26 @@ impl Connector {
                 }
                 Err(error) => {
                     error!("There had been an error processing the row read result: {error}");
-                    consecutive_errors += 1;
-                    if consecutive_errors > reader.max_allowed_consecutive_errors() {
-                        error_reporter.report(EngineError::ReaderFailed(error));
+                    match error_budget.report_error(reader_name) {
+                        ErrorBudgetDecision::Unlimited => {
+                            consecutive_errors += 1;
+                            if consecutive_errors > reader.max_allowed_consecutive_errors() {
+                                error_reporter.report(EngineError::ReaderFailed(error));
+                            }
+                        }
+                        ErrorBudgetDecision::Fail {
+                            num_errors,
+                            num_entries,
+                        } => {
+                            error_reporter.report(error_budget.exceeded_error(
+                                reader_name,
+                                num_errors,
+                                num_entries,
+                            ));
+                        }
+                        ErrorBudgetDecision::Continue
+                        | ErrorBudgetDecision::Pause(_)
+                        | ErrorBudgetDecision::Alert => {}
                     }
                 }
             };
@@ -426,7 +499,7 @@ impl Connector {
         external_persistent_id: Option<&ExternalPersistentId>,
         persistence_mode: PersistenceMode,
         snapshot_access: SnapshotAccess,
-        error_reporter: impl ReportError + 'static,
+        error_reporter: impl ReportError + Clone + 'static,
     ) -> Result<StartedConnectorState, EngineError> {
         assert_eq!(self.num_columns, parser.column_count());
 
@@ -439,6 +512,18 @@ impl Connector {
             parser.short_description()
         );
         let reader_name = reader.name(external_persistent_id, connector_id);
+        self.name.clone_from(&reader_name);
+        self.error_reporter = Some(Box::new(error_reporter.clone()));
+        let error_budget = self.error_budget.clone();
+        let snapshot_access =
+            persistent_storage
+                .as_ref()
+                .map_or(snapshot_access, |persistent_storage| {
+                    persistent_storage
+                        .lock()
+                        .unwrap()
+                        .effective_snapshot_access(snapshot_access)
+                });
 
         let mut snapshot_writer = Self::snapshot_writer(
             reader.as_ref(),
@@ -468,7 +553,14 @@ impl Connector {
                 )
                 .map_err(EngineError::ReaderFailed)?;
                 if realtime_reader_needed {
-                    Self::read_realtime_updates(&mut *reader, &sender, &main_thread, reporter);
+                    Self::read_realtime_updates(
+                        &mut *reader,
+                        &sender,
+                        &main_thread,
+                        reporter,
+                        &reader_name,
+                        &error_budget,
+                    );
                 }
 
                 Ok(())
@@ -478,7 +570,9 @@ impl Connector {
         let mut next_commit_at = self.commit_duration.map(|x| SystemTime::now() + x);
         let mut backfilling_finished = false;
 
-        let connector_monitor = Rc::new(RefCell::new(ConnectorMonitor::new(reader_name)));
+        let connector_monitor = Rc::new(RefCell::new(
+            ConnectorMonitor::new(self.name.clone()).with_error_budget(self.error_budget.clone()),
+        ));
         let cloned_connector_monitor = connector_monitor.clone();
         let mut commit_allowed = true;
         let poller = Box::new(move || {
@@ -502,6 +596,7 @@ impl Connector {
                         let parsed_entries = vec![ParsedEventWithErrors::AdvanceTime];
                         self.on_parsed_data(
                             parsed_entries,
+                            None,
                             None, // no key generation for time advancement
                             input_session.as_mut(),
                             &mut values_to_key,
@@ -516,6 +611,13 @@ impl Connector {
 
             let mut n_entries_in_batch = 0;
             loop {
+                // the entries already read wait in the channel until the pause ends
+                if let Some(pause) = self.error_budget.remaining_pause() {
+                    let resume_at = SystemTime::now() + pause;
+                    return ControlFlow::Continue(Some(
+                        next_commit_at.map_or(resume_at, |commit_at| min(commit_at, resume_at)),
+                    ));
+                }
                 n_entries_in_batch += 1;
                 if n_entries_in_batch == 100_000 {
                     return ControlFlow::Continue(next_commit_at);
@@ -597,6 +699,7 @@ impl Connector {
                         let parsed_entries = vec![ParsedEventWithErrors::AdvanceTime];
                         self.on_parsed_data(
                             parsed_entries,
+                            None,
                             None, // no key generation for time advancement
                             input_session,
                             values_to_key,
@@ -619,7 +722,10 @@ impl Connector {
                     let mut parsed_entries = match parser.parse(&reader_context) {
                         Ok(entries) => entries,
                         Err(e) => {
-                            self.log_parse_error(e);
+                            let rejected_input =
+                                self.rejected_input(Some(&reader_context), Some(&offset));
+                            self.log_parse_error(e, rejected_input.as_ref());
+                            self.on_rejected_entry();
                             return;
                         }
                     };
@@ -630,6 +736,7 @@ impl Connector {
 
                     self.on_parsed_data(
                         parsed_entries,
+                        Some(&reader_context),
                         Some(&offset.clone()),
                         input_session,
                         values_to_key,
@@ -648,10 +755,14 @@ impl Connector {
             Entry::RewindFinishSentinel(restored_frontier) => {
                 assert!(!*backfilling_finished);
                 *backfilling_finished = true;
+                if let Some(ref mut connector_monitor) = connector_monitor {
+                    connector_monitor.mark_initialized();
+                }
                 self.current_frontier = restored_frontier;
                 let parsed_entries = vec![ParsedEventWithErrors::AdvanceTime];
                 self.on_parsed_data(
                     parsed_entries,
+                    None,
                     None, // no key generation for time advancement
                     input_session,
                     values_to_key,
@@ -693,14 +804,19 @@ impl Connector {
             match custom_reader.acquire_custom_data() {
                 (Ok(entries), maybe_offset) => self.on_parsed_data(
                     entries,
+                    None,
                     maybe_offset.as_ref(),
                     input_session,
                     &mut values_to_key,
                     snapshot_writer,
                     &mut None,
                 ),
-                (Err(e), _) => {
+                (Err(e), maybe_offset) => {
+                    if let Some(rejected_input) = self.rejected_input(None, maybe_offset.as_ref()) {
+                        rejected_input.reject(&e);
+                    }
                     error!("Read data parsed unsuccessfully. {e}");
+                    self.on_rejected_entry();
                 }
             };
         }
@@ -725,6 +841,7 @@ impl Connector {
         match parser.parse(raw_read_data) {
             Ok(entries) => self.on_parsed_data(
                 entries,
+                Some(raw_read_data),
                 offset,
                 input_session,
                 values_to_key,
@@ -732,7 +849,11 @@ impl Connector {
                 &mut None,
             ),
             Err(e) => {
+                if let Some(rejected_input) = self.rejected_input(Some(raw_read_data), offset) {
+                    rejected_input.reject(&e);
+                }
                 error!("Read data parsed unsuccessfully. {e}");
+                self.on_rejected_entry();
             }
         }
     }
@@ -757,6 +878,7 @@ impl Connector {
     fn on_parsed_data(
         &mut self,
         parsed_entries: Vec<ParsedEventWithErrors>,
+        reader_context: Option<&ReaderContext>,
         offset: Option<&Offset>,
         input_session: &mut dyn InputAdaptor<Timestamp>,
         mut values_to_key: impl FnMut(Option<&Vec<Value>>, Option<&Offset>) -> Key,
@@ -764,6 +886,11 @@ impl Connector {
         connector_monitor: &mut Option<&mut ConnectorMonitor>,
     ) {
         let error_logger = self.error_logger.clone();
+        let rejected_input = if parsed_entries.iter().any(ParsedEventWithErrors::has_errors) {
+            self.rejected_input(reader_context, offset)
+        } else {
+            None
+        };
         let error_handling_logic: data_format::ErrorRemovalLogic = if self.skip_all_errors {
             Box::new(move |values| values.into_iter().try_collect())
         } else {
@@ -774,7 +901,15 @@ impl Connector {
                     .collect())
             })
         }; // logic to handle errors in values
+        self.on_accepted_entries(&parsed_entries);
         for entry in parsed_entries {
+            if entry.has_errors() {
+                // a single record with all errors of the row
+                if let Some(rejected_input) = &rejected_input {
+                    rejected_input.reject_all(entry.error_messages());
+                }
+                self.on_rejected_entry();
+            }
             let entry = match entry.remove_errors(&error_handling_logic) {
                 Ok(entry) => entry,
                 Err(err) => {
@@ -784,7 +919,7 @@ impl Connector {
                         // if there is an error in key
                         ParseError::ErrorInKey(err).into()
                     };
-                    self.log_parse_error(err);
+                    self.log_parse_error(err, None);
                     continue;
                 }
             };
@@ -827,7 +962,15 @@ impl Connector {
                     Self::on_remove(key.expect("No key"), values, input_session);
                 }
                 ParsedEvent::AdvanceTime => {
+                    let mut span = SpanGuard::start("connector.advance_time", || {
+                        connector_monitor
+                            .as_ref()
+                            .map(|monitor| KeyValue::new("connector", monitor.get_name()))
+                            .into_iter()
+                            .collect()
+                    });
                     let time_advanced = self.advance_time(input_session);
+                    span.set_attribute(timestamp_attribute(time_advanced));
                     if let Some(ref mut connector_monitor) = connector_monitor {
                         connector_monitor.commit();
                     }
@@ -849,7 +992,38 @@ impl Connector {
         }
     }
 
-    fn log_parse_error(&self, error: DynError) {
+    /// Counts the entries without errors, which lower the error rate of the connector.
+    fn on_accepted_entries(&self, parsed_entries: &[ParsedEventWithErrors]) {
+        let num_accepted_entries = parsed_entries
+            .iter()
+            .filter(|entry| {
+                !entry.has_errors() && !matches!(entry, ParsedEventWithErrors::AdvanceTime)
+            })
+            .count();
+        self.error_budget.record_accepted_entries(num_accepted_entries);
+    }
+
+    /// Counts an entry with errors against the error budget of the connector.
+    fn on_rejected_entry(&self) {
+        if let ErrorBudgetDecision::Fail {
+            num_errors,
+            num_entries,
+        } = self.error_budget.report_error(&self.name)
+        {
+            let error = self
+                .error_budget
+                .exceeded_error(&self.name, num_errors, num_entries);
+            match &self.error_reporter {
+                Some(error_reporter) => error_reporter.report_and_panic(error),
+                None => error!("{error}"),
+            }
+        }
+    }
+
+    fn log_parse_error(&self, error: DynError, rejected_input: Option<&RejectedInput>) {
+        if let Some(rejected_input) = rejected_input {
+            rejected_input.reject(&error);
+        }
         if self.skip_all_errors {
             error!("Parse error: {error}");
         } else {
@@ -1,11 +1,15 @@
 // Copyright © 2024 Pathway
 
+use std::cmp::min;
 use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use log::{info, warn};
+use log::{error, info, warn};
 use pyo3::pyclass;
 
+use crate::engine::Error as EngineError;
+
 #[derive(Debug, Clone, Copy)]
 #[pyclass]
 pub struct ConnectorStats {
@@ -16,7 +20,296 @@ pub struct ConnectorStats {
     #[pyo3(get, set)]
     pub num_messages_recently_committed: usize,
     #[pyo3(get, set)]
+    pub initialized: bool,
+    #[pyo3(get, set)]
     pub finished: bool,
+    #[pyo3(get, set)]
+    pub num_errors_from_start: usize,
+    #[pyo3(get, set)]
+    pub num_entries_in_window: usize,
+    #[pyo3(get, set)]
+    pub num_errors_in_window: usize,
+    #[pyo3(get, set)]
+    pub error_budget_exceeded: bool,
+    #[pyo3(get, set)]
+    pub paused: bool,
+}
+
+/// The statistics of an output connector, shared with the prober, as the output
+/// connectors run in their own threads.
+#[derive(Debug, Clone, Copy, Default)]
+#[pyclass]
+pub struct SinkStats {
+    #[pyo3(get, set)]
+    pub num_messages_from_start: usize,
+    #[pyo3(get, set)]
+    pub num_batches_from_start: usize,
+    #[pyo3(get, set)]
+    pub writes_duration_ms: u64,
+    #[pyo3(get, set)]
+    pub last_committed_time: Option<u64>,
+    #[pyo3(get, set)]
+    pub finished: bool,
+}
+
+/// What to do when a connector exceeds its error budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorBudgetAction {
+    /// Terminate the computation.
+    Fail,
+    /// Stop reading and parsing the entries for `initial_backoff`. The pause is
+    /// doubled, up to `max_backoff`, each time the budget is exceeded again right after it.
+    Pause {
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    },
+    /// Keep skipping the erroneous entries, reporting once per window that the
+    /// budget is exceeded.
+    SkipAndAlert,
+}
+
+/// The maximum fraction of the entries of a connector that may be erroneous
+/// within a sliding window.
+///
+/// The rate is only checked once the window holds at least `min_entries` entries,
+/// so that a few errors right after the start don't exceed it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorBudget {
+    pub max_error_rate: f64,
+    pub min_entries: usize,
+    pub window: Duration,
+    pub action: ErrorBudgetAction,
+}
+
+impl ErrorBudget {
+    fn is_exceeded(&self, num_entries: usize, num_errors: usize) -> bool {
+        #[allow(clippy::cast_precision_loss)]
+        let error_rate = num_errors as f64 / num_entries as f64;
+        num_entries >= self.min_entries && num_errors > 0 && error_rate > self.max_error_rate
+    }
+}
+
+/// What the connector has to do after an error is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorBudgetDecision {
+    /// There is no error budget, the connector handles the error as it always did.
+    Unlimited,
+    Continue,
+    /// The budget is exceeded with the given numbers of errors and entries in the window.
+    Fail {
+        num_errors: usize,
+        num_entries: usize,
+    },
+    Pause(Duration),
+    Alert,
+}
+
+/// The number of buckets the window is split into. The entries expire
+/// a bucket at a time, so that they don't have to be stored one by one.
+const WINDOW_BUCKETS: u32 = 60;
+
+#[derive(Debug)]
+struct WindowBucket {
+    start: Instant,
+    num_entries: usize,
+    num_errors: usize,
+}
+
+#[derive(Debug, Default)]
+struct ErrorBudgetState {
+    buckets: VecDeque<WindowBucket>,
+    num_entries_in_window: usize,
+    num_errors_in_window: usize,
+    num_errors_from_start: usize,
+    exceeded_at: Option<Instant>,
+    next_backoff: Option<Duration>,
+    paused_until: Option<Instant>,
+}
+
+impl ErrorBudgetState {
+    fn expire(&mut self, now: Instant, window: Duration) {
+        while let Some(bucket) = self.buckets.front() {
+            if now.duration_since(bucket.start) < window {
+                break;
+            }
+            self.num_entries_in_window -= bucket.num_entries;
+            self.num_errors_in_window -= bucket.num_errors;
+            self.buckets.pop_front();
+        }
+        if self
+            .exceeded_at
+            .is_some_and(|exceeded_at| now.duration_since(exceeded_at) >= window)
+        {
+            self.exceeded_at = None;
+        }
+    }
+
+    fn add(&mut self, now: Instant, window: Duration, num_entries: usize, num_errors: usize) {
+        self.expire(now, window);
+        self.num_entries_in_window += num_entries;
+        self.num_errors_in_window += num_errors;
+        match self.buckets.back_mut() {
+            Some(bucket) if now.duration_since(bucket.start) < window / WINDOW_BUCKETS => {
+                bucket.num_entries += num_entries;
+                bucket.num_errors += num_errors;
+            }
+            _ => self.buckets.push_back(WindowBucket {
+                start: now,
+                num_entries,
+                num_errors,
+            }),
+        }
+    }
+}
+
+/// Counts the entries of a connector and the errors among them, both the ones
+/// returned by its reader and the rejected entries, and checks their rate against
+/// its error budget.
+///
+/// It is shared between the reader thread and the worker running the connector.
+#[derive(Debug)]
+pub struct ErrorBudgetTracker {
+    budget: Option<ErrorBudget>,
+    state: Mutex<ErrorBudgetState>,
+}
+
+impl ErrorBudgetTracker {
+    pub fn new(budget: Option<ErrorBudget>) -> Self {
+        Self {
+            budget,
+            state: Mutex::new(ErrorBudgetState::default()),
+        }
+    }
+
+    pub fn record_error(&self) -> ErrorBudgetDecision {
+        self.record_error_at(Instant::now())
+    }
+
+    pub fn record_error_at(&self, now: Instant) -> ErrorBudgetDecision {
+        let mut state = self.state.lock().unwrap();
+        state.num_errors_from_start += 1;
+        let Some(budget) = self.budget else {
+            return ErrorBudgetDecision::Unlimited;
+        };
+        state.add(now, budget.window, 1, 1);
+        if !budget.is_exceeded(state.num_entries_in_window, state.num_errors_in_window)
+            || state
+                .paused_until
+                .is_some_and(|paused_until| now < paused_until)
+        {
+            return ErrorBudgetDecision::Continue;
+        }
+        let exceeded_recently = state.exceeded_at.is_some();
+        match budget.action {
+            ErrorBudgetAction::Fail => {
+                state.exceeded_at = Some(now);
+                ErrorBudgetDecision::Fail {
+                    num_errors: state.num_errors_in_window,
+                    num_entries: state.num_entries_in_window,
+                }
+            }
+            ErrorBudgetAction::Pause {
+                initial_backoff,
+                max_backoff,
+            } => {
+                let backoff = if exceeded_recently {
+                    state.next_backoff.unwrap_or(initial_backoff)
+                } else {
+                    initial_backoff
+                };
+                state.exceeded_at = Some(now);
+                state.next_backoff = Some(min(backoff.saturating_mul(2), max_backoff));
+                state.paused_until = Some(now + backoff);
+                ErrorBudgetDecision::Pause(backoff)
+            }
+            ErrorBudgetAction::SkipAndAlert if exceeded_recently => ErrorBudgetDecision::Continue,
+            ErrorBudgetAction::SkipAndAlert => {
+                state.exceeded_at = Some(now);
+                ErrorBudgetDecision::Alert
+            }
+        }
+    }
+
+    /// Records the entries processed without errors, which lower the error rate.
+    pub fn record_accepted_entries(&self, num_entries: usize) {
+        self.record_accepted_entries_at(Instant::now(), num_entries);
+    }
+
+    pub fn record_accepted_entries_at(&self, now: Instant, num_entries: usize) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        if num_entries > 0 {
+            self.state
+                .lock()
+                .unwrap()
+                .add(now, budget.window, num_entries, 0);
+        }
+    }
+
+    /// Records an error of the connector `name` and logs the action taken if
+    /// the budget is exceeded.
+    pub fn report_error(&self, name: &str) -> ErrorBudgetDecision {
+        let decision = self.record_error();
+        if let Some(budget) = self.budget {
+            match decision {
+                ErrorBudgetDecision::Pause(backoff) => warn!(
+                    "{name}: more than {} of the entries in the last {:?} are erroneous, pausing the connector for {backoff:?}",
+                    budget.max_error_rate, budget.window
+                ),
+                ErrorBudgetDecision::Alert => error!(
+                    "{name}: more than {} of the entries in the last {:?} are erroneous, they are skipped",
+                    budget.max_error_rate, budget.window
+                ),
+                ErrorBudgetDecision::Unlimited
+                | ErrorBudgetDecision::Continue
+                | ErrorBudgetDecision::Fail { .. } => {}
+            }
+        }
+        decision
+    }
+
+    pub fn exceeded_error(&self, name: &str, num_errors: usize, num_entries: usize) -> EngineError {
+        EngineError::ErrorBudgetExceeded {
+            connector: name.to_string(),
+            num_errors,
+            num_entries,
+            window: self.budget.map_or(Duration::ZERO, |budget| budget.window),
+        }
+    }
+
+    /// Returns for how long the connector should still stop reading and parsing
+    /// the entries.
+    pub fn remaining_pause(&self) -> Option<Duration> {
+        if !matches!(
+            self.budget,
+            Some(ErrorBudget {
+                action: ErrorBudgetAction::Pause { .. },
+                ..
+            })
+        ) {
+            return None;
+        }
+        let paused_until = self.state.lock().unwrap().paused_until?;
+        paused_until
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    fn fill_stats(&self, stats: &mut ConnectorStats) {
+        let now = Instant::now();
+        let mut budget_state = self.state.lock().unwrap();
+        if let Some(budget) = self.budget {
+            budget_state.expire(now, budget.window);
+        }
+        stats.num_errors_from_start = budget_state.num_errors_from_start;
+        stats.num_entries_in_window = budget_state.num_entries_in_window;
+        stats.num_errors_in_window = budget_state.num_errors_in_window;
+        stats.error_budget_exceeded = budget_state.exceeded_at.is_some();
+        stats.paused = budget_state
+            .paused_until
+            .is_some_and(|paused_until| now < paused_until);
+    }
 }
 
 struct ConnectorLogger {
@@ -86,6 +379,7 @@ impl ConnectorLogger {
 pub struct ConnectorMonitor {
     name: String,
     stats: ConnectorStats,
+    error_budget: Arc<ErrorBudgetTracker>,
     last_minute_queue: VecDeque<(usize, Instant)>,
     current_num_messages: usize,
     logger: ConnectorLogger,
@@ -99,18 +393,37 @@ impl ConnectorMonitor {
                 num_messages_from_start: 0,
                 num_messages_in_last_minute: 0,
                 num_messages_recently_committed: 0,
+                initialized: false,
                 finished: false,
+                num_errors_from_start: 0,
+                num_entries_in_window: 0,
+                num_errors_in_window: 0,
+                error_budget_exceeded: false,
+                paused: false,
             },
+            error_budget: Arc::new(ErrorBudgetTracker::new(None)),
             last_minute_queue: VecDeque::new(),
             current_num_messages: 0,
             logger: ConnectorLogger::new(name),
         }
     }
 
+    /// Reports the errors of the connector, as counted by its error budget.
+    #[must_use]
+    pub fn with_error_budget(mut self, error_budget: Arc<ErrorBudgetTracker>) -> Self {
+        self.error_budget = error_budget;
+        self
+    }
+
     pub fn increment(&mut self) {
         self.current_num_messages += 1;
     }
 
+    /// Marks that the persisted data is replayed and the source is read in realtime.
+    pub fn mark_initialized(&mut self) {
+        self.stats.initialized = true;
+    }
+
     pub fn finish(&mut self) {
         self.stats.finished = true;
         self.logger
@@ -141,7 +454,9 @@ impl ConnectorMonitor {
     }
 
     pub fn get_stats(&self) -> ConnectorStats {
-        self.stats
+        let mut stats = self.stats;
+        self.error_budget.fill_stats(&mut stats);
+        stats
     }
 }
 
@@ -154,6 +469,7 @@ pub struct OutputConnectorStats {
     current_writes_duration: Duration,
     messages_written_in_batch: usize,
     messages_written_in_total: usize,
+    shared_stats: Arc<Mutex<SinkStats>>,
 }
 
 impl OutputConnectorStats {
@@ -168,11 +484,28 @@ impl OutputConnectorStats {
 
             messages_written_in_batch: 0,
             messages_written_in_total: 0,
+            shared_stats: Arc::new(Mutex::new(SinkStats::default())),
         }
     }
 
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the statistics that are kept up to date as the batches are written.
+    pub fn shared_stats(&self) -> Arc<Mutex<SinkStats>> {
+        self.shared_stats.clone()
+    }
+
     pub fn on_time_committed(&mut self, t: Option<u64>) {
         let current_timestamp = Instant::now();
+        {
+            let mut shared_stats = self.shared_stats.lock().unwrap();
+            match t {
+                Some(t) => shared_stats.last_committed_time = Some(t),
+                None => shared_stats.finished = true,
+            }
+        }
 
         if t.is_none() {
             // The last event, so we should report what we have
@@ -209,6 +542,12 @@ impl OutputConnectorStats {
         let elapsed = self.batch_start_time.elapsed();
         self.total_writes_duration += elapsed;
         self.current_writes_duration += elapsed;
+
+        let mut shared_stats = self.shared_stats.lock().unwrap();
+        shared_stats.num_messages_from_start = self.messages_written_in_total;
+        shared_stats.num_batches_from_start += 1;
+        shared_stats.writes_duration_ms =
+            u64::try_from(self.total_writes_duration.as_millis()).unwrap_or(u64::MAX);
     }
 
     fn report_stats(&mut self, current_timestamp: Instant, t: Option<u64>) {
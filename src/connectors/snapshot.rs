@@ -1,36 +1,36 @@
 // Copyright © 2024 Pathway
 
 use log::{error, info, warn};
+use std::cmp::max;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
 use std::fs;
 use std::fs::File;
 use std::fs::OpenOptions;
-use std::io::{BufReader, BufWriter, ErrorKind as IoErrorKind, Seek, Write};
+use std::io::{BufReader, BufWriter, Cursor, Error as IoError, Read, Seek, SeekFrom, Write};
 use std::mem::take;
 use std::path::Path;
 use std::path::PathBuf;
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::thread;
+use std::thread::JoinHandle;
 
-use bincode::{deserialize_from, serialize, serialize_into, ErrorKind as BincodeError};
 use futures::channel::oneshot;
 use futures::channel::oneshot::Receiver as OneShotReceiver;
-use futures::channel::oneshot::Sender as OneShotSender;
-use pipe::PipeReader;
-use s3::bucket::Bucket as S3Bucket;
-use s3::error::S3Error;
-use s3::serde_types::Part as S3Part;
 use serde::{Deserialize, Serialize};
 
-use crate::connectors::data_storage::S3CommandName;
-use crate::connectors::data_storage::{
-    CurrentlyProcessedS3Object, ReadError, S3Scanner, WriteError,
+use crate::connectors::data_storage::{ReadError, WriteError};
+use crate::connectors::snapshot_format::{
+    decode_chunk, decode_chunk_prefix, decode_outdated_chunk, encode_chunk, is_chunk_outdated,
+    ChunkDecoder, ChunkEncoder, MAX_HEADER_LEN,
 };
-use crate::deepcopy::DeepCopy;
 use crate::engine::{Key, Value};
 use crate::engine::{Timestamp, TotalFrontier};
 use crate::fs_helpers::ensure_directory;
+use crate::persistence::encryption::Keyring;
 use crate::persistence::frontier::OffsetAntichain;
+use crate::persistence::object_store::ObjectStore;
 use crate::timestamp::current_unix_timestamp_ms;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -58,6 +58,238 @@ impl SnapshotMode {
     }
 }
 
+const COMPACTED_CHUNK_PREFIX: &str = "compacted-";
+const TEMPORARY_CHUNK_SUFFIX: &str = ".tmp";
+
+/// A part of the snapshot, stored in a separate file or object named after its creation time.
+/// A compacted chunk replaces all chunks created not later than itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnapshotChunk {
+    Plain(Timestamp),
+    Compacted(Timestamp),
+}
+
+impl SnapshotChunk {
+    fn parse(name: &str) -> Option<Self> {
+        if name.ends_with(TEMPORARY_CHUNK_SUFFIX) {
+            // a leftover of an interrupted compaction
+            return None;
+        }
+        let parsed = match name.strip_prefix(COMPACTED_CHUNK_PREFIX) {
+            Some(time) => time.parse().map(Self::Compacted),
+            None => name.parse().map(Self::Plain),
+        };
+        if parsed.is_err() {
+            error!("Unparsable timestamp: {name}");
+        }
+        parsed.ok()
+    }
+
+    fn time(self) -> Timestamp {
+        match self {
+            Self::Plain(time) | Self::Compacted(time) => time,
+        }
+    }
+
+    /// Orders the chunks for reading, skipping the ones replaced by the latest compacted chunk.
+    fn to_read(chunks: Vec<Self>) -> Vec<Self> {
+        let last_compacted = chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                Self::Compacted(time) => Some(*time),
+                Self::Plain(_) => None,
+            })
+            .max();
+        let mut chunks: Vec<Self> = chunks
+            .into_iter()
+            .filter(|chunk| match (chunk, last_compacted) {
+                (_, None) => true,
+                (Self::Compacted(time), Some(last_compacted)) => *time == last_compacted,
+                (Self::Plain(time), Some(last_compacted)) => *time > last_compacted,
+            })
+            .collect();
+        chunks.sort_unstable_by_key(|chunk| chunk.time());
+        chunks
+    }
+
+    /// Returns the compacted chunk that replaces `chunks`, unless they are compacted already.
+    fn compacted_replacement(chunks: &[Self]) -> Option<Self> {
+        match chunks {
+            [] | [Self::Compacted(_)] => None,
+            [.., last] => Some(Self::Compacted(last.time())),
+        }
+    }
+
+    /// Creates a chunk of the same kind as `self` that is newer than all existing chunks.
+    fn renewed(self) -> Self {
+        let time = next_chunk_time(&mut Some(self.time()));
+        match self {
+            Self::Plain(_) => Self::Plain(time),
+            Self::Compacted(_) => Self::Compacted(time),
+        }
+    }
+}
+
+impl Display for SnapshotChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plain(time) => write!(f, "{time}"),
+            Self::Compacted(time) => write!(f, "{COMPACTED_CHUNK_PREFIX}{time}"),
+        }
+    }
+}
+
+/// Returns the chunk that replaces `chunks` after the compaction. Besides merging several
+/// chunks, a single compacted chunk is rewritten in place if it's not in the current format,
+/// which is how the snapshots are re-encrypted after the key rotation.
+fn compaction_target(
+    chunks: &[SnapshotChunk],
+    is_outdated: impl FnOnce(SnapshotChunk) -> Result<bool, ReadError>,
+) -> Result<Option<SnapshotChunk>, ReadError> {
+    if let Some(replacement) = SnapshotChunk::compacted_replacement(chunks) {
+        return Ok(Some(replacement));
+    }
+    if let [chunk] = chunks {
+        if is_outdated(*chunk)? {
+            return Ok(Some(*chunk));
+        }
+    }
+    Ok(None)
+}
+
+// Chunk times have to grow strictly, as a compacted chunk replaces all chunks
+// that are not newer than itself.
+fn next_chunk_time(last_chunk_time: &mut Option<Timestamp>) -> Timestamp {
+    let now = Timestamp(
+        u64::try_from(current_unix_timestamp_ms())
+            .expect("number of milliseconds should fit in 64 bits"),
+    );
+    let time = match last_chunk_time {
+        Some(last_chunk_time) => max(now, Timestamp(last_chunk_time.0 + 1)),
+        None => now,
+    };
+    *last_chunk_time = Some(time);
+    time
+}
+
+// Net changes of a single key: consecutive insertions and deletions of its rows cancel out
+// and consecutive upserts collapse to the last one. Changes of different kinds are kept
+// in the order of their events, as an upsert and the rows of the same key interact.
+#[derive(Debug)]
+enum KeyChanges {
+    Rows(BTreeMap<Vec<Value>, isize>),
+    Upsert(Option<Vec<Value>>),
+}
+
+fn add_key_change(changes: &mut Vec<KeyChanges>, event: Event) {
+    if matches!(changes.last(), Some(KeyChanges::Rows(rows)) if rows.is_empty()) {
+        changes.pop();
+    }
+    let (values, diff) = match event {
+        Event::Insert(_, values) => (values, 1),
+        Event::Delete(_, values) => (values, -1),
+        Event::Upsert(_, values) => {
+            if let Some(KeyChanges::Upsert(last_values)) = changes.last_mut() {
+                *last_values = values;
+            } else {
+                changes.push(KeyChanges::Upsert(values));
+            }
+            return;
+        }
+        Event::AdvanceTime(_, _) | Event::Finished => return,
+    };
+    if !matches!(changes.last(), Some(KeyChanges::Rows(_))) {
+        changes.push(KeyChanges::Rows(BTreeMap::new()));
+    }
+    let Some(KeyChanges::Rows(rows)) = changes.last_mut() else {
+        unreachable!("rows were just pushed");
+    };
+    match rows.entry(values) {
+        Entry::Occupied(mut entry) => {
+            *entry.get_mut() += diff;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(diff);
+        }
+    }
+}
+
+/// Consolidates the events preceding the last time advancement below `threshold` into
+/// the net changes of each key: insertions and deletions of the same row cancel out and
+/// upserts are collapsed, while the order of upserts and insertions or deletions of the same
+/// key is kept. The events after that advancement are kept as they are.
+/// Returns `None` if there is no time advancement below `threshold`.
+pub fn compact_events(
+    events: impl IntoIterator<Item = Event>,
+    threshold: TotalFrontier<Timestamp>,
+) -> Option<Vec<Event>> {
+    let mut changes: BTreeMap<Key, Vec<KeyChanges>> = BTreeMap::new();
+    let mut last_advancement = None;
+    let mut pending = Vec::new();
+    let mut threshold_reached = false;
+    for event in events {
+        match event {
+            Event::AdvanceTime(time, frontier)
+                if !threshold_reached && TotalFrontier::At(time) < threshold =>
+            {
+                for event in pending.drain(..) {
+                    if let Event::Insert(key, _) | Event::Delete(key, _) | Event::Upsert(key, _) =
+                        &event
+                    {
+                        add_key_change(changes.entry(*key).or_default(), event);
+                    }
+                }
+                last_advancement = Some(Event::AdvanceTime(time, frontier));
+            }
+            Event::AdvanceTime(_, _) => {
+                threshold_reached = true;
+                pending.push(event);
+            }
+            Event::Finished => {}
+            event => pending.push(event),
+        }
+    }
+    let last_advancement = last_advancement?;
+
+    let mut compacted = Vec::new();
+    for (key, key_changes) in changes {
+        for change in key_changes {
+            match change {
+                KeyChanges::Rows(rows) => {
+                    for (values, count) in rows {
+                        for _ in 0..count.unsigned_abs() {
+                            if count > 0 {
+                                compacted.push(Event::Insert(key, values.clone()));
+                            } else {
+                                compacted.push(Event::Delete(key, values.clone()));
+                            }
+                        }
+                    }
+                }
+                // removals are kept, as the rows may come from snapshots of other workers
+                KeyChanges::Upsert(values) => compacted.push(Event::Upsert(key, values)),
+            }
+        }
+    }
+    compacted.push(last_advancement);
+    compacted.extend(pending);
+    Some(compacted)
+}
+
+/// Reads the events until the end of the snapshot, not stopping at any threshold time.
+pub fn read_all_events(reader: &mut dyn ReadSnapshotEvent) -> Result<Vec<Event>, ReadError> {
+    let mut events = Vec::new();
+    loop {
+        match reader.read()? {
+            Event::Finished => return Ok(events),
+            event => events.push(event),
+        }
+    }
+}
+
 pub trait ReadSnapshotEvent {
     /// This method will be called every so often to read the persisted snapshot.
     /// When there are no entries left, it must return `Event::Finished`.
@@ -93,68 +325,122 @@ pub trait WriteSnapshotEvent: Send {
     /// We use `futures::channel::oneshot::channel` here instead of Future/Promise
     /// because it uses modern Rust Futures that are also used by `async`.
     fn flush(&mut self) -> OneShotReceiver<Result<(), WriteError>>;
+
+    /// Starts consolidating the chunks written so far into a single chunk with the net set
+    /// of rows, up to the time `threshold` which is never rewound past.
+    /// The compaction runs in the background and replaces the chunks atomically, so that
+    /// the readers see either the old chunks or the compacted one.
+    fn compact(&mut self, _threshold: TotalFrontier<Timestamp>) {}
 }
 
 pub struct LocalBinarySnapshotReader {
     root_path: PathBuf,
     reader: Option<BufReader<std::fs::File>>,
+    decoder: ChunkDecoder,
     next_file_idx: usize,
-    times_advanced: Vec<Timestamp>,
+    chunks: Vec<SnapshotChunk>,
+    encryption: Option<Arc<Keyring>>,
+}
+
+fn local_snapshot_chunks(root_path: &Path) -> Result<Vec<SnapshotChunk>, IoError> {
+    let mut chunks = Vec::new();
+    for entry in fs::read_dir(root_path)? {
+        let entry = entry?;
+        if let Ok(file_name) = entry.file_name().into_string() {
+            chunks.extend(SnapshotChunk::parse(&file_name));
+        } else {
+            error!("Unparsable file name: {entry:#?}");
+        }
+    }
+    Ok(SnapshotChunk::to_read(chunks))
 }
 
 impl LocalBinarySnapshotReader {
     pub fn new(root_path: PathBuf) -> Result<LocalBinarySnapshotReader, ReadError> {
-        let mut times_advanced = Vec::new();
-        let entries = fs::read_dir(&root_path).map_err(ReadError::Io)?;
-        for entry in entries {
-            let entry = entry.map_err(ReadError::Io)?;
-            if let Ok(file_name) = entry.file_name().into_string() {
-                if let Ok(parsed_time) = file_name.parse() {
-                    times_advanced.push(parsed_time);
-                } else {
-                    error!("Unparsable timestamp: {file_name}");
-                }
-            } else {
-                error!("Unparsable file name: {entry:#?}");
-            }
-        }
-        times_advanced.sort_unstable();
+        let chunks = local_snapshot_chunks(&root_path).map_err(ReadError::Io)?;
+        Ok(Self::from_chunks(root_path, chunks))
+    }
 
-        Ok(Self {
+    fn from_chunks(root_path: PathBuf, chunks: Vec<SnapshotChunk>) -> Self {
+        Self {
             root_path,
             reader: None,
+            decoder: ChunkDecoder::new(String::new(), None),
             next_file_idx: 0,
-            times_advanced,
-        })
+            chunks,
+            encryption: None,
+        }
+    }
+
+    /// Sets the keys to decrypt the encrypted chunks with.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Option<Arc<Keyring>>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    fn repair_incomplete_chunk(&self) -> Result<(), ReadError> {
+        // Only the chunk that was being written when the program stopped can be incomplete.
+        // It is the last one, as the chunks are read before the new ones are written.
+        if self.decoder.is_encrypted() && self.next_file_idx < self.chunks.len() {
+            return Err(ReadError::TruncatedSnapshot);
+        }
+        let stable_position = self.decoder.stable_position();
+        let file_path =
+            Path::new(&self.root_path).join(self.chunks[self.next_file_idx - 1].to_string());
+        warn!("Snapshot chunk {file_path:?} ends with an incomplete entry, shrinking it to {stable_position} bytes");
+        cut_local_chunk(&file_path, stable_position, self.decoder.end_marker())?;
+        Ok(())
     }
 }
 
+// Cuts the chunk and closes it with the end marker, if it has one.
+fn cut_local_chunk(
+    chunk_path: &Path,
+    length: u64,
+    end_marker: Option<Vec<u8>>,
+) -> Result<(), IoError> {
+    let mut file = OpenOptions::new().write(true).open(chunk_path)?;
+    file.set_len(length)?;
+    if let Some(end_marker) = end_marker {
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&end_marker)?;
+    }
+    file.sync_all()
+}
+
 impl ReadSnapshotEvent for LocalBinarySnapshotReader {
     fn read(&mut self) -> Result<Event, ReadError> {
         loop {
             match &mut self.reader {
-                Some(reader) => match deserialize_from(reader) {
-                    Ok(entry) => return Ok(entry),
-                    Err(e) => match *e {
-                        BincodeError::Io(e) => {
-                            if !matches!(e.kind(), IoErrorKind::UnexpectedEof) {
-                                return Err(ReadError::Io(e));
-                            }
-                            self.reader = None;
-                            continue;
+                Some(reader) => match self.decoder.next_event(reader) {
+                    Ok(Some(entry)) => return Ok(entry),
+                    Ok(None) => {
+                        if !self.decoder.is_complete() {
+                            // a chunk that was being written when the program stopped
+                            self.repair_incomplete_chunk()?;
                         }
-                        _ => return Err(ReadError::Bincode(*e)),
-                    },
+                        self.reader = None;
+                        continue;
+                    }
+                    Err(ReadError::TruncatedSnapshot) => {
+                        // the tail of a chunk that was being written when the program stopped
+                        self.repair_incomplete_chunk()?;
+                        self.reader = None;
+                        continue;
+                    }
+                    Err(e) => return Err(e),
                 },
                 None => {
-                    if self.next_file_idx >= self.times_advanced.len() {
+                    if self.next_file_idx >= self.chunks.len() {
                         break;
                     }
-                    let current_file_path = Path::new(&self.root_path)
-                        .join(format!("{}", self.times_advanced[self.next_file_idx]));
+                    let chunk_name = self.chunks[self.next_file_idx].to_string();
+                    let current_file_path = Path::new(&self.root_path).join(&chunk_name);
                     self.reader = Some(BufReader::new(
                         File::open(current_file_path).map_err(ReadError::Io)?,
                     ));
+                    self.decoder = ChunkDecoder::new(chunk_name, self.encryption.clone());
                     self.next_file_idx += 1;
                 }
             }
@@ -163,20 +449,19 @@ impl ReadSnapshotEvent for LocalBinarySnapshotReader {
     }
 
     fn truncate(&mut self) -> Result<(), ReadError> {
-        if let Some(ref mut reader) = &mut self.reader {
-            let stable_position = reader.stream_position()?;
-            let file_path = Path::new(&self.root_path)
-                .join(format!("{}", self.times_advanced[self.next_file_idx - 1]));
+        if self.reader.is_some() {
+            let stable_position = self.decoder.stable_position();
+            let file_path =
+                Path::new(&self.root_path).join(self.chunks[self.next_file_idx - 1].to_string());
 
             info!("Truncate: Shrink {file_path:?} to {stable_position} bytes");
 
-            let file = OpenOptions::new().write(true).open(file_path)?;
-            file.set_len(stable_position)?;
+            cut_local_chunk(&file_path, stable_position, self.decoder.end_marker())?;
         }
 
-        for unreachable_part in &self.times_advanced[self.next_file_idx..] {
+        for unreachable_part in &self.chunks[self.next_file_idx..] {
             let snapshot_file_to_remove =
-                Path::new(&self.root_path).join(format!("{unreachable_part}"));
+                Path::new(&self.root_path).join(unreachable_part.to_string());
             info!("Truncate: Remove {snapshot_file_to_remove:?}");
             std::fs::remove_file(snapshot_file_to_remove)?;
         }
@@ -187,8 +472,11 @@ impl ReadSnapshotEvent for LocalBinarySnapshotReader {
 
 pub struct LocalBinarySnapshotWriter {
     root_path: PathBuf,
-    lazy_writer: Option<BufWriter<std::fs::File>>,
+    lazy_writer: Option<(BufWriter<std::fs::File>, ChunkEncoder)>,
     mode: SnapshotMode,
+    last_chunk_time: Option<Timestamp>,
+    compaction: Option<JoinHandle<()>>,
+    encryption: Option<Arc<Keyring>>,
 }
 
 impl LocalBinarySnapshotWriter {
@@ -199,8 +487,133 @@ impl LocalBinarySnapshotWriter {
             root_path: path.to_owned(),
             lazy_writer: None,
             mode,
+            last_chunk_time: None,
+            compaction: None,
+            encryption: None,
         })
     }
+
+    /// Enables the encryption of the written chunks with the current key.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Option<Arc<Keyring>>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
+
+// Closes the chunk with the end marker, after which no events can be added to it.
+fn close_local_chunk(
+    (mut writer, encoder): (BufWriter<std::fs::File>, ChunkEncoder),
+) -> Result<(), IoError> {
+    writer.write_all(&encoder.encode_end())?;
+    writer.flush()
+}
+
+impl Drop for LocalBinarySnapshotWriter {
+    fn drop(&mut self) {
+        if let Some(chunk) = self.lazy_writer.take() {
+            if let Err(e) = close_local_chunk(chunk) {
+                error!("Failed to close snapshot chunk: {e}");
+            }
+        }
+        if let Some(compaction) = take(&mut self.compaction) {
+            if let Err(e) = compaction.join() {
+                error!("Failed to join snapshot compaction thread: {e:?}");
+            }
+        }
+    }
+}
+
+fn read_local_chunk_prefix(chunk_path: &Path) -> Result<Vec<u8>, IoError> {
+    let mut prefix = Vec::with_capacity(MAX_HEADER_LEN);
+    File::open(chunk_path)?
+        .take(MAX_HEADER_LEN as u64)
+        .read_to_end(&mut prefix)?;
+    Ok(prefix)
+}
+
+fn compact_local_snapshot(
+    root_path: &Path,
+    chunks: Vec<SnapshotChunk>,
+    threshold: TotalFrontier<Timestamp>,
+    encryption: Option<&Arc<Keyring>>,
+) -> Result<(), ReadError> {
+    let Some(compacted_chunk) = compaction_target(&chunks, |chunk| {
+        let prefix = read_local_chunk_prefix(&root_path.join(chunk.to_string()))?;
+        Ok(is_chunk_outdated(&prefix, encryption.map(AsRef::as_ref)))
+    })?
+    else {
+        return Ok(());
+    };
+    let mut reader =
+        LocalBinarySnapshotReader::from_chunks(root_path.to_path_buf(), chunks.clone())
+            .with_encryption(encryption.cloned());
+    let Some(events) = compact_events(read_all_events(&mut reader)?, threshold) else {
+        return Ok(());
+    };
+
+    let temporary_path = root_path.join(format!("{compacted_chunk}{TEMPORARY_CHUNK_SUFFIX}"));
+    let mut writer = BufWriter::new(File::create(&temporary_path)?);
+    writer.write_all(&encode_chunk(
+        &events,
+        &compacted_chunk.to_string(),
+        encryption.map(AsRef::as_ref),
+    ))?;
+    writer
+        .into_inner()
+        .map_err(|e| ReadError::Io(e.into_error()))?
+        .sync_all()?;
+    // renaming is atomic, so the readers see either the old chunks or the compacted one
+    fs::rename(&temporary_path, root_path.join(compacted_chunk.to_string()))?;
+
+    for chunk in chunks {
+        if chunk == compacted_chunk {
+            continue;
+        }
+        let replaced_chunk_path = root_path.join(chunk.to_string());
+        if let Err(e) = fs::remove_file(&replaced_chunk_path) {
+            warn!("Failed to remove compacted snapshot chunk {replaced_chunk_path:?}: {e}");
+        }
+    }
+    info!(
+        "Compacted snapshot in {root_path:?} into {} entries",
+        events.len()
+    );
+    Ok(())
+}
+
+/// Rewrites the chunks that are not in the current format: the ones written before the framed
+/// format was introduced and, if the encryption is enabled, the ones that aren't encrypted
+/// with the current key. Returns the number of rewritten chunks.
+pub fn migrate_local_chunks(
+    root_path: &Path,
+    encryption: Option<&Arc<Keyring>>,
+) -> Result<usize, ReadError> {
+    let mut n_migrated = 0;
+    for chunk in local_snapshot_chunks(root_path)? {
+        let chunk_name = chunk.to_string();
+        let chunk_path = root_path.join(&chunk_name);
+        let contents = fs::read(&chunk_path)?;
+        if !is_chunk_outdated(&contents, encryption.map(AsRef::as_ref)) {
+            continue;
+        }
+        let events = decode_outdated_chunk(&contents, &chunk_name, encryption.cloned())?;
+        let temporary_path = root_path.join(format!("{chunk}{TEMPORARY_CHUNK_SUFFIX}"));
+        let mut file = File::create(&temporary_path)?;
+        file.write_all(&encode_chunk(
+            &events,
+            &chunk_name,
+            encryption.map(AsRef::as_ref),
+        ))?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, &chunk_path)?;
+        info!(
+            "Migrated snapshot chunk {chunk_path:?} with {} entries",
+            events.len()
+        );
+        n_migrated += 1;
+    }
+    Ok(n_migrated)
 }
 
 impl WriteSnapshotEvent for LocalBinarySnapshotWriter {
@@ -209,29 +622,31 @@ impl WriteSnapshotEvent for LocalBinarySnapshotWriter {
             return Ok(());
         }
 
-        let writer = {
+        let (writer, encoder) = {
             if let Some(lazy_writer) = &mut self.lazy_writer {
                 lazy_writer
             } else {
-                let current_timestamp = current_unix_timestamp_ms();
-                let path = self.root_path.join(format!("{current_timestamp}"));
-
-                self.lazy_writer = Some(BufWriter::new(File::create(path)?));
-                self.lazy_writer.as_mut().unwrap()
+                let chunk =
+                    SnapshotChunk::Plain(next_chunk_time(&mut self.last_chunk_time)).to_string();
+                let path = self.root_path.join(&chunk);
+
+                let mut writer = BufWriter::new(File::create(path)?);
+                let encoder = ChunkEncoder::new(&chunk, self.encryption.as_deref());
+                writer.write_all(&encoder.header())?;
+                self.lazy_writer.insert((writer, encoder))
             }
         };
 
-        serialize_into(writer, &event).map_err(|e| match *e {
-            BincodeError::Io(io_error) => WriteError::Io(*Box::new(io_error)),
-            _ => WriteError::Bincode(*e),
-        })
+        writer
+            .write_all(&encoder.encode_block(event))
+            .map_err(WriteError::Io)
     }
 
     fn flush(&mut self) -> OneShotReceiver<Result<(), WriteError>> {
         let (sender, receiver) = oneshot::channel();
 
         let internal_flush_result: Result<(), WriteError> = match &mut self.lazy_writer {
-            Some(ref mut writer) => writer.flush().map_err(WriteError::Io),
+            Some((ref mut writer, _)) => writer.flush().map_err(WriteError::Io),
             None => Ok(()),
         };
 
@@ -242,330 +657,364 @@ impl WriteSnapshotEvent for LocalBinarySnapshotWriter {
 
         receiver
     }
-}
-
-const SNAPSHOT_CONTENT_TYPE: &str = "application/octet-stream";
-const MAX_CHUNK_LEN: usize = 1_000_000;
 
-pub struct S3Writer {
-    bucket: S3Bucket,
-    key: String,
-    upload_id: String,
-    upload_parts: Vec<S3Part>,
-}
-
-impl S3Writer {
-    pub fn new(bucket: S3Bucket, path: &str) -> Result<Self, (S3CommandName, S3Error)> {
-        let start_upload_response = bucket
-            .initiate_multipart_upload(path, SNAPSHOT_CONTENT_TYPE)
-            .map_err(|e| (S3CommandName::InitiateMultipartUpload, e))?;
-
-        Ok(Self {
-            bucket,
-            key: start_upload_response.key,
-            upload_id: start_upload_response.upload_id,
-            upload_parts: Vec::new(),
-        })
-    }
+    fn compact(&mut self, threshold: TotalFrontier<Timestamp>) {
+        if self
+            .compaction
+            .as_ref()
+            .is_some_and(|compaction| !compaction.is_finished())
+        {
+            return;
+        }
 
-    pub fn put_chunk(&mut self, buffer: Vec<Event>) -> Result<(), (S3CommandName, S3Error)> {
-        let chunk = {
-            let mut chunk = Vec::new();
-            for entry in buffer {
-                let mut entry_serialized = serialize(&entry).expect("unable to serialize an entry");
-                chunk.append(&mut entry_serialized);
+        // the current chunk is closed, so that the compaction only reads complete chunks
+        if let Some(chunk) = self.lazy_writer.take() {
+            if let Err(e) = close_local_chunk(chunk) {
+                error!("Failed to close the snapshot chunk before compaction: {e}");
+                return;
+            }
+        }
+        let chunks = match local_snapshot_chunks(&self.root_path) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                error!("Failed to list snapshot chunks for compaction: {e}");
+                return;
             }
-            chunk
         };
 
-        let part_number =
-            u32::try_from(self.upload_parts.len()).expect("too many upload parts") + 1;
-        let part = self
-            .bucket
-            .put_multipart_chunk(
-                chunk,
-                &self.key,
-                part_number,
-                &self.upload_id,
-                SNAPSHOT_CONTENT_TYPE,
-            )
-            .map_err(|e| (S3CommandName::PutMultipartChunk, e))?;
-        self.upload_parts.push(part);
-
-        Ok(())
+        let root_path = self.root_path.clone();
+        let encryption = self.encryption.clone();
+        let compaction = thread::Builder::new()
+            .name("pathway:snapshot-compaction".to_string())
+            .spawn(move || {
+                if let Err(e) =
+                    compact_local_snapshot(&root_path, chunks, threshold, encryption.as_ref())
+                {
+                    error!("Failed to compact snapshot in {root_path:?}: {e}");
+                }
+            })
+            .expect("compaction thread creation failed");
+        self.compaction = Some(compaction);
     }
+}
 
-    pub fn finalize(self) -> Result<(), (S3CommandName, S3Error)> {
-        let response = self
-            .bucket
-            .complete_multipart_upload(&self.key, &self.upload_id, self.upload_parts)
-            .map_err(|e| (S3CommandName::CompleteMultipartUpload, e))?;
+pub struct ObjectStoreSnapshotReader {
+    store: Arc<dyn ObjectStore>,
+    root_path: String,
+    chunks: Vec<SnapshotChunk>,
+    next_chunk_idx: usize,
+    current_chunk: Option<Cursor<Vec<u8>>>,
+    decoder: ChunkDecoder,
+    encryption: Option<Arc<Keyring>>,
+}
 
-        if response.status_code() == 200 {
-            info!("Snapshot part loaded successfully: {response}");
-        } else {
-            error!("Upload finalization status code incorrect: {:?}", response);
+fn object_store_snapshot_chunks(
+    store: &dyn ObjectStore,
+    root_path: &str,
+) -> Result<Vec<SnapshotChunk>, ReadError> {
+    let prefix = format!("{root_path}/");
+    let mut chunks = Vec::new();
+    for key in store.list_objects(&prefix)? {
+        match key.strip_prefix(&prefix) {
+            Some(chunk_name) if !chunk_name.contains('/') => {
+                chunks.extend(SnapshotChunk::parse(chunk_name));
+            }
+            _ => warn!("Unexpected object in snapshot directory: {key}"),
         }
-
-        Ok(())
     }
+    Ok(SnapshotChunk::to_read(chunks))
 }
 
-pub struct S3SnapshotReader {
-    root_path: String,
-    reader: Option<PipeReader>,
-    next_object_idx: usize,
-    times_advanced: Vec<Timestamp>,
-
-    bucket: S3Bucket,
-    current_state: Option<CurrentlyProcessedS3Object>,
-    current_chunk_len: usize,
-}
-
-impl S3SnapshotReader {
-    pub fn new(bucket: S3Bucket, path: &str) -> Result<S3SnapshotReader, ReadError> {
-        let mut times_advanced = Vec::new();
-
-        let object_lists = bucket
-            .list(path.to_string(), None)
-            .map_err(|e| ReadError::S3(S3CommandName::ListObjectsV2, e))?;
-
-        for list in &object_lists {
-            for object in &list.contents {
-                let path_obj = Path::new(&object.key);
-                let Some(file_name) = path_obj.file_name() else {
-                    warn!("Not file-like path: {}", object.key);
-                    continue;
-                };
-                let Some(file_name_str) = file_name.to_str() else {
-                    error!("Unparsable file name in path {}", object.key);
-                    continue;
-                };
-                if let Ok(timestamp) = file_name_str.parse() {
-                    times_advanced.push(timestamp);
-                } else {
-                    error!("Unparsable timestamp. Full path: {}", object.key);
-                }
-            }
+impl ObjectStoreSnapshotReader {
+    pub fn new(
+        store: Arc<dyn ObjectStore>,
+        root_path: &str,
+    ) -> Result<ObjectStoreSnapshotReader, ReadError> {
+        let chunks = object_store_snapshot_chunks(store.as_ref(), root_path)?;
+        Ok(Self::from_chunks(store, root_path, chunks))
+    }
+
+    fn from_chunks(
+        store: Arc<dyn ObjectStore>,
+        root_path: &str,
+        chunks: Vec<SnapshotChunk>,
+    ) -> Self {
+        Self {
+            store,
+            root_path: root_path.to_string(),
+            chunks,
+            next_chunk_idx: 0,
+            current_chunk: None,
+            decoder: ChunkDecoder::new(String::new(), None),
+            encryption: None,
         }
+    }
 
-        times_advanced.sort_unstable();
+    /// Sets the keys to decrypt the encrypted chunks with.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Option<Arc<Keyring>>) -> Self {
+        self.encryption = encryption;
+        self
+    }
 
-        Ok(Self {
-            bucket,
-            root_path: path.to_string(),
-            reader: None,
-            next_object_idx: 0,
-            times_advanced,
-            current_state: None,
-            current_chunk_len: 0,
+    fn is_current_chunk_read(&self) -> bool {
+        self.current_chunk.as_ref().map_or(true, |chunk| {
+            usize::try_from(chunk.position()).unwrap() >= chunk.get_ref().len()
         })
     }
 }
 
-impl ReadSnapshotEvent for S3SnapshotReader {
+impl ReadSnapshotEvent for ObjectStoreSnapshotReader {
     fn read(&mut self) -> Result<Event, ReadError> {
         loop {
-            match &mut self.reader {
-                Some(reader) => match deserialize_from::<&mut PipeReader, Event>(reader) {
-                    Ok(entry) => {
-                        self.current_chunk_len += 1;
-                        return Ok(entry);
-                    }
-                    Err(e) => match *e {
-                        BincodeError::Io(e) => {
-                            if !matches!(e.kind(), IoErrorKind::UnexpectedEof) {
-                                return Err(ReadError::Io(e));
-                            }
-                            self.reader = None;
-                            continue;
-                        }
-                        _ => return Err(ReadError::Bincode(*e)),
-                    },
-                },
-                None => {
-                    if self.next_object_idx >= self.times_advanced.len() {
-                        break;
-                    }
-                    let current_file_path = format!(
-                        "{}/{}",
-                        self.root_path, self.times_advanced[self.next_object_idx]
-                    );
-                    let (new_current_state, pipe_reader) =
-                        S3Scanner::stream_object_from_path_and_bucket(
-                            &current_file_path,
-                            self.bucket.deep_copy(),
-                        );
-
-                    if let Some(state) = self.current_state.take() {
-                        state.finalize()?;
-                    }
-
-                    self.current_chunk_len = 0;
-                    self.current_state = Some(new_current_state);
-                    self.reader = Some(pipe_reader);
-                    self.next_object_idx += 1;
+            if let Some(chunk) = &mut self.current_chunk {
+                // the objects are put atomically, so an incomplete chunk means damaged data
+                if let Some(event) = self.decoder.next_event(chunk)? {
+                    return Ok(event);
+                }
+                if !self.decoder.is_complete() {
+                    return Err(ReadError::TruncatedSnapshot);
                 }
+                self.current_chunk = None;
             }
+            if self.next_chunk_idx >= self.chunks.len() {
+                return Ok(Event::Finished);
+            }
+            let chunk_name = self.chunks[self.next_chunk_idx].to_string();
+            let chunk_path = format!("{}/{chunk_name}", self.root_path);
+            self.current_chunk = Some(Cursor::new(self.store.get_object(&chunk_path)?));
+            self.decoder = ChunkDecoder::new(chunk_name, self.encryption.clone());
+            self.next_chunk_idx += 1;
         }
-        warn!("Attempted reading after the end of snapshot, returning finish event");
-        Ok(Event::Finished)
     }
 
     fn truncate(&mut self) -> Result<(), ReadError> {
-        // Truncate the current file by saving the currently read chunk
-        if self.next_object_idx > 0 && self.current_chunk_len > 0 {
-            let object_for_truncation = format!(
-                "{}/{}",
-                self.root_path,
-                self.times_advanced[self.next_object_idx - 1]
-            );
-
-            let object_after_truncation =
-                format!("{}/{}", self.root_path, current_unix_timestamp_ms());
-
-            let (_new_current_state, mut pipe_reader) =
-                S3Scanner::stream_object_from_path_and_bucket(
-                    &object_for_truncation,
-                    self.bucket.deep_copy(),
-                );
-            let mut writer = S3Writer::new(self.bucket.deep_copy(), &object_after_truncation)
-                .map_err(|(command, error)| ReadError::S3(command, error))?;
-
-            let mut n_entries_processed = 0;
-            let mut current_chunk = Vec::new();
-            while n_entries_processed < self.current_chunk_len {
-                let maybe_entry_read = deserialize_from::<&mut PipeReader, Event>(&mut pipe_reader);
-                if let Ok(entry) = maybe_entry_read {
-                    current_chunk.push(entry);
-                } else {
-                    error!("Unexpected end of dump object {object_for_truncation}");
-                    break;
-                }
-                if current_chunk.len() == MAX_CHUNK_LEN {
-                    writer
-                        .put_chunk(take(&mut current_chunk))
-                        .map_err(|(command, s3_error)| ReadError::S3(command, s3_error))?;
-                }
-                n_entries_processed += 1;
-            }
-            if !current_chunk.is_empty() {
-                writer
-                    .put_chunk(take(&mut current_chunk))
-                    .map_err(|(command, s3_error)| ReadError::S3(command, s3_error))?;
+        // Only the unread part of the current chunk is dropped, the read part is saved
+        // under a new name, so that the chunk is replaced atomically. The events are encoded
+        // again, as the encrypted ones are bound to the name of the chunk.
+        let mut removal_chunks_start = self.next_chunk_idx;
+        if !self.is_current_chunk_read() {
+            let chunk = self
+                .current_chunk
+                .as_ref()
+                .expect("the current chunk must be present");
+            let stable_position = usize::try_from(self.decoder.stable_position()).unwrap();
+            let chunk_for_truncation = self.chunks[self.next_chunk_idx - 1];
+            let events = decode_chunk_prefix(
+                &chunk.get_ref()[..stable_position],
+                &chunk_for_truncation.to_string(),
+                self.encryption.clone(),
+            )?;
+            if !events.is_empty() {
+                let chunk_after_truncation = chunk_for_truncation.renewed().to_string();
+                let path_after_truncation = format!("{}/{chunk_after_truncation}", self.root_path);
+                info!("Truncate: Save {} entries of {chunk_for_truncation} into {path_after_truncation}", events.len());
+                self.store.put_object(
+                    &path_after_truncation,
+                    &encode_chunk(&events, &chunk_after_truncation, self.encryption.as_deref()),
+                )?;
             }
-
-            writer
-                .finalize()
-                .map_err(|(command, error)| ReadError::S3(command, error))?;
+            removal_chunks_start -= 1;
         }
 
-        // Delete all further non-read files
-        let removal_files_start = self.next_object_idx.saturating_sub(1);
-        for unreachable_part in &self.times_advanced[removal_files_start..] {
-            let snapshot_file_to_remove = format!("{}/{unreachable_part}", self.root_path);
-            self.bucket
-                .delete_object(snapshot_file_to_remove)
-                .map_err(|e| ReadError::S3(S3CommandName::DeleteObject, e))?;
+        for unreachable_part in &self.chunks[removal_chunks_start..] {
+            let snapshot_path_to_remove = format!("{}/{unreachable_part}", self.root_path);
+            info!("Truncate: Remove {snapshot_path_to_remove}");
+            self.store.remove_object(&snapshot_path_to_remove)?;
         }
 
         Ok(())
     }
 }
 
-enum S3SnapshotWriterEvent {
-    Chunk(Vec<Event>),
-    Flush(OneShotSender<Result<(), WriteError>>),
-    Finish,
+pub struct ObjectStoreSnapshotWriter {
+    store: Arc<dyn ObjectStore>,
+    root_path: String,
+    mode: SnapshotMode,
+    // the chunk is named when its first event is written, as the encrypted events
+    // are bound to the name
+    current_chunk: Option<(SnapshotChunk, ChunkEncoder, Vec<u8>)>,
+    last_chunk_time: Option<Timestamp>,
+    compaction: Option<JoinHandle<()>>,
+    encryption: Option<Arc<Keyring>>,
 }
 
-pub struct S3SnapshotWriter {
-    current_chunk: Vec<Event>,
-    chunk_events_sender: Sender<S3SnapshotWriterEvent>,
-    uploader_thread: Option<std::thread::JoinHandle<()>>,
-    mode: SnapshotMode,
+impl ObjectStoreSnapshotWriter {
+    pub fn new(store: Arc<dyn ObjectStore>, root_path: &str, mode: SnapshotMode) -> Self {
+        Self {
+            store,
+            root_path: root_path.to_string(),
+            mode,
+            current_chunk: None,
+            last_chunk_time: None,
+            compaction: None,
+            encryption: None,
+        }
+    }
+
+    /// Enables the encryption of the written chunks with the current key.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Option<Arc<Keyring>>) -> Self {
+        self.encryption = encryption;
+        self
+    }
 }
 
-impl Drop for S3SnapshotWriter {
+impl Drop for ObjectStoreSnapshotWriter {
     fn drop(&mut self) {
-        self.chunk_events_sender
-            .send(S3SnapshotWriterEvent::Finish)
-            .expect("failed to submit the graceful shutdown event");
-        if let Some(uploader_thread) = take(&mut self.uploader_thread) {
-            if let Err(e) = uploader_thread.join() {
-                // there is no formatter for std::any::Any
-                error!("Failed to join s3 snapshot uploader thread: {e:?}");
+        if let Some(compaction) = take(&mut self.compaction) {
+            if let Err(e) = compaction.join() {
+                error!("Failed to join snapshot compaction thread: {e:?}");
             }
         }
     }
 }
 
-impl S3SnapshotWriter {
-    pub fn new(bucket: S3Bucket, chunks_root_path: &str, mode: SnapshotMode) -> Self {
-        let (chunk_events_sender, chunk_events_receiver) = mpsc::channel();
-
-        let inner_chunks_root_path = chunks_root_path.to_string();
-        let uploader_thread = thread::Builder::new()
-            .name("pathway:s3_snapshot-bg-writer".to_string())
-            .spawn(move || {
-                let mut s3_writer = S3Writer::new(bucket.deep_copy(), &format!("{}/{}", inner_chunks_root_path, current_unix_timestamp_ms())).expect("failed to construct s3 writer");
-                loop {
-                    let event = chunk_events_receiver.recv().expect("unexpected termination for s3 events sender");
-                    match event {
-                        S3SnapshotWriterEvent::Chunk(chunk) => {
-                            s3_writer.put_chunk(chunk).expect("failed to write snapshot chunk");
-                        }
-                        S3SnapshotWriterEvent::Flush(sender) => {
-                            let flush_result = s3_writer.finalize().map_err(|(command, s3_error)| WriteError::S3(command, s3_error));
-                            s3_writer = S3Writer::new(bucket.deep_copy(), &format!("{}/{}", inner_chunks_root_path, current_unix_timestamp_ms())).expect("failed to construct s3 writer");
-                            if let Err(unsent_flush_result) = sender.send(flush_result) {
-                                error!("The receiver no longer waits for the result of this flush: {unsent_flush_result:?}");
-                            }
-                        }
-                        S3SnapshotWriterEvent::Finish => break,
-                    }
-                }
-            })
-            .expect("s3 thread creation failed");
+fn compact_object_store_snapshot(
+    store: &dyn ObjectStore,
+    root_path: &str,
+    threshold: TotalFrontier<Timestamp>,
+    encryption: Option<&Arc<Keyring>>,
+) -> Result<(), ReadError> {
+    // the chunks are put at once, so all listed chunks are complete
+    let chunks = object_store_snapshot_chunks(store, root_path)?;
+    let Some(compacted_chunk) = compaction_target(&chunks, |chunk| {
+        let contents = store.get_object(&format!("{root_path}/{chunk}"))?;
+        Ok(is_chunk_outdated(&contents, encryption.map(AsRef::as_ref)))
+    })?
+    else {
+        return Ok(());
+    };
+    let mut events = Vec::new();
+    for chunk in &chunks {
+        events.extend(decode_chunk(
+            &store.get_object(&format!("{root_path}/{chunk}"))?,
+            &chunk.to_string(),
+            encryption.cloned(),
+        )?);
+    }
+    let Some(events) = compact_events(events, threshold) else {
+        return Ok(());
+    };
+
+    // putting an object is atomic, so the readers see either the old chunks or the compacted one
+    store.put_object(
+        &format!("{root_path}/{compacted_chunk}"),
+        &encode_chunk(
+            &events,
+            &compacted_chunk.to_string(),
+            encryption.map(AsRef::as_ref),
+        ),
+    )?;
+
+    for chunk in chunks {
+        if chunk == compacted_chunk {
+            continue;
+        }
+        if let Err(e) = store.remove_object(&format!("{root_path}/{chunk}")) {
+            warn!("Failed to remove compacted snapshot chunk {root_path}/{chunk}: {e}");
+        }
+    }
+    info!(
+        "Compacted snapshot in {root_path} into {} entries",
+        events.len()
+    );
+    Ok(())
+}
 
-        Self {
-            current_chunk: Vec::new(),
-            chunk_events_sender,
-            uploader_thread: Some(uploader_thread),
-            mode,
+/// Rewrites the chunks that are not in the current format: the ones written before the framed
+/// format was introduced and, if the encryption is enabled, the ones that aren't encrypted
+/// with the current key. Returns the number of rewritten chunks.
+pub fn migrate_object_store_chunks(
+    store: &dyn ObjectStore,
+    root_path: &str,
+    encryption: Option<&Arc<Keyring>>,
+) -> Result<usize, ReadError> {
+    let mut n_migrated = 0;
+    for chunk in object_store_snapshot_chunks(store, root_path)? {
+        let chunk_name = chunk.to_string();
+        let chunk_path = format!("{root_path}/{chunk_name}");
+        let contents = store.get_object(&chunk_path)?;
+        if !is_chunk_outdated(&contents, encryption.map(AsRef::as_ref)) {
+            continue;
         }
+        let events = decode_outdated_chunk(&contents, &chunk_name, encryption.cloned())?;
+        store.put_object(
+            &chunk_path,
+            &encode_chunk(&events, &chunk_name, encryption.map(AsRef::as_ref)),
+        )?;
+        info!(
+            "Migrated snapshot chunk {chunk_path} with {} entries",
+            events.len()
+        );
+        n_migrated += 1;
     }
+    Ok(n_migrated)
 }
 
-impl WriteSnapshotEvent for S3SnapshotWriter {
+impl WriteSnapshotEvent for ObjectStoreSnapshotWriter {
     fn write(&mut self, event: &Event) -> Result<(), WriteError> {
         if !self.mode.is_event_included(event) {
             return Ok(());
         }
 
-        self.current_chunk.push(event.clone());
-        if self.current_chunk.len() == MAX_CHUNK_LEN {
-            self.chunk_events_sender
-                .send(S3SnapshotWriterEvent::Chunk(take(&mut self.current_chunk)))
-                .expect("chunk queue submission should not fail");
-        }
+        let (_, encoder, contents) = self.current_chunk.get_or_insert_with(|| {
+            let chunk = SnapshotChunk::Plain(next_chunk_time(&mut self.last_chunk_time));
+            let encoder = ChunkEncoder::new(&chunk.to_string(), self.encryption.as_deref());
+            let header = encoder.header();
+            (chunk, encoder, header)
+        });
+        contents.extend(encoder.encode_block(event));
         Ok(())
     }
 
     fn flush(&mut self) -> OneShotReceiver<Result<(), WriteError>> {
-        if !self.current_chunk.is_empty() {
-            self.chunk_events_sender
-                .send(S3SnapshotWriterEvent::Chunk(take(&mut self.current_chunk)))
-                .expect("chunk queue submission should not fail");
-        }
-
         let (sender, receiver) = oneshot::channel();
-        self.chunk_events_sender
-            .send(S3SnapshotWriterEvent::Flush(sender))
-            .expect("flush event submission should not fail");
+
+        let flush_result = match self.current_chunk.take() {
+            Some((chunk, encoder, mut contents)) => {
+                contents.extend(encoder.encode_end());
+                self.store
+                    .put_object(&format!("{}/{chunk}", self.root_path), &contents)
+                    .map_err(WriteError::ObjectStore)
+            }
+            None => Ok(()),
+        };
+
+        if let Err(unsent_flush_result) = sender.send(flush_result) {
+            error!("The receiver no longer waits for the result of this flush: {unsent_flush_result:?}");
+        }
 
         receiver
     }
+
+    fn compact(&mut self, threshold: TotalFrontier<Timestamp>) {
+        if self
+            .compaction
+            .as_ref()
+            .is_some_and(|compaction| !compaction.is_finished())
+        {
+            return;
+        }
+
+        let store = self.store.clone();
+        let root_path = self.root_path.clone();
+        let encryption = self.encryption.clone();
+        let compaction = thread::Builder::new()
+            .name("pathway:snapshot-compaction".to_string())
+            .spawn(move || {
+                if let Err(e) = compact_object_store_snapshot(
+                    store.as_ref(),
+                    &root_path,
+                    threshold,
+                    encryption.as_ref(),
+                ) {
+                    error!("Failed to compact snapshot in {root_path}: {e}");
+                }
+            })
+            .expect("compaction thread creation failed");
+        self.compaction = Some(compaction);
+    }
 }
 
 pub struct MockSnapshotReader {
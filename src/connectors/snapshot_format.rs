@@ -0,0 +1,532 @@
+// Copyright © 2024 Pathway
+
+//! The framed format of the snapshot chunks.
+//!
+//! A chunk starts with a header, containing the format version and the fingerprint of the
+//! serialized data layout. It is followed by blocks, each holding a single event along
+//! with its length and checksum, so that a partially written tail or a damaged block is
+//! detected instead of being deserialized into garbage.
+//!
+//! If the encryption is enabled, the chunk header has a different magic and also holds
+//! the id of the key and the salt, from which the key of the chunk is derived. The payloads
+//! of the blocks are encrypted with that key and bound to the name of the chunk and to their
+//! positions in it. An encrypted chunk ends with an empty block, so that a chunk which was cut
+//! is detected. The checksums are computed over the encrypted payloads, so that damaged
+//! data is detected even without the key.
+//!
+//! The chunks written before the format was introduced have no header and are a plain
+//! sequence of serialized events. They are still readable and are rewritten in the framed
+//! format by the compaction or by the explicit migration.
+//!
+//! While the encryption is enabled, the chunks that aren't encrypted are rejected, so that
+//! replaced or injected plaintext chunks aren't read. They are only read by the explicit
+//! migration, which encrypts them.
+
+use std::io::{Cursor, Error as IoError, ErrorKind as IoErrorKind, Read};
+use std::sync::Arc;
+
+use bincode::{deserialize, deserialize_from, serialize, ErrorKind as BincodeError};
+use log::warn;
+use ndarray::{ArrayD, IxDyn};
+use once_cell::sync::Lazy;
+use ordered_float::OrderedFloat;
+use serde_json::json;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::connectors::data_storage::ReadError;
+use crate::connectors::snapshot::Event;
+use crate::connectors::{OffsetKey, OffsetValue};
+use crate::engine::{DateTimeNaive, DateTimeUtc, Duration, Key, PyObjectWrapper, Timestamp, Value};
+use crate::persistence::encryption::{ChunkCipher, Error as EncryptionError, Keyring, SALT_LEN};
+use crate::persistence::frontier::OffsetAntichain;
+
+const MAGIC: [u8; 4] = *b"PWSS";
+const ENCRYPTED_MAGIC: [u8; 4] = *b"PWSE";
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 16;
+const KEY_ID_LEN: usize = 8;
+const BLOCK_HEADER_LEN: usize = 12;
+// The writers don't produce larger blocks, so a longer length in a block header
+// means that it's damaged.
+const MAX_BLOCK_PAYLOAD_LEN: u32 = 1 << 30;
+
+/// The number of bytes at the beginning of a chunk that are enough to tell its format.
+pub const MAX_HEADER_LEN: usize = HEADER_LEN + KEY_ID_LEN + SALT_LEN;
+
+// Fails to compile when a variant is added to `Value`, as the variant has to be
+// added to the schema sample as well.
+fn ensure_sampled(value: &Value) {
+    match value {
+        Value::None
+        | Value::Bool(_)
+        | Value::Int(_)
+        | Value::Float(_)
+        | Value::Pointer(_)
+        | Value::String(_)
+        | Value::Bytes(_)
+        | Value::Tuple(_)
+        | Value::IntArray(_)
+        | Value::FloatArray(_)
+        | Value::DateTimeNaive(_)
+        | Value::DateTimeUtc(_)
+        | Value::Duration(_)
+        | Value::Json(_)
+        | Value::Error
+        | Value::PyObjectWrapper(_) => {}
+    }
+}
+
+/// Fingerprint of the serialized layout of the events. It changes when the variants of
+/// `Value`, `Event` or the offsets are reordered or inserted before the sampled ones.
+pub static SCHEMA_FINGERPRINT: Lazy<u64> = Lazy::new(|| {
+    let key = Key(1);
+    let values = vec![
+        Value::None,
+        Value::Bool(true),
+        Value::Int(1),
+        Value::Float(OrderedFloat(1.5)),
+        Value::Pointer(key),
+        Value::String("a".into()),
+        Value::from(&b"b"[..]),
+        Value::Tuple(Arc::from(vec![Value::Int(1)])),
+        Value::from(ArrayD::from_elem(IxDyn(&[1, 2]), 1_i64)),
+        Value::from(ArrayD::from_elem(IxDyn(&[1, 2]), 1.5_f64)),
+        Value::from(DateTimeNaive::new(1)),
+        Value::from(DateTimeUtc::new(1)),
+        Value::from(Duration::new(1)),
+        Value::from(json!({"a": [1, 1.5, "b", null]})),
+        Value::Error,
+    ];
+    values.iter().for_each(ensure_sampled);
+    let mut frontier = OffsetAntichain::new();
+    // a single entry, as the order of entries in the antichain is not deterministic
+    frontier.advance_offset(OffsetKey::Empty, OffsetValue::Empty);
+    let sample = vec![
+        Event::Insert(key, values.clone()),
+        Event::Delete(key, values.clone()),
+        Event::Upsert(key, Some(values)),
+        Event::Upsert(key, None),
+        Event::AdvanceTime(Timestamp(2), frontier),
+        Event::Finished,
+    ];
+    let mut serialized = serialize(&sample).expect("unable to serialize the schema sample");
+    // A Python object can't be created without the interpreter, so the wrapper is
+    // sampled as its variant index, which follows the one of `Value::Error`, and its payload.
+    let error_variant_index = u32::from_le_bytes(
+        serialize(&Value::Error).expect("unable to serialize the schema sample")[..4]
+            .try_into()
+            .unwrap(),
+    );
+    serialized.extend(
+        serialize(&(error_variant_index + 1, PyObjectWrapper::layout_sample()))
+            .expect("unable to serialize the schema sample"),
+    );
+    xxh3_64(&serialized)
+});
+
+fn frame_block(payload: &[u8]) -> Vec<u8> {
+    let payload_len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_BLOCK_PAYLOAD_LEN)
+        .expect("snapshot entry is too large");
+    let mut block = Vec::with_capacity(BLOCK_HEADER_LEN + payload.len());
+    block.extend_from_slice(&payload_len.to_le_bytes());
+    block.extend_from_slice(&xxh3_64(payload).to_le_bytes());
+    block.extend_from_slice(payload);
+    block
+}
+
+/// Encodes the events of a single chunk. The name of the chunk has to be known
+/// in advance, as the encrypted blocks are bound to it.
+#[derive(Debug)]
+pub struct ChunkEncoder {
+    cipher: Option<ChunkCipher>,
+    n_blocks: u64,
+}
+
+impl ChunkEncoder {
+    pub fn new(chunk_name: &str, encryption: Option<&Keyring>) -> Self {
+        Self {
+            cipher: encryption.map(|keyring| keyring.new_chunk_cipher(chunk_name)),
+            n_blocks: 0,
+        }
+    }
+
+    pub fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(MAX_HEADER_LEN);
+        header.extend_from_slice(match self.cipher {
+            Some(_) => &ENCRYPTED_MAGIC,
+            None => &MAGIC,
+        });
+        header.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+        header.extend_from_slice(&SCHEMA_FINGERPRINT.to_le_bytes());
+        if let Some(cipher) = &self.cipher {
+            header.extend_from_slice(&cipher.key_id().to_le_bytes());
+            header.extend_from_slice(cipher.salt());
+        }
+        header
+    }
+
+    pub fn encode_block(&mut self, event: &Event) -> Vec<u8> {
+        let mut payload = serialize(event).expect("unable to serialize an entry");
+        if let Some(cipher) = &self.cipher {
+            payload = cipher.encrypt_block(self.n_blocks, &payload);
+        }
+        self.n_blocks += 1;
+        frame_block(&payload)
+    }
+
+    /// Returns the marker of the end of the chunk, which is empty if it isn't encrypted.
+    pub fn encode_end(&self) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => frame_block(&cipher.encrypt_end(self.n_blocks)),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Encodes the events as a complete chunk with the given name.
+pub fn encode_chunk<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    chunk_name: &str,
+    encryption: Option<&Keyring>,
+) -> Vec<u8> {
+    let mut encoder = ChunkEncoder::new(chunk_name, encryption);
+    let mut chunk = encoder.header();
+    for event in events {
+        chunk.extend(encoder.encode_block(event));
+    }
+    chunk.extend(encoder.encode_end());
+    chunk
+}
+
+// Unlike `read_exact`, tells how many bytes were read before the end of the input.
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, IoError> {
+    let mut n_read = 0;
+    while n_read < buffer.len() {
+        match reader.read(&mut buffer[n_read..]) {
+            Ok(0) => break,
+            Ok(n) => n_read += n,
+            Err(e) if e.kind() == IoErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n_read)
+}
+
+// Tells how many bytes an event of a legacy chunk takes.
+struct CountingReader<R> {
+    inner: R,
+    n_read: u64,
+}
+
+impl<R: Read> CountingReader<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, n_read: 0 }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        let n = self.inner.read(buf)?;
+        self.n_read += n as u64;
+        Ok(n)
+    }
+}
+
+#[derive(Debug)]
+enum ChunkFormat {
+    Unknown,
+    Framed { cipher: Option<Box<ChunkCipher>> },
+    Legacy { unread_prefix: Option<[u8; 4]> },
+}
+
+/// Decodes the events of a single chunk from a reader, which is passed to each call,
+/// so that the chunk readers can keep their own reader types.
+#[derive(Debug)]
+pub struct ChunkDecoder {
+    chunk_name: String,
+    format: ChunkFormat,
+    stable_position: u64,
+    n_blocks: u64,
+    is_ended: bool,
+    accepts_plaintext: bool,
+    encryption: Option<Arc<Keyring>>,
+}
+
+impl ChunkDecoder {
+    pub fn new(chunk_name: String, encryption: Option<Arc<Keyring>>) -> Self {
+        Self {
+            chunk_name,
+            format: ChunkFormat::Unknown,
+            stable_position: 0,
+            n_blocks: 0,
+            is_ended: false,
+            accepts_plaintext: encryption.is_none(),
+            encryption,
+        }
+    }
+
+    /// Accepts the chunks that aren't encrypted even if the encryption is enabled.
+    /// Only for the migration, which encrypts them.
+    #[must_use]
+    pub fn accepting_plaintext(mut self) -> Self {
+        self.accepts_plaintext = true;
+        self
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        matches!(self.format, ChunkFormat::Legacy { .. })
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.format, ChunkFormat::Framed { cipher: Some(_) })
+    }
+
+    /// Tells whether the whole chunk has been decoded. Only the encrypted chunks have
+    /// the marker of the end, for the others it is enough that the input is over.
+    pub fn is_complete(&self) -> bool {
+        !self.is_encrypted() || self.is_ended
+    }
+
+    /// The number of bytes of the chunk that have been decoded successfully, not including
+    /// the end marker. A chunk can be cut to this length and then closed with `end_marker`.
+    pub fn stable_position(&self) -> u64 {
+        self.stable_position
+    }
+
+    /// The marker that closes the chunk cut at the stable position, if it's encrypted.
+    pub fn end_marker(&self) -> Option<Vec<u8>> {
+        match &self.format {
+            ChunkFormat::Framed {
+                cipher: Some(cipher),
+            } => Some(frame_block(&cipher.encrypt_end(self.n_blocks))),
+            _ => None,
+        }
+    }
+
+    /// Returns the next event of the chunk or `None` if the chunk is over.
+    /// The end of an encrypted chunk that is missing its marker is reported
+    /// by `is_complete`, as a chunk may be read only partially on purpose.
+    pub fn next_event(&mut self, reader: &mut impl Read) -> Result<Option<Event>, ReadError> {
+        loop {
+            match &mut self.format {
+                ChunkFormat::Unknown => {
+                    let mut prefix = [0; 4];
+                    match read_up_to(reader, &mut prefix)? {
+                        0 => return Ok(None),
+                        4 => {}
+                        _ => return Err(ReadError::TruncatedSnapshot),
+                    }
+                    if prefix != ENCRYPTED_MAGIC && !self.accepts_plaintext {
+                        return Err(EncryptionError::Unencrypted.into());
+                    }
+                    self.format = if prefix == MAGIC || prefix == ENCRYPTED_MAGIC {
+                        let cipher = self.read_header(reader, prefix == ENCRYPTED_MAGIC)?;
+                        ChunkFormat::Framed { cipher }
+                    } else {
+                        // a legacy chunk starts with the variant index of an event,
+                        // which can't be equal to the magic bytes
+                        ChunkFormat::Legacy {
+                            unread_prefix: Some(prefix),
+                        }
+                    };
+                }
+                ChunkFormat::Legacy { unread_prefix } => {
+                    let (event, n_read): (bincode::Result<Event>, _) =
+                        if let Some(prefix) = unread_prefix.take() {
+                            let mut reader = CountingReader::new(Cursor::new(prefix).chain(reader));
+                            (deserialize_from(&mut reader), reader.n_read)
+                        } else {
+                            let mut reader = CountingReader::new(reader);
+                            (deserialize_from(&mut reader), reader.n_read)
+                        };
+                    return match event {
+                        Ok(event) => {
+                            self.stable_position += n_read;
+                            Ok(Some(event))
+                        }
+                        Err(e) => match *e {
+                            // the chunk is over only if it ends between the events
+                            BincodeError::Io(e)
+                                if e.kind() == IoErrorKind::UnexpectedEof && n_read == 0 =>
+                            {
+                                Ok(None)
+                            }
+                            BincodeError::Io(e) if e.kind() == IoErrorKind::UnexpectedEof => {
+                                Err(ReadError::TruncatedSnapshot)
+                            }
+                            BincodeError::Io(e) => Err(ReadError::Io(e)),
+                            e => Err(ReadError::Bincode(e)),
+                        },
+                    };
+                }
+                ChunkFormat::Framed { .. } => return self.read_block(reader),
+            }
+        }
+    }
+
+    fn read_header(
+        &mut self,
+        reader: &mut impl Read,
+        is_encrypted: bool,
+    ) -> Result<Option<Box<ChunkCipher>>, ReadError> {
+        let mut header = [0; MAX_HEADER_LEN - 4];
+        let header_len = if is_encrypted {
+            header.len()
+        } else {
+            HEADER_LEN - 4
+        };
+        let header = &mut header[..header_len];
+        if read_up_to(reader, header)? < header.len() {
+            return Err(ReadError::TruncatedSnapshot);
+        }
+        let version = u32::from_le_bytes(header[..4].try_into().unwrap());
+        if version != SNAPSHOT_FORMAT_VERSION {
+            return Err(ReadError::UnsupportedSnapshotVersion(version));
+        }
+        let fingerprint = u64::from_le_bytes(header[4..12].try_into().unwrap());
+        if fingerprint != *SCHEMA_FINGERPRINT {
+            return Err(ReadError::IncompatibleSnapshotSchema {
+                found: fingerprint,
+                expected: *SCHEMA_FINGERPRINT,
+            });
+        }
+        self.stable_position = (header_len + 4) as u64;
+        if !is_encrypted {
+            return Ok(None);
+        }
+        let Some(keyring) = &self.encryption else {
+            return Err(EncryptionError::MissingKey.into());
+        };
+        let key_id = u64::from_le_bytes(header[12..12 + KEY_ID_LEN].try_into().unwrap());
+        let salt = header[12 + KEY_ID_LEN..].try_into().unwrap();
+        let cipher = keyring.chunk_cipher(key_id, salt, &self.chunk_name)?;
+        Ok(Some(Box::new(cipher)))
+    }
+
+    fn read_block(&mut self, reader: &mut impl Read) -> Result<Option<Event>, ReadError> {
+        if self.is_ended {
+            // nothing can follow the end marker
+            return match read_up_to(reader, &mut [0])? {
+                0 => Ok(None),
+                _ => Err(ReadError::MalformedData),
+            };
+        }
+        let mut block_header = [0; BLOCK_HEADER_LEN];
+        match read_up_to(reader, &mut block_header)? {
+            0 => return Ok(None),
+            BLOCK_HEADER_LEN => {}
+            _ => return Err(ReadError::TruncatedSnapshot),
+        }
+        let payload_len = u32::from_le_bytes(block_header[..4].try_into().unwrap());
+        let checksum = u64::from_le_bytes(block_header[4..].try_into().unwrap());
+
+        if payload_len > MAX_BLOCK_PAYLOAD_LEN {
+            warn!(
+                "Snapshot block at byte {} has a malformed length {payload_len}",
+                self.stable_position
+            );
+            return Err(ReadError::MalformedData);
+        }
+        // the buffer grows with the data that is actually there, not with the declared length
+        let mut payload = Vec::new();
+        reader
+            .by_ref()
+            .take(u64::from(payload_len))
+            .read_to_end(&mut payload)?;
+        if payload.len() < payload_len as usize {
+            return Err(ReadError::TruncatedSnapshot);
+        }
+        if xxh3_64(&payload) != checksum {
+            warn!("Snapshot block at byte {} is damaged", self.stable_position);
+            return Err(ReadError::SnapshotChecksumMismatch);
+        }
+        let block_len = BLOCK_HEADER_LEN + payload.len();
+        if let ChunkFormat::Framed {
+            cipher: Some(cipher),
+        } = &self.format
+        {
+            let Some(plaintext) = cipher.decrypt_block(self.n_blocks, &payload)? else {
+                self.is_ended = true;
+                return self.read_block(reader);
+            };
+            payload = plaintext;
+        }
+        let event = deserialize(&payload).map_err(|e| ReadError::Bincode(*e))?;
+        self.stable_position += block_len as u64;
+        self.n_blocks += 1;
+        Ok(Some(event))
+    }
+}
+
+/// Tells whether the chunk, given by its first `MAX_HEADER_LEN` bytes, has to be rewritten
+/// to be in the current format: it was written before the framed format was introduced,
+/// or it isn't encrypted with the current key while the encryption is enabled.
+pub fn is_chunk_outdated(chunk_prefix: &[u8], encryption: Option<&Keyring>) -> bool {
+    if chunk_prefix.is_empty() {
+        false
+    } else if chunk_prefix.starts_with(&MAGIC) {
+        encryption.is_some()
+    } else if chunk_prefix.starts_with(&ENCRYPTED_MAGIC) {
+        // an encrypted chunk can't be rewritten without the keys
+        encryption.is_some_and(|keyring| {
+            chunk_prefix.get(HEADER_LEN..HEADER_LEN + KEY_ID_LEN)
+                != Some(&keyring.current_key_id().to_le_bytes()[..])
+        })
+    } else {
+        true
+    }
+}
+
+fn decode_events(chunk: &[u8], decoder: &mut ChunkDecoder) -> Result<Vec<Event>, ReadError> {
+    let mut reader = Cursor::new(chunk);
+    let mut events = Vec::new();
+    while let Some(event) = decoder.next_event(&mut reader)? {
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn decode_complete_chunk(chunk: &[u8], mut decoder: ChunkDecoder) -> Result<Vec<Event>, ReadError> {
+    let events = decode_events(chunk, &mut decoder)?;
+    if !decoder.is_complete() {
+        return Err(ReadError::TruncatedSnapshot);
+    }
+    Ok(events)
+}
+
+/// Decodes all events of a complete chunk held in memory.
+pub fn decode_chunk(
+    chunk: &[u8],
+    chunk_name: &str,
+    encryption: Option<Arc<Keyring>>,
+) -> Result<Vec<Event>, ReadError> {
+    decode_complete_chunk(chunk, ChunkDecoder::new(chunk_name.to_string(), encryption))
+}
+
+/// Decodes all events of a complete chunk that is going to be rewritten in the current
+/// format, which may be unencrypted even if the encryption is enabled.
+pub fn decode_outdated_chunk(
+    chunk: &[u8],
+    chunk_name: &str,
+    encryption: Option<Arc<Keyring>>,
+) -> Result<Vec<Event>, ReadError> {
+    decode_complete_chunk(
+        chunk,
+        ChunkDecoder::new(chunk_name.to_string(), encryption).accepting_plaintext(),
+    )
+}
+
+/// Decodes the events of the beginning of a chunk held in memory, which may end
+/// at any block, e.g. at the stable position of a decoder of the whole chunk.
+pub fn decode_chunk_prefix(
+    chunk_prefix: &[u8],
+    chunk_name: &str,
+    encryption: Option<Arc<Keyring>>,
+) -> Result<Vec<Event>, ReadError> {
+    decode_events(
+        chunk_prefix,
+        &mut ChunkDecoder::new(chunk_name.to_string(), encryption),
+    )
+}
@@ -3,6 +3,7 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::non_canonical_partial_ord_impl)] // False positive with Derivative
 
+pub mod arrangement_stats;
 mod complex_columns;
 pub mod config;
 mod export;
@@ -14,7 +15,10 @@ mod variable;
 use crate::connectors::adaptors::{GenericValues, ValuesSessionAdaptor};
 use crate::connectors::data_format::{Formatter, Parser};
 use crate::connectors::data_storage::{ReaderBuilder, Writer};
-use crate::connectors::monitoring::{ConnectorMonitor, ConnectorStats, OutputConnectorStats};
+use crate::connectors::dead_letter::{DeadLetterQueue, DeadLetterRecord};
+use crate::connectors::monitoring::{
+    ConnectorMonitor, ConnectorStats, ErrorBudget, OutputConnectorStats, SinkStats,
+};
 use crate::connectors::snapshot::Event as SnapshotEvent;
 use crate::connectors::{read_persisted_state, ARTIFICIAL_TIME_ON_REWIND_START};
 use crate::connectors::{Connector, PersistenceMode, SnapshotAccess, SnapshotMode};
@@ -34,7 +38,7 @@ use crate::timestamp::current_unix_timestamp_ms;
 
 use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
-use std::cmp::min;
+use std::cmp::{min, Reverse};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::iter::once;
@@ -60,6 +64,7 @@ use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
 use differential_dataflow::operators::reduce::{Reduce, ReduceCore};
 use differential_dataflow::operators::JoinCore;
 use differential_dataflow::trace::implementations::ord::{OrdKeySpine, OrdValSpine};
+use differential_dataflow::trace::{BatchReader, Cursor};
 use differential_dataflow::Collection;
 use differential_dataflow::{AsCollection as _, Data};
 use futures::future::BoxFuture;
@@ -68,6 +73,7 @@ use itertools::{chain, process_results, Itertools};
 use log::{error, info};
 use ndarray::ArrayD;
 use once_cell::unsync::{Lazy, OnceCell};
+use opentelemetry::KeyValue;
 use pyo3::PyObject;
 use serde::{Deserialize, Serialize};
 use timely::dataflow::operators::probe::Handle as ProbeHandle;
@@ -80,13 +86,17 @@ use timely::progress::timestamp::Refines;
 use timely::progress::Timestamp as TimestampTrait;
 use xxhash_rust::xxh3::Xxh3 as Hasher;
 
+use self::arrangement_stats::{arrange_deferred, ArrangementStatsRegistry};
 use self::complex_columns::complex_columns;
 use self::export::{export_table, import_table};
 use self::maybe_total::{MaybeTotalScope, MaybeTotalTimestamp, NotTotal, Total};
 use self::operators::output::{ConsolidateForOutput, OutputBatch};
 use self::operators::prev_next::add_prev_next_pointers;
+use self::operators::session_window::SessionWindows;
 use self::operators::stateful_reduce::StatefulReduce;
+use self::operators::temporal_join::{AsofDirection, JoinSide, TemporalJoin};
 use self::operators::time_column::{MaxTimestamp, TimeColumnBuffer};
+use self::operators::top_k::TopK;
 use self::operators::{ArrangeWithTypes, MapWithConsistentDeletions, MapWrapped};
 use self::operators::{MaybeTotal, Reshard};
 use self::shard::Shard;
@@ -95,25 +105,29 @@ use super::error::{DataError, DataResult, DynError, DynResult, Trace};
 use super::expression::AnyExpression;
 use super::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
 use super::graph::{DataRow, ExportedTable, OperatorProperties, SubscribeCallbacks};
-use super::http_server::maybe_run_http_server_thread;
+use super::health::{HealthState, HEARTBEAT_INTERVAL};
+use super::http_server::{maybe_run_http_server_thread, monitoring_http_address};
 use super::license::License;
 use super::progress_reporter::{maybe_run_reporter, MonitoringLevel};
 use super::reduce::{
-    AnyReducer, ArgMaxReducer, ArgMinReducer, ArraySumReducer, CountReducer, EarliestReducer,
-    FloatSumReducer, IntSumReducer, LatestReducer, MaxReducer, MinReducer, ReducerImpl,
-    SemigroupReducerImpl, SortedTupleReducer, StatefulCombineFn, StatefulReducer, TupleReducer,
-    UniqueReducer,
+    AnyReducer, ArgMaxReducer, ArgMinReducer, ArraySumReducer, CountDistinctApproxReducer,
+    CountDistinctReducer, CountReducer, EarliestReducer, FloatSumReducer, IntSumReducer,
+    LatestReducer, MaxReducer, MinReducer, MomentsReducer, MomentsStatistic, QuantilesReducer,
+    ReducerImpl, SemigroupReducerImpl, SortedTupleReducer, StatefulCombineFn, StatefulReducer,
+    TopKOrder, TopKReducer, TupleReducer, UniqueReducer,
 };
 use super::report_error::{
     LogError, ReportError, ReportErrorExt, SpawnWithReporter, UnwrapWithErrorLogger,
     UnwrapWithReporter,
 };
-use super::telemetry::maybe_run_telemetry_thread;
+use super::telemetry::{
+    maybe_run_telemetry_thread, timestamp_attribute, SpanGuard, TimestampSpans,
+};
 use super::{
     BatchWrapper, ColumnHandle, ColumnPath, ColumnProperties, ComplexColumn, Error, ErrorLogHandle,
     Expression, ExpressionData, Graph, IterationLogic, IxKeyPolicy, JoinData, JoinType, Key,
     LegacyTable, OperatorStats, ProberStats, Reducer, ReducerData, Result, ShardPolicy,
-    TableHandle, TableProperties, Timestamp, UniverseHandle, Value,
+    TableHandle, TableProperties, Timestamp, UniverseHandle, Value, Window, WindowBehavior,
 };
 use crate::external_integration::{
     make_accessor, make_option_accessor, ExternalIndex, IndexDerivedImpl,
@@ -189,6 +203,16 @@ impl<S: MaybeTotalScope> Deref for Values<S> {
 }
 
 impl<S: MaybeTotalScope> Values<S> {
+    fn scope(&self) -> S {
+        match self {
+            Self::Int { int_collection, .. } => int_collection.scope(),
+            Self::Pointer {
+                pointer_collection, ..
+            } => pointer_collection.scope(),
+            Self::Generic { generic_collection } => generic_collection.scope(),
+        }
+    }
+
     fn as_generic(&self) -> &Collection<S, (Key, Value)> {
         match self {
             Self::Int {
@@ -247,6 +271,7 @@ enum UniverseData<S: MaybeTotalScope> {
         collection: Keys<S>,
         arranged: OnceCell<KeysArranged<S>>,
         consolidated: OnceCell<Keys<S>>,
+        created_at: usize,
     },
     FromArranged {
         arranged: KeysArranged<S>,
@@ -258,10 +283,12 @@ impl<S: MaybeTotalScope> UniverseData<S> {
     fn from_collection(collection: Keys<S>) -> Self {
         let arranged = OnceCell::new();
         let consolidated = OnceCell::new();
+        let created_at = collection.scope().new_identifier();
         Self::FromCollection {
             collection,
             arranged,
             consolidated,
+            created_at,
         }
     }
 
@@ -289,8 +316,13 @@ impl<S: MaybeTotalScope> UniverseData<S> {
             Self::FromCollection {
                 collection,
                 arranged,
+                created_at,
                 ..
-            } => arranged.get_or_init(|| collection.arrange()),
+            } => arranged.get_or_init(|| {
+                arrange_deferred(&mut collection.scope(), *created_at, || {
+                    collection.arrange()
+                })
+            }),
         }
     }
 
@@ -342,29 +374,41 @@ enum ColumnData<S: MaybeTotalScope> {
         arranged: OnceCell<ValuesArranged<S>>,
         consolidated: OnceCell<Values<S>>,
         keys: OnceCell<Keys<S>>,
+        created_at: usize,
     },
     Arranged {
         arranged: ValuesArranged<S>,
         collection: OnceCell<Values<S>>,
         keys: OnceCell<Keys<S>>,
+        created_at: usize,
     },
 }
 
 impl<S: MaybeTotalScope> ColumnData<S> {
     fn from_collection(collection: Values<S>) -> Self {
+        let created_at = collection.scope().new_identifier();
         Self::Collection {
             collection,
             arranged: OnceCell::new(),
             consolidated: OnceCell::new(),
             keys: OnceCell::new(),
+            created_at,
         }
     }
 
     fn from_arranged(arranged: ValuesArranged<S>) -> Self {
+        let created_at = arranged.stream.scope().new_identifier();
         Self::Arranged {
             collection: OnceCell::new(),
             arranged,
             keys: OnceCell::new(),
+            created_at,
+        }
+    }
+
+    fn created_at(&self) -> usize {
+        match self {
+            Self::Collection { created_at, .. } | Self::Arranged { created_at, .. } => *created_at,
         }
     }
 
@@ -382,9 +426,16 @@ impl<S: MaybeTotalScope> ColumnData<S> {
     fn arranged(&self) -> &ValuesArranged<S> {
         match self {
             Self::Arranged { arranged, .. } => arranged,
-            Self::Collection { arranged, .. } => {
-                arranged.get_or_init(|| self.collection().arrange())
-            }
+            Self::Collection {
+                collection,
+                arranged,
+                created_at,
+                ..
+            } => arranged.get_or_init(|| {
+                arrange_deferred(&mut collection.scope(), *created_at, || {
+                    collection.arrange()
+                })
+            }),
         }
     }
 
@@ -398,7 +449,8 @@ impl<S: MaybeTotalScope> ColumnData<S> {
     }
 
     fn keys_arranged(&self) -> KeysArranged<S> {
-        self.keys().arrange()
+        let keys = self.keys();
+        arrange_deferred(&mut keys.scope(), self.created_at(), || keys.arrange())
         // FIXME: maybe sth better if it is possible to extract arranged keys from an arranged collection
     }
 
@@ -573,6 +625,8 @@ impl ErrorLog {
 struct ErrorLogger {
     operator_id: i64,
     error_log: Option<ErrorLog>,
+    health: Arc<HealthState>,
+    dead_letter_queue: Option<Arc<DeadLetterQueue>>,
 }
 
 impl ErrorLogger {
@@ -583,6 +637,15 @@ impl ErrorLogger {
         let trace = trace.unwrap_or_default();
         let error = error.to_string();
         error!("{error} in operator {}. {trace}", self.operator_id);
+        self.health
+            .record_error(self.operator_id, error.clone(), trace.clone());
+        if let Some(dead_letter_queue) = self.dead_letter_queue.as_ref() {
+            dead_letter_queue.send(DeadLetterRecord::data_error(
+                Some(self.operator_id),
+                Some(trace.clone()),
+                error.clone(),
+            ));
+        }
         if let Some(error_log) = self.error_log.as_ref() {
             error_log.insert(Value::from(
                 [
@@ -606,6 +669,35 @@ impl LogError for ErrorLogger {
     }
 }
 
+/// Sends the data errors to the dead-letter queue before terminating the computation.
+struct TerminatingErrorLogger {
+    operator_id: Option<i64>,
+    error_reporter: ErrorReporter,
+    dead_letter_queue: Arc<DeadLetterQueue>,
+}
+
+impl LogError for TerminatingErrorLogger {
+    fn log_error(&self, error: DataError) {
+        if !matches!(error, DataError::ErrorInValue) {
+            self.dead_letter_queue.send(DeadLetterRecord::data_error(
+                self.operator_id,
+                None,
+                error.to_string(),
+            ));
+        }
+        self.error_reporter.report_and_panic(error);
+    }
+
+    fn log_error_with_trace(&self, error: DynError, trace: &Trace) {
+        self.dead_letter_queue.send(DeadLetterRecord::data_error(
+            self.operator_id,
+            Some(format!("{trace}")),
+            error.to_string(),
+        ));
+        self.error_reporter.report_and_panic_with_trace(error, trace);
+    }
+}
+
 struct Prober {
     input_time: Option<Timestamp>,
     input_time_changed: Option<SystemTime>,
@@ -673,6 +765,8 @@ impl Prober {
         output_probe: &ProbeHandle<Timestamp>,
         intermediate_probes: &HashMap<usize, ProbeHandle<Timestamp>>,
         connector_monitors: &[Rc<RefCell<ConnectorMonitor>>],
+        sink_stats: &[(String, Arc<Mutex<SinkStats>>)],
+        arrangement_stats: &ArrangementStatsRegistry,
     ) {
         let now = Lazy::new(SystemTime::now);
 
@@ -727,6 +821,12 @@ impl Prober {
                 output_stats: Self::create_stats(output_probe, self.input_time),
                 operators_stats: self.stats.clone(),
                 connector_stats,
+                sink_stats: sink_stats
+                    .iter()
+                    .map(|(name, stats)| (name.clone(), *stats.lock().unwrap()))
+                    .collect(),
+                arrangement_stats: arrangement_stats.per_worker(),
+                operator_arrangement_stats: arrangement_stats.per_operator(),
             };
 
             (self.callback)(prober_stats);
@@ -763,6 +863,7 @@ struct DataflowGraphInner<S: MaybeTotalScope> {
     pollers: Vec<Box<dyn FnMut() -> ControlFlow<(), Option<SystemTime>>>>,
     connector_threads: Vec<JoinHandle<()>>,
     connector_monitors: Vec<Rc<RefCell<ConnectorMonitor>>>,
+    sink_stats: Vec<(String, Arc<Mutex<SinkStats>>)>,
     error_reporter: ErrorReporter,
     input_probe: ProbeHandle<S::Timestamp>,
     output_probe: ProbeHandle<S::Timestamp>,
@@ -772,11 +873,15 @@ struct DataflowGraphInner<S: MaybeTotalScope> {
     persistence_config: Option<PersistenceManagerConfig>,
     worker_persistent_storage: SharedWorkerPersistentStorage,
     persisted_states_count: u64,
+    checkpointed_tables_count: u64,
     config: Arc<Config>,
     terminate_on_error: bool,
     default_error_log: Option<ErrorLog>,
     current_error_log: Option<ErrorLog>,
     current_operator_properties: Option<OperatorProperties>,
+    arrangement_stats: Arc<ArrangementStatsRegistry>,
+    health: Arc<HealthState>,
+    dead_letter_queue: Option<Arc<DeadLetterQueue>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -955,6 +1060,34 @@ enum MaybeUpdate<T> {
     Update(T),
 }
 
+fn extract_join_key(
+    key: &Key,
+    values: &Value,
+    column_paths: &[ColumnPath],
+    shard_policy: ShardPolicy,
+    error_reporter: &ErrorReporter,
+    error_logger: &mut dyn LogError,
+) -> Option<Key> {
+    let join_key_parts: DataResult<Vec<_>> = column_paths
+        .iter()
+        .map(|path| path.extract(key, values))
+        .collect::<Result<Vec<_>>>()
+        .unwrap_with_reporter(error_reporter)
+        .into_iter()
+        .map(|v| v.into_result().map_err(|_err| DataError::ErrorInJoin))
+        .try_collect();
+    match join_key_parts {
+        Ok(join_key_parts) => {
+            let join_key = shard_policy.generate_key(&join_key_parts);
+            Some(join_key)
+        }
+        Err(error) => {
+            error_logger.log_error(error);
+            None
+        }
+    }
+}
+
 #[allow(clippy::unnecessary_wraps)] // we want to always return Result for symmetry
 impl<S: MaybeTotalScope> DataflowGraphInner<S> {
     #[allow(clippy::too_many_arguments)]
@@ -966,6 +1099,9 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
         config: Arc<Config>,
         terminate_on_error: bool,
         default_error_log: Option<ErrorLog>,
+        arrangement_stats: Arc<ArrangementStatsRegistry>,
+        health: Arc<HealthState>,
+        dead_letter_queue: Option<Arc<DeadLetterQueue>>,
     ) -> Result<Self> {
         let worker_persistent_storage = {
             if let Some(persistence_config) = &persistence_config {
@@ -988,6 +1124,7 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             pollers: Vec::new(),
             connector_threads: Vec::new(),
             connector_monitors: Vec::new(),
+            sink_stats: Vec::new(),
             error_reporter,
             input_probe: ProbeHandle::new(),
             output_probe: ProbeHandle::new(),
@@ -997,11 +1134,15 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             persistence_config,
             worker_persistent_storage,
             persisted_states_count: 0,
+            checkpointed_tables_count: 0,
             config,
             terminate_on_error,
             default_error_log,
             current_error_log: None,
             current_operator_properties: None,
+            arrangement_stats,
+            health,
+            dead_letter_queue,
         })
     }
 
@@ -1477,6 +1618,19 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             let function = function.clone();
             let trace = trace.clone();
             let future = async move {
+                let _span = SpanGuard::start("udf.async_apply", || match trace.as_ref() {
+                    Trace::Frame {
+                        file_name,
+                        line_number,
+                        function,
+                        ..
+                    } => vec![
+                        KeyValue::new("code.filepath", file_name.clone()),
+                        KeyValue::new("code.lineno", i64::from(*line_number)),
+                        KeyValue::new("code.function", function.clone()),
+                    ],
+                    Trace::Empty => Vec::new(),
+                });
                 let value = async {
                     function(key, &args).await.unwrap_or_log_with_trace(
                         error_logger.as_ref(),
@@ -1974,6 +2128,54 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             .alloc(Table::from_collection(new_values).with_properties(table_properties)))
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn top_n_table(
+        &mut self,
+        table_handle: TableHandle,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: usize,
+        offset: usize,
+        descending: bool,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>
+    where
+        <S as MaybeTotalScope>::MaybeTotalTimestamp: TotalOrder,
+    {
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+
+        let error_reporter = self.error_reporter.clone();
+
+        let new_values = table
+            .values()
+            .map_named("top_n_table::instance_order", move |(id, values)| {
+                let instance = instance_column_path
+                    .extract(&id, &values)
+                    .unwrap_with_reporter(&error_reporter);
+                let order_value = key_column_path
+                    .extract(&id, &values)
+                    .unwrap_with_reporter(&error_reporter);
+                let order = if descending {
+                    TopKOrder::Descending(Reverse(order_value))
+                } else {
+                    TopKOrder::Ascending(order_value)
+                };
+                (Key::for_value(&instance), (order, id, values))
+            })
+            .top_k_rows_named("top_n_table::top_k", n, offset)
+            .map_named(
+                "top_n_table::output",
+                |(_instance, (_order, id, values))| (id, values),
+            );
+
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(new_values).with_properties(table_properties)))
+    }
+
     fn update_rows_arrange(
         &mut self,
         table_handle: TableHandle,
@@ -2273,6 +2475,7 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
     }
 
     #[allow(clippy::too_many_lines)]
+    #[allow(clippy::needless_pass_by_value)]
     fn join_tables(
         &mut self,
         left_data: JoinData,
@@ -2281,34 +2484,10 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
         join_type: JoinType,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        fn extract_join_key(
-            key: &Key,
-            values: &Value,
-            column_paths: &[ColumnPath],
-            shard_policy: ShardPolicy,
-            error_reporter: &ErrorReporter,
-            error_logger: &mut dyn LogError,
-        ) -> Option<Key> {
-            let join_key_parts: DataResult<Vec<_>> = column_paths
-                .iter()
-                .map(|path| path.extract(key, values))
-                .collect::<Result<Vec<_>>>()
-                .unwrap_with_reporter(error_reporter)
-                .into_iter()
-                .map(|v| v.into_result().map_err(|_err| DataError::ErrorInJoin))
-                .try_collect();
-            match join_key_parts {
-                Ok(join_key_parts) => {
-                    let join_key = shard_policy.generate_key(&join_key_parts);
-                    Some(join_key)
-                }
-                Err(error) => {
-                    error_logger.log_error(error);
-                    None
-                }
-            }
+        if join_type.is_temporal() {
+            // temporal joins require totally ordered time
+            return Err(Error::NotSupportedInIteration);
         }
-
         if left_data.column_paths.len() != right_data.column_paths.len() {
             return Err(Error::DifferentJoinConditionLengths);
         }
@@ -2361,6 +2540,50 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
                 });
         let join_right = right_with_join_key
             .flat_map(|(join_key, right_key_values)| Some((join_key?, right_key_values)));
+
+        if matches!(join_type, JoinType::LeftSemi | JoinType::LeftAnti) {
+            // only the presence of a join key on the right side matters
+            let right_join_keys: ArrangedByKey<S, Key, ()> = join_right
+                .map_named("join::right_join_keys", |(join_key, _right_key_values)| {
+                    join_key
+                })
+                .distinct()
+                .map_named("join::right_join_keys_wrap", |join_key| (join_key, ()))
+                .arrange();
+            let matched_left = join_left_arranged
+                .join_core(&right_join_keys, |_join_key, left_key_values, ()| {
+                    once(left_key_values.clone())
+                });
+            let result = if join_type == JoinType::LeftSemi {
+                matched_left
+            } else {
+                // rows with errors in the join condition were already reported
+                // when extracting keys, so they are not a part of the result
+                join_left
+                    .map_named("join::left_anti_unwrap", |(_join_key, left_key_values)| {
+                        left_key_values
+                    })
+                    .concat(&matched_left.negate())
+            };
+            let result =
+                result.map_named("join::result_left_semi_anti", |(left_key, left_values)| {
+                    (
+                        left_key,
+                        Value::from(
+                            [
+                                Value::Pointer(left_key),
+                                left_values,
+                                Value::None,
+                                Value::None,
+                            ]
+                            .as_slice(),
+                        ),
+                    )
+                });
+            let result_table = Table::from_collection(result).with_properties(table_properties);
+            return Ok(self.tables.alloc(result_table));
+        }
+
         let join_right_arranged: ArrangedByKey<S, Key, (Key, Value)> = join_right.arrange();
 
         let join_left_right = join_left_arranged
@@ -2577,9 +2800,33 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
         Ok(())
     }
 
+    fn arrangement_stats(&self) -> Result<Arc<ArrangementStatsRegistry>> {
+        Ok(self.arrangement_stats.clone())
+    }
+
     fn create_error_logger(&self) -> Result<Box<dyn LogError>> {
+        self.error_logger(self.dead_letter_queue.clone())
+    }
+
+    fn error_logger(
+        &self,
+        dead_letter_queue: Option<Arc<DeadLetterQueue>>,
+    ) -> Result<Box<dyn LogError>> {
         if self.terminate_on_error {
-            Ok(Box::new(self.error_reporter.clone()))
+            let Some(dead_letter_queue) = dead_letter_queue else {
+                return Ok(Box::new(self.error_reporter.clone()));
+            };
+            let operator_id = self
+                .current_operator_properties
+                .as_ref()
+                .map(|operator_properties| operator_properties.id.try_into())
+                .transpose()
+                .map_err(DynError::from)?;
+            Ok(Box::new(TerminatingErrorLogger {
+                operator_id,
+                error_reporter: self.error_reporter.clone(),
+                dead_letter_queue,
+            }))
         } else {
             let operator_properties = self
                 .current_operator_properties
@@ -2597,11 +2844,22 @@ impl<S: MaybeTotalScope> DataflowGraphInner<S> {
             Ok(Box::new(ErrorLogger {
                 operator_id: operator_properties.id.try_into().map_err(DynError::from)?,
                 error_log,
+                health: self.health.clone(),
+                dead_letter_queue,
             }))
         }
     }
 
     fn set_operator_properties(&mut self, operator_properties: OperatorProperties) -> Result<()> {
+        // timely operators get sequential global ids, so all operators created
+        // from now on, until the next call, belong to this user-level operator
+        let first_global_id = self.scope.new_identifier();
+        self.arrangement_stats.register_operator(
+            self.worker_index(),
+            first_global_id,
+            operator_properties.id,
+            operator_properties.trace.clone(),
+        );
         self.current_operator_properties = Some(operator_properties);
         Ok(())
     }
@@ -2701,6 +2959,36 @@ impl<S: MaybeTotalScope, R: ReducerImpl> DataflowReducer<S> for R {
     }
 }
 
+// Reducers with semigroup states are computed by summing up the states as differences,
+// so only one accumulated state per group has to be kept.
+fn reduce_semigroup<S: MaybeTotalScope, R: SemigroupReducerImpl>(
+    reducer: Rc<R>,
+    values: &Collection<S, (Key, Key, Vec<Value>)>,
+    error_logger: Rc<dyn LogError>,
+    name: &str,
+) -> Values<S> {
+    values
+        .map_named(&format!("{name}::reduce::init"), {
+            let reducer = reducer.clone();
+            move |(source_key, result_key, values)| {
+                let state = if values.contains(&Value::Error) {
+                    reducer.init_error()
+                } else {
+                    reducer
+                        .init(&source_key, &values[0])
+                        .unwrap_or_else_log(error_logger.as_ref(), || reducer.init_error())
+                };
+                (result_key, state)
+            }
+        })
+        .explode(|(key, state)| once((key, state)))
+        .count()
+        .map_named(&format!("{name}::reduce"), move |(key, state)| {
+            (key, reducer.finish(state))
+        })
+        .into()
+}
+
 impl<S: MaybeTotalScope> DataflowReducer<S> for IntSumReducer {
     fn reduce(
         self: Rc<Self>,
@@ -2708,26 +2996,51 @@ impl<S: MaybeTotalScope> DataflowReducer<S> for IntSumReducer {
         error_logger: Rc<dyn LogError>,
         _trace: Trace,
     ) -> Values<S> {
-        values
-            .map_named("IntSumReducer::reduce::init", {
-                let self_ = self.clone();
-                move |(source_key, result_key, values)| {
-                    let state = if values.contains(&Value::Error) {
-                        self_.init_error()
-                    } else {
-                        self_
-                            .init(&source_key, &values[0])
-                            .unwrap_or_else_log(error_logger.as_ref(), || self_.init_error())
-                    };
-                    (result_key, state)
-                }
-            })
-            .explode(|(key, state)| once((key, state)))
-            .count()
-            .map_named("IntSumReducer::reduce", move |(key, state)| {
-                (key, self.finish(state))
-            })
-            .into()
+        reduce_semigroup(self, values, error_logger, "IntSumReducer")
+    }
+}
+
+impl<S: MaybeTotalScope> DataflowReducer<S> for MomentsReducer {
+    fn reduce(
+        self: Rc<Self>,
+        values: &Collection<S, (Key, Key, Vec<Value>)>,
+        error_logger: Rc<dyn LogError>,
+        _trace: Trace,
+    ) -> Values<S> {
+        reduce_semigroup(self, values, error_logger, "MomentsReducer")
+    }
+}
+
+impl<S: MaybeTotalScope> DataflowReducer<S> for CountDistinctReducer {
+    fn reduce(
+        self: Rc<Self>,
+        values: &Collection<S, (Key, Key, Vec<Value>)>,
+        error_logger: Rc<dyn LogError>,
+        _trace: Trace,
+    ) -> Values<S> {
+        reduce_semigroup(self, values, error_logger, "CountDistinctReducer")
+    }
+}
+
+impl<S: MaybeTotalScope> DataflowReducer<S> for CountDistinctApproxReducer {
+    fn reduce(
+        self: Rc<Self>,
+        values: &Collection<S, (Key, Key, Vec<Value>)>,
+        error_logger: Rc<dyn LogError>,
+        _trace: Trace,
+    ) -> Values<S> {
+        reduce_semigroup(self, values, error_logger, "CountDistinctApproxReducer")
+    }
+}
+
+impl<S: MaybeTotalScope> DataflowReducer<S> for QuantilesReducer {
+    fn reduce(
+        self: Rc<Self>,
+        values: &Collection<S, (Key, Key, Vec<Value>)>,
+        error_logger: Rc<dyn LogError>,
+        _trace: Trace,
+    ) -> Values<S> {
+        reduce_semigroup(self, values, error_logger, "QuantilesReducer")
     }
 }
 
@@ -2848,6 +3161,43 @@ where
     }
 }
 
+// Top-k reducer that keeps only the k best elements of each group in its state.
+// Usable in scopes with totally ordered time only.
+struct BoundedTopKReducer(TopKReducer);
+
+impl<S> DataflowReducer<S> for BoundedTopKReducer
+where
+    S: MaybeTotalScope,
+    S::MaybeTotalTimestamp: TotalOrder,
+{
+    fn reduce(
+        self: Rc<Self>,
+        values: &Collection<S, (Key, Key, Vec<Value>)>,
+        error_logger: Rc<dyn LogError>,
+        _trace: Trace,
+    ) -> Values<S> {
+        let self_ = self.clone();
+        values
+            .map_named(
+                "BoundedTopKReducer::reduce::init",
+                move |(source_key, result_key, values)| {
+                    let element = self_
+                        .0
+                        .element(&source_key, &values)
+                        .unwrap_or_else_log(error_logger.as_ref(), || {
+                            self_.0.error_element(&source_key)
+                        });
+                    (result_key, element)
+                },
+            )
+            .top_k_named("BoundedTopKReducer::reduce::top_k", self.0.k())
+            .map_named("BoundedTopKReducer::reduce", move |(key, elements)| {
+                (key, self.0.finish_elements(&elements))
+            })
+            .into()
+    }
+}
+
 trait CreateDataflowReducer<S: MaybeTotalScope> {
     fn create_dataflow_reducer(reducer: &Reducer) -> Result<Rc<dyn DataflowReducer<S>>>;
 }
@@ -2871,6 +3221,24 @@ where
             Reducer::Tuple { skip_nones } => Rc::new(TupleReducer::new(*skip_nones)),
 
             Reducer::Any => Rc::new(AnyReducer),
+            Reducer::Mean => Rc::new(MomentsReducer::new(MomentsStatistic::Mean)),
+            Reducer::Variance { sample } => {
+                Rc::new(MomentsReducer::new(MomentsStatistic::Variance {
+                    sample: *sample,
+                }))
+            }
+            Reducer::StdDev { sample } => Rc::new(MomentsReducer::new(MomentsStatistic::StdDev {
+                sample: *sample,
+            })),
+            Reducer::CountDistinct => Rc::new(CountDistinctReducer),
+            Reducer::CountDistinctApprox { precision } => {
+                Rc::new(CountDistinctApproxReducer::new(*precision)?)
+            }
+            Reducer::Quantile { quantile } => Rc::new(QuantilesReducer::new_single(*quantile)?),
+            Reducer::Quantiles { quantiles } => {
+                Rc::new(QuantilesReducer::new_multiple(quantiles.clone())?)
+            }
+            Reducer::TopK { k, order } => Rc::new(TopKReducer::new(*k, *order)),
             Reducer::Stateful { .. } | Reducer::Earliest | Reducer::Latest => {
                 return Err(Error::NotSupportedInIteration)
             }
@@ -2890,6 +3258,7 @@ where
             Reducer::Stateful { combine_fn } => Rc::new(StatefulReducer::new(combine_fn.clone())),
             Reducer::Earliest => Rc::new(EarliestReducer),
             Reducer::Latest => Rc::new(LatestReducer),
+            Reducer::TopK { k, order } => Rc::new(BoundedTopKReducer(TopKReducer::new(*k, *order))),
             other => NotTotal::create_dataflow_reducer(other)?,
         };
 
@@ -3147,6 +3516,147 @@ where
             .tables
             .alloc(Table::from_collection(new_values_persisted).with_properties(table_properties)))
     }
+
+    /// Checkpoints the contents of a table that is an input of a stateful operator.
+    ///
+    /// The consolidated batches of the table arrangement are persisted together with
+    /// their upper frontiers. After a restart the arrangement contents are restored from
+    /// the checkpoint up to the last finalized time, while the connectors don't replay
+    /// the data they have already read (see
+    /// [`WorkerPersistentStorage::effective_snapshot_access`]). Hence the only rows that
+    /// reach the table at the rewind time are the ones recomputed from the restored
+    /// states of other operators or emitted again by static tables, and both are already
+    /// a part of the checkpoint.
+    ///
+    /// The persisted batches form a log of the changes of the arrangement, so they are
+    /// periodically compacted into its consolidated contents at the globally finalized time
+    /// (see [`compact_events`](crate::connectors::snapshot::compact_events)).
+    fn checkpoint_table(
+        &mut self,
+        table_handle: TableHandle,
+        operator_name: &str,
+    ) -> Result<TableHandle> {
+        let Some(worker_persistent_storage) = self.worker_persistent_storage.clone() else {
+            return Ok(table_handle);
+        };
+        let (checkpointing_enabled, is_restored) = {
+            let storage = worker_persistent_storage.lock().unwrap();
+            (
+                storage.operator_state_checkpointing_enabled(),
+                storage.is_restored_from_past_runs(),
+            )
+        };
+        if !checkpointing_enabled {
+            return Ok(table_handle);
+        }
+
+        self.checkpointed_tables_count += 1;
+        let external_persistent_id =
+            format!("{operator_name}-state-{}", self.checkpointed_tables_count);
+        info!("Persistent ID autogenerated for {operator_name} state: {external_persistent_id}");
+        let persistent_id = external_persistent_id.clone().into_persistent_id();
+
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+        let table_properties = table.properties.clone();
+        let snapshot_writer = worker_persistent_storage
+            .lock()
+            .unwrap()
+            .create_checkpoint_writer(persistent_id)
+            .map_err(Error::SnapshotWriterError)?;
+
+        let arranged: ValuesArranged<S> = if is_restored {
+            table
+                .values()
+                .inner
+                .filter(|(_data, time, _diff)| *time != ARTIFICIAL_TIME_ON_REWIND_START)
+                .as_collection()
+                .arrange_named("checkpoint_table::arrange")
+        } else {
+            table.values_arranged().clone()
+        };
+        let error_reporter = self.error_reporter.clone();
+        let persisted_stream = arranged.stream.inspect(move |batch| {
+            let mut snapshot_writer = snapshot_writer.lock().unwrap();
+            let mut cursor = batch.cursor();
+            while let Some(key) = cursor.get_key(batch) {
+                while let Some(values) = cursor.get_val(batch) {
+                    let mut diff = 0;
+                    cursor.map_times(batch, |_time, time_diff| diff += time_diff);
+                    if diff != 0 {
+                        let values_vec: Vec<Value> =
+                            (**values.as_tuple().unwrap_with_reporter(&error_reporter)).into();
+                        let event = if diff > 0 {
+                            SnapshotEvent::Insert(*key, values_vec)
+                        } else {
+                            SnapshotEvent::Delete(*key, values_vec)
+                        };
+                        for _ in 0..diff.unsigned_abs() {
+                            snapshot_writer
+                                .write(&event)
+                                .expect("Failed to save row in persistent buffer.");
+                        }
+                    }
+                    cursor.step_val(batch);
+                }
+                cursor.step_key(batch);
+            }
+            assert!(batch.upper().len() <= 1);
+            if let Some(time) = batch.upper().first() {
+                snapshot_writer
+                    .write(&SnapshotEvent::AdvanceTime(*time, OffsetAntichain::new()))
+                    .expect("Failed to save time advancement in persistent buffer.");
+            }
+        });
+        let new_rows = Arranged {
+            stream: persisted_stream,
+            trace: arranged.trace,
+        }
+        .as_collection(|key, values| (*key, values.clone()));
+
+        let mut input_session: InputSession<Timestamp, (Key, Vec<Value>), isize> =
+            InputSession::new();
+        let restored_rows = input_session
+            .to_collection(&mut self.scope)
+            .map_named("checkpoint_table::restore", |(key, values)| {
+                (key, Value::from(values.as_slice()))
+            });
+        let snapshot_reader_state = read_persisted_state(
+            input_session,
+            worker_persistent_storage,
+            &external_persistent_id,
+            persistent_id,
+        );
+        self.pollers.push(snapshot_reader_state.poller);
+        self.connector_threads
+            .push(snapshot_reader_state.input_thread_handle);
+
+        Ok(self.tables.alloc(
+            Table::from_collection(new_rows.concat(&restored_rows))
+                .with_properties(table_properties),
+        ))
+    }
+
+    /// Operators whose results depend on the processing time or on the order of updates
+    /// can't be restored from the checkpoints of their inputs.
+    fn ensure_not_checkpointed(&self, operator_name: &'static str) -> Result<()> {
+        let checkpointing_enabled =
+            self.worker_persistent_storage
+                .as_ref()
+                .is_some_and(|worker_persistent_storage| {
+                    worker_persistent_storage
+                        .lock()
+                        .unwrap()
+                        .operator_state_checkpointing_enabled()
+                });
+        if checkpointing_enabled {
+            Err(Error::NotSupportedWithCheckpointing(operator_name))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -3231,11 +3741,13 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn connector_table(
         &mut self,
         mut reader: Box<dyn ReaderBuilder>,
         parser: Box<dyn Parser>,
         commit_duration: Option<Duration>,
+        error_budget: Option<ErrorBudget>,
         parallel_readers: usize,
         table_properties: Arc<TableProperties>,
         external_persistent_id: Option<&ExternalPersistentId>,
@@ -3278,12 +3790,16 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                 .as_ref()
                 .map_or(SnapshotAccess::Full, |config| config.snapshot_access);
 
+            // the rejected entries are sent to the dead-letter queue by the connector
+            // itself, together with their payloads
             let connector = Connector::new(
                 commit_duration,
                 parser.column_count(),
                 self.terminate_on_error,
-                self.create_error_logger()?.into(),
-            );
+                self.error_logger(None)?.into(),
+            )
+            .with_dead_letter_queue(self.dead_letter_queue.clone())
+            .with_error_budget(error_budget);
             let state = connector.run(
                 reader,
                 parser,
@@ -3372,6 +3888,197 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
             .alloc(Table::from_collection(new_table).with_properties(table_properties)))
     }
 
+    fn temporal_join_tables(
+        &mut self,
+        left_data: JoinData,
+        right_data: JoinData,
+        shard_policy: ShardPolicy,
+        join_type: JoinType,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        if left_data.column_paths.len() != right_data.column_paths.len() {
+            return Err(Error::DifferentJoinConditionLengths);
+        }
+        let (Some(left_time_column_path), Some(right_time_column_path)) = (
+            left_data.time_column_path.clone(),
+            right_data.time_column_path.clone(),
+        ) else {
+            return Err(Error::MissingJoinTimeColumn);
+        };
+
+        let left_table = self
+            .tables
+            .get(left_data.table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+        let right_table = self
+            .tables
+            .get(right_data.table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+
+        let error_reporter_left = self.error_reporter.clone();
+        let error_reporter_right = self.error_reporter.clone();
+
+        let mut error_logger_left = self.create_error_logger()?;
+        let mut error_logger_right = self.create_error_logger()?;
+        let error_logger: Rc<dyn LogError> = self.create_error_logger()?.into();
+
+        let join_left = left_table.values().flat_map(move |(key, values)| {
+            let join_key = extract_join_key(
+                &key,
+                &values,
+                &left_data.column_paths,
+                shard_policy,
+                &error_reporter_left,
+                error_logger_left.as_mut(),
+            )?;
+            let time = left_time_column_path
+                .extract(&key, &values)
+                .unwrap_with_reporter(&error_reporter_left);
+            Some((join_key, (time, JoinSide::Left, key, values)))
+        });
+        let join_right = right_table.values().flat_map(move |(key, values)| {
+            let join_key = extract_join_key(
+                &key,
+                &values,
+                &right_data.column_paths,
+                shard_policy,
+                &error_reporter_right,
+                error_logger_right.as_mut(),
+            )?;
+            let time = right_time_column_path
+                .extract(&key, &values)
+                .unwrap_with_reporter(&error_reporter_right);
+            Some((join_key, (time, JoinSide::Right, key, values)))
+        });
+        let join_input = join_left.concat(&join_right);
+
+        let result = match join_type {
+            JoinType::AsofBackward { tolerance } => {
+                join_input.asof_join(AsofDirection::Backward, tolerance, error_logger)
+            }
+            JoinType::AsofForward { tolerance } => {
+                join_input.asof_join(AsofDirection::Forward, tolerance, error_logger)
+            }
+            JoinType::AsofNearest { tolerance } => {
+                join_input.asof_join(AsofDirection::Nearest, tolerance, error_logger)
+            }
+            JoinType::Interval {
+                lower,
+                upper,
+                cutoff,
+            } => join_input.interval_join(lower, upper, cutoff, error_logger),
+            _ => return Err(Error::BadJoinType),
+        };
+
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(result).with_properties(table_properties)))
+    }
+
+    fn window_table(
+        &mut self,
+        table_handle: TableHandle,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        behavior: WindowBehavior,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let table = self
+            .tables
+            .get(table_handle)
+            .ok_or(Error::InvalidTableHandle)?;
+
+        let error_reporter = self.error_reporter.clone();
+        let error_logger: Rc<dyn LogError> = self.create_error_logger()?.into();
+
+        let is_session = matches!(window, Window::Session { .. });
+        // rows extended with window start and end, together with the row time
+        let windowed = if let Window::Session { max_gap } = &window {
+            table
+                .values()
+                .map_named("window_table::session_instance", move |(key, values)| {
+                    let time = time_column_path
+                        .extract(&key, &values)
+                        .unwrap_with_reporter(&error_reporter);
+                    let instance = instance_column_path
+                        .extract(&key, &values)
+                        .unwrap_with_reporter(&error_reporter);
+                    (Key::for_value(&instance), (time, key, values))
+                })
+                .session_windows(
+                    max_gap.clone(),
+                    behavior.cutoff.clone(),
+                    behavior.keep_results,
+                    error_logger.clone(),
+                )
+        } else {
+            let error_logger = error_logger.clone();
+            table.values().flat_map(move |(key, values)| {
+                let time = time_column_path
+                    .extract(&key, &values)
+                    .unwrap_with_reporter(&error_reporter);
+                let windows = window
+                    .assign(&time)
+                    .unwrap_or_log(error_logger.as_ref(), Vec::new());
+                windows.into_iter().map(move |(start, end)| {
+                    let new_key =
+                        Key::for_values(&[Value::from(key), start.clone()]).with_shard_of(key);
+                    let new_values =
+                        Value::Tuple([values.clone(), start, end].into_iter().collect());
+                    (new_key, (new_values, time.clone()))
+                })
+            })
+        };
+
+        let has_cutoff = behavior.cutoff.is_some();
+        let has_delay = behavior.delay.is_some();
+        let keep_results = behavior.keep_results;
+        let mut windowed = windowed.flat_map(move |(key, (values, time))| {
+            let Value::Tuple(parts) = &values else {
+                unreachable!("rows assigned to windows are tuples");
+            };
+            let (delay_threshold, cutoff_threshold) =
+                match behavior.thresholds(&parts[1], &parts[2]) {
+                    Ok(thresholds) => thresholds,
+                    Err(error) => {
+                        error_logger.log_error(error);
+                        return None;
+                    }
+                };
+            Some((key, (values, time, delay_threshold, cutoff_threshold)))
+        });
+        // late rows of session windows are ignored by the session operator itself
+        if has_cutoff && !is_session {
+            let (on_time, _late) = windowed.freeze(
+                |(_values, _time, _delay_threshold, cutoff_threshold)| cutoff_threshold.clone(),
+                |(_values, time, _delay_threshold, _cutoff_threshold)| time.clone(),
+            );
+            windowed = on_time;
+        }
+        if has_delay {
+            windowed = windowed.postpone(
+                windowed.scope(),
+                |(_values, _time, delay_threshold, _cutoff_threshold)| delay_threshold.clone(),
+                |(_values, time, _delay_threshold, _cutoff_threshold)| time.clone(),
+                true,
+            );
+        }
+        if has_cutoff && !is_session {
+            windowed = windowed.forget(
+                |(_values, _time, _delay_threshold, cutoff_threshold)| cutoff_threshold.clone(),
+                |(_values, time, _delay_threshold, _cutoff_threshold)| time.clone(),
+                keep_results,
+            );
+        }
+        let new_table =
+            windowed.map_named("window_table::output", |(key, (values, ..))| (key, values));
+
+        Ok(self
+            .tables
+            .alloc(Table::from_collection(new_table).with_properties(table_properties)))
+    }
+
     fn forget_immediately(
         &mut self,
         table_handle: TableHandle,
@@ -3454,6 +4161,12 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
             stats.on_batch_entry_written();
         }
         stats.on_batch_finished();
+        let _span = SpanGuard::start("sink.flush", || {
+            vec![
+                KeyValue::new("connector", stats.get_name()),
+                timestamp_attribute(time),
+            ]
+        });
         data_sink.flush(false).map_err(DynError::from)?;
 
         Ok(())
@@ -3515,6 +4228,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
             // connector_monitors vector contains monitors only for input connectors
             let output_connector_id = self.connector_threads.len() - self.connector_monitors.len();
             let mut stats = OutputConnectorStats::new(data_sink.name(output_connector_id));
+            self.sink_stats.push((stats.get_name(), stats.shared_stats()));
 
             let output_joiner_handle = Builder::new()
                 .name(thread_name)
@@ -3540,6 +4254,12 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                                     &worker_persistent_storage,
                                 );
                                 if t.is_none() {
+                                    let _span = SpanGuard::start("sink.flush", || {
+                                        vec![
+                                            KeyValue::new("connector", stats.get_name()),
+                                            KeyValue::new("forced", true),
+                                        ]
+                                    });
                                     data_sink.flush(true).map_err(DynError::from)?;
                                     break Ok(());
                                 }
@@ -3689,6 +4409,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> DataflowGraphInner<S>
                 self.config.clone(),
                 self.terminate_on_error,
                 self.current_error_log.clone(),
+                self.arrangement_stats.clone(),
+                self.health.clone(),
+                self.dead_letter_queue.clone(),
             )?;
             let mut subgraph_ref = subgraph.0.borrow_mut();
             let mut state = BeforeIterate::new(self, &mut subgraph_ref, step);
@@ -4208,6 +4931,7 @@ where
 struct InnerDataflowGraph<S: MaybeTotalScope>(RefCell<DataflowGraphInner<S>>);
 
 impl<S: MaybeTotalScope> InnerDataflowGraph<S> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         scope: S,
         error_reporter: ErrorReporter,
@@ -4215,6 +4939,9 @@ impl<S: MaybeTotalScope> InnerDataflowGraph<S> {
         config: Arc<Config>,
         terminate_on_error: bool,
         default_error_log: Option<ErrorLog>,
+        arrangement_stats: Arc<ArrangementStatsRegistry>,
+        health: Arc<HealthState>,
+        dead_letter_queue: Option<Arc<DeadLetterQueue>>,
     ) -> Result<Self> {
         Ok(Self(RefCell::new(DataflowGraphInner::new(
             scope,
@@ -4224,6 +4951,9 @@ impl<S: MaybeTotalScope> InnerDataflowGraph<S> {
             config,
             terminate_on_error,
             default_error_log,
+            arrangement_stats,
+            health,
+            dead_letter_queue,
         )?)))
     }
 }
@@ -4434,6 +5164,18 @@ where
         Err(Error::NotSupportedInIteration)
     }
 
+    fn window_table(
+        &self,
+        _table_handle: TableHandle,
+        _time_column_path: ColumnPath,
+        _instance_column_path: ColumnPath,
+        _window: Window,
+        _behavior: WindowBehavior,
+        _table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        Err(Error::NotSupportedInIteration)
+    }
+
     fn forget_immediately(
         &self,
         _table_handle: TableHandle,
@@ -4548,6 +5290,19 @@ where
         Err(Error::NotSupportedInIteration)
     }
 
+    fn top_n_table(
+        &self,
+        _table_handle: TableHandle,
+        _key_column_path: ColumnPath,
+        _instance_column_path: ColumnPath,
+        _n: usize,
+        _offset: usize,
+        _descending: bool,
+        _table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        Err(Error::NotSupportedInIteration)
+    }
+
     fn reindex_table(
         &self,
         table_handle: TableHandle,
@@ -4719,6 +5474,7 @@ where
         _reader: Box<dyn ReaderBuilder>,
         _parser: Box<dyn Parser>,
         _commit_duration: Option<Duration>,
+        _error_budget: Option<ErrorBudget>,
         _parallel_readers: usize,
         _table_properties: Arc<TableProperties>,
         _external_persistent_id: Option<&ExternalPersistentId>,
@@ -4766,6 +5522,10 @@ where
         self.0.borrow_mut().probe_table(table_handle, operator_id)
     }
 
+    fn arrangement_stats(&self) -> Result<Arc<ArrangementStatsRegistry>> {
+        self.0.borrow().arrangement_stats()
+    }
+
     fn export_table(
         &self,
         _table_handle: TableHandle,
@@ -4795,6 +5555,7 @@ struct OuterDataflowGraph<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>>(
 );
 
 impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> OuterDataflowGraph<S> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         scope: S,
         error_reporter: ErrorReporter,
@@ -4802,6 +5563,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> OuterDataflowGraph<S>
         persistence_config: Option<PersistenceManagerOuterConfig>,
         config: Arc<Config>,
         terminate_on_error: bool,
+        arrangement_stats: Arc<ArrangementStatsRegistry>,
+        health: Arc<HealthState>,
+        dead_letter_queue: Option<Arc<DeadLetterQueue>>,
     ) -> Result<Self> {
         let worker_idx = scope.index();
         let total_workers = scope.peers();
@@ -4813,6 +5577,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> OuterDataflowGraph<S>
             config,
             terminate_on_error,
             None,
+            arrangement_stats,
+            health,
+            dead_letter_queue,
         )?)))
     }
 }
@@ -5019,7 +5786,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         mark_forgetting_records: bool,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().forget(
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("forget")?;
+        inner.forget(
             table_handle,
             threshold_time_column_path,
             current_time_column_path,
@@ -5028,6 +5797,27 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         )
     }
 
+    fn window_table(
+        &self,
+        table_handle: TableHandle,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        behavior: WindowBehavior,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("window_table")?;
+        inner.window_table(
+            table_handle,
+            time_column_path,
+            instance_column_path,
+            window,
+            behavior,
+            table_properties,
+        )
+    }
+
     fn forget_immediately(
         &self,
         table_handle: TableHandle,
@@ -5055,7 +5845,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         current_time_column_path: ColumnPath,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().freeze(
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("freeze")?;
+        inner.freeze(
             table_handle,
             threshold_time_column_path,
             current_time_column_path,
@@ -5070,7 +5862,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         current_time_column_path: ColumnPath,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().buffer(
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("buffer")?;
+        inner.buffer(
             table_handle,
             threshold_time_column_path,
             current_time_column_path,
@@ -5095,7 +5889,11 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         same_universes: bool,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().restrict_or_override_table_universe(
+        let mut inner = self.0.borrow_mut();
+        let original_table_handle =
+            inner.checkpoint_table(original_table_handle, "restrict_or_override")?;
+        let new_table_handle = inner.checkpoint_table(new_table_handle, "restrict_or_override")?;
+        inner.restrict_or_override_table_universe(
             original_table_handle,
             new_table_handle,
             same_universes,
@@ -5109,9 +5907,13 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         other_table_handles: Vec<TableHandle>,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0
-            .borrow_mut()
-            .intersect_tables(table_handle, other_table_handles, table_properties)
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "intersect")?;
+        let other_table_handles = other_table_handles
+            .into_iter()
+            .map(|handle| inner.checkpoint_table(handle, "intersect"))
+            .collect::<Result<_>>()?;
+        inner.intersect_tables(table_handle, other_table_handles, table_properties)
     }
 
     fn subtract_table(
@@ -5120,9 +5922,10 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         right_table_handle: TableHandle,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0
-            .borrow_mut()
-            .subtract_table(left_table_handle, right_table_handle, table_properties)
+        let mut inner = self.0.borrow_mut();
+        let left_table_handle = inner.checkpoint_table(left_table_handle, "subtract")?;
+        let right_table_handle = inner.checkpoint_table(right_table_handle, "subtract")?;
+        inner.subtract_table(left_table_handle, right_table_handle, table_properties)
     }
 
     fn concat_tables(
@@ -5153,10 +5956,35 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         instance_column_path: ColumnPath,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().sort_table(
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "sort")?;
+        inner.sort_table(
+            table_handle,
+            key_column_path,
+            instance_column_path,
+            table_properties,
+        )
+    }
+
+    fn top_n_table(
+        &self,
+        table_handle: TableHandle,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: usize,
+        offset: usize,
+        descending: bool,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "top_n")?;
+        inner.top_n_table(
             table_handle,
             key_column_path,
             instance_column_path,
+            n,
+            offset,
+            descending,
             table_properties,
         )
     }
@@ -5167,9 +5995,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         reindexing_column_path: ColumnPath,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0
-            .borrow_mut()
-            .reindex_table(table_handle, reindexing_column_path, table_properties)
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "reindex")?;
+        inner.reindex_table(table_handle, reindexing_column_path, table_properties)
     }
 
     fn update_rows_table(
@@ -5178,9 +6006,10 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         update_handle: TableHandle,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0
-            .borrow_mut()
-            .update_rows_table(table_handle, update_handle, table_properties)
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "update_rows")?;
+        let update_handle = inner.checkpoint_table(update_handle, "update_rows")?;
+        inner.update_rows_table(table_handle, update_handle, table_properties)
     }
 
     fn update_cells_table(
@@ -5191,7 +6020,10 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         update_paths: Vec<ColumnPath>,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().update_cells_table(
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "update_cells")?;
+        let update_handle = inner.checkpoint_table(update_handle, "update_cells")?;
+        inner.update_cells_table(
             table_handle,
             update_handle,
             column_paths,
@@ -5209,7 +6041,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         set_id: bool,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().group_by_table(
+        let mut inner = self.0.borrow_mut();
+        let table_handle = inner.checkpoint_table(table_handle, "group_by")?;
+        inner.group_by_table(
             table_handle,
             grouping_columns_paths,
             shard_policy,
@@ -5247,7 +6081,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         upper_path: ColumnPath,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().gradual_broadcast(
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("gradual_broadcast")?;
+        inner.gradual_broadcast(
             input_table_handle,
             threshold_table_handle,
             lower_path,
@@ -5259,12 +6095,14 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
 
     fn use_external_index_as_of_now(
         &self,
-        index_stream: ExternalIndexData,
+        mut index_stream: ExternalIndexData,
         query_stream: ExternalIndexQuery,
         table_properties: Arc<TableProperties>,
         external_index: Box<dyn ExternalIndex>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().use_external_index_as_of_now(
+        let mut inner = self.0.borrow_mut();
+        index_stream.table = inner.checkpoint_table(index_stream.table, "external_index")?;
+        inner.use_external_index_as_of_now(
             index_stream,
             query_stream,
             table_properties,
@@ -5280,7 +6118,10 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         ix_key_policy: IxKeyPolicy,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().ix_table(
+        let mut inner = self.0.borrow_mut();
+        let to_ix_handle = inner.checkpoint_table(to_ix_handle, "ix")?;
+        let key_handle = inner.checkpoint_table(key_handle, "ix")?;
+        inner.ix_table(
             to_ix_handle,
             key_handle,
             key_column_path,
@@ -5291,13 +6132,25 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
 
     fn join_tables(
         &self,
-        left_data: JoinData,
-        right_data: JoinData,
+        mut left_data: JoinData,
+        mut right_data: JoinData,
         shard_policy: ShardPolicy,
         join_type: JoinType,
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle> {
-        self.0.borrow_mut().join_tables(
+        let mut inner = self.0.borrow_mut();
+        left_data.table_handle = inner.checkpoint_table(left_data.table_handle, "join")?;
+        right_data.table_handle = inner.checkpoint_table(right_data.table_handle, "join")?;
+        if join_type.is_temporal() {
+            return inner.temporal_join_tables(
+                left_data,
+                right_data,
+                shard_policy,
+                join_type,
+                table_properties,
+            );
+        }
+        inner.join_tables(
             left_data,
             right_data,
             shard_policy,
@@ -5314,9 +6167,9 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         limit: Option<u32>,
         logic: IterationLogic<'a>,
     ) -> Result<(Vec<LegacyTable>, Vec<LegacyTable>)> {
-        self.0
-            .borrow_mut()
-            .iterate(iterated, iterated_with_universe, extra, limit, logic)
+        let mut inner = self.0.borrow_mut();
+        inner.ensure_not_checkpointed("iterate")?;
+        inner.iterate(iterated, iterated_with_universe, extra, limit, logic)
     }
 
     fn complex_columns(&self, inputs: Vec<ComplexColumn>) -> Result<Vec<ColumnHandle>> {
@@ -5341,6 +6194,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         reader: Box<dyn ReaderBuilder>,
         parser: Box<dyn Parser>,
         commit_duration: Option<Duration>,
+        error_budget: Option<ErrorBudget>,
         parallel_readers: usize,
         table_properties: Arc<TableProperties>,
         external_persistent_id: Option<&ExternalPersistentId>,
@@ -5349,6 +6203,7 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
             reader,
             parser,
             commit_duration,
+            error_budget,
             parallel_readers,
             table_properties,
             external_persistent_id,
@@ -5401,6 +6256,10 @@ impl<S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>> Graph for OuterDataflo
         self.0.borrow_mut().probe_table(table_handle, operator_id)
     }
 
+    fn arrangement_stats(&self) -> Result<Arc<ArrangementStatsRegistry>> {
+        self.0.borrow().arrangement_stats()
+    }
+
     fn export_table(
         &self,
         table_handle: TableHandle,
@@ -5436,10 +6295,13 @@ pub fn run_with_new_dataflow_graph<R, R2>(
     ignore_asserts: bool,
     monitoring_level: MonitoringLevel,
     with_http_server: bool,
+    monitoring_http_host: Option<&str>,
+    monitoring_http_port: Option<u16>,
     persistence_config: Option<PersistenceManagerOuterConfig>,
     #[allow(unused)] license: &License,
     telemetry_config: TelemetryConfig,
     terminate_on_error: bool,
+    dead_letter_queue: Option<DeadLetterQueue>,
 ) -> Result<Vec<R2>>
 where
     R: 'static,
@@ -5454,12 +6316,26 @@ where
         info!("Running in YOLO mode: {}", YOLO.iter().format(", "));
     }
 
+    let http_server_address = with_http_server
+        .then(|| {
+            monitoring_http_address(
+                monitoring_http_host,
+                monitoring_http_port,
+                config.process_id(),
+            )
+        })
+        .transpose()?;
     let config = Arc::new(config);
     let (error_reporter, error_receiver) = ErrorReporter::create();
     let failed = Arc::new(AtomicBool::new(false));
     let failed_2 = failed.clone();
+    let arrangement_stats = Arc::new(ArrangementStatsRegistry::default());
+    let health = Arc::new(HealthState::new(config.threads()));
+    let dead_letter_queue = dead_letter_queue.map(Arc::new);
+    let dead_letter_queue_2 = dead_letter_queue.clone();
 
     let guards = execute(config.to_timely_config(), move |worker| {
+        let worker_health = health.register_worker(worker.index());
         catch_unwind(AssertUnwindSafe(|| {
             if let Ok(addr) = env::var("DIFFERENTIAL_LOG_ADDR") {
                 if let Ok(stream) = std::net::TcpStream::connect(&addr) {
@@ -5468,6 +6344,9 @@ where
                 } else {
                     panic!("Could not connect to differential log address: {addr:?}");
                 }
+            } else {
+                // the arrangement sizes are taken from the same log
+                arrangement_stats.attach(worker);
             }
 
             let (
@@ -5476,6 +6355,7 @@ where
                 mut pollers,
                 connector_threads,
                 connector_monitors,
+                sink_stats,
                 input_probe,
                 output_probe,
                 intermediate_probes,
@@ -5491,6 +6371,9 @@ where
                     persistence_config.clone(),
                     config.clone(),
                     terminate_on_error,
+                    arrangement_stats.clone(),
+                    health.clone(),
+                    dead_letter_queue_2.clone(),
                 )
                 .unwrap_with_reporter(&error_reporter);
                 let telemetry_runner = maybe_run_telemetry_thread(&graph, telemetry_config.clone());
@@ -5498,7 +6381,7 @@ where
                 let progress_reporter_runner =
                     maybe_run_reporter(&monitoring_level, &graph, stats_monitor.clone());
                 let http_server_runner =
-                    maybe_run_http_server_thread(with_http_server, &graph, config.process_id());
+                    maybe_run_http_server_thread(http_server_address, &graph, health.clone());
                 let graph = graph.0.into_inner();
                 (
                     res,
@@ -5506,6 +6389,7 @@ where
                     graph.pollers,
                     graph.connector_threads,
                     graph.connector_monitors,
+                    graph.sink_stats,
                     graph.input_probe,
                     graph.output_probe,
                     graph.probes,
@@ -5516,17 +6400,25 @@ where
                 )
             });
 
+            let mut timestamp_spans = TimestampSpans::new(worker.index());
             loop {
                 if failed.load(Ordering::SeqCst) {
                     resume_unwind(Box::new("other worker panicked"));
                 }
 
+                timestamp_spans.update(
+                    input_probe.with_frontier(|frontier| frontier.to_owned().into()),
+                    output_probe.with_frontier(|frontier| frontier.to_owned().into()),
+                );
+
                 for prober in &mut probers {
                     prober.update(
                         &input_probe,
                         &output_probe,
                         &intermediate_probes,
                         &connector_monitors,
+                        &sink_stats,
+                        &arrangement_stats,
                     );
                 }
 
@@ -5566,10 +6458,26 @@ where
                     flushers.clear();
                 }
 
+                worker_health.heartbeat(connector_monitors.iter().all(|connector_monitor| {
+                    let stats = (**connector_monitor).borrow().get_stats();
+                    stats.initialized || stats.finished
+                }));
+
+                // with the health endpoints, wake up at least every heartbeat interval
+                // to report that the worker is alive
+                let next_step_duration = if http_server_address.is_some() {
+                    Some(
+                        next_step_duration
+                            .map_or(HEARTBEAT_INTERVAL, |x| min(x, HEARTBEAT_INTERVAL)),
+                    )
+                } else {
+                    next_step_duration
+                };
                 if !worker.step_or_park(next_step_duration) {
                     break;
                 }
             }
+            worker_health.finish();
 
             for connector_thread in connector_threads {
                 connector_thread
@@ -5583,6 +6491,8 @@ where
                     &output_probe,
                     &intermediate_probes,
                     &connector_monitors,
+                    &sink_stats,
+                    &arrangement_stats,
                 );
             }
 
@@ -5593,6 +6503,7 @@ where
             finish(res)
         }))
         .unwrap_or_else(|panic_payload| {
+            worker_health.fail();
             let error = Error::from_panic_payload(panic_payload);
             let message = error.to_string();
             error_reporter.report(error);
@@ -5628,6 +6539,9 @@ where
                 handle.thread().unpark();
             }
             catch_unwind(AssertUnwindSafe(|| drop(guards.join()))).unwrap_or(());
+            if let Some(dead_letter_queue) = dead_letter_queue {
+                dead_letter_queue.close();
+            }
             return Err(error);
         }
     };
@@ -5636,6 +6550,9 @@ where
         .join()
         .into_iter()
         .map(|res| res.map_err(Error::WorkerPanic))
-        .collect::<Result<Vec<_>>>()?;
-    Ok(res)
+        .collect::<Result<Vec<_>>>();
+    if let Some(dead_letter_queue) = dead_letter_queue {
+        dead_letter_queue.close();
+    }
+    res
 }
@@ -0,0 +1,279 @@
+// Copyright © 2024 Pathway
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use differential_dataflow::logging::DifferentialEvent;
+use pyo3::pyclass;
+use timely::communication::Allocate;
+use timely::worker::{AsWorker, Worker};
+
+use crate::engine::error::Trace;
+use crate::engine::{Key, Timestamp, Value};
+
+/// The size of a single update stored in an arrangement, not counting the heap
+/// allocations owned by the values.
+const APPROXIMATE_UPDATE_SIZE: usize = size_of::<((Key, Value), Timestamp, isize)>();
+
+const DEFERRED_ARRANGEMENTS_LOG: &str = "pathway/deferred_arrangements";
+
+/// The total size of a group of arrangements.
+#[derive(Debug, Clone, Copy, Default)]
+#[pyclass]
+pub struct ArrangementStats {
+    #[pyo3(get, set)]
+    pub num_batches: usize,
+    #[pyo3(get, set)]
+    pub num_records: usize,
+    /// The number of records multiplied by the inline size of a record. The heap
+    /// data owned by the values, such as strings, arrays and JSONs, isn't counted,
+    /// so it is a lower bound of the memory used by the arrangements.
+    #[pyo3(get, set)]
+    pub approximate_bytes: usize,
+}
+
+impl ArrangementStats {
+    fn apply(&mut self, batches_diff: isize, records_diff: isize) {
+        self.num_batches = self.num_batches.saturating_add_signed(batches_diff);
+        self.num_records = self.num_records.saturating_add_signed(records_diff);
+        self.approximate_bytes = self.num_records.saturating_mul(APPROXIMATE_UPDATE_SIZE);
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        self.num_batches += other.num_batches;
+        self.num_records += other.num_records;
+        self.approximate_bytes += other.approximate_bytes;
+    }
+}
+
+/// The total size of the arrangements created by a user-level operator,
+/// summed over all workers of the process.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct OperatorArrangementStats {
+    /// Id of the operator, as passed in `OperatorProperties`. `None` groups
+    /// the arrangements created outside of any user-level operator.
+    #[pyo3(get)]
+    pub operator_id: Option<usize>,
+    #[pyo3(get)]
+    pub trace: Trace,
+    #[pyo3(get)]
+    pub num_arrangements: usize,
+    #[pyo3(get)]
+    pub stats: ArrangementStats,
+}
+
+fn signed(length: usize) -> isize {
+    isize::try_from(length).unwrap_or(isize::MAX)
+}
+
+/// Marks the global ids of the operators arranging data after the data was
+/// created, e.g. when the arrangement is first needed by another operator.
+#[derive(Debug, Clone, Copy)]
+struct DeferredArrangement {
+    first_global_id: usize,
+    end_global_id: usize,
+    created_at: usize,
+}
+
+#[derive(Debug, Default)]
+struct WorkerArrangements {
+    /// Arrangement sizes, by the global id of the timely operator maintaining them.
+    arrangements: BTreeMap<usize, ArrangementStats>,
+    /// User-level operator ids, by the first global id allocated while
+    /// the operator was being built.
+    operators: BTreeMap<usize, usize>,
+    /// The end of the range of deferred arrangements and the global id allocated
+    /// when their data was created, by the start of the range.
+    deferred: BTreeMap<usize, (usize, usize)>,
+}
+
+impl WorkerArrangements {
+    fn operator_of(&self, arrangement_id: usize) -> Option<usize> {
+        // the deferred arrangements belong to the operator creating the data
+        let global_id = match self.deferred.range(..=arrangement_id).next_back() {
+            Some((_first_global_id, (end_global_id, created_at)))
+                if arrangement_id < *end_global_id =>
+            {
+                *created_at
+            }
+            _ => arrangement_id,
+        };
+        self.operators
+            .range(..=global_id)
+            .next_back()
+            .map(|(_first_global_id, operator_id)| *operator_id)
+    }
+}
+
+/// Builds an arrangement of the data created when `created_at` was allocated,
+/// so that its size is attributed to the operator creating the data and not
+/// to the one being built when the arrangement is first needed.
+pub fn arrange_deferred<W: AsWorker, T>(
+    worker: &mut W,
+    created_at: usize,
+    arrange: impl FnOnce() -> T,
+) -> T {
+    let first_global_id = worker.new_identifier();
+    let arranged = arrange();
+    let end_global_id = worker.new_identifier();
+    let logger = worker
+        .log_register()
+        .get::<DeferredArrangement>(DEFERRED_ARRANGEMENTS_LOG);
+    if let Some(logger) = logger {
+        logger.log(DeferredArrangement {
+            first_global_id,
+            end_global_id,
+            created_at,
+        });
+    }
+    arranged
+}
+
+/// Collects the sizes of the arrangements of all workers of the process from
+/// the differential dataflow logs.
+#[derive(Debug, Default)]
+pub struct ArrangementStatsRegistry {
+    workers: Mutex<BTreeMap<usize, WorkerArrangements>>,
+    traces: Mutex<HashMap<usize, Trace>>,
+}
+
+impl ArrangementStatsRegistry {
+    /// Starts collecting the arrangement sizes of the worker.
+    /// It has to be called before the dataflow is built.
+    pub fn attach<A: Allocate>(self: &Arc<Self>, worker: &mut Worker<A>) {
+        let worker_index = worker.index();
+        let registry = self.clone();
+        registry
+            .workers
+            .lock()
+            .unwrap()
+            .insert(worker_index, WorkerArrangements::default());
+        worker.log_register().insert::<DifferentialEvent, _>(
+            "differential/arrange",
+            move |_time, events: &mut Vec<(Duration, usize, DifferentialEvent)>| {
+                let mut diffs: BTreeMap<usize, (isize, isize)> = BTreeMap::new();
+                for (_time, _worker, event) in events.drain(..) {
+                    match event {
+                        DifferentialEvent::Batch(batch) => {
+                            let diff = diffs.entry(batch.operator).or_default();
+                            diff.0 += 1;
+                            diff.1 += signed(batch.length);
+                        }
+                        DifferentialEvent::Merge(merge) => {
+                            if let Some(length) = merge.complete {
+                                let diff = diffs.entry(merge.operator).or_default();
+                                diff.0 -= 1;
+                                diff.1 +=
+                                    signed(length) - signed(merge.length1) - signed(merge.length2);
+                            }
+                        }
+                        DifferentialEvent::Drop(drop) => {
+                            let diff = diffs.entry(drop.operator).or_default();
+                            diff.0 -= 1;
+                            diff.1 -= signed(drop.length);
+                        }
+                        DifferentialEvent::MergeShortfall(_) | DifferentialEvent::TraceShare(_) => {
+                        }
+                    }
+                }
+                if diffs.is_empty() {
+                    return;
+                }
+                let mut workers = registry.workers.lock().unwrap();
+                let arrangements = &mut workers.entry(worker_index).or_default().arrangements;
+                for (arrangement_id, (batches_diff, records_diff)) in diffs {
+                    arrangements
+                        .entry(arrangement_id)
+                        .or_default()
+                        .apply(batches_diff, records_diff);
+                }
+            },
+        );
+        let registry = self.clone();
+        worker.log_register().insert::<DeferredArrangement, _>(
+            DEFERRED_ARRANGEMENTS_LOG,
+            move |_time, events: &mut Vec<(Duration, usize, DeferredArrangement)>| {
+                let mut workers = registry.workers.lock().unwrap();
+                let deferred = &mut workers.entry(worker_index).or_default().deferred;
+                for (_time, _worker, event) in events.drain(..) {
+                    deferred.insert(
+                        event.first_global_id,
+                        (event.end_global_id, event.created_at),
+                    );
+                }
+            },
+        );
+    }
+
+    /// Marks the start of building a user-level operator. All arrangements
+    /// whose global ids are not smaller than `first_global_id` (and smaller than
+    /// the one of the next registered operator) are attributed to it.
+    pub fn register_operator(
+        &self,
+        worker_index: usize,
+        first_global_id: usize,
+        operator_id: usize,
+        trace: Trace,
+    ) {
+        self.workers
+            .lock()
+            .unwrap()
+            .entry(worker_index)
+            .or_default()
+            .operators
+            .insert(first_global_id, operator_id);
+        self.traces.lock().unwrap().insert(operator_id, trace);
+    }
+
+    /// Returns the arrangement sizes of each worker of the process.
+    pub fn per_worker(&self) -> BTreeMap<usize, ArrangementStats> {
+        self.workers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(worker_index, worker)| {
+                let mut total = ArrangementStats::default();
+                for stats in worker.arrangements.values() {
+                    total.accumulate(stats);
+                }
+                (*worker_index, total)
+            })
+            .collect()
+    }
+
+    /// Returns the arrangement sizes of each user-level operator,
+    /// the biggest memory consumers first.
+    pub fn per_operator(&self) -> Vec<OperatorArrangementStats> {
+        let mut per_operator: HashMap<Option<usize>, OperatorArrangementStats> = HashMap::new();
+        {
+            let traces = self.traces.lock().unwrap();
+            for worker in self.workers.lock().unwrap().values() {
+                for (arrangement_id, stats) in &worker.arrangements {
+                    if stats.num_batches == 0 {
+                        continue;
+                    }
+                    let operator_id = worker.operator_of(*arrangement_id);
+                    let entry = per_operator.entry(operator_id).or_insert_with(|| {
+                        OperatorArrangementStats {
+                            operator_id,
+                            trace: operator_id
+                                .and_then(|operator_id| traces.get(&operator_id).cloned())
+                                .unwrap_or(Trace::Empty),
+                            num_arrangements: 0,
+                            stats: ArrangementStats::default(),
+                        }
+                    });
+                    entry.num_arrangements += 1;
+                    entry.stats.accumulate(stats);
+                }
+            }
+        }
+        let mut result: Vec<_> = per_operator.into_values().collect();
+        result.sort_by_key(|entry| (Reverse(entry.stats.approximate_bytes), entry.operator_id));
+        result
+    }
+}
@@ -4,8 +4,11 @@ pub mod external_index;
 pub mod gradual_broadcast;
 pub mod output;
 pub mod prev_next;
+pub mod session_window;
 pub mod stateful_reduce;
+pub mod temporal_join;
 pub mod time_column;
+pub mod top_k;
 mod utils;
 
 use std::any::type_name;
@@ -0,0 +1,375 @@
+// Copyright © 2024 Pathway
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::panic::Location;
+use std::rc::Rc;
+
+use differential_dataflow::trace::{BatchReader, Cursor};
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::Operator;
+
+use super::ArrangeWithTypes;
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::dataflow::ArrangedByKey;
+use crate::engine::report_error::{LogError, UnwrapWithErrorLogger};
+use crate::engine::value::KeyImpl;
+use crate::engine::window::{shift, within_session_gap};
+use crate::engine::{Key, Timestamp, Value};
+
+// (time, row key) -> (row values, count)
+type SessionRows = BTreeMap<(Value, Key), (Value, isize)>;
+// (time, row key) -> (output key, output values, count)
+type SessionOutput = BTreeMap<(Value, Key), (Key, Value, isize)>;
+// ((output key, output values, time), diff)
+type SessionChanges = Vec<((Key, Value, Value), isize)>;
+
+fn rows_at_or_after(time: &Value) -> (Value, Key) {
+    (time.clone(), Key(KeyImpl::MIN))
+}
+
+fn rows_at_or_before(time: &Value) -> (Value, Key) {
+    (time.clone(), Key(KeyImpl::MAX))
+}
+
+#[derive(Default)]
+struct InstanceSessions {
+    rows: SessionRows,
+    output: SessionOutput,
+    max_time: Option<Value>,
+}
+
+impl InstanceSessions {
+    fn apply(&mut self, time: Value, key: Key, values: Value, diff: isize) {
+        if self
+            .max_time
+            .as_ref()
+            .map_or(true, |max_time| time > *max_time)
+        {
+            self.max_time = Some(time.clone());
+        }
+        match self.rows.entry((time, key)) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().1 += diff;
+                if entry.get().1 <= 0 {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                // retractions of rows dropped after their cutoff are ignored
+                if diff > 0 {
+                    entry.insert((values, diff));
+                }
+            }
+        }
+    }
+
+    fn previous_time(&self, time: &Value) -> Option<&Value> {
+        self.rows
+            .range(..rows_at_or_after(time))
+            .next_back()
+            .map(|((time, _key), _)| time)
+    }
+
+    fn next_time(&self, time: &Value) -> Option<&Value> {
+        self.rows
+            .range((Excluded(rows_at_or_before(time)), Unbounded))
+            .next()
+            .map(|((time, _key), _)| time)
+    }
+
+    // start of the session that a row at `time` belongs to
+    fn session_start(&self, time: &Value, max_gap: &Value, error_logger: &dyn LogError) -> Value {
+        let mut start = time.clone();
+        while let Some(previous) = self.previous_time(&start) {
+            if !within_session_gap(previous, &start, max_gap).unwrap_or_log(error_logger, false) {
+                break;
+            }
+            start = previous.clone();
+        }
+        start
+    }
+
+    // end of the session that a row at `time` belongs to
+    fn session_end(&self, time: &Value, max_gap: &Value, error_logger: &dyn LogError) -> Value {
+        let mut end = time.clone();
+        while let Some(next) = self.next_time(&end) {
+            if !within_session_gap(&end, next, max_gap).unwrap_or_log(error_logger, false) {
+                break;
+            }
+            end = next.clone();
+        }
+        end
+    }
+
+    // a row is late if the session it would belong to ended more than `cutoff` before
+    // the largest time seen in the instance
+    fn is_late(
+        &self,
+        time: &Value,
+        max_gap: &Value,
+        cutoff: &Value,
+        error_logger: &dyn LogError,
+    ) -> bool {
+        self.max_time.as_ref().is_some_and(|max_time| {
+            let end = self.session_end(time, max_gap, error_logger);
+            shift(&end, cutoff).unwrap_or_log(error_logger, Value::None) < *max_time
+        })
+    }
+
+    // output of the rows between `start` and `end`, which have to be the bounds of sessions
+    fn compute_output(
+        &self,
+        start: &Value,
+        end: &Value,
+        max_gap: &Value,
+        error_logger: &dyn LogError,
+    ) -> SessionOutput {
+        let rows: Vec<_> = self
+            .rows
+            .range(rows_at_or_after(start)..=rows_at_or_before(end))
+            .collect();
+        let mut output = SessionOutput::new();
+        let mut session_begin = 0;
+        for (i, ((time, _key), _)) in rows.iter().enumerate() {
+            let session_continues = rows.get(i + 1).is_some_and(|((next_time, _key), _)| {
+                within_session_gap(time, next_time, max_gap).unwrap_or_log(error_logger, false)
+            });
+            if session_continues {
+                continue;
+            }
+            let session_start = &rows[session_begin].0 .0;
+            for ((row_time, key), (values, count)) in &rows[session_begin..=i] {
+                let output_key = Key::for_values(&[Value::from(*key), session_start.clone()])
+                    .with_shard_of(*key);
+                let output_values = Value::Tuple(
+                    [values.clone(), session_start.clone(), time.clone()]
+                        .into_iter()
+                        .collect(),
+                );
+                output.insert(
+                    (row_time.clone(), *key),
+                    (output_key, output_values, *count),
+                );
+            }
+            session_begin = i + 1;
+        }
+        output
+    }
+
+    // replaces the output of the rows between `start` and `end`
+    fn replace_output(
+        &mut self,
+        start: &Value,
+        end: &Value,
+        new_output: SessionOutput,
+        changes: &mut SessionChanges,
+    ) {
+        let old_rows: Vec<_> = self
+            .output
+            .range(rows_at_or_after(start)..=rows_at_or_before(end))
+            .map(|(row, _)| row.clone())
+            .collect();
+        for row in old_rows {
+            let old_entry = self.output.remove(&row).unwrap();
+            if new_output.get(&row) != Some(&old_entry) {
+                let (key, values, count) = old_entry;
+                changes.push(((key, values, row.0.clone()), -count));
+            }
+        }
+        for (row, entry) in new_output {
+            if self.output.get(&row) != Some(&entry) {
+                let (key, values, count) = entry.clone();
+                changes.push(((key, values, row.0.clone()), count));
+            }
+            self.output.insert(row, entry);
+        }
+    }
+
+    // recomputes only the sessions containing the changed times
+    fn update_output(
+        &mut self,
+        changed_times: &BTreeSet<Value>,
+        max_gap: &Value,
+        error_logger: &dyn LogError,
+    ) -> SessionChanges {
+        let mut changes = Vec::new();
+        let mut updated_until: Option<Value> = None;
+        for time in changed_times {
+            if updated_until.as_ref().is_some_and(|end| time <= end) {
+                continue;
+            }
+            let start = self.session_start(time, max_gap, error_logger);
+            let end = self.session_end(time, max_gap, error_logger);
+            let new_output = self.compute_output(&start, &end, max_gap, error_logger);
+            self.replace_output(&start, &end, new_output, &mut changes);
+            updated_until = Some(end);
+        }
+        changes
+    }
+
+    // removes the leading sessions that can't be changed by rows that are not late
+    fn prune(
+        &mut self,
+        max_gap: &Value,
+        cutoff: &Value,
+        error_logger: &dyn LogError,
+    ) -> SessionChanges {
+        let mut changes = Vec::new();
+        let Some(max_time) = self.max_time.clone() else {
+            return changes;
+        };
+        while let Some(((first_time, _key), _)) = self.rows.first_key_value() {
+            let end = self.session_end(first_time, max_gap, error_logger);
+            let is_closed = shift(&end, max_gap)
+                .and_then(|end| shift(&end, cutoff))
+                .is_ok_and(|threshold| threshold < max_time);
+            if !is_closed {
+                break;
+            }
+            let closed_output = if let Some(rest) = self.next_time(&end).map(rows_at_or_after) {
+                self.rows = self.rows.split_off(&rest);
+                let remaining_output = self.output.split_off(&rest);
+                std::mem::replace(&mut self.output, remaining_output)
+            } else {
+                self.rows.clear();
+                std::mem::take(&mut self.output)
+            };
+            for ((time, _key), (key, values, count)) in closed_output {
+                changes.push(((key, values, time), -count));
+            }
+        }
+        changes
+    }
+}
+
+pub trait SessionWindows<S>
+where
+    S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>,
+{
+    /// Assigns rows, grouped by instance, to sessions: maximal sequences of rows with gaps
+    /// between consecutive times not exceeding `max_gap`. Returns rows extended with
+    /// the session start and end, keyed by the row key and the session start, together
+    /// with the row time.
+    ///
+    /// With `cutoff` set, rows are ignored if the session they would belong to ends more
+    /// than `cutoff` before the largest time seen so far in their instance. A session is
+    /// removed from the state once the largest time passes its end shifted by `max_gap`
+    /// and `cutoff`, as no row that is not ignored can join it then. Its results are
+    /// retracted, at neu times if `keep_results` is set, so that they can be filtered
+    /// out afterwards.
+    fn session_windows(
+        &self,
+        max_gap: Value,
+        cutoff: Option<Value>,
+        keep_results: bool,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, (Value, Value))>;
+}
+
+impl<S> SessionWindows<S> for Collection<S, (Key, (Value, Key, Value))>
+where
+    S: MaybeTotalScope<MaybeTotalTimestamp = Timestamp>,
+{
+    #[track_caller]
+    fn session_windows(
+        &self,
+        max_gap: Value,
+        cutoff: Option<Value>,
+        keep_results: bool,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, (Value, Value))> {
+        let caller = Location::caller();
+        let name = format!("SessionWindows at {caller}");
+
+        let arranged: ArrangedByKey<S, Key, (Value, Key, Value)> =
+            self.arrange_named("Arrange: SessionWindows");
+        let mut instances: HashMap<Key, InstanceSessions> = HashMap::new();
+        arranged
+            .stream
+            .unary(Pipeline, &name, move |_, _| {
+                move |input, output| {
+                    let mut last_capability = None;
+                    let mut updated_instances = HashSet::new();
+                    input.for_each(|cap, data| {
+                        let mut session = output.session(&cap);
+                        for batch in data.iter() {
+                            let mut cursor = batch.cursor();
+                            while let Some(instance) = cursor.get_key(batch) {
+                                let mut data_by_time = BTreeMap::new();
+                                while let Some((time, key, values)) = cursor.get_val(batch) {
+                                    cursor.map_times(batch, |timestamp, diff| {
+                                        data_by_time
+                                            .entry(*timestamp)
+                                            .or_insert_with(Vec::new)
+                                            .push((time.clone(), *key, values.clone(), *diff));
+                                    });
+                                    cursor.step_val(batch);
+                                }
+                                let sessions = instances.entry(*instance).or_default();
+                                for (timestamp, data) in data_by_time {
+                                    let mut changed_times = BTreeSet::new();
+                                    for (time, key, values, diff) in data {
+                                        if let Some(cutoff) = &cutoff {
+                                            if diff > 0
+                                                && sessions.is_late(
+                                                    &time,
+                                                    &max_gap,
+                                                    cutoff,
+                                                    error_logger.as_ref(),
+                                                )
+                                            {
+                                                continue;
+                                            }
+                                        }
+                                        changed_times.insert(time.clone());
+                                        sessions.apply(time, key, values, diff);
+                                    }
+                                    for ((key, values, time), diff) in sessions.update_output(
+                                        &changed_times,
+                                        &max_gap,
+                                        error_logger.as_ref(),
+                                    ) {
+                                        session.give(((key, (values, time)), timestamp, diff));
+                                    }
+                                }
+                                updated_instances.insert(*instance);
+                                cursor.step_key(batch);
+                            }
+                        }
+                        last_capability = Some(cap.retain());
+                    });
+
+                    let Some(capability) = last_capability else {
+                        return;
+                    };
+                    let Some(cutoff) = &cutoff else {
+                        instances.retain(|_instance, sessions| !sessions.rows.is_empty());
+                        return;
+                    };
+                    let timestamp = if keep_results {
+                        Timestamp(capability.time().0 + 1) // neu time
+                    } else {
+                        *capability.time()
+                    };
+                    let capability = capability.delayed(&timestamp);
+                    let mut session = output.session(&capability);
+                    // the largest time of an instance is kept after all its sessions are
+                    // removed, so that late rows are still recognized
+                    for instance in updated_instances {
+                        let sessions = instances
+                            .get_mut(&instance)
+                            .expect("updated instance should be present");
+                        for ((key, values, time), diff) in
+                            sessions.prune(&max_gap, cutoff, error_logger.as_ref())
+                        {
+                            session.give(((key, (values, time)), timestamp, diff));
+                        }
+                    }
+                }
+            })
+            .as_collection()
+    }
+}
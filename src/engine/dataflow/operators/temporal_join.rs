@@ -0,0 +1,554 @@
+// Copyright © 2024 Pathway
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound;
+use std::panic::Location;
+use std::rc::Rc;
+
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::{AsCollection, Collection};
+use serde::{Deserialize, Serialize};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Capability, Operator};
+use timely::order::TotalOrder;
+
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::dataflow::shard::Shard;
+use crate::engine::report_error::{LogError, UnwrapWithErrorLogger};
+use crate::engine::window::{shift, shift_back, time_difference};
+use crate::engine::{Key, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum JoinSide {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsofDirection {
+    Backward,
+    Forward,
+    Nearest,
+}
+
+// (time, side, row key, row values)
+type TemporalJoinInput = (Value, JoinSide, Key, Value);
+// time -> (row key, row values) -> count
+type Rows<T> = BTreeMap<Value, BTreeMap<(Key, Value), T>>;
+
+fn update_count(rows: &mut Rows<isize>, time: &Value, key: Key, values: Value, diff: isize) {
+    let rows_at_time = rows.entry(time.clone()).or_default();
+    match rows_at_time.entry((key, values)) {
+        Entry::Occupied(mut entry) => {
+            *entry.get_mut() += diff;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(diff);
+        }
+    }
+    if rows_at_time.is_empty() {
+        rows.remove(time);
+    }
+}
+
+fn contains_row(rows: &Rows<isize>, time: &Value, key: Key, values: &Value) -> bool {
+    rows.get(time)
+        .is_some_and(|rows_at_time| rows_at_time.contains_key(&(key, values.clone())))
+}
+
+fn result_row(
+    join_key: Key,
+    left_key: Key,
+    left_values: &Value,
+    right: Option<(Key, &Value)>,
+) -> (Key, Value) {
+    match right {
+        Some((right_key, right_values)) => (
+            Key::for_values(&[Value::from(left_key), Value::from(right_key)])
+                .with_shard_of(join_key),
+            Value::from(
+                [
+                    Value::Pointer(left_key),
+                    left_values.clone(),
+                    Value::Pointer(right_key),
+                    right_values.clone(),
+                ]
+                .as_slice(),
+            ),
+        ),
+        None => (
+            Key::for_values(&[Value::from(left_key), Value::None]).with_shard_of(join_key),
+            Value::from(
+                [
+                    Value::Pointer(left_key),
+                    left_values.clone(),
+                    Value::None,
+                    Value::None,
+                ]
+                .as_slice(),
+            ),
+        ),
+    }
+}
+
+trait TemporalJoinGroup: 'static {
+    fn update(
+        &mut self,
+        join_key: Key,
+        updates: Vec<(TemporalJoinInput, isize)>,
+        output: &mut Vec<((Key, Value), isize)>,
+    );
+
+    fn is_empty(&self) -> bool;
+}
+
+// Updates are exchanged by the join key and kept only until their timestamp is complete,
+// so every row is stored once, in the state of its group.
+#[track_caller]
+fn temporal_join_core<S, G>(
+    collection: &Collection<S, (Key, TemporalJoinInput)>,
+    name: &str,
+    mut new_group: impl FnMut() -> G + 'static,
+) -> Collection<S, (Key, Value)>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+    G: TemporalJoinGroup,
+{
+    let caller = Location::caller();
+    let operator_name = format!("{name} at {caller}");
+
+    let exchange = Exchange::new(
+        |((join_key, _input), _time, _diff): &((Key, TemporalJoinInput), S::Timestamp, isize)| {
+            join_key.shard()
+        },
+    );
+    collection
+        .inner
+        .unary_frontier(exchange, &operator_name, move |_capability, _info| {
+            let mut input_buffer = Vec::new();
+            let mut pending: BTreeMap<
+                S::Timestamp,
+                (
+                    Capability<S::Timestamp>,
+                    HashMap<Key, Vec<(TemporalJoinInput, isize)>>,
+                ),
+            > = BTreeMap::new();
+            let mut groups: HashMap<Key, G> = HashMap::new();
+            let mut changes = Vec::new();
+            move |input, output| {
+                input.for_each(|capability, data| {
+                    data.swap(&mut input_buffer);
+                    for ((join_key, input), timestamp, diff) in input_buffer.drain(..) {
+                        let (_capability, updates) = pending
+                            .entry(timestamp.clone())
+                            .or_insert_with(|| (capability.delayed(&timestamp), HashMap::new()));
+                        updates
+                            .entry(join_key)
+                            .or_insert_with(Vec::new)
+                            .push((input, diff));
+                    }
+                });
+                while let Some(entry) = pending.first_entry() {
+                    if input.frontier().less_equal(entry.key()) {
+                        break;
+                    }
+                    let (timestamp, (capability, updates_by_key)) = entry.remove_entry();
+                    let mut session = output.session(&capability);
+                    for (join_key, mut updates) in updates_by_key {
+                        consolidate(&mut updates);
+                        let group = groups.entry(join_key).or_insert_with(&mut new_group);
+                        group.update(join_key, updates, &mut changes);
+                        if group.is_empty() {
+                            groups.remove(&join_key);
+                        }
+                        for (row, diff) in changes.drain(..) {
+                            session.give((row, timestamp.clone(), diff));
+                        }
+                    }
+                }
+            }
+        })
+        .as_collection()
+}
+
+struct AsofJoinGroup {
+    direction: AsofDirection,
+    tolerance: Option<Value>,
+    error_logger: Rc<dyn LogError>,
+    left: Rows<isize>,
+    right: Rows<isize>,
+}
+
+impl AsofJoinGroup {
+    fn backward_match(&self, time: &Value) -> Option<(&Value, &(Key, Value))> {
+        let (right_time, rows) = self.right.range(..=time.clone()).next_back()?;
+        let (row, _count) = rows.last_key_value()?;
+        Some((right_time, row))
+    }
+
+    fn forward_match(&self, time: &Value) -> Option<(&Value, &(Key, Value))> {
+        let (right_time, rows) = self.right.range(time.clone()..).next()?;
+        let (row, _count) = rows.first_key_value()?;
+        Some((right_time, row))
+    }
+
+    fn find_match(&self, time: &Value) -> Option<&(Key, Value)> {
+        let error_logger = self.error_logger.as_ref();
+        let (right_time, row, is_backward) = match self.direction {
+            AsofDirection::Backward => {
+                let (right_time, row) = self.backward_match(time)?;
+                (right_time, row, true)
+            }
+            AsofDirection::Forward => {
+                let (right_time, row) = self.forward_match(time)?;
+                (right_time, row, false)
+            }
+            AsofDirection::Nearest => match (self.backward_match(time), self.forward_match(time)) {
+                (Some((backward_time, backward_row)), Some((forward_time, forward_row))) => {
+                    let backward_distance = time_difference(time, backward_time);
+                    let forward_distance = time_difference(forward_time, time);
+                    match (backward_distance, forward_distance) {
+                        (Ok(backward_distance), Ok(forward_distance)) => {
+                            if backward_distance <= forward_distance {
+                                (backward_time, backward_row, true)
+                            } else {
+                                (forward_time, forward_row, false)
+                            }
+                        }
+                        (Err(error), _) | (_, Err(error)) => {
+                            error_logger.log_error(error);
+                            return None;
+                        }
+                    }
+                }
+                (Some((right_time, row)), None) => (right_time, row, true),
+                (None, Some((right_time, row))) => (right_time, row, false),
+                (None, None) => return None,
+            },
+        };
+        let within_tolerance = match &self.tolerance {
+            None => true,
+            Some(tolerance) if is_backward => shift(right_time, tolerance)
+                .map(|limit| limit >= *time)
+                .unwrap_or_log(error_logger, false),
+            Some(tolerance) => shift(time, tolerance)
+                .map(|limit| limit >= *right_time)
+                .unwrap_or_log(error_logger, false),
+        };
+        within_tolerance.then_some(row)
+    }
+
+    // left rows between the right rows neighbouring `time`, inclusive
+    fn affected_left_rows(&self, time: &Value, affected: &mut BTreeSet<(Value, Key, Value)>) {
+        let lower = self
+            .right
+            .range(..time.clone())
+            .next_back()
+            .map_or(Bound::Unbounded, |(time, _rows)| {
+                Bound::Included(time.clone())
+            });
+        let upper = self
+            .right
+            .range((Bound::Excluded(time.clone()), Bound::Unbounded))
+            .next()
+            .map_or(Bound::Unbounded, |(time, _rows)| {
+                Bound::Included(time.clone())
+            });
+        for (left_time, rows) in self.left.range((lower, upper)) {
+            for (key, values) in rows.keys() {
+                affected.insert((left_time.clone(), *key, values.clone()));
+            }
+        }
+    }
+}
+
+impl AsofJoinGroup {
+    fn result(
+        &self,
+        join_key: Key,
+        left_row: &(Value, Key, Value),
+    ) -> Option<((Key, Value), isize)> {
+        let (time, key, values) = left_row;
+        let count = *self.left.get(time)?.get(&(*key, values.clone()))?;
+        let right = self
+            .find_match(time)
+            .map(|(right_key, right_values)| (*right_key, right_values));
+        Some((result_row(join_key, *key, values, right), count))
+    }
+}
+
+impl TemporalJoinGroup for AsofJoinGroup {
+    // Results are not stored: the results of the affected left rows are computed before
+    // and after applying the updates, and only the differences are emitted.
+    fn update(
+        &mut self,
+        join_key: Key,
+        updates: Vec<(TemporalJoinInput, isize)>,
+        output: &mut Vec<((Key, Value), isize)>,
+    ) {
+        let mut affected = BTreeSet::new();
+        for ((time, side, key, values), _diff) in &updates {
+            match side {
+                JoinSide::Left => {
+                    affected.insert((time.clone(), *key, values.clone()));
+                }
+                JoinSide::Right => self.affected_left_rows(time, &mut affected),
+            }
+        }
+        let old_results: Vec<_> = affected
+            .into_iter()
+            .map(|left_row| {
+                let old_result = self.result(join_key, &left_row);
+                (left_row, old_result)
+            })
+            .collect();
+
+        for ((time, side, key, values), diff) in updates {
+            let rows = match side {
+                JoinSide::Left => &mut self.left,
+                JoinSide::Right => &mut self.right,
+            };
+            update_count(rows, &time, key, values, diff);
+        }
+
+        for (left_row, old_result) in old_results {
+            let new_result = self.result(join_key, &left_row);
+            if new_result != old_result {
+                if let Some((row, count)) = old_result {
+                    output.push((row, -count));
+                }
+                if let Some((row, count)) = new_result {
+                    output.push((row, count));
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.left.is_empty() && self.right.is_empty()
+    }
+}
+
+struct IntervalJoinGroup {
+    lower: Value,
+    upper: Value,
+    cutoff: Option<Value>,
+    error_logger: Rc<dyn LogError>,
+    left: Rows<isize>,
+    right: Rows<isize>,
+    max_time: Option<Value>,
+}
+
+impl IntervalJoinGroup {
+    fn rows_in_range(
+        rows: &Rows<isize>,
+        lower: Value,
+        upper: Value,
+    ) -> impl Iterator<Item = (&(Key, Value), isize)> {
+        let range = if lower <= upper {
+            Some(rows.range(lower..=upper))
+        } else {
+            None
+        };
+        range
+            .into_iter()
+            .flatten()
+            .flat_map(|(_time, rows)| rows.iter().map(|(row, count)| (row, *count)))
+    }
+
+    // With a cutoff, rows older than the largest time seen by more than the cutoff
+    // are ignored, and so are retractions of rows that were already forgotten.
+    fn is_ignored(
+        &self,
+        side: JoinSide,
+        time: &Value,
+        key: Key,
+        values: &Value,
+        diff: isize,
+    ) -> bool {
+        let (Some(cutoff), Some(max_time)) = (&self.cutoff, &self.max_time) else {
+            return false;
+        };
+        if diff < 0 {
+            let rows = match side {
+                JoinSide::Left => &self.left,
+                JoinSide::Right => &self.right,
+            };
+            return !contains_row(rows, time, key, values);
+        }
+        shift(time, cutoff)
+            .map(|threshold| threshold < *max_time)
+            .unwrap_or_log(self.error_logger.as_ref(), false)
+    }
+
+    // Forgets rows that can only be matched by ignored rows: left rows with
+    // `time + upper < max_time - cutoff` and right rows with `time - lower < max_time - cutoff`.
+    // Their results are kept in the output.
+    fn forget(&mut self) {
+        let (Some(cutoff), Some(max_time)) = (&self.cutoff, &self.max_time) else {
+            return;
+        };
+        let Some(oldest_kept) = shift_back(max_time, cutoff)
+            .map(Some)
+            .unwrap_or_log(self.error_logger.as_ref(), None)
+        else {
+            return;
+        };
+        if let Some(left_threshold) = shift_back(&oldest_kept, &self.upper)
+            .map(Some)
+            .unwrap_or_log(self.error_logger.as_ref(), None)
+        {
+            self.left = self.left.split_off(&left_threshold);
+        }
+        if let Some(right_threshold) = shift(&oldest_kept, &self.lower)
+            .map(Some)
+            .unwrap_or_log(self.error_logger.as_ref(), None)
+        {
+            self.right = self.right.split_off(&right_threshold);
+        }
+    }
+}
+
+impl TemporalJoinGroup for IntervalJoinGroup {
+    fn update(
+        &mut self,
+        join_key: Key,
+        updates: Vec<(TemporalJoinInput, isize)>,
+        output: &mut Vec<((Key, Value), isize)>,
+    ) {
+        let error_logger = self.error_logger.clone();
+        // changes of left rows are joined with the previous right rows
+        // and changes of right rows with the current left rows
+        let (left_updates, right_updates): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .filter(|((time, side, key, values), diff)| {
+                !self.is_ignored(*side, time, *key, values, *diff)
+            })
+            .partition(|((_time, side, _key, _values), _diff)| *side == JoinSide::Left);
+        for ((time, _side, _key, _values), _diff) in left_updates.iter().chain(&right_updates) {
+            if self
+                .max_time
+                .as_ref()
+                .map_or(true, |max_time| time > max_time)
+            {
+                self.max_time = Some(time.clone());
+            }
+        }
+        for ((time, _side, key, values), diff) in left_updates {
+            let bounds = shift(&time, &self.lower).and_then(|lower| {
+                let upper = shift(&time, &self.upper)?;
+                Ok((lower, upper))
+            });
+            if let Some((lower, upper)) =
+                bounds.map(Some).unwrap_or_log(error_logger.as_ref(), None)
+            {
+                for ((right_key, right_values), count) in
+                    Self::rows_in_range(&self.right, lower, upper)
+                {
+                    let row = result_row(join_key, key, &values, Some((*right_key, right_values)));
+                    output.push((row, diff * count));
+                }
+            }
+            update_count(&mut self.left, &time, key, values, diff);
+        }
+        for ((time, _side, key, values), diff) in right_updates {
+            let bounds = shift_back(&time, &self.upper).and_then(|lower| {
+                let upper = shift_back(&time, &self.lower)?;
+                Ok((lower, upper))
+            });
+            if let Some((lower, upper)) =
+                bounds.map(Some).unwrap_or_log(error_logger.as_ref(), None)
+            {
+                for ((left_key, left_values), count) in
+                    Self::rows_in_range(&self.left, lower, upper)
+                {
+                    let row = result_row(join_key, *left_key, left_values, Some((key, &values)));
+                    output.push((row, diff * count));
+                }
+            }
+            update_count(&mut self.right, &time, key, values, diff);
+        }
+        self.forget();
+    }
+
+    fn is_empty(&self) -> bool {
+        // the largest time is kept as long as it is needed to ignore late rows
+        self.left.is_empty() && self.right.is_empty() && self.cutoff.is_none()
+    }
+}
+
+pub trait TemporalJoin<S>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+{
+    /// Matches every left row with the right row with the same join key that is the closest
+    /// in time in the given direction, not further than `tolerance`. Ties are broken by
+    /// the right row key. Left rows without a match are kept with empty right columns.
+    fn asof_join(
+        &self,
+        direction: AsofDirection,
+        tolerance: Option<Value>,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, Value)>;
+
+    /// Matches every left row with all right rows with the same join key and time within
+    /// `[left_time + lower, left_time + upper]`.
+    ///
+    /// With `cutoff` set, rows older than the largest time seen for their join key by more
+    /// than `cutoff` are ignored, and rows that can be matched only by such rows are removed
+    /// from the state. Their results remain in the output.
+    fn interval_join(
+        &self,
+        lower: Value,
+        upper: Value,
+        cutoff: Option<Value>,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, Value)>;
+}
+
+impl<S> TemporalJoin<S> for Collection<S, (Key, TemporalJoinInput)>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+{
+    #[track_caller]
+    fn asof_join(
+        &self,
+        direction: AsofDirection,
+        tolerance: Option<Value>,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, Value)> {
+        temporal_join_core(self, "AsofJoin", move || AsofJoinGroup {
+            direction,
+            tolerance: tolerance.clone(),
+            error_logger: error_logger.clone(),
+            left: Rows::new(),
+            right: Rows::new(),
+        })
+    }
+
+    #[track_caller]
+    fn interval_join(
+        &self,
+        lower: Value,
+        upper: Value,
+        cutoff: Option<Value>,
+        error_logger: Rc<dyn LogError>,
+    ) -> Collection<S, (Key, Value)> {
+        temporal_join_core(self, "IntervalJoin", move || IntervalJoinGroup {
+            lower: lower.clone(),
+            upper: upper.clone(),
+            cutoff: cutoff.clone(),
+            error_logger: error_logger.clone(),
+            left: Rows::new(),
+            right: Rows::new(),
+            max_time: None,
+        })
+    }
+}
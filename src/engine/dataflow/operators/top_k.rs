@@ -0,0 +1,335 @@
+// Copyright © 2024 Pathway
+
+use std::cmp::min;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::panic::Location;
+
+use differential_dataflow::operators::arrange::Arranged;
+use differential_dataflow::trace::{BatchReader, Cursor, TraceReader};
+use differential_dataflow::{AsCollection, Collection, Data, ExchangeData};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::Operator;
+use timely::order::TotalOrder;
+use timely::progress::Antichain;
+use timely::PartialOrder;
+
+use super::ArrangeWithTypes;
+use crate::engine::dataflow::maybe_total::MaybeTotalScope;
+use crate::engine::dataflow::shard::Shard;
+use crate::engine::dataflow::ArrangedByKey;
+
+pub trait TopK<S, K, V>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+{
+    /// Maintains the `k` smallest values for every key. Only these values are kept
+    /// in the operator state; the underlying arrangement is read only when a retraction
+    /// removes one of them and the remaining ones are not enough to tell the new result.
+    #[track_caller]
+    fn top_k(&self, k: usize) -> Collection<S, (K, Vec<V>)> {
+        self.top_k_named("TopK", k)
+    }
+
+    fn top_k_named(&self, name: &str, k: usize) -> Collection<S, (K, Vec<V>)>;
+
+    /// Like [`TopK::top_k`], but skips the `offset` smallest values and emits only
+    /// the values that enter or leave the remaining `k` instead of whole vectors.
+    #[track_caller]
+    fn top_k_rows(&self, k: usize, offset: usize) -> Collection<S, (K, V)> {
+        self.top_k_rows_named("TopKRows", k, offset)
+    }
+
+    fn top_k_rows_named(&self, name: &str, k: usize, offset: usize) -> Collection<S, (K, V)>;
+}
+
+impl<S, K, V> TopK<S, K, V> for Collection<S, (K, V)>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+    K: ExchangeData + Shard + Hash,
+    V: ExchangeData,
+{
+    #[track_caller]
+    fn top_k_named(&self, name: &str, k: usize) -> Collection<S, (K, Vec<V>)> {
+        let arranged: ArrangedByKey<S, K, V> = self.arrange_named(&format!("Arrange: {name}"));
+        arranged.top_k_named(name, k)
+    }
+
+    #[track_caller]
+    fn top_k_rows_named(&self, name: &str, k: usize, offset: usize) -> Collection<S, (K, V)> {
+        let arranged: ArrangedByKey<S, K, V> = self.arrange_named(&format!("Arrange: {name}"));
+        arranged.top_k_rows_named(name, k, offset)
+    }
+}
+
+struct TopKGroup<V> {
+    elements: BTreeMap<V, isize>,
+    stored_count: isize,
+    group_size: isize,
+}
+
+impl<V: Ord + Clone> TopKGroup<V> {
+    fn new() -> Self {
+        Self {
+            elements: BTreeMap::new(),
+            stored_count: 0,
+            group_size: 0,
+        }
+    }
+
+    fn limit(k: usize) -> isize {
+        isize::try_from(k).unwrap_or(isize::MAX)
+    }
+
+    fn add(&mut self, element: V, diff: isize) {
+        self.stored_count += diff;
+        match self.elements.entry(element) {
+            Entry::Vacant(entry) => {
+                entry.insert(diff);
+            }
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += diff;
+            }
+        }
+    }
+
+    fn trim(&mut self, k: usize) {
+        let limit = Self::limit(k);
+        while self.stored_count > limit {
+            let mut last = self
+                .elements
+                .last_entry()
+                .expect("stored count should match stored elements");
+            let excess = min(*last.get(), self.stored_count - limit);
+            *last.get_mut() -= excess;
+            self.stored_count -= excess;
+            if *last.get() == 0 {
+                last.remove();
+            }
+        }
+    }
+
+    /// Applies updates to the group. Returns `false` if a retraction removed a stored
+    /// element and the stored elements no longer have to be the `k` smallest ones.
+    fn update(&mut self, k: usize, updates: Vec<(V, isize)>) -> bool {
+        let mut removed_stored = false;
+        for (element, diff) in updates {
+            self.group_size += diff;
+            if diff > 0 {
+                self.add(element, diff);
+                self.trim(k);
+            } else if let Some(count) = self.elements.get_mut(&element) {
+                // retractions of elements that are not stored are outside of the top k
+                let removed = min(*count, -diff);
+                *count -= removed;
+                self.stored_count -= removed;
+                if *count == 0 {
+                    self.elements.remove(&element);
+                }
+                removed_stored = true;
+            }
+        }
+        !removed_stored || self.stored_count == self.group_size
+    }
+
+    fn top(&self) -> Option<Vec<V>> {
+        if self.elements.is_empty() {
+            return None;
+        }
+        let mut result = Vec::new();
+        for (element, count) in &self.elements {
+            for _ in 0..*count {
+                result.push(element.clone());
+            }
+        }
+        Some(result)
+    }
+
+    fn recompute<Tr>(trace: &mut Tr, key: &Tr::Key, time: &Tr::Time, k: usize) -> Self
+    where
+        Tr: TraceReader<Val = V, R = isize>,
+        Tr::Key: Data,
+        Tr::Time: PartialOrder,
+    {
+        let mut group = Self::new();
+        let (mut cursor, storage) = trace.cursor();
+        cursor.seek_key(&storage, key);
+        if cursor.get_key(&storage) != Some(key) {
+            return group;
+        }
+        while let Some(val) = cursor.get_val(&storage) {
+            let mut count = 0;
+            cursor.map_times(&storage, |val_time, diff| {
+                if val_time.less_equal(time) {
+                    count += *diff;
+                }
+            });
+            if count > 0 {
+                group.group_size += count;
+                if group.stored_count < Self::limit(k) {
+                    group.add(val.clone(), count);
+                }
+            }
+            cursor.step_val(&storage);
+        }
+        group.trim(k);
+        group
+    }
+}
+
+/// Returns the changes turning the sorted `old` values into the sorted `new` values.
+fn sorted_difference<V: Ord + Clone>(old: &[V], new: &[V]) -> Vec<(V, isize)> {
+    let mut result = Vec::new();
+    let mut old = old.iter().peekable();
+    let mut new = new.iter().peekable();
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(old_value), Some(new_value)) if old_value == new_value => {
+                old.next();
+                new.next();
+            }
+            (Some(old_value), Some(new_value)) if old_value < new_value => {
+                result.push(((*old_value).clone(), -1));
+                old.next();
+            }
+            (_, Some(new_value)) => {
+                result.push(((*new_value).clone(), 1));
+                new.next();
+            }
+            (Some(old_value), None) => {
+                result.push(((*old_value).clone(), -1));
+                old.next();
+            }
+            (None, None) => break,
+        }
+    }
+    result
+}
+
+/// Maintains the `k` smallest values for every key and passes the old and the new
+/// top of every changed group to `changes`, which produces the output updates.
+fn top_k_operator<S, Tr, D, F>(
+    arranged: &Arranged<S, Tr>,
+    name: &str,
+    k: usize,
+    mut changes: F,
+) -> Collection<S, D>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+    Tr: TraceReader<Time = S::Timestamp, R = isize> + Clone + 'static,
+    Tr::Key: Data + Hash,
+    Tr::Val: Data,
+    D: Data,
+    F: FnMut(&Tr::Key, Option<Vec<Tr::Val>>, Option<Vec<Tr::Val>>) -> Vec<(D, isize)> + 'static,
+{
+    let mut trace = arranged.trace.clone();
+    let mut groups: HashMap<Tr::Key, TopKGroup<Tr::Val>> = HashMap::new();
+    arranged
+        .stream
+        .unary(Pipeline, name, move |_, _| {
+            move |input, output| {
+                let mut upper_limit: Option<Antichain<S::Timestamp>> = None;
+                input.for_each(|cap, data| {
+                    let mut session = output.session(&cap);
+                    for batch in data.iter() {
+                        upper_limit = Some(batch.upper().clone());
+                        let mut cursor = batch.cursor();
+                        while let Some(key) = cursor.get_key(batch) {
+                            let mut data_by_time = BTreeMap::new();
+                            while let Some(val) = cursor.get_val(batch) {
+                                cursor.map_times(batch, |time, diff| {
+                                    data_by_time
+                                        .entry(time.clone())
+                                        .or_insert_with(Vec::new)
+                                        .push((val.clone(), *diff));
+                                });
+                                cursor.step_val(batch);
+                            }
+                            let mut group = groups.remove(key).unwrap_or_else(TopKGroup::new);
+                            for (time, data) in data_by_time {
+                                let old_top = group.top();
+                                let complete = group.update(k, data);
+                                let limit = min(TopKGroup::<Tr::Val>::limit(k), group.group_size);
+                                if !complete || group.stored_count < limit {
+                                    group = TopKGroup::recompute(&mut trace, key, &time, k);
+                                }
+                                let new_top = group.top();
+                                if new_top == old_top {
+                                    continue;
+                                }
+                                for (data, diff) in changes(key, old_top, new_top) {
+                                    session.give((data, time.clone(), diff));
+                                }
+                            }
+                            if group.group_size != 0 {
+                                groups.insert(key.clone(), group);
+                            }
+                            cursor.step_key(batch);
+                        }
+                    }
+                });
+                if let Some(upper_limit) = upper_limit {
+                    // the trace is only read at times of future batches
+                    trace.set_logical_compaction(upper_limit.borrow());
+                    trace.set_physical_compaction(upper_limit.borrow());
+                }
+            }
+        })
+        .as_collection()
+}
+
+impl<S, Tr> TopK<S, Tr::Key, Tr::Val> for Arranged<S, Tr>
+where
+    S: MaybeTotalScope,
+    S::Timestamp: TotalOrder,
+    Tr: TraceReader<Time = S::Timestamp, R = isize> + Clone + 'static,
+    Tr::Key: Data + Hash,
+    Tr::Val: Data,
+{
+    #[track_caller]
+    fn top_k_named(&self, name: &str, k: usize) -> Collection<S, (Tr::Key, Vec<Tr::Val>)> {
+        let caller = Location::caller();
+        let name = format!("{name} at {caller}");
+        top_k_operator(self, &name, k, |key, old_top, new_top| {
+            let mut result = Vec::new();
+            if let Some(old_top) = old_top {
+                result.push(((key.clone(), old_top), -1));
+            }
+            if let Some(new_top) = new_top {
+                result.push(((key.clone(), new_top), 1));
+            }
+            result
+        })
+    }
+
+    #[track_caller]
+    fn top_k_rows_named(
+        &self,
+        name: &str,
+        k: usize,
+        offset: usize,
+    ) -> Collection<S, (Tr::Key, Tr::Val)> {
+        let caller = Location::caller();
+        let name = format!("{name} at {caller}");
+        top_k_operator(
+            self,
+            &name,
+            offset.saturating_add(k),
+            move |key, old_top, new_top| {
+                let old_top = old_top.unwrap_or_default();
+                let new_top = new_top.unwrap_or_default();
+                sorted_difference(
+                    old_top.get(offset..).unwrap_or_default(),
+                    new_top.get(offset..).unwrap_or_default(),
+                )
+                .into_iter()
+                .map(|(value, diff)| ((key.clone(), value), diff))
+                .collect()
+            },
+        )
+    }
+}
@@ -4,6 +4,7 @@ use std::any::Any;
 use std::error;
 use std::fmt;
 use std::result;
+use std::time::Duration;
 
 use super::ColumnPath;
 use super::{Key, Value};
@@ -67,6 +68,9 @@ pub enum Error {
     #[error("wrong join type")]
     BadJoinType,
 
+    #[error("temporal join requires time columns of both tables")]
+    MissingJoinTimeColumn,
+
     #[error("wrong ix key policy")]
     BadIxKeyPolicy,
 
@@ -82,6 +86,9 @@ pub enum Error {
     #[error("operation is not supported inside iterate")]
     NotSupportedInIteration,
 
+    #[error("{0} is not supported when operator state is checkpointed")]
+    NotSupportedWithCheckpointing(&'static str),
+
     #[error("length mismatch")]
     LengthMismatch,
 
@@ -125,9 +132,23 @@ pub enum Error {
     #[error("reader failed: {0:?}")]
     ReaderFailed(#[source] ReadError),
 
+    #[error("connector {connector} exceeded its error budget: {num_errors} of {num_entries} entries in the last {window:?} were erroneous")]
+    ErrorBudgetExceeded {
+        connector: String,
+        num_errors: usize,
+        num_entries: usize,
+        window: Duration,
+    },
+
     #[error("computation of imported table failed")]
     ImportedTableFailed,
 
+    #[error("invalid monitoring http host {0:?}, expected an IP address")]
+    InvalidMonitoringHttpHost(String),
+
+    #[error("monitoring http port {base_port} shifted by process id {process_id} is out of range")]
+    MonitoringHttpPortOutOfRange { base_port: u16, process_id: usize },
+
     #[error("operator_id not set")]
     OperatorIdNotSet,
 
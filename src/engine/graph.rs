@@ -2,7 +2,7 @@
 
 use std::any::Any;
 use std::cell::Cell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::ControlFlow;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
@@ -18,16 +18,20 @@ use scopeguard::defer;
 
 use crate::connectors::data_format::{Formatter, Parser};
 use crate::connectors::data_storage::{ReaderBuilder, Writer};
-use crate::connectors::monitoring::ConnectorStats;
+use crate::connectors::monitoring::{ConnectorStats, ErrorBudget, SinkStats};
 use crate::external_integration::ExternalIndex;
 use crate::persistence::ExternalPersistentId;
 use crate::python_api::extract_value;
 
+use super::dataflow::arrangement_stats::{
+    ArrangementStats, ArrangementStatsRegistry, OperatorArrangementStats,
+};
 use super::error::{DynResult, Trace};
 use super::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
 use super::reduce::StatefulCombineFn;
 use super::{
     Error, Expression, Key, Reducer, Result, ShardPolicy, Timestamp, TotalFrontier, Type, Value,
+    Window, WindowBehavior,
 };
 
 macro_rules! define_handle {
@@ -309,6 +313,7 @@ impl ReducerData {
 pub struct JoinData {
     pub table_handle: TableHandle,
     pub column_paths: Vec<ColumnPath>,
+    pub time_column_path: Option<ColumnPath>,
 }
 
 impl JoinData {
@@ -316,8 +321,15 @@ impl JoinData {
         JoinData {
             table_handle,
             column_paths,
+            time_column_path: None,
         }
     }
+
+    #[must_use]
+    pub fn with_time_column_path(mut self, time_column_path: ColumnPath) -> Self {
+        self.time_column_path = Some(time_column_path);
+        self
+    }
 }
 
 pub enum Computer {
@@ -449,6 +461,7 @@ impl TableProperties {
 pub struct OperatorProperties {
     pub id: usize,
     pub depends_on_error_log: bool,
+    pub trace: Trace,
 }
 
 pub type IterationLogic<'a> = Box<
@@ -476,7 +489,7 @@ impl BatchWrapper {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum JoinType {
     Inner,
     LeftOuter,
@@ -484,6 +497,22 @@ pub enum JoinType {
     FullOuter,
     LeftKeysSubset,
     LeftKeysFull,
+    LeftSemi,
+    LeftAnti,
+    AsofBackward {
+        tolerance: Option<Value>,
+    },
+    AsofForward {
+        tolerance: Option<Value>,
+    },
+    AsofNearest {
+        tolerance: Option<Value>,
+    },
+    Interval {
+        lower: Value,
+        upper: Value,
+        cutoff: Option<Value>,
+    },
 }
 
 impl JoinType {
@@ -498,6 +527,17 @@ impl JoinType {
             _ => Err(Error::BadJoinType),
         }
     }
+
+    /// Temporal joins match rows by the time columns of `JoinData` in addition to join keys.
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            Self::AsofBackward { .. }
+                | Self::AsofForward { .. }
+                | Self::AsofNearest { .. }
+                | Self::Interval { .. }
+        )
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -560,6 +600,12 @@ pub struct ProberStats {
     pub operators_stats: HashMap<usize, OperatorStats>,
     #[pyo3(get, set)]
     pub connector_stats: Vec<(String, ConnectorStats)>,
+    #[pyo3(get, set)]
+    pub sink_stats: Vec<(String, SinkStats)>,
+    #[pyo3(get, set)]
+    pub arrangement_stats: BTreeMap<usize, ArrangementStats>,
+    #[pyo3(get, set)]
+    pub operator_arrangement_stats: Vec<OperatorArrangementStats>,
 }
 
 pub type OnDataFn = Box<dyn FnMut(Key, &[Value], Timestamp, isize) -> DynResult<()>>;
@@ -782,6 +828,20 @@ pub trait Graph {
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle>;
 
+    /// Assigns rows to tumbling, sliding or session windows based on the time column.
+    /// Each output row is the input row extended with the window start and end.
+    /// A row belonging to many sliding windows is emitted once per window.
+    /// Sessions are computed separately for each value of the instance column.
+    fn window_table(
+        &self,
+        table_handle: TableHandle,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        behavior: WindowBehavior,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>;
+
     fn forget_immediately(
         &self,
         table_handle: TableHandle,
@@ -859,6 +919,21 @@ pub trait Graph {
         table_properties: Arc<TableProperties>,
     ) -> Result<TableHandle>;
 
+    /// Keeps, for every instance, the rows at positions `offset..offset + n` in the order
+    /// of the key column (ties broken by row ids). Only a bounded number of rows per
+    /// instance is held in the operator state.
+    #[allow(clippy::too_many_arguments)]
+    fn top_n_table(
+        &self,
+        table_handle: TableHandle,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: usize,
+        offset: usize,
+        descending: bool,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle>;
+
     fn reindex_table(
         &self,
         table_handle: TableHandle,
@@ -958,11 +1033,13 @@ pub trait Graph {
         column_path: ColumnPath,
     ) -> Result<()>;
 
+    #[allow(clippy::too_many_arguments)]
     fn connector_table(
         &self,
         reader: Box<dyn ReaderBuilder>,
         parser: Box<dyn Parser>,
         commit_duration: Option<Duration>,
+        error_budget: Option<ErrorBudget>,
         parallel_readers: usize,
         table_properties: Arc<TableProperties>,
         external_persistent_id: Option<&ExternalPersistentId>,
@@ -994,6 +1071,8 @@ pub trait Graph {
 
     fn probe_table(&self, table_handle: TableHandle, operator_id: usize) -> Result<()>;
 
+    fn arrangement_stats(&self) -> Result<Arc<ArrangementStatsRegistry>>;
+
     fn export_table(
         &self,
         table_handle: TableHandle,
@@ -1276,6 +1355,27 @@ impl Graph for ScopedGraph {
             )
         })
     }
+    fn window_table(
+        &self,
+        table_handle: TableHandle,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        behavior: WindowBehavior,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.try_with(|g| {
+            g.window_table(
+                table_handle,
+                time_column_path,
+                instance_column_path,
+                window,
+                behavior,
+                table_properties,
+            )
+        })
+    }
+
     fn forget_immediately(
         &self,
         table_handle: TableHandle,
@@ -1403,6 +1503,29 @@ impl Graph for ScopedGraph {
         })
     }
 
+    fn top_n_table(
+        &self,
+        table_handle: TableHandle,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: usize,
+        offset: usize,
+        descending: bool,
+        table_properties: Arc<TableProperties>,
+    ) -> Result<TableHandle> {
+        self.try_with(|g| {
+            g.top_n_table(
+                table_handle,
+                key_column_path,
+                instance_column_path,
+                n,
+                offset,
+                descending,
+                table_properties,
+            )
+        })
+    }
+
     fn reindex_table(
         &self,
         table_handle: TableHandle,
@@ -1574,6 +1697,7 @@ impl Graph for ScopedGraph {
         reader: Box<dyn ReaderBuilder>,
         parser: Box<dyn Parser>,
         commit_duration: Option<Duration>,
+        error_budget: Option<ErrorBudget>,
         parallel_readers: usize,
         table_properties: Arc<TableProperties>,
         external_persistent_id: Option<&ExternalPersistentId>,
@@ -1583,6 +1707,7 @@ impl Graph for ScopedGraph {
                 reader,
                 parser,
                 commit_duration,
+                error_budget,
                 parallel_readers,
                 table_properties,
                 external_persistent_id,
@@ -1630,6 +1755,10 @@ impl Graph for ScopedGraph {
         self.try_with(|g| g.probe_table(table_handle, operator_id))
     }
 
+    fn arrangement_stats(&self) -> Result<Arc<ArrangementStatsRegistry>> {
+        self.try_with(|g| g.arrangement_stats())
+    }
+
     fn export_table(
         &self,
         table_handle: TableHandle,
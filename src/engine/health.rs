@@ -0,0 +1,130 @@
+// Copyright © 2024 Pathway
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::timestamp::current_unix_timestamp_ms;
+
+/// The longest time a worker can spend in a single iteration of its event loop
+/// before it is considered stuck.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The longest time a worker waits for new events, so that the idle workers
+/// keep reporting that they are alive.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+fn now_ms() -> u64 {
+    u64::try_from(current_unix_timestamp_ms())
+        .expect("number of milliseconds should fit in 64 bits")
+}
+
+/// The state of the event loop of a single worker.
+#[derive(Debug, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct WorkerHealth {
+    last_heartbeat_ms: AtomicU64,
+    connectors_initialized: AtomicBool,
+    finished: AtomicBool,
+    failed: AtomicBool,
+}
+
+impl WorkerHealth {
+    /// Reports that the worker completed an iteration of its event loop.
+    pub fn heartbeat(&self, connectors_initialized: bool) {
+        self.last_heartbeat_ms.store(now_ms(), Ordering::Relaxed);
+        self.connectors_initialized
+            .store(connectors_initialized, Ordering::Relaxed);
+    }
+
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    pub fn fail(&self) {
+        self.failed.store(true, Ordering::Relaxed);
+    }
+
+    fn is_healthy(&self, now_ms: u64) -> bool {
+        if self.failed.load(Ordering::Relaxed) {
+            return false;
+        }
+        if self.finished.load(Ordering::Relaxed) {
+            return true;
+        }
+        let last_heartbeat_ms = self.last_heartbeat_ms.load(Ordering::Relaxed);
+        last_heartbeat_ms != 0
+            && Duration::from_millis(now_ms.saturating_sub(last_heartbeat_ms))
+                <= HEALTH_CHECK_TIMEOUT
+    }
+
+    fn is_ready(&self) -> bool {
+        self.finished.load(Ordering::Relaxed) || self.connectors_initialized.load(Ordering::Relaxed)
+    }
+}
+
+/// The most recent error that was sent to the error log.
+#[derive(Debug, Clone)]
+pub struct LastError {
+    pub operator_id: i64,
+    pub message: String,
+    pub trace: String,
+    pub time_ms: u64,
+}
+
+/// Tracks the liveness and the readiness of all workers of the process.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct HealthState {
+    num_workers: usize,
+    workers: Mutex<BTreeMap<usize, Arc<WorkerHealth>>>,
+    last_error: Mutex<Option<LastError>>,
+}
+
+impl HealthState {
+    /// Creates the state for a process running `num_workers` workers.
+    pub fn new(num_workers: usize) -> Self {
+        Self {
+            num_workers,
+            workers: Mutex::new(BTreeMap::new()),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn register_worker(&self, worker_index: usize) -> Arc<WorkerHealth> {
+        self.workers
+            .lock()
+            .unwrap()
+            .entry(worker_index)
+            .or_default()
+            .clone()
+    }
+
+    /// Checks whether no worker has failed and all of them make progress.
+    pub fn is_healthy(&self) -> bool {
+        let now_ms = now_ms();
+        let workers = self.workers.lock().unwrap();
+        workers.len() == self.num_workers
+            && workers.values().all(|worker| worker.is_healthy(now_ms))
+    }
+
+    /// Checks whether all workers replayed the persisted data and initialized their connectors.
+    pub fn is_ready(&self) -> bool {
+        let workers = self.workers.lock().unwrap();
+        workers.len() == self.num_workers && workers.values().all(|worker| worker.is_ready())
+    }
+
+    pub fn record_error(&self, operator_id: i64, message: String, trace: String) {
+        *self.last_error.lock().unwrap() = Some(LastError {
+            operator_id,
+            message,
+            trace,
+            time_ms: now_ms(),
+        });
+    }
+
+    pub fn last_error(&self) -> Option<LastError> {
+        self.last_error.lock().unwrap().clone()
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright © 2024 Pathway
 
 use std::env;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::thread::{Builder, JoinHandle};
 use std::time::SystemTime;
@@ -10,16 +11,354 @@ use hyper::service::{make_service_fn, service_fn};
 use hyper::{header, Body, Method, Response, Server, StatusCode};
 use log::{error, info};
 use prometheus_client::encoding::text::encode;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::registry::Registry;
+use serde_json::{json, Value as JsonValue};
 use tokio::sync::oneshot::Sender;
 
+use super::dataflow::arrangement_stats::{ArrangementStats, OperatorArrangementStats};
+use super::error::Trace;
+use super::graph::OperatorStats;
+use super::health::HealthState;
 use super::Error;
 use super::Graph;
 use super::ProberStats;
+use crate::jemalloc::memory_stats;
 
+const DEFAULT_MONITORING_HTTP_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 const DEFAULT_MONITORING_HTTP_PORT: u16 = 20000;
 
+type Labels = Vec<(String, String)>;
+
+fn latency_to_gauge_value(latency: Option<u64>) -> i64 {
+    latency.map_or(-1, |latency| i64::try_from(latency).unwrap_or(i64::MAX))
+}
+
+fn count_to_gauge_value(count: usize) -> i64 {
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+fn label(name: &str, value: &impl ToString) -> Labels {
+    vec![(name.to_string(), value.to_string())]
+}
+
+fn operator_arrangement_labels(stats: &OperatorArrangementStats) -> Labels {
+    let operator_id = stats.operator_id.map_or_else(
+        || "internal".to_string(),
+        |operator_id| operator_id.to_string(),
+    );
+    let mut labels = label("operator_id", &operator_id);
+    if let Trace::Frame {
+        file_name,
+        line_number,
+        ..
+    } = &stats.trace
+    {
+        labels.push(("location".to_string(), format!("{file_name}:{line_number}")));
+    }
+    labels
+}
+
+fn register_gauges(
+    registry: &mut Registry,
+    name: &str,
+    help: &str,
+    values: impl IntoIterator<Item = (Labels, i64)>,
+) {
+    let family = Family::<Labels, Gauge>::default();
+    for (labels, value) in values {
+        family.get_or_create(&labels).set(value);
+    }
+    registry.register(name, help, family);
+}
+
+fn register_counters(
+    registry: &mut Registry,
+    name: &str,
+    help: &str,
+    values: impl IntoIterator<Item = (Labels, u64)>,
+) {
+    let family = Family::<Labels, Counter>::default();
+    for (labels, value) in values {
+        family.get_or_create(&labels).inc_by(value);
+    }
+    registry.register(name, help, family);
+}
+
+fn register_operator_metrics(registry: &mut Registry, stats: &ProberStats) {
+    register_gauges(
+        registry,
+        "operator_lag_ms",
+        "A lag of the operator behind the input in milliseconds (-1 when unknown)",
+        stats
+            .operators_stats
+            .iter()
+            .map(|(operator_id, operator_stats)| {
+                (
+                    label("operator_id", operator_id),
+                    latency_to_gauge_value(operator_stats.lag),
+                )
+            }),
+    );
+    register_gauges(
+        registry,
+        "operator_done",
+        "Whether the operator has finished processing the data",
+        stats
+            .operators_stats
+            .iter()
+            .map(|(operator_id, operator_stats)| {
+                (
+                    label("operator_id", operator_id),
+                    i64::from(operator_stats.done),
+                )
+            }),
+    );
+}
+
+fn register_input_connector_metrics(registry: &mut Registry, stats: &ProberStats) {
+    register_counters(
+        registry,
+        "input_connector_messages",
+        "A number of messages read by the input connector",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                connector_stats.num_messages_from_start as u64,
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_messages_in_last_minute",
+        "A number of messages read by the input connector in the last minute",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                count_to_gauge_value(connector_stats.num_messages_in_last_minute),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_messages_recently_committed",
+        "A number of messages in the last commit of the input connector",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                count_to_gauge_value(connector_stats.num_messages_recently_committed),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_finished",
+        "Whether the input connector has finished reading",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                i64::from(connector_stats.finished),
+            )
+        }),
+    );
+    register_counters(
+        registry,
+        "input_connector_errors",
+        "A number of errors produced by the input connector",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                connector_stats.num_errors_from_start as u64,
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_entries_in_window",
+        "A number of entries read by the input connector within its error budget window",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                count_to_gauge_value(connector_stats.num_entries_in_window),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_errors_in_window",
+        "A number of errors produced by the input connector within its error budget window",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                count_to_gauge_value(connector_stats.num_errors_in_window),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_error_budget_exceeded",
+        "Whether the input connector has recently exceeded its error budget",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (
+                label("connector", name),
+                i64::from(connector_stats.error_budget_exceeded),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "input_connector_paused",
+        "Whether the input connector is paused after exceeding its error budget",
+        stats.connector_stats.iter().map(|(name, connector_stats)| {
+            (label("connector", name), i64::from(connector_stats.paused))
+        }),
+    );
+}
+
+fn register_output_connector_metrics(registry: &mut Registry, stats: &ProberStats) {
+    register_counters(
+        registry,
+        "output_connector_messages",
+        "A number of messages written by the output connector",
+        stats.sink_stats.iter().map(|(name, sink_stats)| {
+            (
+                label("connector", name),
+                sink_stats.num_messages_from_start as u64,
+            )
+        }),
+    );
+    register_counters(
+        registry,
+        "output_connector_batches",
+        "A number of batches written by the output connector",
+        stats.sink_stats.iter().map(|(name, sink_stats)| {
+            (
+                label("connector", name),
+                sink_stats.num_batches_from_start as u64,
+            )
+        }),
+    );
+    register_counters(
+        registry,
+        "output_connector_writes_duration_ms",
+        "A total duration of writes of the output connector in milliseconds",
+        stats
+            .sink_stats
+            .iter()
+            .map(|(name, sink_stats)| (label("connector", name), sink_stats.writes_duration_ms)),
+    );
+    register_gauges(
+        registry,
+        "output_connector_committed_time",
+        "The last time committed by the output connector (-1 when none)",
+        stats.sink_stats.iter().map(|(name, sink_stats)| {
+            (
+                label("connector", name),
+                latency_to_gauge_value(sink_stats.last_committed_time),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "output_connector_finished",
+        "Whether the output connector has finished writing",
+        stats
+            .sink_stats
+            .iter()
+            .map(|(name, sink_stats)| (label("connector", name), i64::from(sink_stats.finished))),
+    );
+}
+
+fn register_memory_metrics(registry: &mut Registry, stats: &ProberStats) {
+    register_gauges(
+        registry,
+        "arrangement_records",
+        "A number of records held in the arrangements of the worker",
+        stats
+            .arrangement_stats
+            .iter()
+            .map(|(worker_id, arrangement_stats)| {
+                (
+                    label("worker", worker_id),
+                    count_to_gauge_value(arrangement_stats.num_records),
+                )
+            }),
+    );
+    register_gauges(
+        registry,
+        "arrangement_batches",
+        "A number of batches held in the arrangements of the worker",
+        stats
+            .arrangement_stats
+            .iter()
+            .map(|(worker_id, arrangement_stats)| {
+                (
+                    label("worker", worker_id),
+                    count_to_gauge_value(arrangement_stats.num_batches),
+                )
+            }),
+    );
+    register_gauges(
+        registry,
+        "arrangement_bytes",
+        "An approximate size of the records in the arrangements of the worker in bytes, \
+        not counting the heap data of the values",
+        stats
+            .arrangement_stats
+            .iter()
+            .map(|(worker_id, arrangement_stats)| {
+                (
+                    label("worker", worker_id),
+                    count_to_gauge_value(arrangement_stats.approximate_bytes),
+                )
+            }),
+    );
+    register_gauges(
+        registry,
+        "operator_arrangement_records",
+        "A number of records held in the arrangements created by the operator",
+        stats.operator_arrangement_stats.iter().map(|entry| {
+            (
+                operator_arrangement_labels(entry),
+                count_to_gauge_value(entry.stats.num_records),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "operator_arrangement_batches",
+        "A number of batches held in the arrangements created by the operator",
+        stats.operator_arrangement_stats.iter().map(|entry| {
+            (
+                operator_arrangement_labels(entry),
+                count_to_gauge_value(entry.stats.num_batches),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "operator_arrangement_bytes",
+        "An approximate size of the records in the arrangements created by the operator in bytes, \
+        not counting the heap data of the values",
+        stats.operator_arrangement_stats.iter().map(|entry| {
+            (
+                operator_arrangement_labels(entry),
+                count_to_gauge_value(entry.stats.approximate_bytes),
+            )
+        }),
+    );
+    register_gauges(
+        registry,
+        "memory_bytes",
+        "The memory statistics of the allocator in bytes",
+        memory_stats()
+            .into_iter()
+            .map(|(kind, value)| (label("kind", &kind), count_to_gauge_value(value))),
+    );
+}
+
 /// Retrieves metrics from prober stats in the `OpenMetrics` format
 /// See <https://github.com/OpenObservability/OpenMetrics>
 fn metrics_from_stats(stats: &Arc<ArcSwapOption<ProberStats>>) -> String {
@@ -30,17 +369,7 @@ fn metrics_from_stats(stats: &Arc<ArcSwapOption<ProberStats>>) -> String {
         let mut registry = <Registry>::default();
 
         let input_latency_ms: Gauge = Gauge::default();
-        input_latency_ms.set(
-            if let Some(latency) = stats_owned.input_stats.latency(now) {
-                if let Ok(latency_converted) = i64::try_from(latency) {
-                    latency_converted
-                } else {
-                    i64::MAX
-                }
-            } else {
-                -1
-            },
-        );
+        input_latency_ms.set(latency_to_gauge_value(stats_owned.input_stats.latency(now)));
         registry.register(
             "input_latency_ms",
             "A latency of input in milliseconds (-1 when finished)",
@@ -48,44 +377,200 @@ fn metrics_from_stats(stats: &Arc<ArcSwapOption<ProberStats>>) -> String {
         );
 
         let output_latency_ms: Gauge = Gauge::default();
-        output_latency_ms.set(
-            if let Some(latency) = stats_owned.output_stats.latency(now) {
-                if let Ok(latency_converted) = i64::try_from(latency) {
-                    latency_converted
-                } else {
-                    i64::MAX
-                }
-            } else {
-                -1
-            },
-        );
+        output_latency_ms.set(latency_to_gauge_value(
+            stats_owned.output_stats.latency(now),
+        ));
         registry.register(
             "output_latency_ms",
             "A latency of output in milliseconds (-1 when finished)",
             output_latency_ms,
         );
 
+        register_operator_metrics(&mut registry, &stats_owned);
+        register_input_connector_metrics(&mut registry, &stats_owned);
+        register_output_connector_metrics(&mut registry, &stats_owned);
+        register_memory_metrics(&mut registry, &stats_owned);
+
         encode(&mut metrics_text, &registry).unwrap();
     }
     metrics_text
 }
 
+fn operator_stats_to_json(stats: &OperatorStats) -> JsonValue {
+    json!({
+        "time": stats.time.map(|time| time.0),
+        "lag": stats.lag,
+        "done": stats.done,
+    })
+}
+
+fn arrangement_stats_to_json(stats: &ArrangementStats) -> JsonValue {
+    json!({
+        "num_batches": stats.num_batches,
+        "num_records": stats.num_records,
+        "approximate_bytes": stats.approximate_bytes,
+    })
+}
+
+fn trace_to_json(trace: &Trace) -> JsonValue {
+    match trace {
+        Trace::Frame {
+            line,
+            file_name,
+            line_number,
+            function,
+        } => json!({
+            "line": line,
+            "file_name": file_name,
+            "line_number": line_number,
+            "function": function,
+        }),
+        Trace::Empty => JsonValue::Null,
+    }
+}
+
+fn prober_stats_to_json(stats: &ProberStats) -> JsonValue {
+    json!({
+        "input": operator_stats_to_json(&stats.input_stats),
+        "output": operator_stats_to_json(&stats.output_stats),
+        "operators": stats
+            .operators_stats
+            .iter()
+            .map(|(operator_id, operator_stats)| {
+                (operator_id.to_string(), operator_stats_to_json(operator_stats))
+            })
+            .collect::<serde_json::Map<_, _>>(),
+        "connectors": stats
+            .connector_stats
+            .iter()
+            .map(|(name, connector_stats)| {
+                json!({
+                    "name": name,
+                    "num_messages_from_start": connector_stats.num_messages_from_start,
+                    "num_messages_in_last_minute": connector_stats.num_messages_in_last_minute,
+                    "num_messages_recently_committed":
+                        connector_stats.num_messages_recently_committed,
+                    "initialized": connector_stats.initialized,
+                    "finished": connector_stats.finished,
+                    "num_errors_from_start": connector_stats.num_errors_from_start,
+                    "num_entries_in_window": connector_stats.num_entries_in_window,
+                    "num_errors_in_window": connector_stats.num_errors_in_window,
+                    "error_budget_exceeded": connector_stats.error_budget_exceeded,
+                    "paused": connector_stats.paused,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "sinks": stats
+            .sink_stats
+            .iter()
+            .map(|(name, sink_stats)| {
+                json!({
+                    "name": name,
+                    "num_messages_from_start": sink_stats.num_messages_from_start,
+                    "num_batches_from_start": sink_stats.num_batches_from_start,
+                    "writes_duration_ms": sink_stats.writes_duration_ms,
+                    "last_committed_time": sink_stats.last_committed_time,
+                    "finished": sink_stats.finished,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "arrangements": {
+            "per_worker": stats
+                .arrangement_stats
+                .iter()
+                .map(|(worker_id, arrangement_stats)| {
+                    (worker_id.to_string(), arrangement_stats_to_json(arrangement_stats))
+                })
+                .collect::<serde_json::Map<_, _>>(),
+            "per_operator": stats
+                .operator_arrangement_stats
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "operator_id": entry.operator_id,
+                        "trace": trace_to_json(&entry.trace),
+                        "num_arrangements": entry.num_arrangements,
+                        "stats": arrangement_stats_to_json(&entry.stats),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// Describes the state of the computation in JSON: the prober stats,
+/// the health of the workers and the last error sent to the error log.
+fn status_json(stats: &Arc<ArcSwapOption<ProberStats>>, health: &HealthState) -> String {
+    let stats_owned = stats.load().clone();
+    let last_error = health.last_error().map(|last_error| {
+        json!({
+            "operator_id": last_error.operator_id,
+            "message": last_error.message,
+            "trace": last_error.trace,
+            "time_ms": last_error.time_ms,
+        })
+    });
+    json!({
+        "healthy": health.is_healthy(),
+        "ready": health.is_ready(),
+        "stats": stats_owned.as_deref().map(prober_stats_to_json),
+        "last_error": last_error,
+    })
+    .to_string()
+}
+
+fn probe_response(response: &mut Response<Body>, passed: bool) {
+    if passed {
+        *response.body_mut() = Body::from("ok");
+    } else {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        *response.body_mut() = Body::from("unavailable");
+    }
+}
+
+/// Returns the address the http server of the process listens on: the configured host
+/// (`PATHWAY_MONITORING_HTTP_HOST` or localhost by default) and the configured port
+/// (`PATHWAY_MONITORING_HTTP_PORT` by default) plus `process_id`.
+pub fn monitoring_http_address(
+    monitoring_http_host: Option<&str>,
+    monitoring_http_port: Option<u16>,
+    process_id: usize,
+) -> Result<SocketAddr, Error> {
+    let host = match monitoring_http_host
+        .map(str::to_string)
+        .or_else(|| env::var("PATHWAY_MONITORING_HTTP_HOST").ok())
+    {
+        Some(host) => host
+            .parse::<IpAddr>()
+            .map_err(|_| Error::InvalidMonitoringHttpHost(host))?,
+        None => DEFAULT_MONITORING_HTTP_HOST,
+    };
+    let base_port: u16 = monitoring_http_port.unwrap_or_else(|| {
+        env::var("PATHWAY_MONITORING_HTTP_PORT")
+            .ok()
+            .unwrap_or_default()
+            .parse::<u16>()
+            .unwrap_or(DEFAULT_MONITORING_HTTP_PORT)
+    });
+    let port = u16::try_from(process_id)
+        .ok()
+        .and_then(|process_id| base_port.checked_add(process_id))
+        .ok_or(Error::MonitoringHttpPortOutOfRange {
+            base_port,
+            process_id,
+        })?;
+    Ok(SocketAddr::new(host, port))
+}
+
 /// Starts a lightweight http server allowing monitoring.
-/// Available at: http://localhost:PORT/status
-/// where PORT is `PATHWAY_MONITORING_HTTP_PORT + process_id`
+/// Available at: `http://ADDRESS/status`, where ADDRESS is given by [`monitoring_http_address`].
 /// It uses tokio and hyper. The status is passed using arcswap to avoid mutexes.
 pub fn start_http_server_thread(
-    process_id: u16,
-    // monitoring_status: Arc<ArcSwap<String>>,
+    addr: SocketAddr,
     stats: Arc<ArcSwapOption<ProberStats>>,
+    health: Arc<HealthState>,
     http_terminate_receiver: tokio::sync::oneshot::Receiver<()>,
 ) -> JoinHandle<()> {
-    let monitoring_http_port: u16 = env::var("PATHWAY_MONITORING_HTTP_PORT")
-        .ok()
-        .unwrap_or_default()
-        .parse::<u16>()
-        .unwrap_or(DEFAULT_MONITORING_HTTP_PORT);
-
     Builder::new()
         .name("pathway:http_monitoring".to_string())
         .spawn(move || {
@@ -95,21 +580,38 @@ pub fn start_http_server_thread(
                 .build()
                 .unwrap()
                 .block_on(async {
-                    let addr = ([127, 0, 0, 1], monitoring_http_port + process_id).into();
                     let make_service = make_service_fn(move |_| {
                         let stats = stats.clone();
+                        let health = health.clone();
                         async move {
                             Ok::<_, Error>(service_fn(move |req| {
                                 let stats = stats.clone();
+                                let health = health.clone();
 
                                 async move {
                                     let mut response = Response::new(Body::empty());
                                     let stats = stats.clone();
 
-                                    let metrics_text = metrics_from_stats(&stats);
                                     match (req.method(), req.uri().path()) {
+                                        (&Method::GET, "/healthz") => {
+                                            probe_response(&mut response, health.is_healthy());
+                                        }
+                                        (&Method::GET, "/readyz") => {
+                                            probe_response(&mut response, health.is_ready());
+                                        }
+                                        (&Method::GET, "/status.json") => {
+                                            *response.body_mut() =
+                                                Body::from(status_json(&stats, &health));
+                                            response.headers_mut().insert(
+                                                header::CONTENT_TYPE,
+                                                header::HeaderValue::from_static(
+                                                    "application/json",
+                                                ),
+                                            );
+                                        }
                                         (&Method::GET, "/status") => {
-                                            *response.body_mut() = Body::from(metrics_text);
+                                            *response.body_mut() =
+                                                Body::from(metrics_from_stats(&stats));
                                             response.headers_mut().insert(
                                                 header::CONTENT_TYPE,
                                                 header::HeaderValue::from_static(
@@ -117,8 +619,9 @@ pub fn start_http_server_thread(
                                                 ),
                                             );
                                         }
-                                        (&Method::GET, "/metrics") => {                              
-                                            *response.body_mut() = Body::from(metrics_text);
+                                        (&Method::GET, "/metrics") => {
+                                            *response.body_mut() =
+                                                Body::from(metrics_from_stats(&stats));
                                             response.headers_mut().insert(
                                                 header::CONTENT_TYPE,
                                                 header::HeaderValue::from_static(
@@ -143,9 +646,7 @@ pub fn start_http_server_thread(
                     let graceful = server.with_graceful_shutdown(shutdown_signal);
                     info!("Metrics available at http://{addr}");
                     if let Err(e) = graceful.await {
-                        error!(
-                            "http monitoring server error for process {process_id}: {e}"
-                        );
+                        error!("http monitoring server error at {addr}: {e}");
                     }
                 });
         })
@@ -158,16 +659,16 @@ pub struct Runner {
 }
 
 impl Runner {
-    fn run(stats: &Arc<ArcSwapOption<ProberStats>>, process_id: usize) -> Runner {
+    fn run(
+        stats: &Arc<ArcSwapOption<ProberStats>>,
+        health: Arc<HealthState>,
+        addr: SocketAddr,
+    ) -> Runner {
         let (http_terminate_transmitter, http_terminate_receiver) =
             tokio::sync::oneshot::channel::<()>();
         let http_server_thread_handle = {
             let stats = Arc::clone(stats);
-            start_http_server_thread(
-                u16::try_from(process_id).unwrap(),
-                stats,
-                http_terminate_receiver,
-            )
+            start_http_server_thread(addr, stats, health, http_terminate_receiver)
         };
         Runner {
             http_server_thread_handle: Some(http_server_thread_handle),
@@ -192,18 +693,19 @@ impl Drop for Runner {
 }
 
 pub fn maybe_run_http_server_thread(
-    with_http_server: bool,
+    http_server_address: Option<SocketAddr>,
     graph: &dyn Graph,
-    process_id: usize,
+    health: Arc<HealthState>,
 ) -> Option<Runner> {
-    if with_http_server && graph.worker_index() == 0 {
+    let addr = http_server_address?;
+    if graph.worker_index() == 0 {
         let stats_shared = Arc::new(ArcSwapOption::from(None));
-        let http_server_runner = Runner::run(&stats_shared, process_id);
+        let http_server_runner = Runner::run(&stats_shared, health, addr);
 
         graph
             .attach_prober(
                 Box::new(move |prober_stats| stats_shared.store(Some(Arc::new(prober_stats)))),
-                false,
+                true,
                 false,
             )
             .expect("Failed to start http monitoring server");
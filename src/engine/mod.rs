@@ -15,6 +15,9 @@ pub use self::value::{Key, KeyImpl, ShardPolicy, Type, Value};
 pub mod reduce;
 pub use reduce::Reducer;
 
+pub mod window;
+pub use window::{Window, WindowBehavior};
+
 pub mod graph;
 pub use graph::{
     BatchWrapper, ColumnHandle, ColumnPath, ColumnProperties, ComplexColumn, Computer,
@@ -24,6 +27,8 @@ pub use graph::{
     TableProperties, UniverseHandle,
 };
 
+pub mod health;
+
 pub mod http_server;
 pub use http_server::maybe_run_http_server_thread;
 
@@ -76,6 +76,15 @@ impl PyObjectWrapper {
         })?;
         Ok(Self::new(object, serializer))
     }
+
+    /// A value with the serialized layout of a wrapper, which can be created
+    /// without the Python interpreter.
+    pub fn layout_sample() -> impl Serialize {
+        PyObjectWrapperIntermediate {
+            object: b"object".to_vec(),
+            serializer: b"serializer".to_vec(),
+        }
+    }
 }
 
 impl Display for PyObjectWrapper {
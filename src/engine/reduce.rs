@@ -7,12 +7,17 @@ use differential_dataflow::{
     difference::{Multiply, Semigroup},
     ExchangeData,
 };
+use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
 use std::num::NonZeroUsize;
 use std::{any::type_name, iter::repeat};
 use std::{cmp::Reverse, sync::Arc};
+use xxhash_rust::xxh3::{xxh3_64_with_seed, Xxh3 as Hasher};
 
+use super::value::HashInto;
 use super::{error::DynResult, DataError, Key, Value};
 
 pub type StatefulCombineFn =
@@ -35,10 +40,18 @@ pub enum Reducer {
     Stateful { combine_fn: StatefulCombineFn },
     Earliest,
     Latest,
+    Mean,
+    Variance { sample: bool },
+    StdDev { sample: bool },
+    CountDistinct,
+    CountDistinctApprox { precision: u8 },
+    Quantile { quantile: f64 },
+    Quantiles { quantiles: Vec<f64> },
+    TopK { k: usize, order: TopKOrdering },
 }
 
 pub trait SemigroupReducerImpl: 'static {
-    type State: ExchangeData + Semigroup + Multiply<isize>;
+    type State: ExchangeData + Semigroup + Multiply<isize, Output = Self::State>;
 
     fn init(&self, key: &Key, value: &Value) -> DynResult<Self::State>;
 
@@ -173,6 +186,922 @@ impl SemigroupReducerImpl for IntSumReducer {
     }
 }
 
+// Number of 64-bit limbs of an `ExactSum`. Every finite `f64` is a multiple of 2^-1074
+// below 2^1024, so a sum of up to 2^63 of them fits in 2162 bits, together with the sign.
+const EXACT_SUM_LIMBS: usize = 34;
+// Exponent of the least significant bit of an `ExactSum`.
+const EXACT_SUM_MIN_EXPONENT: i32 = -1074;
+
+// A sum of floats kept without rounding, as a fixed-point two's complement integer.
+// Adding such sums is associative, so they are equal regardless of the order
+// of additions, and a retraction cancels out the value it retracts exactly.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct ExactSum {
+    limbs: Vec<u64>,
+}
+
+impl ExactSum {
+    fn zero() -> Self {
+        Self {
+            limbs: vec![0; EXACT_SUM_LIMBS],
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_f64(value: f64) -> Self {
+        debug_assert!(value.is_finite());
+        let bits = value.to_bits();
+        let exponent_bits = (bits >> 52) & 0x7ff;
+        let fraction = bits & ((1 << 52) - 1);
+        // the value is mantissa * 2^(EXACT_SUM_MIN_EXPONENT + shift)
+        let (mantissa, shift) = if exponent_bits == 0 {
+            (fraction, 0)
+        } else {
+            (fraction | (1 << 52), exponent_bits - 1)
+        };
+        let shifted = u128::from(mantissa) << (shift % 64);
+        let index = usize::try_from(shift / 64).unwrap();
+        let mut sum = Self::zero();
+        sum.limbs[index] = shifted as u64;
+        sum.limbs[index + 1] = (shifted >> 64) as u64;
+        if value.is_sign_negative() {
+            sum.negate();
+        }
+        sum
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|limb| *limb == 0)
+    }
+
+    fn is_negative(&self) -> bool {
+        self.limbs[EXACT_SUM_LIMBS - 1] >> 63 == 1
+    }
+
+    fn negate(&mut self) {
+        let mut carry = true;
+        for limb in &mut self.limbs {
+            let (negated, overflow) = (!*limb).overflowing_add(u64::from(carry));
+            *limb = negated;
+            carry = overflow;
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        let mut carry = false;
+        for (limb, other_limb) in self.limbs.iter_mut().zip(&other.limbs) {
+            let (sum, first_overflow) = limb.overflowing_add(*other_limb);
+            let (sum, second_overflow) = sum.overflowing_add(u64::from(carry));
+            *limb = sum;
+            carry = first_overflow || second_overflow;
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn multiply(&mut self, factor: isize) {
+        let magnitude = u128::from(u64::try_from(factor.unsigned_abs()).unwrap());
+        let mut carry = 0;
+        for limb in &mut self.limbs {
+            let product = u128::from(*limb) * magnitude + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        if factor < 0 {
+            self.negate();
+        }
+    }
+
+    // The three most significant nonzero limbs hold more bits than a double-double
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap
+    )]
+    fn to_double_double(&self) -> DoubleDouble {
+        let mut magnitude = self.clone();
+        if self.is_negative() {
+            magnitude.negate();
+        }
+        let Some(top) = magnitude.limbs.iter().rposition(|limb| *limb != 0) else {
+            return DoubleDouble::ZERO;
+        };
+        let mut result = DoubleDouble::ZERO;
+        for index in top.saturating_sub(2)..=top {
+            let limb = magnitude.limbs[index];
+            let exponent = 64 * index as i32 + EXACT_SUM_MIN_EXPONENT;
+            result = result.add(DoubleDouble::from(scale(
+                (limb >> 32) as f64,
+                exponent + 32,
+            )));
+            result = result.add(DoubleDouble::from(scale(
+                (limb & 0xffff_ffff) as f64,
+                exponent,
+            )));
+        }
+        if self.is_negative() {
+            result.neg()
+        } else {
+            result
+        }
+    }
+}
+
+// Multiplies by 2^exponent in two steps, as 2^exponent alone may not be representable.
+fn scale(value: f64, exponent: i32) -> f64 {
+    let half = exponent / 2;
+    value * 2.0_f64.powi(half) * 2.0_f64.powi(exponent - half)
+}
+
+fn two_sum(lhs: f64, rhs: f64) -> (f64, f64) {
+    let sum = lhs + rhs;
+    let rhs_part = sum - lhs;
+    let error = (lhs - (sum - rhs_part)) + (rhs - rhs_part);
+    (sum, error)
+}
+
+// An unevaluated sum of two floats, with about twice the precision of `f64`,
+// used to compute the statistics from the exact sums without cancellation errors.
+#[derive(Debug, Clone, Copy)]
+struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+}
+
+impl DoubleDouble {
+    const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+
+    fn normalized(hi: f64, lo: f64) -> Self {
+        let (hi, lo) = two_sum(hi, lo);
+        Self { hi, lo }
+    }
+
+    fn add(self, other: Self) -> Self {
+        let (sum, error) = two_sum(self.hi, other.hi);
+        Self::normalized(sum, error + self.lo + other.lo)
+    }
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+
+    fn mul(self, other: Self) -> Self {
+        let product = self.hi * other.hi;
+        let error = self.hi.mul_add(other.hi, -product) + self.hi * other.lo + self.lo * other.hi;
+        Self::normalized(product, error)
+    }
+
+    fn div(self, divisor: f64) -> Self {
+        let quotient = self.hi / divisor;
+        let product = quotient * divisor;
+        let product_error = quotient.mul_add(divisor, -product);
+        let (difference, difference_error) = two_sum(self.hi, -product);
+        let remainder = difference + (difference_error - product_error + self.lo);
+        Self::normalized(quotient, remainder / divisor)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+}
+
+// Count, sum and sum of squares of a group. The sums are exact, so the states
+// add up to the same value in any order and a retraction is just an addition
+// of a negated single-element state, which cancels it out completely.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MomentsState {
+    count: isize,
+    sum: ExactSum,
+    sum_of_squares: ExactSum,
+    error_count: isize,
+}
+
+impl Semigroup for MomentsState {
+    fn is_zero(&self) -> bool {
+        self.count.is_zero()
+            && self.sum.is_zero()
+            && self.sum_of_squares.is_zero()
+            && self.error_count.is_zero()
+    }
+
+    fn plus_equals(&mut self, rhs: &Self) {
+        self.count.plus_equals(&rhs.count);
+        self.sum.add(&rhs.sum);
+        self.sum_of_squares.add(&rhs.sum_of_squares);
+        self.error_count.plus_equals(&rhs.error_count);
+    }
+}
+
+impl Multiply<isize> for MomentsState {
+    type Output = Self;
+    fn multiply(mut self, rhs: &isize) -> Self::Output {
+        self.count *= rhs;
+        self.sum.multiply(*rhs);
+        self.sum_of_squares.multiply(*rhs);
+        self.error_count *= rhs;
+        self
+    }
+}
+
+impl MomentsState {
+    // None if the value or its square is not finite
+    pub fn single(val: f64) -> Option<Self> {
+        let square = val * val;
+        if !square.is_finite() {
+            return None;
+        }
+        // the square is split into two floats, so that it is exact
+        let mut sum_of_squares = ExactSum::from_f64(square);
+        sum_of_squares.add(&ExactSum::from_f64(val.mul_add(val, -square)));
+        Some(Self {
+            count: 1,
+            sum: ExactSum::from_f64(val),
+            sum_of_squares,
+            error_count: 0,
+        })
+    }
+    pub fn error() -> Self {
+        Self {
+            count: 0,
+            sum: ExactSum::zero(),
+            sum_of_squares: ExactSum::zero(),
+            error_count: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MomentsStatistic {
+    Mean,
+    Variance { sample: bool },
+    StdDev { sample: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MomentsReducer {
+    statistic: MomentsStatistic,
+}
+
+impl MomentsReducer {
+    pub fn new(statistic: MomentsStatistic) -> Self {
+        Self { statistic }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn mean(state: &MomentsState) -> Option<f64> {
+        (state.count > 0).then(|| {
+            state
+                .sum
+                .to_double_double()
+                .div(state.count as f64)
+                .to_f64()
+        })
+    }
+
+    // None if the variance is undefined, i.e. for a sample variance of a single element
+    #[allow(clippy::cast_precision_loss)]
+    fn variance(state: &MomentsState, sample: bool) -> Option<f64> {
+        let degrees_of_freedom = if sample { state.count - 1 } else { state.count };
+        if degrees_of_freedom <= 0 {
+            return None;
+        }
+        // sum of squared differences from the mean, sum_of_squares - sum^2 / count
+        let sum = state.sum.to_double_double();
+        let squared_differences = state
+            .sum_of_squares
+            .to_double_double()
+            .add(sum.mul(sum).div(state.count as f64).neg());
+        // the rounding of the sums converted to floats can make it slightly negative
+        Some((squared_differences.to_f64() / degrees_of_freedom as f64).max(0.0))
+    }
+}
+
+impl SemigroupReducerImpl for MomentsReducer {
+    type State = MomentsState;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn init(&self, key: &Key, value: &Value) -> DynResult<Self::State> {
+        let state = match value {
+            Value::Int(i) => MomentsState::single(*i as f64),
+            Value::Float(f) => MomentsState::single(f.0),
+            _ => None,
+        };
+        state.ok_or_else(|| {
+            DataError::ReducerInitializationError {
+                reducer_type: type_name::<Self>().to_string(),
+                value: value.clone(),
+                source_key: *key,
+            }
+            .into()
+        })
+    }
+
+    fn init_error(&self) -> Self::State {
+        MomentsState::error()
+    }
+
+    fn finish(&self, state: Self::State) -> Value {
+        if state.error_count != 0 {
+            return Value::Error;
+        }
+        let result = match self.statistic {
+            MomentsStatistic::Mean => Self::mean(&state),
+            MomentsStatistic::Variance { sample } => Self::variance(&state, sample),
+            MomentsStatistic::StdDev { sample } => Self::variance(&state, sample).map(f64::sqrt),
+        };
+        result.map_or(Value::None, Value::from)
+    }
+}
+
+// Multiset of elements with their multiplicities. Elements whose multiplicity
+// drops to zero are removed, so retractions shrink the state back.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MultisetState<T: Ord> {
+    elements: BTreeMap<T, isize>,
+    error_count: isize,
+}
+
+impl<T: Ord + Clone + Debug + 'static> Semigroup for MultisetState<T> {
+    fn is_zero(&self) -> bool {
+        self.elements.is_empty() && self.error_count.is_zero()
+    }
+
+    // elements are cloned only when they are new to the state
+    fn plus_equals(&mut self, rhs: &Self) {
+        for (element, count) in &rhs.elements {
+            if let Some(current) = self.elements.get_mut(element) {
+                *current += count;
+                if *current == 0 {
+                    self.elements.remove(element);
+                }
+            } else if *count != 0 {
+                self.elements.insert(element.clone(), *count);
+            }
+        }
+        self.error_count.plus_equals(&rhs.error_count);
+    }
+}
+
+impl<T: Ord> Multiply<isize> for MultisetState<T> {
+    type Output = Self;
+    fn multiply(self, rhs: &isize) -> Self::Output {
+        let elements = if *rhs == 0 {
+            BTreeMap::new()
+        } else {
+            self.elements
+                .into_iter()
+                .map(|(element, count)| (element, count * rhs))
+                .collect()
+        };
+        let error_count = self.error_count * rhs;
+        Self {
+            elements,
+            error_count,
+        }
+    }
+}
+
+impl<T: Ord> MultisetState<T> {
+    pub fn single(element: T) -> Self {
+        Self {
+            elements: BTreeMap::from([(element, 1)]),
+            error_count: 0,
+        }
+    }
+    pub fn error() -> Self {
+        Self {
+            elements: BTreeMap::new(),
+            error_count: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CountDistinctReducer;
+
+impl SemigroupReducerImpl for CountDistinctReducer {
+    type State = MultisetState<Value>;
+
+    fn init(&self, _key: &Key, value: &Value) -> DynResult<Self::State> {
+        Ok(MultisetState::single(value.clone()))
+    }
+
+    fn init_error(&self) -> Self::State {
+        MultisetState::error()
+    }
+
+    fn finish(&self, state: Self::State) -> Value {
+        if state.error_count != 0 {
+            Value::Error
+        } else {
+            Value::Int(state.elements.len().try_into().unwrap())
+        }
+    }
+}
+
+// HyperLogLog in which every register keeps the multiplicities of all ranks observed in it
+// instead of only the maximal one (a counting HLL). This makes it possible to retract values:
+// the register value is the largest rank with a nonzero multiplicity. The state is keyed by
+// (register, rank), so its size is bounded by the number of registers times the number of ranks,
+// independently of the number of distinct values.
+#[derive(Debug, Clone, Copy)]
+pub struct CountDistinctApproxReducer {
+    precision: u8,
+}
+
+impl CountDistinctApproxReducer {
+    pub const MIN_PRECISION: u8 = 4;
+    pub const MAX_PRECISION: u8 = 18;
+
+    pub fn new(precision: u8) -> Result<Self, DataError> {
+        if !(Self::MIN_PRECISION..=Self::MAX_PRECISION).contains(&precision) {
+            return Err(DataError::ValueError(format!(
+                "HyperLogLog precision has to be between {} and {}, got {precision}",
+                Self::MIN_PRECISION,
+                Self::MAX_PRECISION
+            )));
+        }
+        Ok(Self { precision })
+    }
+
+    fn register_and_rank(self, value: &Value) -> (u32, u8) {
+        let mut hasher = Hasher::default();
+        value.hash_into(&mut hasher);
+        let hash = hasher.digest();
+        let register = u32::try_from(hash >> (64 - u32::from(self.precision))).unwrap();
+        let remaining_bits = hash << self.precision;
+        let max_rank = 64 - u32::from(self.precision) + 1;
+        let rank = (remaining_bits.leading_zeros() + 1).min(max_rank);
+        (register, u8::try_from(rank).unwrap())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::naive_bytecount)]
+    fn estimate(self, registers: &BTreeMap<(u32, u8), isize>) -> f64 {
+        let registers_count = 1_usize << self.precision;
+        let mut max_ranks = vec![0_u8; registers_count];
+        for ((register, rank), count) in registers {
+            if *count > 0 {
+                let max_rank = &mut max_ranks[usize::try_from(*register).unwrap()];
+                *max_rank = (*max_rank).max(*rank);
+            }
+        }
+        let m = registers_count as f64;
+        let alpha = match registers_count {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let harmonic_sum: f64 = max_ranks
+            .iter()
+            .map(|rank| 2.0_f64.powi(-i32::from(*rank)))
+            .sum();
+        let estimate = alpha * m * m / harmonic_sum;
+        let empty_registers = max_ranks.iter().filter(|rank| **rank == 0).count();
+        if estimate <= 2.5 * m && empty_registers > 0 {
+            // linear counting is more accurate for small cardinalities
+            m * (m / empty_registers as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+impl SemigroupReducerImpl for CountDistinctApproxReducer {
+    type State = MultisetState<(u32, u8)>;
+
+    fn init(&self, _key: &Key, value: &Value) -> DynResult<Self::State> {
+        Ok(MultisetState::single(self.register_and_rank(value)))
+    }
+
+    fn init_error(&self) -> Self::State {
+        MultisetState::error()
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn finish(&self, state: Self::State) -> Value {
+        if state.error_count != 0 {
+            Value::Error
+        } else {
+            Value::Int(self.estimate(&state.elements).round() as i64)
+        }
+    }
+}
+
+// Maximal relative error of a value returned by the quantile reducers.
+const QUANTILES_SKETCH_RELATIVE_ACCURACY: f64 = 0.01;
+
+// Bucket of a DDSketch: values are assigned to buckets with exponentially growing bounds,
+// so that every value in a bucket is within the relative accuracy from every other one.
+// Ordered by the values they contain.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SketchBucket {
+    Negative(Reverse<i32>),
+    Zero,
+    Positive(i32),
+}
+
+impl SketchBucket {
+    fn gamma() -> f64 {
+        (1.0 + QUANTILES_SKETCH_RELATIVE_ACCURACY) / (1.0 - QUANTILES_SKETCH_RELATIVE_ACCURACY)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn for_value(value: f64) -> Self {
+        let index = |magnitude: f64| (magnitude.ln() / Self::gamma().ln()).ceil() as i32;
+        if value > 0.0 {
+            Self::Positive(index(value))
+        } else if value < 0.0 {
+            Self::Negative(Reverse(index(-value)))
+        } else {
+            Self::Zero
+        }
+    }
+}
+
+// Values in a bucket, summarized by their count and the sum of the bit representations
+// of their magnitudes. Bit representations of non-negative floats are ordered like the floats,
+// so their mean decodes to a value between the smallest and the largest value in the bucket,
+// and to the exact value if all values in the bucket are equal. Unlike float sums,
+// integer sums don't depend on the order of additions and retractions cancel out exactly.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SketchBucketSummary {
+    count: isize,
+    bits_sum: i128,
+}
+
+impl SketchBucketSummary {
+    fn single(value: f64) -> Self {
+        Self {
+            count: 1,
+            bits_sum: i128::from(value.abs().to_bits()),
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.count == 0 && self.bits_sum == 0
+    }
+
+    fn value(self, bucket: SketchBucket) -> f64 {
+        let mean_bits = self.bits_sum / self.count as i128;
+        let magnitude = f64::from_bits(u64::try_from(mean_bits).unwrap_or_default());
+        match bucket {
+            SketchBucket::Positive(_) => magnitude,
+            SketchBucket::Negative(_) => -magnitude,
+            SketchBucket::Zero => 0.0,
+        }
+    }
+}
+
+// The exact values are kept in a table with this many cells in each subtable. Every value
+// is hashed to one cell in each subtable, and the table can be decoded while it has more than
+// about 1.3 times as many cells as distinct values, so groups with up to about 1700 distinct
+// values get exact quantiles.
+const EXACT_QUANTILES_SUBTABLE_SIZE: u64 = 768;
+const EXACT_QUANTILES_HASHES: u64 = 3;
+
+fn exact_values_cell_indexes(bits: u64) -> impl Iterator<Item = u32> {
+    (0..EXACT_QUANTILES_HASHES).map(move |seed| {
+        let hash = xxh3_64_with_seed(&bits.to_le_bytes(), seed);
+        u32::try_from(seed * EXACT_QUANTILES_SUBTABLE_SIZE + hash % EXACT_QUANTILES_SUBTABLE_SIZE)
+            .unwrap()
+    })
+}
+
+// Cell of an invertible Bloom lookup table: the number of values hashed to it, the sum
+// of their bit representations and the sum of their check hashes. The sums wrap around,
+// so that they stay additive and retractions cancel out exactly.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExactValuesCell {
+    count: isize,
+    bits_sum: u128,
+    check_sum: u128,
+}
+
+impl ExactValuesCell {
+    #[allow(clippy::cast_sign_loss)]
+    fn new(bits: u64, count: isize) -> Self {
+        let factor = count as i128 as u128;
+        let check_hash = xxh3_64_with_seed(&bits.to_le_bytes(), EXACT_QUANTILES_HASHES);
+        Self {
+            count,
+            bits_sum: u128::from(bits).wrapping_mul(factor),
+            check_sum: u128::from(check_hash).wrapping_mul(factor),
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.count == 0 && self.bits_sum == 0 && self.check_sum == 0
+    }
+
+    fn add(&mut self, other: Self) {
+        self.count += other.count;
+        self.bits_sum = self.bits_sum.wrapping_add(other.bits_sum);
+        self.check_sum = self.check_sum.wrapping_add(other.check_sum);
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn multiply(self, factor: isize) -> Self {
+        let wrapping_factor = factor as i128 as u128;
+        Self {
+            count: self.count * factor,
+            bits_sum: self.bits_sum.wrapping_mul(wrapping_factor),
+            check_sum: self.check_sum.wrapping_mul(wrapping_factor),
+        }
+    }
+
+    // The bit representation and the count of the only value in the cell, if there is one.
+    #[allow(clippy::cast_possible_wrap)]
+    fn single_value(self) -> Option<(u64, isize)> {
+        if self.count == 0 {
+            return None;
+        }
+        let bits = u64::try_from(self.bits_sum as i128 / self.count as i128).ok()?;
+        (Self::new(bits, self.count) == self).then_some((bits, self.count))
+    }
+}
+
+// Exact values of a group in an invertible Bloom lookup table. It is a sum of the tables
+// of single values, so it is the same regardless of the order of updates, and its size
+// is bounded by the number of cells. The values are recovered by repeatedly removing
+// the values that are alone in some cell, which fails only if there are too many of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExactValues {
+    cells: BTreeMap<u32, ExactValuesCell>,
+}
+
+impl ExactValues {
+    fn single(value: f64) -> Self {
+        let bits = value.to_bits();
+        Self {
+            cells: exact_values_cell_indexes(bits)
+                .map(|index| (index, ExactValuesCell::new(bits, 1)))
+                .collect(),
+        }
+    }
+
+    fn add_to_cell(cells: &mut BTreeMap<u32, ExactValuesCell>, index: u32, cell: ExactValuesCell) {
+        let current = cells.entry(index).or_insert(ExactValuesCell::new(0, 0));
+        current.add(cell);
+        if current.is_zero() {
+            cells.remove(&index);
+        }
+    }
+
+    fn plus_equals(&mut self, rhs: &Self) {
+        for (index, cell) in &rhs.cells {
+            Self::add_to_cell(&mut self.cells, *index, *cell);
+        }
+    }
+
+    fn multiply(self, factor: isize) -> Self {
+        Self {
+            cells: self
+                .cells
+                .into_iter()
+                .map(|(index, cell)| (index, cell.multiply(factor)))
+                .filter(|(_index, cell)| !cell.is_zero())
+                .collect(),
+        }
+    }
+
+    // (value, count) pairs in increasing order of values, None if there are too many values
+    fn decode(&self) -> Option<Vec<(f64, isize)>> {
+        let mut cells = self.cells.clone();
+        let mut values = BTreeMap::new();
+        let mut pending: Vec<u32> = cells.keys().copied().collect();
+        while let Some(index) = pending.pop() {
+            let Some((bits, count)) = cells.get(&index).and_then(|cell| cell.single_value()) else {
+                continue;
+            };
+            values.insert(OrderedFloat(f64::from_bits(bits)), count);
+            for other_index in exact_values_cell_indexes(bits) {
+                Self::add_to_cell(&mut cells, other_index, ExactValuesCell::new(bits, -count));
+                pending.push(other_index);
+            }
+        }
+        cells.is_empty().then(|| {
+            values
+                .into_iter()
+                .map(|(value, count)| (value.0, count))
+                .collect()
+        })
+    }
+}
+
+// Exact values of a group along with a DDSketch with exact bucket counts, used when there are
+// too many values to decode the exact ones. Since both are sums over the values, merging states
+// and retracting values are just additions, so the state is the same regardless of the order
+// of updates. The size of the sketch is bounded by the number of buckets covering the range
+// of values (logarithmic in the range), and groups in which no two distinct values
+// are within the relative accuracy from each other get exact quantiles from it as well.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct QuantilesState {
+    exact: ExactValues,
+    buckets: BTreeMap<SketchBucket, SketchBucketSummary>,
+    error_count: isize,
+}
+
+impl Semigroup for QuantilesState {
+    fn is_zero(&self) -> bool {
+        self.exact.cells.is_empty() && self.buckets.is_empty() && self.error_count.is_zero()
+    }
+
+    fn plus_equals(&mut self, rhs: &Self) {
+        self.error_count.plus_equals(&rhs.error_count);
+        self.exact.plus_equals(&rhs.exact);
+        for (bucket, summary) in &rhs.buckets {
+            let current = self.buckets.entry(*bucket).or_insert(SketchBucketSummary {
+                count: 0,
+                bits_sum: 0,
+            });
+            current.count += summary.count;
+            current.bits_sum += summary.bits_sum;
+            if current.is_zero() {
+                self.buckets.remove(bucket);
+            }
+        }
+    }
+}
+
+impl Multiply<isize> for QuantilesState {
+    type Output = Self;
+    fn multiply(self, rhs: &isize) -> Self::Output {
+        let buckets = if *rhs == 0 {
+            BTreeMap::new()
+        } else {
+            self.buckets
+                .into_iter()
+                .map(|(bucket, summary)| {
+                    let summary = SketchBucketSummary {
+                        count: summary.count * rhs,
+                        bits_sum: summary.bits_sum * *rhs as i128,
+                    };
+                    (bucket, summary)
+                })
+                .collect()
+        };
+        let error_count = self.error_count * rhs;
+        Self {
+            exact: self.exact.multiply(*rhs),
+            buckets,
+            error_count,
+        }
+    }
+}
+
+impl QuantilesState {
+    pub fn single(value: f64) -> Self {
+        // -0.0 and 0.0 are the same value
+        let value = value + 0.0;
+        Self {
+            exact: ExactValues::single(value),
+            buckets: BTreeMap::from([(
+                SketchBucket::for_value(value),
+                SketchBucketSummary::single(value),
+            )]),
+            error_count: 0,
+        }
+    }
+    pub fn error() -> Self {
+        Self {
+            exact: ExactValues::default(),
+            buckets: BTreeMap::new(),
+            error_count: 1,
+        }
+    }
+
+    // (value, count) pairs in increasing order of values
+    fn weighted_values(&self) -> Vec<(f64, isize)> {
+        let Some(values) = self.exact.decode() else {
+            return self
+                .buckets
+                .iter()
+                .filter(|(_bucket, summary)| summary.count > 0)
+                .map(|(bucket, summary)| (summary.value(*bucket), summary.count))
+                .collect();
+        };
+        values
+            .into_iter()
+            .filter(|(_value, count)| *count > 0)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuantilesReducer {
+    quantiles: Vec<f64>,
+    single: bool,
+}
+
+impl QuantilesReducer {
+    pub fn new_single(quantile: f64) -> Result<Self, DataError> {
+        Self::check_quantile(quantile)?;
+        Ok(Self {
+            quantiles: vec![quantile],
+            single: true,
+        })
+    }
+
+    pub fn new_multiple(quantiles: Vec<f64>) -> Result<Self, DataError> {
+        if quantiles.is_empty() {
+            return Err(DataError::ValueError(
+                "at least one quantile is required".to_string(),
+            ));
+        }
+        for quantile in &quantiles {
+            Self::check_quantile(*quantile)?;
+        }
+        Ok(Self {
+            quantiles,
+            single: false,
+        })
+    }
+
+    fn check_quantile(quantile: f64) -> Result<(), DataError> {
+        if (0.0..=1.0).contains(&quantile) {
+            Ok(())
+        } else {
+            Err(DataError::ValueError(format!(
+                "quantile has to be between 0 and 1, got {quantile}"
+            )))
+        }
+    }
+
+    // Linear interpolation between the closest ranks, like numpy's default method.
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn quantile(values: &[(f64, isize)], quantile: f64) -> f64 {
+        let total: isize = values.iter().map(|(_value, count)| count).sum();
+        let value_at = |rank: isize| {
+            let mut seen = 0;
+            for (value, count) in values {
+                seen += count;
+                if seen > rank {
+                    return *value;
+                }
+            }
+            values.last().expect("values should not be empty").0
+        };
+        let position = quantile * (total - 1) as f64;
+        let lower = position.floor();
+        let lower_value = value_at(lower as isize);
+        let upper_value = value_at(position.ceil() as isize);
+        lower_value + (upper_value - lower_value) * (position - lower)
+    }
+}
+
+impl SemigroupReducerImpl for QuantilesReducer {
+    type State = QuantilesState;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn init(&self, key: &Key, value: &Value) -> DynResult<Self::State> {
+        match value {
+            Value::Int(i) => Ok(QuantilesState::single(*i as f64)),
+            Value::Float(f) if !f.is_nan() => Ok(QuantilesState::single(f.0)),
+            value => Err(DataError::ReducerInitializationError {
+                reducer_type: type_name::<Self>().to_string(),
+                value: value.clone(),
+                source_key: *key,
+            }
+            .into()),
+        }
+    }
+
+    fn init_error(&self) -> Self::State {
+        QuantilesState::error()
+    }
+
+    fn finish(&self, state: Self::State) -> Value {
+        if state.error_count != 0 {
+            return Value::Error;
+        }
+        let values = state.weighted_values();
+        if values.is_empty() {
+            return Value::None;
+        }
+        let mut results = self
+            .quantiles
+            .iter()
+            .map(|quantile| Value::from(Self::quantile(&values, *quantile)));
+        if self.single {
+            results.next().expect("quantiles should not be empty")
+        } else {
+            Value::Tuple(results.collect())
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct CountReducer;
 
@@ -568,6 +1497,103 @@ impl ReducerImpl for TupleReducer {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum TopKOrder {
+    Ascending(Value),
+    Descending(Reverse<Value>),
+}
+
+// ties in the ordering value are broken by the source key
+pub type TopKElement = (TopKOrder, Key, Value);
+
+// how the elements of a top-k reducer are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopKOrdering {
+    pub descending: bool,
+    // index of the reducer argument used for ordering, the reduced value itself if `None`
+    pub order_column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TopKReducer {
+    k: usize,
+    ordering: TopKOrdering,
+}
+
+impl TopKReducer {
+    pub fn new(k: usize, ordering: TopKOrdering) -> Self {
+        Self { k, ordering }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn element(&self, key: &Key, values: &[Value]) -> DynResult<TopKElement> {
+        let order_value = match self.ordering.order_column {
+            Some(order_column) => values.get(order_column).ok_or_else(|| {
+                DataError::ValueError(format!(
+                    "top_k ordering column {order_column} is missing, got {} arguments",
+                    values.len()
+                ))
+            })?,
+            None => &values[0],
+        }
+        .clone();
+        let order = if self.ordering.descending {
+            TopKOrder::Descending(Reverse(order_value))
+        } else {
+            TopKOrder::Ascending(order_value)
+        };
+        Ok((order, *key, values[0].clone()))
+    }
+
+    // element with an error as the value, so that the result of the group is an error
+    pub fn error_element(&self, key: &Key) -> TopKElement {
+        (TopKOrder::Ascending(Value::Error), *key, Value::Error)
+    }
+
+    pub fn finish_elements<'a>(
+        &self,
+        elements: impl IntoIterator<Item = &'a TopKElement>,
+    ) -> Value {
+        let mut pairs = Vec::new();
+        for (_, key, value) in elements {
+            if *value == Value::Error {
+                return Value::Error;
+            }
+            pairs.push(Value::from(
+                [value.clone(), Value::Pointer(*key)].as_slice(),
+            ));
+        }
+        pairs.as_slice().into()
+    }
+}
+
+impl ReducerImpl for TopKReducer {
+    type State = Vec<TopKElement>;
+
+    fn init(&self, key: &Key, values: &[Value]) -> DynResult<Self::State> {
+        Ok(vec![self.element(key, values)?])
+    }
+
+    fn combine<'a>(
+        &self,
+        values: impl IntoIterator<Item = (&'a Self::State, NonZeroUsize)>,
+    ) -> DynResult<Self::State> {
+        Ok(values
+            .into_iter()
+            .flat_map(|(state, cnt)| state.iter().flat_map(move |v| repeat(v).take(cnt.get())))
+            .k_smallest(self.k)
+            .cloned()
+            .collect())
+    }
+
+    fn finish(&self, state: Self::State) -> Value {
+        self.finish_elements(&state)
+    }
+}
+
 #[derive(Clone)]
 pub struct StatefulReducer {
     combine_fn: StatefulCombineFn,
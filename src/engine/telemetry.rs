@@ -1,10 +1,13 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     sync::Arc,
     thread::{Builder, JoinHandle},
     time::{Duration, SystemTime},
 };
 
-use super::{error::DynError, license::License, Graph, ProberStats, Result};
+use super::{
+    error::DynError, license::License, Graph, ProberStats, Result, Timestamp, TotalFrontier,
+};
 use crate::env::parse_env_var;
 use arc_swap::ArcSwapOption;
 use itertools::Itertools;
@@ -13,8 +16,11 @@ use nix::sys::{
     resource::{getrusage, UsageWho},
     time::TimeValLike,
 };
+use opentelemetry::global::BoxedSpan;
 use opentelemetry::metrics::noop::NoopMeterProvider;
-use opentelemetry::{global, KeyValue};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{Span as _, Tracer as _};
+use opentelemetry::{global, Context, KeyValue};
 use opentelemetry_otlp::{Protocol, WithExportConfig};
 use opentelemetry_sdk::{
     metrics::{
@@ -50,6 +56,15 @@ const LICENSE_KEY: &str = "license.key";
 
 const LOCAL_DEV_NAMESPACE: &str = "local-dev";
 
+const TRACER_NAME: &str = "pathway-engine";
+const TIMESTAMP: &str = "timestamp";
+const TIMESTAMP_UNTIL: &str = "timestamp.until";
+const WORKER_INDEX: &str = "worker.index";
+const TIMESTAMP_PROCESSING_SPAN: &str = "timestamp.process";
+
+/// The context the engine spans are attached to. Set only while the traces are exported.
+static PARENT_CONTEXT: ArcSwapOption<Context> = ArcSwapOption::const_empty();
+
 struct Telemetry {
     pub config: Box<TelemetryEnabled>,
 }
@@ -102,6 +117,9 @@ impl Telemetry {
         }
 
         global::set_tracer_provider(provider_builder.build().clone());
+        PARENT_CONTEXT.store(Some(Arc::new(parent_context(
+            self.config.trace_parent.as_deref(),
+        ))));
     }
 
     fn init_meter_provider(&self) -> Option<SdkMeterProvider> {
@@ -153,6 +171,7 @@ impl Drop for TelemetryGuard {
         }
         global::set_meter_provider(NoopMeterProvider::new());
 
+        PARENT_CONTEXT.store(None);
         global::shutdown_tracer_provider();
     }
 }
@@ -170,6 +189,116 @@ fn root_trace_id(trace_parent: Option<&str>) -> Option<&str> {
     }
 }
 
+fn parent_context(trace_parent: Option<&str>) -> Context {
+    let mut carrier = HashMap::new();
+    if let Some(trace_parent) = trace_parent {
+        carrier.insert("traceparent".to_string(), trace_parent.to_string());
+    }
+    TraceContextPropagator::new().extract(&carrier)
+}
+
+/// A span that ends when dropped. It is a no-op if the traces are not exported.
+#[must_use]
+pub struct SpanGuard(Option<BoxedSpan>);
+
+impl SpanGuard {
+    /// Starts a span as a child of the `trace_parent` of the run.
+    /// The attributes are computed only if the traces are exported.
+    pub fn start(name: &'static str, attributes: impl FnOnce() -> Vec<KeyValue>) -> Self {
+        let Some(parent_context) = PARENT_CONTEXT.load_full() else {
+            return Self(None);
+        };
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder(name)
+            .with_attributes(attributes())
+            .start_with_context(&tracer, &parent_context);
+        Self(Some(span))
+    }
+
+    pub fn set_attribute(&mut self, attribute: KeyValue) {
+        if let Some(span) = &mut self.0 {
+            span.set_attribute(attribute);
+        }
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if let Some(span) = &mut self.0 {
+            span.end();
+        }
+    }
+}
+
+pub fn timestamp_attribute(timestamp: Timestamp) -> KeyValue {
+    KeyValue::new(TIMESTAMP, i64::try_from(timestamp.0).unwrap_or(i64::MAX))
+}
+
+/// Traces the timestamps processed by a worker, from the moment its inputs
+/// are closed to the moment they reach the outputs. The frontiers are observed
+/// between the steps of the worker, so a span covers all timestamps closed since
+/// the previous observation, even if they were already output in the same step.
+pub struct TimestampSpans {
+    worker_index: usize,
+    input_frontier: Option<TotalFrontier<Timestamp>>,
+    /// The first timestamp and the span of each range of closed timestamps,
+    /// by the input frontier closing the range.
+    spans: BTreeMap<TotalFrontier<Timestamp>, (Timestamp, SpanGuard)>,
+}
+
+impl TimestampSpans {
+    pub fn new(worker_index: usize) -> Self {
+        Self {
+            worker_index,
+            input_frontier: None,
+            spans: BTreeMap::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        input_frontier: TotalFrontier<Timestamp>,
+        output_frontier: TotalFrontier<Timestamp>,
+    ) {
+        if let Some(TotalFrontier::At(first_closed_time)) = self.input_frontier {
+            if input_frontier > TotalFrontier::At(first_closed_time) {
+                let worker_index = self.worker_index;
+                let span = SpanGuard::start(TIMESTAMP_PROCESSING_SPAN, || {
+                    let mut attributes = vec![
+                        timestamp_attribute(first_closed_time),
+                        KeyValue::new(
+                            WORKER_INDEX,
+                            i64::try_from(worker_index).unwrap_or(i64::MAX),
+                        ),
+                    ];
+                    if let TotalFrontier::At(input_time) = input_frontier {
+                        attributes.push(KeyValue::new(
+                            TIMESTAMP_UNTIL,
+                            i64::try_from(input_time.0).unwrap_or(i64::MAX),
+                        ));
+                    }
+                    attributes
+                });
+                self.spans.insert(input_frontier, (first_closed_time, span));
+            }
+        }
+        self.input_frontier = Some(input_frontier);
+        // dropping the spans of the timestamps that reached the outputs ends them
+        self.spans
+            .retain(|closing_frontier, _span| *closing_frontier > output_frontier);
+    }
+
+    /// Returns the first timestamps of the traced ranges that haven't reached
+    /// the outputs yet.
+    pub fn pending_timestamps(&self) -> Vec<Timestamp> {
+        self.spans
+            .values()
+            .map(|(first_closed_time, _span)| *first_closed_time)
+            .collect()
+    }
+}
+
 fn deduplicate(input: Vec<Option<String>>) -> Vec<String> {
     input.into_iter().flatten().sorted().dedup().collect()
 }
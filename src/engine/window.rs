@@ -0,0 +1,324 @@
+// Copyright © 2024 Pathway
+
+use std::fmt::Display;
+
+use num_integer::Integer;
+
+use super::error::DataResult;
+use super::time::DateTime;
+use super::{DataError, DateTimeNaive, DateTimeUtc, Value};
+
+#[derive(Debug, Clone)]
+pub enum Window {
+    Tumbling {
+        duration: Value,
+        origin: Option<Value>,
+    },
+    Sliding {
+        hop: Value,
+        duration: Value,
+        origin: Option<Value>,
+    },
+    Session {
+        max_gap: Value,
+    },
+}
+
+/// Controls when rows assigned to windows are emitted and forgotten.
+/// `delay` postpones a window until the time column reaches its start shifted by `delay`.
+/// `cutoff` makes rows arriving after the time column passes the window end shifted by
+/// `cutoff` ignored, and allows the state of such windows to be dropped.
+/// With `keep_results` the results of dropped windows remain in the output.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct WindowBehavior {
+    pub delay: Option<Value>,
+    pub cutoff: Option<Value>,
+    pub keep_results: bool,
+}
+
+impl WindowBehavior {
+    /// Returns the times after which a window starting at `start` and ending at `end`
+    /// is emitted and after which it is closed. If there is no delay or cutoff,
+    /// the respective time is `Value::None`.
+    pub fn thresholds(&self, start: &Value, end: &Value) -> DataResult<(Value, Value)> {
+        let delay_threshold = match &self.delay {
+            Some(delay) => shift(start, delay)?,
+            None => Value::None,
+        };
+        let cutoff_threshold = match &self.cutoff {
+            Some(cutoff) => shift(end, cutoff)?,
+            None => Value::None,
+        };
+        Ok((delay_threshold, cutoff_threshold))
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(i) => Some(*i as f64),
+        Value::Float(f) => Some(f.0),
+        _ => None,
+    }
+}
+
+/// Moves `time` forward by `offset`.
+/// Offsets of datetimes are durations, offsets of numbers are numbers.
+pub fn shift(time: &Value, offset: &Value) -> DataResult<Value> {
+    match (time, offset) {
+        (Value::Int(time), Value::Int(offset)) => time
+            .checked_add(*offset)
+            .map(Value::Int)
+            .ok_or_else(|| DataError::ValueError("window time overflow".to_string())),
+        (Value::Float(time), offset) if as_float(offset).is_some() => {
+            Ok(Value::from(time.0 + as_float(offset).unwrap()))
+        }
+        (Value::DateTimeNaive(time), Value::Duration(offset)) => {
+            Ok(Value::DateTimeNaive(*time + *offset))
+        }
+        (Value::DateTimeUtc(time), Value::Duration(offset)) => {
+            Ok(Value::DateTimeUtc(*time + *offset))
+        }
+        (time, offset) => Err(DataError::ValueError(format!(
+            "can't shift time {time:?} by {offset:?}"
+        ))),
+    }
+}
+
+/// Moves `time` backward by `offset`.
+pub fn shift_back(time: &Value, offset: &Value) -> DataResult<Value> {
+    let negated_offset = match offset {
+        Value::Int(offset) => offset
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| DataError::ValueError("window time overflow".to_string()))?,
+        Value::Float(offset) => Value::from(-offset.0),
+        Value::Duration(offset) => Value::Duration(-*offset),
+        offset => {
+            return Err(DataError::ValueError(format!(
+                "can't shift time {time:?} by {offset:?}"
+            )))
+        }
+    };
+    shift(time, &negated_offset)
+}
+
+/// Returns the offset by which `earlier` has to be shifted to get `later`.
+pub fn time_difference(later: &Value, earlier: &Value) -> DataResult<Value> {
+    match (later, earlier) {
+        (Value::Int(later), Value::Int(earlier)) => later
+            .checked_sub(*earlier)
+            .map(Value::Int)
+            .ok_or_else(|| DataError::ValueError("window time overflow".to_string())),
+        (Value::DateTimeNaive(later), Value::DateTimeNaive(earlier)) => {
+            Ok(Value::Duration(*later - *earlier))
+        }
+        (Value::DateTimeUtc(later), Value::DateTimeUtc(earlier)) => {
+            Ok(Value::Duration(*later - *earlier))
+        }
+        (later, earlier) => match (as_float(later), as_float(earlier)) {
+            (Some(later), Some(earlier)) => Ok(Value::from(later - earlier)),
+            _ => Err(DataError::ValueError(format!(
+                "can't subtract time {earlier:?} from {later:?}"
+            ))),
+        },
+    }
+}
+
+// Every time is assigned to at most this many windows, so that a sliding window
+// with a hop much shorter than its duration can't exhaust the memory.
+const MAX_WINDOWS_PER_TIME: i64 = 1 << 20;
+
+// The window indexes are kept below 2^53, where `f64` still represents every integer.
+const MAX_FLOAT_WINDOW_INDEX: f64 = 9_007_199_254_740_992.0;
+
+fn check_positive(length: i64) -> DataResult<()> {
+    if length > 0 {
+        Ok(())
+    } else {
+        Err(DataError::ValueError(
+            "window length has to be positive".to_string(),
+        ))
+    }
+}
+
+fn too_many_windows(hop: impl Display, duration: impl Display) -> DataError {
+    DataError::ValueError(format!(
+        "windows of duration {duration} placed every {hop} overlap too much, \
+        at most {MAX_WINDOWS_PER_TIME} windows can contain a single time"
+    ))
+}
+
+// starts of windows of length `duration` placed every `hop` from `origin` that contain `time`
+fn integer_window_starts(time: i64, origin: i64, hop: i64, duration: i64) -> DataResult<Vec<i64>> {
+    check_positive(hop)?;
+    check_positive(duration)?;
+    if Integer::div_ceil(&duration, &hop) > MAX_WINDOWS_PER_TIME {
+        return Err(too_many_windows(hop, duration));
+    }
+    let overflow = || DataError::ValueError("window time overflow".to_string());
+    let offset = time.checked_sub(origin).ok_or_else(overflow)?;
+    let mut start = Integer::div_floor(&offset, &hop)
+        .checked_mul(hop)
+        .and_then(|offset| offset.checked_add(origin))
+        .ok_or_else(overflow)?;
+    let mut starts = Vec::new();
+    // the window starting at `start` contains `time` while `time - start < duration`
+    while time.checked_sub(start).ok_or_else(overflow)? < duration {
+        starts.push(start);
+        let Some(previous_start) = start.checked_sub(hop) else {
+            break;
+        };
+        start = previous_start;
+    }
+    starts.reverse();
+    Ok(starts)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_precision_loss)]
+#[allow(clippy::cast_sign_loss)]
+fn float_window_starts(time: f64, origin: f64, hop: f64, duration: f64) -> DataResult<Vec<f64>> {
+    if hop <= 0.0 || duration <= 0.0 || !hop.is_finite() || !duration.is_finite() {
+        return Err(DataError::ValueError(
+            "window length has to be positive".to_string(),
+        ));
+    }
+    if (duration / hop).ceil() > MAX_WINDOWS_PER_TIME as f64 {
+        return Err(too_many_windows(hop, duration));
+    }
+    if !time.is_finite() || !origin.is_finite() {
+        return Err(DataError::ValueError(format!(
+            "window time has to be finite, got {time} with origin {origin}"
+        )));
+    }
+    let index = ((time - origin) / hop).floor();
+    if !index.is_finite() || index.abs() > MAX_FLOAT_WINDOW_INDEX {
+        return Err(DataError::ValueError("window time overflow".to_string()));
+    }
+    let mut index = index as i64;
+    let mut starts = Vec::new();
+    // the rounding can't make a time belong to more windows than the bound allows
+    while origin + index as f64 * hop + duration > time
+        && starts.len() <= MAX_WINDOWS_PER_TIME as usize
+    {
+        starts.push(origin + index as f64 * hop);
+        index -= 1;
+    }
+    starts.reverse();
+    Ok(starts)
+}
+
+fn window_starts(
+    time: &Value,
+    origin: Option<&Value>,
+    hop: &Value,
+    duration: &Value,
+) -> DataResult<Vec<Value>> {
+    match (time, origin, hop, duration) {
+        (Value::Int(time), None | Some(Value::Int(_)), Value::Int(hop), Value::Int(duration)) => {
+            let origin = origin.map_or(0, |origin| origin.as_int().unwrap());
+            Ok(integer_window_starts(*time, origin, *hop, *duration)?
+                .into_iter()
+                .map(Value::Int)
+                .collect())
+        }
+        (Value::Float(time), origin, hop, duration)
+            if origin.map_or(true, |origin| as_float(origin).is_some())
+                && as_float(hop).is_some()
+                && as_float(duration).is_some() =>
+        {
+            let origin = origin.map_or(0.0, |origin| as_float(origin).unwrap());
+            Ok(float_window_starts(
+                time.0,
+                origin,
+                as_float(hop).unwrap(),
+                as_float(duration).unwrap(),
+            )?
+            .into_iter()
+            .map(Value::from)
+            .collect())
+        }
+        (
+            Value::DateTimeNaive(time),
+            None | Some(Value::DateTimeNaive(_)),
+            Value::Duration(hop),
+            Value::Duration(duration),
+        ) => {
+            let origin = match origin {
+                Some(Value::DateTimeNaive(origin)) => origin.timestamp(),
+                _ => 0,
+            };
+            Ok(integer_window_starts(
+                time.timestamp(),
+                origin,
+                hop.nanoseconds(),
+                duration.nanoseconds(),
+            )?
+            .into_iter()
+            .map(|start| Value::DateTimeNaive(DateTimeNaive::new(start)))
+            .collect())
+        }
+        (
+            Value::DateTimeUtc(time),
+            None | Some(Value::DateTimeUtc(_)),
+            Value::Duration(hop),
+            Value::Duration(duration),
+        ) => {
+            let origin = match origin {
+                Some(Value::DateTimeUtc(origin)) => origin.timestamp(),
+                _ => 0,
+            };
+            Ok(integer_window_starts(
+                time.timestamp(),
+                origin,
+                hop.nanoseconds(),
+                duration.nanoseconds(),
+            )?
+            .into_iter()
+            .map(|start| Value::DateTimeUtc(DateTimeUtc::new(start)))
+            .collect())
+        }
+        (time, _, _, _) => Err(DataError::ValueError(format!(
+            "window parameters don't match the type of time {time:?}"
+        ))),
+    }
+}
+
+impl Window {
+    /// Returns `(start, end)` of all tumbling or sliding windows containing `time`.
+    /// Windows include their start and exclude their end.
+    pub fn assign(&self, time: &Value) -> DataResult<Vec<(Value, Value)>> {
+        let (hop, duration, origin) = match self {
+            Self::Tumbling { duration, origin } => (duration, duration, origin),
+            Self::Sliding {
+                hop,
+                duration,
+                origin,
+            } => (hop, duration, origin),
+            Self::Session { .. } => {
+                return Err(DataError::ValueError(
+                    "session windows depend on other rows".to_string(),
+                ))
+            }
+        };
+        window_starts(time, origin.as_ref(), hop, duration)?
+            .into_iter()
+            .map(|start| {
+                let end = shift(&start, duration)?;
+                Ok((start, end))
+            })
+            .collect()
+    }
+}
+
+/// Tells whether rows at `previous_time` and `next_time`, `previous_time <= next_time`,
+/// are close enough to belong to the same session.
+pub fn within_session_gap(
+    previous_time: &Value,
+    next_time: &Value,
+    max_gap: &Value,
+) -> DataResult<bool> {
+    Ok(shift(previous_time, max_gap)? >= *next_time)
+}
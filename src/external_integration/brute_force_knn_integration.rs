@@ -1,22 +1,28 @@
 // Copyright © 2024 Pathway
 
-use itertools::Itertools;
 use ndarray::{s, Array2, ArrayView2, Axis};
 
 use crate::engine::error::DynResult;
 use crate::engine::{Error, Key};
+use crate::mat_mul;
 use ordered_float::{self, OrderedFloat};
 use std::cmp::max;
+use std::collections::BinaryHeap;
 
 use super::{
-    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyFilter, KeyScoreMatch,
     KeyToU64IdMapper, NonFilteringExternalIndex,
 };
 
+type BruteForceQuery<'a> = (Key, &'a [f64], usize, Option<KeyFilter<'a>>);
+
 #[derive(Clone, Copy, Debug)]
 pub enum BruteForceKnnMetricKind {
     L2sq,
     Cos,
+    Ip,
+    L1,
+    Hamming,
 }
 
 pub struct BruteForceKNNIndex {
@@ -50,21 +56,152 @@ impl BruteForceKNNIndex {
         })
     }
 
-    fn fill_distances(
+    fn compute_distances(
         &self,
         index_arr: &ArrayView2<f64>,
         query_arr: &ArrayView2<f64>,
-        dot_p: &mut Array2<f64>,
-    ) {
+    ) -> Array2<f64> {
         match self.metric {
             BruteForceKnnMetricKind::L2sq => {
-                fill_l2sq_distances(index_arr, query_arr, dot_p);
+                let mut dot_p = index_arr.dot(query_arr);
+                fill_l2sq_distances(index_arr, query_arr, &mut dot_p);
+                dot_p
             }
             BruteForceKnnMetricKind::Cos => {
-                fill_cos_distances(index_arr, query_arr, dot_p);
+                let mut dot_p = index_arr.dot(query_arr);
+                fill_cos_distances(index_arr, query_arr, &mut dot_p);
+                dot_p
+            }
+            BruteForceKnnMetricKind::Ip => {
+                let mut dot_p = index_arr.dot(query_arr);
+                dot_p.mapv_inplace(|x| 1.0 - x);
+                dot_p
+            }
+            BruteForceKnnMetricKind::L1 => {
+                elementwise_distances(index_arr, query_arr, |a, b| (a - b).abs())
+            }
+            // coordinates are treated as bits (positive = set), like in binary embeddings
+            BruteForceKnnMetricKind::Hamming => {
+                elementwise_distances(index_arr, query_arr, |a, b| {
+                    f64::from(u8::from((a > 0.0) != (b > 0.0)))
+                })
+            }
+        }
+    }
+
+    fn key_for_row(&self, idx: usize) -> Key {
+        self.key_to_id_mapper
+            .get_key_for_id(u64::try_from(idx).unwrap())
+            .unwrap()
+    }
+
+    // Computes the distances in blocks of at most `auxiliary_space` entries, keeping a bounded
+    // top-k for each query. Queries are copied into a matrix one column block at a time,
+    // so the auxiliary memory stays bounded regardless of the number of queries.
+    fn search_batch(
+        &self,
+        queries: &[BruteForceQuery],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        if self.current_size == 0 {
+            return queries
+                .iter()
+                .map(|(key, _, _, _)| (*key, Ok(Vec::new())))
+                .collect();
+        }
+
+        let index_arr = self
+            .index_array
+            .slice(s![..self.current_size, ..self.dimensions]);
+
+        let mut nearest: Vec<_> = queries
+            .iter()
+            .map(|(_key, _data, limit, _predicate)| KSmallest::new(*limit))
+            .collect();
+        let mut query_arr = Array2::<f64>::default((self.dimensions, 0));
+        let mut filled_columns = 0..0;
+        for (index_rows, query_columns) in
+            mat_mul::product_blocks(self.current_size, queries.len(), self.auxiliary_space)
+        {
+            // blocks come column block by column block, so the queries are copied once per block
+            if query_columns != filled_columns {
+                query_arr = Array2::<f64>::default((self.dimensions, query_columns.len()));
+                for (mut col, (_key, data, _k, _predicate)) in query_arr
+                    .axis_iter_mut(Axis(1))
+                    .zip(&queries[query_columns.clone()])
+                {
+                    for (entry, val) in col.iter_mut().zip(*data) {
+                        *entry = *val;
+                    }
+                }
+                filled_columns = query_columns.clone();
             }
+            let distances = self.compute_distances(
+                &index_arr.slice(s![index_rows.clone(), ..]),
+                &query_arr.view(),
+            );
+            for (col, query_idx) in distances.axis_iter(Axis(1)).zip(query_columns) {
+                let (_key, _data, _limit, predicate) = &queries[query_idx];
+                let query_nearest = &mut nearest[query_idx];
+                for (distance, idx) in col.iter().zip(index_rows.clone()) {
+                    let candidate = (OrderedFloat::from(*distance), idx); //order by distance
+                    if query_nearest.accepts(&candidate)
+                        && predicate.map_or(true, |predicate| predicate(self.key_for_row(idx)))
+                    {
+                        query_nearest.push(candidate);
+                    }
+                }
+            }
+        }
+
+        queries
+            .iter()
+            .zip(nearest)
+            .map(|((key, _data, _limit, _predicate), nearest)| {
+                let result = nearest
+                    .into_sorted_vec()
+                    .into_iter()
+                    .map(|(distance, i)| KeyScoreMatch {
+                        key: self.key_for_row(i),
+                        score: -(*distance),
+                    })
+                    .collect();
+                (*key, Ok(result))
+            })
+            .collect()
+    }
+}
+
+// bounded max-heap keeping the `limit` smallest elements pushed into it
+struct KSmallest<T> {
+    limit: usize,
+    heap: BinaryHeap<T>,
+}
+
+impl<T: Ord> KSmallest<T> {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::with_capacity(limit),
         }
     }
+
+    fn accepts(&self, element: &T) -> bool {
+        if self.heap.len() < self.limit {
+            return true;
+        }
+        self.heap.peek().is_some_and(|largest| element < largest)
+    }
+
+    fn push(&mut self, element: T) {
+        if self.heap.len() == self.limit {
+            self.heap.pop();
+        }
+        self.heap.push(element);
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        self.heap.into_sorted_vec()
+    }
 }
 
 fn fill_cos_distances(
@@ -89,6 +226,22 @@ fn fill_cos_distances(
     }
 }
 
+// metrics that can't be expressed with a dot product are computed coordinate by coordinate
+fn elementwise_distances(
+    index_arr: &ArrayView2<f64>,
+    query_arr: &ArrayView2<f64>,
+    distance_term: impl Fn(f64, f64) -> f64,
+) -> Array2<f64> {
+    Array2::from_shape_fn((index_arr.nrows(), query_arr.ncols()), |(i, j)| {
+        index_arr
+            .row(i)
+            .iter()
+            .zip(query_arr.column(j))
+            .map(|(a, b)| distance_term(*a, *b))
+            .sum()
+    })
+}
+
 fn fill_l2sq_distances(
     index_arr: &ArrayView2<f64>,
     query_arr: &ArrayView2<f64>,
@@ -188,51 +341,22 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for BruteForceKNNIndex {
         &self,
         queries: &[(Key, Vec<f64>, usize)],
     ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
-        if self.current_size == 0 {
-            return queries
-                .iter()
-                .map(|(key, _, _)| (*key, Ok(Vec::new())))
-                .collect();
-        }
-
-        let index_arr = self
-            .index_array
-            .slice(s![..self.current_size, ..self.dimensions]);
+        let queries: Vec<BruteForceQuery> = queries
+            .iter()
+            .map(|(key, data, limit)| (*key, data.as_slice(), *limit, None))
+            .collect();
+        self.search_batch(&queries)
+    }
 
-        let mut ret = Vec::with_capacity(queries.len());
-        let max_queries = max(1, self.auxiliary_space / self.current_size);
-        let query_batches = queries.chunks(max_queries);
-        let mut query_arr = Array2::<f64>::default((self.dimensions, max_queries));
-        for query_batch in query_batches {
-            for (mut col, (_key, data, _k)) in query_arr.axis_iter_mut(Axis(1)).zip(query_batch) {
-                for (entry, val) in col.iter_mut().zip(data) {
-                    *entry = *val;
-                }
-            }
-            let slice_query_array = query_arr.slice(s![..self.dimensions, ..query_batch.len()]);
-            let mut dot_p = index_arr.dot(&slice_query_array);
-            self.fill_distances(&index_arr, &slice_query_array, &mut dot_p);
-
-            ret.extend(dot_p.axis_iter(Axis(1)).zip(query_batch).map(
-                |(col, (key, _data, limit))| {
-                    let result = col
-                        .iter()
-                        .enumerate()
-                        .map(|(idx, x)| (OrderedFloat::from(*x), idx)) //order by distance
-                        .k_smallest(*limit)
-                        .map(|(distance, i)| KeyScoreMatch {
-                            key: self
-                                .key_to_id_mapper
-                                .get_key_for_id(u64::try_from(i).unwrap())
-                                .unwrap(),
-                            score: -(*distance),
-                        })
-                        .collect();
-                    (*key, Ok(result))
-                },
-            ));
-        }
-        ret
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        let queries: Vec<BruteForceQuery> = queries
+            .iter()
+            .map(|(key, data, limit, predicate)| (*key, data.as_slice(), *limit, Some(*predicate)))
+            .collect();
+        Some(self.search_batch(&queries))
     }
 }
 
@@ -3,6 +3,7 @@
 pub mod brute_force_knn_integration;
 pub mod tantivy_integration;
 pub mod usearch_integration;
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::{collections::HashMap, rc::Rc, sync::Arc};
 
@@ -16,7 +17,7 @@ use jmespath::{
 use differential_dataflow::difference::Abelian;
 
 use crate::engine::dataflow::operators::external_index::Index as IndexTrait;
-use crate::engine::error::DynResult;
+use crate::engine::error::{DynError, DynResult};
 use crate::engine::report_error::{
     LogError, ReportError, UnwrapWithErrorLogger, UnwrapWithReporter,
 };
@@ -315,10 +316,14 @@ pub struct KeyScoreMatch {
 }
 
 impl KeyScoreMatch {
-    fn key(&self) -> Key {
+    pub fn key(&self) -> Key {
         self.key
     }
 
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
     fn into_value(self) -> Value {
         Value::Tuple(Arc::new([Value::from(self.key), Value::from(self.score)]))
     }
@@ -330,13 +335,34 @@ pub trait Unpack<Type> {
     fn unpack(self) -> DynResult<Type>;
 }
 
-// to vector of floats
+// to vector of floats, binary embeddings are unpacked bit by bit (most significant bit first)
 impl Unpack<Vec<f64>> for Value {
     fn unpack(self) -> DynResult<Vec<f64>> {
+        if let Value::Bytes(bytes) = &self {
+            return Ok(bytes
+                .iter()
+                .flat_map(|byte| (0..8).rev().map(move |bit| f64::from((byte >> bit) & 1)))
+                .collect());
+        }
         self.as_tuple()?.iter().map(Value::as_float).try_collect()
     }
 }
 
+// one or more vectors attached to a single key, e.g. embeddings of document chunks
+pub struct VectorGroup(pub Vec<Vec<f64>>);
+
+// a group is always a tuple of vectors, even if it contains a single vector
+impl Unpack<VectorGroup> for Value {
+    fn unpack(self) -> DynResult<VectorGroup> {
+        Ok(VectorGroup(
+            self.as_tuple()?
+                .iter()
+                .map(|vector| vector.clone().unpack())
+                .try_collect()?,
+        ))
+    }
+}
+
 // to JMESPath Variable
 impl Unpack<Variable> for Value {
     fn unpack(self) -> DynResult<Variable> {
@@ -352,6 +378,8 @@ impl Unpack<String> for Value {
     }
 }
 
+pub type KeyFilter<'a> = &'a dyn Fn(Key) -> bool;
+
 pub trait NonFilteringExternalIndex<DataType, QueryType> {
     fn add(&mut self, batch: Vec<(Key, DataType)>) -> Vec<(Key, DynResult<()>)>;
     fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)>;
@@ -359,6 +387,16 @@ pub trait NonFilteringExternalIndex<DataType, QueryType> {
         &self,
         queries: &[(Key, QueryType, usize)],
     ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>;
+
+    // Search in which the predicate is evaluated while the index is traversed, so that only
+    // entries accepted by the predicate count towards the limit. Indices that can't do that
+    // return None and the results are filtered after the search instead.
+    fn filtered_search(
+        &self,
+        _queries: &[(Key, QueryType, usize, KeyFilter)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        None
+    }
 }
 
 pub struct DerivedFilteredSearchIndex<DataType, QueryType> {
@@ -409,29 +447,88 @@ where
         Ok((query_point, limit, filter))
     }
 
+    fn evaluate_filter(&self, key: Key, expr: &Expression) -> DynResult<bool> {
+        let result = match self.filter_data_map.get(&key) {
+            Some(filter_data) => expr.search(filter_data)?,
+            None => expr.search(&Variable::Null)?,
+        };
+        result.as_boolean().ok_or_else(|| {
+            DataError::ValueError(
+                "jmespath filter expression did not return a boolean value".to_string(),
+            )
+            .into()
+        })
+    }
+
     fn filter_results(
         &self,
         results: Vec<KeyScoreMatch>,
         expr: &Expression,
     ) -> DynResult<Vec<KeyScoreMatch>> {
-        let res_with_expr: Vec<(KeyScoreMatch, Rc<Variable>)> = results
-            .into_iter()
-            .map(|sm| {
-                expr.search(&self.filter_data_map[&sm.key()])
-                    .map(|r| (sm, r))
-            })
-            .try_collect()?;
+        let mut filtered = Vec::with_capacity(results.len());
+        for sm in results {
+            if self.evaluate_filter(sm.key(), expr)? {
+                filtered.push(sm);
+            }
+        }
+        Ok(filtered)
+    }
 
-        Ok(res_with_expr
-            .into_iter()
-            .filter_map(|(k, e)| match e.as_boolean() {
-                Some(true) => Some(Ok(k)),
-                Some(false) => None,
-                None => Some(Err(DataError::ValueError(
-                    "jmespath filter expression did not return a boolean value".to_string(),
-                ))),
+    // Tries to push the filters down into the inner index. Returns the queries
+    // that have to be answered by filtering the results after the search.
+    fn search_with_pushed_down_filters<'a>(
+        &self,
+        filtering_queries: &'a [(Key, QueryType, usize, Expression<'a>)],
+        responses: &mut Vec<(Key, DynResult<Value>)>,
+    ) -> Vec<PendingQueryEntry<'a, QueryType>>
+    where
+        QueryType: Clone,
+    {
+        let filter_errors: Vec<RefCell<Option<DynError>>> = filtering_queries
+            .iter()
+            .map(|_| RefCell::new(None))
+            .collect();
+        let predicates: Vec<_> = filtering_queries
+            .iter()
+            .zip(&filter_errors)
+            .map(|((_, _, _, expr), filter_error)| {
+                move |key: Key| match self.evaluate_filter(key, expr) {
+                    Ok(accepted) => accepted,
+                    Err(error) => {
+                        filter_error.borrow_mut().get_or_insert(error);
+                        false
+                    }
+                }
+            })
+            .collect();
+        let queries: Vec<_> = filtering_queries
+            .iter()
+            .zip(&predicates)
+            .map(|((key, query, limit, _), predicate)| {
+                (*key, query.clone(), *limit, predicate as KeyFilter)
             })
-            .try_collect()?)
+            .collect();
+
+        let Some(answers) = self.inner.filtered_search(&queries) else {
+            return filtering_queries
+                .iter()
+                .map(|(key, query, limit, filter)| (key, (query, *limit, *limit, filter)))
+                .collect();
+        };
+
+        for ((key, results), filter_error) in answers.into_iter().zip(filter_errors) {
+            if let Some(error) = filter_error.into_inner() {
+                responses.push((key, Err(error)));
+                continue;
+            }
+            responses.push((
+                key,
+                results.map(|results| {
+                    Value::Tuple(results.into_iter().map(KeyScoreMatch::into_value).collect())
+                }),
+            ));
+        }
+        Vec::new()
     }
 
     fn retain_unfinished_queries<'a>(
@@ -560,10 +657,7 @@ where
             }
         }
 
-        let mut pending = Vec::with_capacity(filtering_queries.len());
-        for (key, query, limit, filter) in &filtering_queries {
-            pending.push((key, (query, *limit, *limit, filter)));
-        }
+        let mut pending = self.search_with_pushed_down_filters(&filtering_queries, &mut responses);
 
         while !pending.is_empty() {
             let queries: Vec<_> = pending
@@ -1,28 +1,35 @@
 // Copyright © 2024 Pathway
 
 use std::cmp::max;
+use std::collections::HashSet;
+use std::slice;
 use std::sync::Arc;
 
 use crate::engine::error::DynResult;
-use crate::engine::{Error, Key};
+use crate::engine::{DataError, Error, Key};
 use log::warn;
-use usearch::ffi::{IndexOptions, MetricKind, ScalarKind};
+use usearch::ffi::{IndexOptions, Matches, MetricKind, ScalarKind};
 use usearch::{new_index, Index};
 
 use super::{
-    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyScoreMatch,
-    KeyToU64IdMapper, NonFilteringExternalIndex,
+    DerivedFilteredSearchIndex, ExternalIndex, ExternalIndexFactory, KeyFilter, KeyScoreMatch,
+    KeyToU64IdMapper, NonFilteringExternalIndex, VectorGroup,
 };
 
 #[derive(Clone, Copy)]
 pub struct USearchMetricKind(pub MetricKind);
 
+#[derive(Clone, Copy)]
+pub struct USearchScalarKind(pub ScalarKind);
+
 pub struct USearchKNNIndex {
     index: Arc<Index>,
     key_to_id_mapper: KeyToU64IdMapper,
+    multi: bool,
 }
 
 impl USearchKNNIndex {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         dimensions: usize,
         reserved_space: usize,
@@ -30,15 +37,17 @@ impl USearchKNNIndex {
         connectivity: usize,
         expansion_add: usize,
         expansion_search: usize,
+        quantization: ScalarKind,
+        multi: bool,
     ) -> DynResult<USearchKNNIndex> {
         let options = IndexOptions {
             dimensions,
             metric,
-            quantization: ScalarKind::F16,
+            quantization,
             connectivity,
             expansion_add,
             expansion_search,
-            multi: false,
+            multi,
         };
 
         let index = new_index(&options)?;
@@ -47,15 +56,55 @@ impl USearchKNNIndex {
         Ok(USearchKNNIndex {
             index: Arc::from(index),
             key_to_id_mapper: KeyToU64IdMapper::new(),
+            multi,
         })
     }
 
     fn search_one(&self, data: &[f64], limit: usize) -> DynResult<Vec<KeyScoreMatch>> {
-        let matches = self.index.search(data, limit)?;
-        Ok(matches
+        self.search_matching(data, limit, &|_key| true)
+    }
+
+    fn filtered_search_one(
+        &self,
+        data: &[f64],
+        limit: usize,
+        predicate: KeyFilter,
+    ) -> DynResult<Vec<KeyScoreMatch>> {
+        self.search_matching(data, limit, predicate)
+    }
+
+    // a search for `limit` vectors can return fewer than `limit` matching keys, either because
+    // the predicate rejects some of them or, in a multi-vector index, because a key is matched
+    // by several of its vectors; the search is repeated with a doubled count until there are
+    // enough matching keys or the index is exhausted
+    fn search_matching(
+        &self,
+        data: &[f64],
+        limit: usize,
+        predicate: KeyFilter,
+    ) -> DynResult<Vec<KeyScoreMatch>> {
+        let mut count = limit;
+        loop {
+            let matches = self.index.search(data, count)?;
+            let fetched = matches.keys.len();
+            let mut result = self.matches_to_keys(matches);
+            result.retain(|key_match| predicate(key_match.key));
+            if result.len() >= limit || fetched < count || count >= self.index.size() {
+                result.truncate(limit);
+                return Ok(result);
+            }
+            count = max(2 * count, 1);
+        }
+    }
+
+    // matches are sorted by distance, so for each key only the first (best) one is kept
+    fn matches_to_keys(&self, matches: Matches) -> Vec<KeyScoreMatch> {
+        let mut seen_ids = HashSet::with_capacity(matches.keys.len());
+        matches
             .keys
             .into_iter()
             .zip(matches.distances)
+            .filter(|(k, _d)| seen_ids.insert(*k))
             .filter_map(|(k, d)| {
                 let Some(key) = self.key_to_id_mapper.get_key_for_id(k) else {
                     warn!("USearch index returned a nonexistent ID {k}, ignoring");
@@ -66,12 +115,26 @@ impl USearchKNNIndex {
                     score: -f64::from(d),
                 })
             })
-            .collect())
+            .collect()
     }
 
-    fn add_one(&mut self, key: Key, data: &[f64]) -> DynResult<()> {
+    fn add_one(&mut self, key: Key, vectors: &[Vec<f64>]) -> DynResult<()> {
+        if vectors.is_empty() || (!self.multi && vectors.len() != 1) {
+            return Err(DataError::ValueError(format!(
+                "USearch index expects {} vector per entry, got {}",
+                if self.multi {
+                    "at least one"
+                } else {
+                    "exactly one"
+                },
+                vectors.len()
+            ))
+            .into());
+        }
         let key_id = self.key_to_id_mapper.get_next_free_u64_id(key);
-        self.index.add(key_id, data)?;
+        for vector in vectors {
+            self.index.add(key_id, vector)?;
+        }
         Ok(())
     }
 
@@ -80,33 +143,32 @@ impl USearchKNNIndex {
         self.index.remove(key_id)?;
         Ok(())
     }
-}
 
-impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for USearchKNNIndex {
-    fn add(&mut self, add_data: Vec<(Key, Vec<f64>)>) -> Vec<(Key, DynResult<()>)> {
-        if self.index.size() + add_data.len() > self.index.capacity() {
+    fn add_batch(&mut self, add_data: Vec<(Key, &[Vec<f64>])>) -> Vec<(Key, DynResult<()>)> {
+        let vectors_count: usize = add_data.iter().map(|(_key, vectors)| vectors.len()).sum();
+        if self.index.size() + vectors_count > self.index.capacity() {
             assert!(self
                 .index
                 .reserve(max(
                     2 * self.index.capacity(),
-                    self.index.size() + add_data.len()
+                    self.index.size() + vectors_count
                 ))
                 .is_ok());
         }
 
         add_data
             .into_iter()
-            .map(|(key, data)| (key, self.add_one(key, &data)))
+            .map(|(key, vectors)| (key, self.add_one(key, vectors)))
             .collect()
     }
 
-    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+    fn remove_batch(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
         keys.into_iter()
             .map(|key| (key, self.remove_one(key)))
             .collect()
     }
 
-    fn search(
+    fn search_batch(
         &self,
         queries: &[(Key, Vec<f64>, usize)],
     ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
@@ -115,6 +177,78 @@ impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for USearchKNNIndex {
             .map(|(key, data, limit)| (*key, self.search_one(data, *limit)))
             .collect()
     }
+
+    fn filtered_search_batch(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        queries
+            .iter()
+            .map(|(key, data, limit, predicate)| {
+                (*key, self.filtered_search_one(data, *limit, *predicate))
+            })
+            .collect()
+    }
+}
+
+// an index with a single vector per key
+impl NonFilteringExternalIndex<Vec<f64>, Vec<f64>> for USearchKNNIndex {
+    fn add(&mut self, add_data: Vec<(Key, Vec<f64>)>) -> Vec<(Key, DynResult<()>)> {
+        self.add_batch(
+            add_data
+                .iter()
+                .map(|(key, vector)| (*key, slice::from_ref(vector)))
+                .collect(),
+        )
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        self.remove_batch(keys)
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        self.search_batch(queries)
+    }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        Some(self.filtered_search_batch(queries))
+    }
+}
+
+// a multi-vector index, each key has a group of vectors
+impl NonFilteringExternalIndex<VectorGroup, Vec<f64>> for USearchKNNIndex {
+    fn add(&mut self, add_data: Vec<(Key, VectorGroup)>) -> Vec<(Key, DynResult<()>)> {
+        self.add_batch(
+            add_data
+                .iter()
+                .map(|(key, VectorGroup(vectors))| (*key, vectors.as_slice()))
+                .collect(),
+        )
+    }
+
+    fn remove(&mut self, keys: Vec<Key>) -> Vec<(Key, DynResult<()>)> {
+        self.remove_batch(keys)
+    }
+
+    fn search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize)],
+    ) -> Vec<(Key, DynResult<Vec<KeyScoreMatch>>)> {
+        self.search_batch(queries)
+    }
+
+    fn filtered_search(
+        &self,
+        queries: &[(Key, Vec<f64>, usize, KeyFilter)],
+    ) -> Option<Vec<(Key, DynResult<Vec<KeyScoreMatch>>)>> {
+        Some(self.filtered_search_batch(queries))
+    }
 }
 
 // index factory structure
@@ -125,9 +259,12 @@ pub struct USearchKNNIndexFactory {
     connectivity: usize,
     expansion_add: usize,
     expansion_search: usize,
+    quantization: ScalarKind,
+    multi: bool,
 }
 
 impl USearchKNNIndexFactory {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         dimensions: usize,
         reserved_space: usize,
@@ -135,6 +272,8 @@ impl USearchKNNIndexFactory {
         connectivity: usize,
         expansion_add: usize,
         expansion_search: usize,
+        quantization: ScalarKind,
+        multi: bool,
     ) -> USearchKNNIndexFactory {
         USearchKNNIndexFactory {
             dimensions,
@@ -143,6 +282,8 @@ impl USearchKNNIndexFactory {
             connectivity,
             expansion_add,
             expansion_search,
+            quantization,
+            multi,
         }
     }
 }
@@ -157,7 +298,17 @@ impl ExternalIndexFactory for USearchKNNIndexFactory {
             self.connectivity,
             self.expansion_add,
             self.expansion_search,
+            self.quantization,
+            self.multi,
         )?;
-        Ok(Box::new(DerivedFilteredSearchIndex::new(Box::new(u_index))))
+        if self.multi {
+            Ok(Box::new(DerivedFilteredSearchIndex::<VectorGroup, _>::new(
+                Box::new(u_index),
+            )))
+        } else {
+            Ok(Box::new(DerivedFilteredSearchIndex::<Vec<f64>, _>::new(
+                Box::new(u_index),
+            )))
+        }
     }
 }
@@ -12,20 +12,77 @@ pub mod connectors;
 pub mod deepcopy;
 pub mod engine;
 pub mod external_integration;
+pub mod mat_mul;
 pub mod persistence;
 pub mod python_api;
 
 mod env;
 mod fs_helpers;
-mod mat_mul;
 mod pipe;
 mod retry;
 mod timestamp;
 
 #[cfg(not(feature = "standard-allocator"))]
 mod jemalloc {
+    use std::ffi::CStr;
+    use std::mem::size_of;
+    use std::ptr;
+
     use jemallocator::Jemalloc;
 
     #[global_allocator]
     static GLOBAL_ALLOCATOR: Jemalloc = Jemalloc;
+
+    const MEMORY_STATS: [(&str, &CStr); 5] = [
+        ("allocated", c"stats.allocated"),
+        ("active", c"stats.active"),
+        ("resident", c"stats.resident"),
+        ("mapped", c"stats.mapped"),
+        ("retained", c"stats.retained"),
+    ];
+
+    fn read_stat(name: &CStr) -> Option<usize> {
+        let mut value: usize = 0;
+        let mut len = size_of::<usize>();
+        // SAFETY: the statistics read are of type `size_t` and `len` is its size
+        let result = unsafe {
+            jemalloc_sys::mallctl(
+                name.as_ptr(),
+                ptr::addr_of_mut!(value).cast(),
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        (result == 0).then_some(value)
+    }
+
+    /// Returns the memory statistics of the allocator in bytes.
+    pub fn memory_stats() -> Vec<(&'static str, usize)> {
+        // the statistics are cached by jemalloc until the epoch is advanced
+        let mut epoch: u64 = 1;
+        let mut len = size_of::<u64>();
+        // SAFETY: `epoch` is read and written as `uint64_t` and `len` is its size
+        unsafe {
+            jemalloc_sys::mallctl(
+                c"epoch".as_ptr(),
+                ptr::addr_of_mut!(epoch).cast(),
+                &mut len,
+                ptr::addr_of_mut!(epoch).cast(),
+                len,
+            );
+        }
+        MEMORY_STATS
+            .iter()
+            .filter_map(|(name, stat)| read_stat(stat).map(|value| (*name, value)))
+            .collect()
+    }
+}
+
+#[cfg(feature = "standard-allocator")]
+mod jemalloc {
+    /// The statistics are not available with the standard allocator.
+    pub fn memory_stats() -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
 }
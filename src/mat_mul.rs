@@ -1,7 +1,13 @@
 // Copyright © 2024 Pathway
 
+use std::cmp::{max, min};
+use std::ops::Range;
+
 use ndarray::{arr0, ArrayD, ArrayViewD, Ix1, Ix2, LinalgScalar};
 
+// narrower blocks make the matrix multiplication degenerate into many vector products
+const MIN_BLOCK_COLUMNS: usize = 256;
+
 pub fn mat_mul<T>(a: &ArrayViewD<T>, b: &ArrayViewD<T>) -> Option<ArrayD<T>>
 where
     T: LinalgScalar,
@@ -27,3 +33,30 @@ where
     }
     None
 }
+
+// Splits a `rows` x `columns` matrix product into blocks of at most `max_block_size` entries
+// (unless a single column is larger than that). Blocks are returned column block by column block.
+pub fn product_blocks(
+    rows: usize,
+    columns: usize,
+    max_block_size: usize,
+) -> impl Iterator<Item = (Range<usize>, Range<usize>)> {
+    let max_block_size = max(max_block_size, 1);
+    let block_columns = min(
+        columns,
+        max(
+            max_block_size / max(rows, 1),
+            min(MIN_BLOCK_COLUMNS, max_block_size),
+        ),
+    )
+    .max(1);
+    let block_rows = min(rows, max(max_block_size / block_columns, 1)).max(1);
+    (0..columns).step_by(block_columns).flat_map(move |column| {
+        (0..rows).step_by(block_rows).map(move |row| {
+            (
+                row..min(row + block_rows, rows),
+                column..min(column + block_columns, columns),
+            )
+        })
+    })
+}
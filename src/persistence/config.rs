@@ -3,55 +3,219 @@
 #![allow(clippy::module_name_repetitions)]
 
 use log::{error, info, warn};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
 use std::fs;
 use std::io::Error as IoError;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use s3::bucket::Bucket as S3Bucket;
-
-use crate::connectors::data_storage::S3CommandName;
 use crate::connectors::data_storage::{ReadError, WriteError};
 use crate::connectors::snapshot::{
-    Event, LocalBinarySnapshotReader, LocalBinarySnapshotWriter, MockSnapshotReader,
-    ReadSnapshotEvent, S3SnapshotReader, S3SnapshotWriter, SnapshotMode, SnapshotReader,
+    migrate_local_chunks, migrate_object_store_chunks, Event, LocalBinarySnapshotReader,
+    LocalBinarySnapshotWriter, MockSnapshotReader, ObjectStoreSnapshotReader,
+    ObjectStoreSnapshotWriter, ReadSnapshotEvent, SnapshotMode, SnapshotReader,
 };
 use crate::connectors::{PersistenceMode, SnapshotAccess};
-use crate::deepcopy::DeepCopy;
 use crate::engine::{Timestamp, TotalFrontier};
 use crate::fs_helpers::ensure_directory;
+use crate::persistence::encryption::Keyring;
 use crate::persistence::metadata_backends::Error as MetadataBackendError;
 use crate::persistence::metadata_backends::{
-    FilesystemKVStorage, MetadataBackend, MockKVStorage, S3KVStorage,
+    EncryptedKVStorage, FilesystemKVStorage, MetadataBackend, MockKVStorage, ObjectStoreKVStorage,
 };
-use crate::persistence::state::MetadataAccessor;
+use crate::persistence::object_store::{ObjectStore, ObjectStoreConfig};
+use crate::persistence::state::{MetadataAccessor, RollbackTarget};
 use crate::persistence::{PersistentId, SharedSnapshotWriter};
 
 const STREAMS_DIRECTORY_NAME: &str = "streams";
 
 pub type ConnectorWorkerPair = (PersistentId, usize);
 
+fn object_store_streams_path(root_path: &str) -> String {
+    match root_path.strip_suffix('/').unwrap_or(root_path) {
+        "" => format!("{STREAMS_DIRECTORY_NAME}/"),
+        root_path => format!("{root_path}/{STREAMS_DIRECTORY_NAME}/"),
+    }
+}
+
 /// Metadata storage handles the frontier over all persisted data sources.
 /// When we restart the computation, it will start from the frontier stored
 /// in the metadata storage
 #[derive(Debug, Clone)]
 pub enum MetadataStorageConfig {
     Filesystem(PathBuf),
-    S3 { bucket: S3Bucket, root_path: String },
+    ObjectStore {
+        store: ObjectStoreConfig,
+        root_path: String,
+    },
     Mock,
 }
 
+impl MetadataStorageConfig {
+    /// Creates the backend, which encrypts the stored values if `encryption` is given.
+    pub fn create_backend(
+        &self,
+        encryption: Option<&Arc<Keyring>>,
+    ) -> Result<Box<dyn MetadataBackend>, MetadataBackendError> {
+        let backend: Box<dyn MetadataBackend> = match self {
+            MetadataStorageConfig::Filesystem(root_path) => {
+                Box::new(FilesystemKVStorage::new(root_path)?)
+            }
+            MetadataStorageConfig::ObjectStore { store, root_path } => {
+                Box::new(ObjectStoreKVStorage::new(store.connect()?, root_path))
+            }
+            MetadataStorageConfig::Mock => Box::new(MockKVStorage {}),
+        };
+        Ok(match encryption {
+            Some(encryption) => Box::new(EncryptedKVStorage::new(backend, encryption.clone())),
+            None => backend,
+        })
+    }
+}
+
 /// Stream storage handles the snapshot, which will be loaded when Pathway
 /// program restarts. It can also handle the cache for UDF calls
 #[derive(Debug, Clone)]
 pub enum StreamStorageConfig {
     Filesystem(PathBuf),
-    S3 { bucket: S3Bucket, root_path: String },
+    ObjectStore {
+        store: ObjectStoreConfig,
+        root_path: String,
+    },
     Mock(HashMap<ConnectorWorkerPair, Vec<Event>>),
 }
 
+impl StreamStorageConfig {
+    /// Returns all persisted snapshot streams as pairs of the persistent id and
+    /// the id of the worker that has written the stream.
+    pub fn snapshot_streams(&self) -> Result<Vec<ConnectorWorkerPair>, ReadError> {
+        let mut streams = BTreeSet::new();
+        match self {
+            StreamStorageConfig::Filesystem(root_path) => {
+                let streams_dir = root_path.join(STREAMS_DIRECTORY_NAME);
+                if !streams_dir.exists() {
+                    return Ok(Vec::new());
+                }
+                for worker_entry in fs::read_dir(&streams_dir)? {
+                    let worker_entry = worker_entry?;
+                    let Some(worker_id) = parse_path_component(&worker_entry.file_name()) else {
+                        warn!("Unexpected object in snapshot directory: {worker_entry:?}");
+                        continue;
+                    };
+                    for stream_entry in fs::read_dir(worker_entry.path())? {
+                        let stream_entry = stream_entry?;
+                        match parse_path_component(&stream_entry.file_name()) {
+                            Some(persistent_id) => {
+                                streams.insert((persistent_id, worker_id));
+                            }
+                            None => {
+                                warn!("Unexpected object in snapshot directory: {stream_entry:?}");
+                            }
+                        }
+                    }
+                }
+            }
+            StreamStorageConfig::ObjectStore { store, root_path } => {
+                let snapshots_root_path = object_store_streams_path(root_path);
+                let keys = store.connect()?.list_objects(&snapshots_root_path)?;
+                streams.extend(parse_snapshot_keys(
+                    keys.iter().map(String::as_str),
+                    &snapshots_root_path,
+                ));
+            }
+            StreamStorageConfig::Mock(event_map) => streams.extend(event_map.keys().copied()),
+        }
+        Ok(streams.into_iter().collect())
+    }
+
+    /// Creates a reader of the stream, written by the given worker.
+    pub fn create_worker_snapshot_reader(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+        encryption: Option<Arc<Keyring>>,
+    ) -> Result<Box<dyn ReadSnapshotEvent>, ReadError> {
+        match self {
+            StreamStorageConfig::Filesystem(root_path) => Ok(Box::new(
+                LocalBinarySnapshotReader::new(
+                    root_path
+                        .join(STREAMS_DIRECTORY_NAME)
+                        .join(worker_id.to_string())
+                        .join(persistent_id.to_string()),
+                )?
+                .with_encryption(encryption),
+            )),
+            StreamStorageConfig::ObjectStore { store, root_path } => {
+                let snapshot_path = format!(
+                    "{}{worker_id}/{persistent_id}",
+                    object_store_streams_path(root_path)
+                );
+                Ok(Box::new(
+                    ObjectStoreSnapshotReader::new(store.connect()?, &snapshot_path)?
+                        .with_encryption(encryption),
+                ))
+            }
+            StreamStorageConfig::Mock(event_map) => {
+                let events = event_map
+                    .get(&(persistent_id, worker_id))
+                    .cloned()
+                    .unwrap_or_default();
+                Ok(Box::new(MockSnapshotReader::new(events)))
+            }
+        }
+    }
+
+    /// Rewrites the chunks of the stream, written by the given worker, that are not in
+    /// the current format: the ones that predate the framed snapshot format and, if
+    /// `encryption` is given, the ones that aren't encrypted with the current key.
+    /// Returns the number of rewritten chunks.
+    pub fn migrate_worker_snapshot(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+        encryption: Option<&Arc<Keyring>>,
+    ) -> Result<usize, ReadError> {
+        match self {
+            StreamStorageConfig::Filesystem(root_path) => migrate_local_chunks(
+                &root_path
+                    .join(STREAMS_DIRECTORY_NAME)
+                    .join(worker_id.to_string())
+                    .join(persistent_id.to_string()),
+                encryption,
+            ),
+            StreamStorageConfig::ObjectStore { store, root_path } => {
+                let snapshot_path = format!(
+                    "{}{worker_id}/{persistent_id}",
+                    object_store_streams_path(root_path)
+                );
+                migrate_object_store_chunks(store.connect()?.as_ref(), &snapshot_path, encryption)
+            }
+            StreamStorageConfig::Mock(_) => Ok(0),
+        }
+    }
+}
+
+fn parse_path_component<T: FromStr>(name: &OsStr) -> Option<T> {
+    name.to_str()?.parse().ok()
+}
+
+// The keys have the form {snapshots_root_path}{worker_id}/{persistent_id}/{snapshot_block_id}
+fn parse_snapshot_keys<'a>(
+    keys: impl Iterator<Item = &'a str> + 'a,
+    snapshots_root_path: &'a str,
+) -> impl Iterator<Item = ConnectorWorkerPair> + 'a {
+    keys.filter_map(move |key| {
+        let snapshot_path_block = key.strip_prefix(snapshots_root_path)?;
+        let mut path_parts = snapshot_path_block.split('/');
+        let worker_id = path_parts.next()?.parse().ok()?;
+        let persistent_id = path_parts.next()?.parse().ok()?;
+        Some((persistent_id, worker_id))
+    })
+}
+
 /// Persistence in Pathway consists of two parts: actual frontier
 /// storage and maintenance and snapshotting.
 ///
@@ -65,6 +229,10 @@ pub struct PersistenceManagerOuterConfig {
     snapshot_access: SnapshotAccess,
     persistence_mode: PersistenceMode,
     continue_after_replay: bool,
+    snapshot_compaction_interval: Option<Duration>,
+    checkpoint_operator_state: bool,
+    encryption: Option<Arc<Keyring>>,
+    rollback_to: Option<RollbackTarget>,
 }
 
 impl PersistenceManagerOuterConfig {
@@ -83,9 +251,48 @@ impl PersistenceManagerOuterConfig {
             snapshot_access,
             persistence_mode,
             continue_after_replay,
+            snapshot_compaction_interval: None,
+            checkpoint_operator_state: false,
+            encryption: None,
+            rollback_to: None,
         }
     }
 
+    /// Enables periodic compaction of the input snapshots up to the finalized time.
+    #[must_use]
+    pub fn with_snapshot_compaction_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_compaction_interval = Some(interval);
+        self
+    }
+
+    /// Makes the stateful operators checkpoint their inputs, so that after a restart
+    /// their state is loaded instead of being recomputed from the replayed data.
+    #[must_use]
+    pub fn with_operator_state_checkpointing(mut self, enabled: bool) -> Self {
+        self.checkpoint_operator_state = enabled;
+        self
+    }
+
+    /// Encrypts the snapshots and the metadata with the current key of the keyring.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Arc<Keyring>) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Restarts the computation as of the earlier finalized time `timestamp` instead of
+    /// the latest one. The rollback is recorded in the metadata along with `generation`,
+    /// so it happens only on the first start with this setting. To roll back to the same
+    /// time once again, a new `generation` has to be given.
+    #[must_use]
+    pub fn with_rollback_to(mut self, timestamp: Timestamp, generation: u64) -> Self {
+        self.rollback_to = Some(RollbackTarget {
+            timestamp,
+            generation,
+        });
+        self
+    }
+
     pub fn into_inner(self, worker_id: usize, total_workers: usize) -> PersistenceManagerConfig {
         PersistenceManagerConfig::new(self, worker_id, total_workers)
     }
@@ -102,7 +309,11 @@ pub struct PersistenceManagerConfig {
     pub continue_after_replay: bool,
     pub worker_id: usize,
     pub snapshot_interval: Duration,
+    pub snapshot_compaction_interval: Option<Duration>,
+    pub checkpoint_operator_state: bool,
     total_workers: usize,
+    encryption: Option<Arc<Keyring>>,
+    rollback_to: Option<RollbackTarget>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -157,22 +368,25 @@ impl PersistenceManagerConfig {
             persistence_mode: outer_config.persistence_mode,
             continue_after_replay: outer_config.continue_after_replay,
             snapshot_interval: outer_config.snapshot_interval,
+            snapshot_compaction_interval: outer_config.snapshot_compaction_interval,
+            checkpoint_operator_state: outer_config.checkpoint_operator_state,
             worker_id,
             total_workers,
+            encryption: outer_config.encryption,
+            rollback_to: outer_config.rollback_to,
         }
     }
 
     pub fn create_metadata_storage(&self) -> Result<MetadataAccessor, MetadataBackendError> {
-        let backend: Box<dyn MetadataBackend> = match &self.metadata_storage {
-            MetadataStorageConfig::Filesystem(root_path) => {
-                Box::new(FilesystemKVStorage::new(root_path)?)
-            }
-            MetadataStorageConfig::S3 { bucket, root_path } => {
-                Box::new(S3KVStorage::new(bucket.deep_copy(), root_path))
-            }
-            MetadataStorageConfig::Mock => Box::new(MockKVStorage {}),
-        };
-        MetadataAccessor::new(backend, self.worker_id)
+        let backend = self
+            .metadata_storage
+            .create_backend(self.encryption.as_ref())?;
+        let mut metadata_storage =
+            MetadataAccessor::new(backend, self.worker_id, self.total_workers)?;
+        if let Some(target) = self.rollback_to {
+            metadata_storage.rollback_to(target)?;
+        }
+        Ok(metadata_storage)
     }
 
     pub fn create_snapshot_readers(
@@ -188,14 +402,21 @@ impl PersistenceManagerConfig {
                 let assigned_snapshot_paths =
                     self.assigned_snapshot_paths(root_path, persistent_id, query_purpose)?;
                 for (worker_id, path) in assigned_snapshot_paths {
-                    reader_impls.insert(worker_id, Box::new(LocalBinarySnapshotReader::new(path)?));
+                    reader_impls.insert(
+                        worker_id,
+                        Box::new(
+                            LocalBinarySnapshotReader::new(path)?
+                                .with_encryption(self.encryption.clone()),
+                        ),
+                    );
                 }
                 reader_impls
             }
-            StreamStorageConfig::S3 { bucket, root_path } => {
+            StreamStorageConfig::ObjectStore { store, root_path } => {
                 let mut reader_impls = HashMap::<usize, Box<dyn ReadSnapshotEvent>>::new();
-                let assigned_snapshot_paths = self.assigned_s3_snapshot_paths(
-                    &bucket.deep_copy(),
+                let store = store.connect()?;
+                let assigned_snapshot_paths = self.assigned_object_store_snapshot_paths(
+                    store.as_ref(),
                     root_path,
                     persistent_id,
                     query_purpose,
@@ -203,7 +424,10 @@ impl PersistenceManagerConfig {
                 for (worker_id, path) in assigned_snapshot_paths {
                     reader_impls.insert(
                         worker_id,
-                        Box::new(S3SnapshotReader::new(bucket.deep_copy(), &path)?),
+                        Box::new(
+                            ObjectStoreSnapshotReader::new(store.clone(), &path)?
+                                .with_encryption(self.encryption.clone()),
+                        ),
                     );
                 }
                 reader_impls
@@ -245,15 +469,19 @@ impl PersistenceManagerConfig {
                 LocalBinarySnapshotWriter::new(
                     &self.snapshot_writer_path(root_path, persistent_id)?,
                     snapshot_mode,
-                )?,
+                )?
+                .with_encryption(self.encryption.clone()),
             )))),
-            StreamStorageConfig::S3 { bucket, root_path } => {
-                let snapshot_path = self.s3_snapshot_path(root_path, persistent_id);
-                Ok(Arc::new(Mutex::new(Box::new(S3SnapshotWriter::new(
-                    bucket.deep_copy(),
-                    &snapshot_path,
-                    snapshot_mode,
-                )))))
+            StreamStorageConfig::ObjectStore { store, root_path } => {
+                let snapshot_path = format!(
+                    "{}{}/{persistent_id}",
+                    object_store_streams_path(root_path),
+                    self.worker_id
+                );
+                Ok(Arc::new(Mutex::new(Box::new(
+                    ObjectStoreSnapshotWriter::new(store.connect()?, &snapshot_path, snapshot_mode)
+                        .with_encryption(self.encryption.clone()),
+                ))))
             }
             StreamStorageConfig::Mock(_) => {
                 unreachable!()
@@ -274,15 +502,6 @@ impl PersistenceManagerConfig {
         Ok(worker_path.join(persistent_id.to_string()))
     }
 
-    fn s3_snapshot_path(&self, root_path: &str, persistent_id: PersistentId) -> String {
-        format!(
-            "{}/streams/{}/{}",
-            root_path.strip_suffix('/').unwrap_or(root_path),
-            self.worker_id,
-            persistent_id
-        )
-    }
-
     fn assigned_snapshot_paths(
         &self,
         root_path: &Path,
@@ -332,56 +551,45 @@ impl PersistenceManagerConfig {
         Ok(assigned_paths)
     }
 
-    fn assigned_s3_snapshot_paths(
+    fn assigned_object_store_snapshot_paths(
         &self,
-        bucket: &S3Bucket,
+        store: &dyn ObjectStore,
         root_path: &str,
         persistent_id: PersistentId,
         query_purpose: ReadersQueryPurpose,
     ) -> Result<HashMap<usize, String>, ReadError> {
-        let snapshots_root_path = format!(
-            "{}/streams/",
-            root_path.strip_suffix('/').unwrap_or(root_path)
-        );
-        let prefix_len = snapshots_root_path.len();
-        let object_lists = bucket
-            .list(snapshots_root_path.clone(), None)
-            .map_err(|e| ReadError::S3(S3CommandName::ListObjectsV2, e))?;
+        let snapshots_root_path = object_store_streams_path(root_path);
 
         let mut assigned_paths = HashMap::new();
+        for key in store.list_objects(&snapshots_root_path)? {
+            let Some(snapshot_path_block) = key.strip_prefix(&snapshots_root_path) else {
+                continue;
+            };
+            // snapshot_path_block has the form {worker_id}/{persistent_id}/{snapshot_block_id}
+            let path_parts: Vec<&str> = snapshot_path_block.split('/').collect();
+            if path_parts.len() != 3 {
+                error!("Incorrect path block format: {snapshot_path_block}");
+                continue;
+            }
+            if path_parts[1] != persistent_id.to_string() {
+                continue;
+            }
 
-        for list in &object_lists {
-            for object in &list.contents {
-                let key: &str = &object.key;
-                assert!(key.len() > prefix_len);
-                let snapshot_path_block = key[prefix_len..].to_string();
-                // snapshot_path_block has the form {worker_id}/{persistent_id}/{snapshot_block_id}
-                let path_parts: Vec<&str> = snapshot_path_block.split('/').collect();
-                if path_parts.len() != 3 {
-                    error!("Incorrect path block format: {snapshot_path_block}");
-                    continue;
-                }
-
-                let parsed_worker_id: usize = match path_parts[0].parse() {
-                    Ok(worker_id) => worker_id,
-                    Err(e) => {
-                        error!("Could not parse worker id from snapshot directory {key:?}. Error details: {e}");
-                        continue;
-                    }
-                };
-
-                if !query_purpose.includes_worker(
-                    parsed_worker_id,
-                    self.worker_id,
-                    self.total_workers,
-                ) {
+            let parsed_worker_id: usize = match path_parts[0].parse() {
+                Ok(worker_id) => worker_id,
+                Err(e) => {
+                    error!("Could not parse worker id from snapshot directory {key:?}. Error details: {e}");
                     continue;
                 }
+            };
 
-                let snapshot_path =
-                    format!("{snapshots_root_path}{parsed_worker_id}/{persistent_id}");
-                assigned_paths.insert(parsed_worker_id, snapshot_path);
+            if !query_purpose.includes_worker(parsed_worker_id, self.worker_id, self.total_workers)
+            {
+                continue;
             }
+
+            let snapshot_path = format!("{snapshots_root_path}{parsed_worker_id}/{persistent_id}");
+            assigned_paths.insert(parsed_worker_id, snapshot_path);
         }
 
         Ok(assigned_paths)
@@ -0,0 +1,342 @@
+// Copyright © 2024 Pathway
+
+//! Client-side authenticated encryption of the persisted snapshots and metadata.
+//!
+//! The data is encrypted with AES-256-GCM. Each encrypted piece of data is tagged with
+//! the id of the key it was encrypted with, so that the keys can be rotated: the new data
+//! is always encrypted with the current key, while the previous keys are kept only to
+//! decrypt the data that hasn't been rewritten yet. The snapshots are re-encrypted with
+//! the current key when they are compacted and the metadata when it is saved.
+//!
+//! The keys are never used for the encryption directly. Each snapshot chunk and each metadata
+//! value is encrypted with its own key, derived with HMAC-SHA256 from the configured key and
+//! a random salt stored along with the data. As the derived keys aren't reused, the nonces
+//! can be deterministic: the blocks of a chunk use their indices. The key ids are derived
+//! with HMAC-SHA256 as well, so that they tell nothing about the keys.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::Error as IoError;
+use std::path::Path;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The encryption keys, separated by commas. The first one is the current key.
+pub const ENCRYPTION_KEY_ENV_VAR: &str = "PATHWAY_PERSISTENCE_ENCRYPTION_KEY";
+
+/// The path to the file with the encryption keys, one per line. The first one is the current key.
+pub const ENCRYPTION_KEY_FILE_ENV_VAR: &str = "PATHWAY_PERSISTENCE_ENCRYPTION_KEY_FILE";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const KEY_ID_LEN: usize = 8;
+pub const SALT_LEN: usize = 16;
+
+// The purposes of the derived keys, so that the keys derived for one of them
+// are never equal to the ones derived for another.
+const KEY_ID_CONTEXT: &[u8] = b"pathway-persistence-key-id";
+const CHUNK_KEY_CONTEXT: &[u8] = b"pathway-persistence-chunk-key";
+const VALUE_KEY_CONTEXT: &[u8] = b"pathway-persistence-value-key";
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("failed to read the encryption key file: {0}")]
+    Io(#[from] IoError),
+
+    #[error("encryption key must be 32 bytes written as 64 hexadecimal digits")]
+    MalformedKey,
+
+    #[error("no encryption key given")]
+    NoKeys,
+
+    #[error("data is encrypted, but no encryption key is configured")]
+    MissingKey,
+
+    #[error("data is not encrypted, while the encryption is enabled; migrate the persisted state to encrypt it")]
+    Unencrypted,
+
+    #[error("data is encrypted with an unknown key {0:#018x}")]
+    UnknownKey(u64),
+
+    #[error("failed to decrypt data, it is either damaged or encrypted with another key")]
+    Decryption,
+
+    #[error("malformed encrypted data")]
+    MalformedCiphertext,
+}
+
+fn parse_key(key: &str) -> Result<[u8; KEY_LEN], Error> {
+    let key = key.trim();
+    if key.len() != 2 * KEY_LEN || !key.is_ascii() {
+        return Err(Error::MalformedKey);
+    }
+    let mut parsed = [0; KEY_LEN];
+    for (i, byte) in parsed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[2 * i..2 * i + 2], 16).map_err(|_| Error::MalformedKey)?;
+    }
+    Ok(parsed)
+}
+
+fn derive_key(key: &[u8; KEY_LEN], context: &[u8], salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC should accept keys of any length");
+    mac.update(context);
+    mac.update(salt);
+    mac.finalize().into_bytes().into()
+}
+
+fn key_id(key: &[u8; KEY_LEN]) -> u64 {
+    let derived = derive_key(key, KEY_ID_CONTEXT, &[]);
+    u64::from_le_bytes(derived[..KEY_ID_LEN].try_into().unwrap())
+}
+
+fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// The current encryption key along with the previous ones, which are still needed
+/// to decrypt the data written before the rotation.
+#[derive(Clone)]
+pub struct Keyring {
+    // the first key is the current one
+    keys: Vec<(u64, [u8; KEY_LEN])>,
+}
+
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keyring")
+            .field("current_key_id", &self.current_key_id())
+            .field("n_keys", &self.keys.len())
+            .finish()
+    }
+}
+
+impl Keyring {
+    pub fn new(current_key: [u8; KEY_LEN], previous_keys: &[[u8; KEY_LEN]]) -> Self {
+        let keys = std::iter::once(&current_key)
+            .chain(previous_keys)
+            .map(|key| (key_id(key), *key))
+            .collect();
+        Self { keys }
+    }
+
+    /// Parses the hexadecimal keys, separated by commas or whitespace.
+    /// The first one is the current key.
+    pub fn parse(keys: &str) -> Result<Self, Error> {
+        let keys = keys
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|key| !key.is_empty())
+            .map(parse_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (current_key, previous_keys) = keys.split_first().ok_or(Error::NoKeys)?;
+        Ok(Self::new(*current_key, previous_keys))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Loads the keys from the given file or, if it's not given, from the environment.
+    /// Returns `None` if no keys are configured, meaning that the encryption is disabled.
+    pub fn load(key_file: Option<&Path>) -> Result<Option<Self>, Error> {
+        if let Some(key_file) = key_file {
+            return Self::from_file(key_file).map(Some);
+        }
+        if let Ok(keys) = env::var(ENCRYPTION_KEY_ENV_VAR) {
+            return Self::parse(&keys).map(Some);
+        }
+        if let Ok(key_file) = env::var(ENCRYPTION_KEY_FILE_ENV_VAR) {
+            return Self::from_file(Path::new(&key_file)).map(Some);
+        }
+        Ok(None)
+    }
+
+    pub fn current_key_id(&self) -> u64 {
+        self.keys[0].0
+    }
+
+    fn key(&self, key_id: u64) -> Result<&[u8; KEY_LEN], Error> {
+        self.keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, key)| key)
+            .ok_or(Error::UnknownKey(key_id))
+    }
+
+    /// Creates the cipher for a new chunk, which encrypts it with the current key.
+    pub fn new_chunk_cipher(&self, chunk_name: &str) -> ChunkCipher {
+        let (key_id, key) = &self.keys[0];
+        ChunkCipher::new(*key_id, key, new_salt(), chunk_name)
+    }
+
+    /// Creates the cipher for an existing chunk, given the key id and the salt from its header.
+    pub fn chunk_cipher(
+        &self,
+        key_id: u64,
+        salt: [u8; SALT_LEN],
+        chunk_name: &str,
+    ) -> Result<ChunkCipher, Error> {
+        Ok(ChunkCipher::new(
+            key_id,
+            self.key(key_id)?,
+            salt,
+            chunk_name,
+        ))
+    }
+
+    /// Encrypts a value with the current key. The result contains the id of the key and
+    /// the salt of the derived key, so that it can be decrypted by `open` without other data
+    /// than `context`, which has to be the same as in this call.
+    pub fn seal(&self, context: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let (key_id, key) = &self.keys[0];
+        let salt = new_salt();
+        let cipher = Aes256Gcm::new(&derive_key(key, VALUE_KEY_CONTEXT, &salt).into());
+        // the derived key encrypts a single value, so the nonce can be constant
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&[0; NONCE_LEN]),
+                Payload {
+                    msg: plaintext,
+                    aad: context,
+                },
+            )
+            .expect("encryption of a buffer in memory should not fail");
+        let mut sealed = Vec::with_capacity(KEY_ID_LEN + SALT_LEN + ciphertext.len());
+        sealed.extend_from_slice(&key_id.to_le_bytes());
+        sealed.extend_from_slice(&salt);
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    pub fn open(&self, context: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+        if sealed.len() < KEY_ID_LEN + SALT_LEN {
+            return Err(Error::MalformedCiphertext);
+        }
+        let (key_id, rest) = sealed.split_at(KEY_ID_LEN);
+        let (salt, ciphertext) = rest.split_at(SALT_LEN);
+        let key = self.key(u64::from_le_bytes(key_id.try_into().unwrap()))?;
+        let cipher = Aes256Gcm::new(&derive_key(key, VALUE_KEY_CONTEXT, salt).into());
+        cipher
+            .decrypt(
+                Nonce::from_slice(&[0; NONCE_LEN]),
+                Payload {
+                    msg: ciphertext,
+                    aad: context,
+                },
+            )
+            .map_err(|_| Error::Decryption)
+    }
+}
+
+/// Encrypts the blocks of a single snapshot chunk with the key derived for the chunk.
+///
+/// The additional authenticated data of a block contains the name of the chunk and
+/// the index of the block, so that the blocks can't be reordered or moved between chunks.
+/// The last block of a chunk is an empty one, marking its end, so that the chunk can't be
+/// cut without notice.
+pub struct ChunkCipher {
+    key_id: u64,
+    salt: [u8; SALT_LEN],
+    chunk_name: String,
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for ChunkCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkCipher")
+            .field("key_id", &self.key_id)
+            .field("chunk_name", &self.chunk_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChunkCipher {
+    fn new(key_id: u64, key: &[u8; KEY_LEN], salt: [u8; SALT_LEN], chunk_name: &str) -> Self {
+        Self {
+            key_id,
+            salt,
+            chunk_name: chunk_name.to_string(),
+            cipher: Aes256Gcm::new(&derive_key(key, CHUNK_KEY_CONTEXT, &salt).into()),
+        }
+    }
+
+    pub fn key_id(&self) -> u64 {
+        self.key_id
+    }
+
+    pub fn salt(&self) -> &[u8; SALT_LEN] {
+        &self.salt
+    }
+
+    // The end marker has a nonce of its own, as it can be written again at the same index
+    // when a chunk is cut, while the nonce of a data block must never be reused.
+    fn nonce(block_index: u64, is_end: bool) -> [u8; NONCE_LEN] {
+        let mut nonce = [0; NONCE_LEN];
+        nonce[..8].copy_from_slice(&block_index.to_le_bytes());
+        nonce[8] = u8::from(is_end);
+        nonce
+    }
+
+    fn associated_data(&self, block_index: u64, is_end: bool) -> Vec<u8> {
+        let mut associated_data = self.chunk_name.as_bytes().to_vec();
+        associated_data.extend_from_slice(&block_index.to_le_bytes());
+        associated_data.push(u8::from(is_end));
+        associated_data
+    }
+
+    fn encrypt(&self, block_index: u64, is_end: bool, plaintext: &[u8]) -> Vec<u8> {
+        self.cipher
+            .encrypt(
+                Nonce::from_slice(&Self::nonce(block_index, is_end)),
+                Payload {
+                    msg: plaintext,
+                    aad: &self.associated_data(block_index, is_end),
+                },
+            )
+            .expect("encryption of a buffer in memory should not fail")
+    }
+
+    /// Encrypts the block with the given index. The block must not be empty.
+    pub fn encrypt_block(&self, block_index: u64, plaintext: &[u8]) -> Vec<u8> {
+        assert!(
+            !plaintext.is_empty(),
+            "an empty block marks the end of a chunk"
+        );
+        self.encrypt(block_index, false, plaintext)
+    }
+
+    /// Creates the marker of the end of the chunk, following the block with the given index.
+    pub fn encrypt_end(&self, block_index: u64) -> Vec<u8> {
+        self.encrypt(block_index, true, &[])
+    }
+
+    /// Decrypts the block with the given index. Returns `None` if it's the end marker.
+    pub fn decrypt_block(
+        &self,
+        block_index: u64,
+        ciphertext: &[u8],
+    ) -> Result<Option<Vec<u8>>, Error> {
+        // only the end marker has an empty plaintext, so its ciphertext is just the tag
+        let is_end = ciphertext.len() == TAG_LEN;
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&Self::nonce(block_index, is_end)),
+                Payload {
+                    msg: ciphertext,
+                    aad: &self.associated_data(block_index, is_end),
+                },
+            )
+            .map_err(|_| Error::Decryption)?;
+        Ok((!is_end).then_some(plaintext))
+    }
+}
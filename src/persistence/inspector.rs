@@ -0,0 +1,215 @@
+// Copyright © 2024 Pathway
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use log::info;
+use serde_json::{json, Value as JsonValue};
+
+use crate::connectors::data_storage::ReadError;
+use crate::connectors::snapshot::{read_all_events, Event, SnapshotReader};
+use crate::engine::{Timestamp, TotalFrontier, Value};
+use crate::persistence::config::{ConnectorWorkerPair, MetadataStorageConfig, StreamStorageConfig};
+use crate::persistence::encryption::Keyring;
+use crate::persistence::metadata_backends::{EncryptedKVStorage, Error as MetadataBackendError};
+use crate::persistence::state::{MetadataAccessor, StoredMetadata};
+use crate::persistence::PersistentId;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Read(#[from] ReadError),
+
+    #[error(transparent)]
+    Metadata(#[from] MetadataBackendError),
+
+    #[error("the persisted state is in use by the workers {0:?}; if the program has crashed, force the change")]
+    StateInUse(Vec<usize>),
+}
+
+/// Offline access to the persisted state: listing and dumping the snapshot streams and
+/// the metadata, and rolling the streams back to an earlier time.
+///
+/// The state isn't modified while a program with the same persistence config is running,
+/// unless it's forced.
+#[allow(clippy::module_name_repetitions)]
+pub struct PersistenceInspector {
+    metadata_storage: MetadataStorageConfig,
+    stream_storage: StreamStorageConfig,
+    encryption: Option<Arc<Keyring>>,
+    force: bool,
+}
+
+impl PersistenceInspector {
+    pub fn new(
+        metadata_storage: MetadataStorageConfig,
+        stream_storage: StreamStorageConfig,
+    ) -> Self {
+        Self {
+            metadata_storage,
+            stream_storage,
+            encryption: None,
+            force: false,
+        }
+    }
+
+    /// Sets the keys to decrypt the persisted state with. The migrated streams are
+    /// encrypted with the current key.
+    #[must_use]
+    pub fn with_encryption(mut self, encryption: Option<Arc<Keyring>>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Allows modifying the state even if the run locks of the workers are present.
+    /// A program that has crashed leaves its locks behind.
+    #[must_use]
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    fn ensure_not_in_use(&self) -> Result<(), Error> {
+        if self.force {
+            return Ok(());
+        }
+        let backend = self
+            .metadata_storage
+            .create_backend(self.encryption.as_ref())?;
+        let locked_workers = MetadataAccessor::locked_workers(backend.as_ref())?;
+        if locked_workers.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::StateInUse(locked_workers))
+        }
+    }
+
+    pub fn snapshot_streams(&self) -> Result<Vec<ConnectorWorkerPair>, ReadError> {
+        self.stream_storage.snapshot_streams()
+    }
+
+    pub fn metadata_blocks(&self) -> Result<BTreeMap<usize, StoredMetadata>, MetadataBackendError> {
+        MetadataAccessor::latest_blocks(
+            self.metadata_storage
+                .create_backend(self.encryption.as_ref())?
+                .as_ref(),
+        )
+    }
+
+    pub fn finalized_timestamps(
+        &self,
+    ) -> Result<BTreeMap<usize, TotalFrontier<Timestamp>>, MetadataBackendError> {
+        Ok(self
+            .metadata_blocks()?
+            .into_iter()
+            .map(|(worker_id, block)| (worker_id, block.last_advanced_timestamp()))
+            .collect())
+    }
+
+    pub fn read_events(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+    ) -> Result<Vec<Event>, ReadError> {
+        let mut reader = self.stream_storage.create_worker_snapshot_reader(
+            persistent_id,
+            worker_id,
+            self.encryption.clone(),
+        )?;
+        read_all_events(reader.as_mut())
+    }
+
+    /// Removes the events following the first time advancement not earlier than `time`,
+    /// the same way as it is done when the stream is rewound up to the finalized time.
+    /// The finalized times of all workers are rolled back to `time` as well, so that
+    /// the other streams are truncated at it on the next run.
+    /// Returns the number of the events left in the stream.
+    pub fn truncate_stream(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+        time: Timestamp,
+    ) -> Result<usize, Error> {
+        self.ensure_not_in_use()?;
+        let mut backend = self
+            .metadata_storage
+            .create_backend(self.encryption.as_ref())?;
+        MetadataAccessor::roll_back_blocks(backend.as_mut(), time)?;
+
+        let reader_impl = self.stream_storage.create_worker_snapshot_reader(
+            persistent_id,
+            worker_id,
+            self.encryption.clone(),
+        )?;
+        let mut reader = SnapshotReader::new(reader_impl, TotalFrontier::At(time), true)?;
+        while !matches!(reader.read()?, Event::Finished) {}
+        let n_events_left = self.read_events(persistent_id, worker_id)?.len();
+        info!(
+            "Truncated the stream {persistent_id} of worker {worker_id} to {n_events_left} events"
+        );
+        Ok(n_events_left)
+    }
+
+    /// Rewrites the chunks of the stream that predate the framed snapshot format or,
+    /// if the encryption is enabled, aren't encrypted with the current key.
+    /// Returns the number of the rewritten chunks.
+    pub fn migrate_stream(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+    ) -> Result<usize, Error> {
+        self.ensure_not_in_use()?;
+        let n_migrated = self.stream_storage.migrate_worker_snapshot(
+            persistent_id,
+            worker_id,
+            self.encryption.as_ref(),
+        )?;
+        info!("Migrated {n_migrated} chunks of the stream {persistent_id} of worker {worker_id}");
+        Ok(n_migrated)
+    }
+
+    /// Encrypts the metadata values that were stored unencrypted, if the encryption
+    /// is enabled. Returns the number of the encrypted values.
+    pub fn migrate_metadata(&self) -> Result<usize, Error> {
+        self.ensure_not_in_use()?;
+        let Some(encryption) = &self.encryption else {
+            return Ok(0);
+        };
+        let mut backend = EncryptedKVStorage::new(
+            self.metadata_storage.create_backend(None)?,
+            encryption.clone(),
+        );
+        let n_migrated = backend.encrypt_plaintext_values()?;
+        info!("Encrypted {n_migrated} metadata values");
+        Ok(n_migrated)
+    }
+}
+
+fn values_to_json(values: &[Value]) -> JsonValue {
+    values.iter().map(ToString::to_string).collect()
+}
+
+/// Represents the snapshot event in JSON. The keys and the values are formatted the same
+/// way as they are printed, since some of them have no JSON counterpart.
+pub fn event_to_json(event: &Event) -> JsonValue {
+    match event {
+        Event::Insert(key, values) => {
+            json!({"type": "insert", "key": key.to_string(), "values": values_to_json(values)})
+        }
+        Event::Delete(key, values) => {
+            json!({"type": "delete", "key": key.to_string(), "values": values_to_json(values)})
+        }
+        Event::Upsert(key, values) => json!({
+            "type": "upsert",
+            "key": key.to_string(),
+            "values": values.as_deref().map(values_to_json),
+        }),
+        Event::AdvanceTime(time, frontier) => json!({
+            "type": "advance_time",
+            "time": time.0,
+            "frontier": frontier,
+        }),
+        Event::Finished => json!({"type": "finished"}),
+    }
+}
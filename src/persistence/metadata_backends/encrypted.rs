@@ -0,0 +1,72 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use crate::persistence::encryption::{Error as EncryptionError, Keyring};
+use crate::persistence::metadata_backends::{Error, MetadataBackend};
+
+const ENCRYPTED_VALUE_PREFIX: &str = "encrypted:";
+const BASE32_ALPHABET: base32::Alphabet = base32::Alphabet::Crockford;
+
+/// Encrypts the values stored in the wrapped backend. The values that were stored
+/// unencrypted are rejected, so that they can't be injected, until they are encrypted
+/// by the explicit migration. An encrypted value is bound to its key, so it can't be
+/// moved under another one.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EncryptedKVStorage {
+    backend: Box<dyn MetadataBackend>,
+    encryption: Arc<Keyring>,
+}
+
+impl EncryptedKVStorage {
+    pub fn new(backend: Box<dyn MetadataBackend>, encryption: Arc<Keyring>) -> Self {
+        Self {
+            backend,
+            encryption,
+        }
+    }
+
+    /// Encrypts the values that were stored before the encryption was enabled.
+    /// Returns the number of the encrypted values.
+    pub fn encrypt_plaintext_values(&mut self) -> Result<usize, Error> {
+        let mut n_encrypted = 0;
+        for key in self.backend.list_keys()? {
+            let value = self.backend.get_value(&key)?;
+            if !value.starts_with(ENCRYPTED_VALUE_PREFIX) {
+                self.put_value(&key, &value)?;
+                n_encrypted += 1;
+            }
+        }
+        Ok(n_encrypted)
+    }
+}
+
+impl MetadataBackend for EncryptedKVStorage {
+    fn list_keys(&self) -> Result<Vec<String>, Error> {
+        self.backend.list_keys()
+    }
+
+    fn get_value(&self, key: &str) -> Result<String, Error> {
+        let value = self.backend.get_value(key)?;
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_VALUE_PREFIX) else {
+            return Err(EncryptionError::Unencrypted.into());
+        };
+        let sealed =
+            base32::decode(BASE32_ALPHABET, encoded).ok_or(EncryptionError::MalformedCiphertext)?;
+        let decrypted = self.encryption.open(key.as_bytes(), &sealed)?;
+        Ok(String::from_utf8(decrypted).map_err(|e| e.utf8_error())?)
+    }
+
+    fn put_value(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        // the value is always encrypted with the current key, which rotates the key
+        let sealed = self.encryption.seal(key.as_bytes(), value.as_bytes());
+        let encoded = base32::encode(BASE32_ALPHABET, &sealed);
+        self.backend
+            .put_value(key, &format!("{ENCRYPTED_VALUE_PREFIX}{encoded}"))
+    }
+
+    fn remove_key(&self, key: &str) -> Result<(), Error> {
+        self.backend.remove_key(key)
+    }
+}
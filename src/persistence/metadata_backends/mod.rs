@@ -4,15 +4,20 @@ use std::fmt::Debug;
 use std::io::Error as IoError;
 use std::str::Utf8Error;
 
-use ::s3::error::S3Error;
 use serde_json::Error as ParseError;
 
+use crate::engine::{Timestamp, TotalFrontier};
+use crate::persistence::encryption::Error as EncryptionError;
+use crate::persistence::object_store::Error as ObjectStoreError;
+
+pub mod encrypted;
 pub mod file;
 pub mod mock;
-pub mod s3;
+pub mod object_store;
+pub use encrypted::EncryptedKVStorage;
 pub use file::FilesystemKVStorage;
 pub use mock::MockKVStorage;
-pub use s3::S3KVStorage;
+pub use object_store::ObjectStoreKVStorage;
 
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -21,13 +26,30 @@ pub enum Error {
     FileSystem(#[from] IoError),
 
     #[error(transparent)]
-    S3(#[from] S3Error),
+    Utf8(#[from] Utf8Error),
 
     #[error(transparent)]
-    Utf8(#[from] Utf8Error),
+    ObjectStore(#[from] ObjectStoreError),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
 
     #[error("metadata entry {0:?} incorrectly formatted: {1}")]
     IncorrectFormat(String, #[source] ParseError),
+
+    #[error(
+        "can't roll back to time {requested}, as only the times up to {finalized:?} are finalized"
+    )]
+    RollbackTimeNotFinalized {
+        requested: Timestamp,
+        finalized: TotalFrontier<Timestamp>,
+    },
+
+    #[error("can't roll back to time {requested}, as the snapshots are compacted up to {compaction_threshold:?}")]
+    RollbackTimeCompacted {
+        requested: Timestamp,
+        compaction_threshold: TotalFrontier<Timestamp>,
+    },
 }
 
 pub trait MetadataBackend: Send + Debug {
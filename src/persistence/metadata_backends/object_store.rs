@@ -0,0 +1,62 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use crate::persistence::metadata_backends::{Error, MetadataBackend};
+use crate::persistence::object_store::ObjectStore;
+
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ObjectStoreKVStorage {
+    store: Arc<dyn ObjectStore>,
+    root_path: String,
+}
+
+impl ObjectStoreKVStorage {
+    pub fn new(store: Arc<dyn ObjectStore>, root_path: &str) -> Self {
+        let mut root_path_prepared = root_path.to_string();
+        if !root_path.is_empty() && !root_path.ends_with('/') {
+            root_path_prepared += "/";
+        }
+        Self {
+            store,
+            root_path: root_path_prepared,
+        }
+    }
+
+    fn full_key_path(&self, key: &str) -> String {
+        self.root_path.clone() + key
+    }
+}
+
+impl MetadataBackend for ObjectStoreKVStorage {
+    fn list_keys(&self) -> Result<Vec<String>, Error> {
+        let keys = self
+            .store
+            .list_objects(&self.root_path)?
+            .into_iter()
+            .filter_map(|key| {
+                let key = key.strip_prefix(&self.root_path)?;
+                // the snapshots are stored in the nested paths
+                (!key.contains('/')).then(|| key.to_string())
+            })
+            .collect();
+        Ok(keys)
+    }
+
+    fn get_value(&self, key: &str) -> Result<String, Error> {
+        let value = self.store.get_object(&self.full_key_path(key))?;
+        Ok(std::str::from_utf8(&value)?.to_string())
+    }
+
+    fn put_value(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        self.store
+            .put_object(&self.full_key_path(key), value.as_bytes())?;
+        Ok(())
+    }
+
+    fn remove_key(&self, key: &str) -> Result<(), Error> {
+        self.store.remove_object(&self.full_key_path(key))?;
+        Ok(())
+    }
+}
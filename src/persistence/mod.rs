@@ -7,8 +7,11 @@ use xxhash_rust::xxh3::Xxh3 as Hasher;
 use crate::connectors::snapshot::WriteSnapshotEvent;
 
 pub mod config;
+pub mod encryption;
 pub mod frontier;
+pub mod inspector;
 pub mod metadata_backends;
+pub mod object_store;
 pub mod state;
 pub mod tracker;
 
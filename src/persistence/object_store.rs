@@ -0,0 +1,570 @@
+// Copyright © 2024 Pathway
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use log::warn;
+use once_cell::sync::OnceCell;
+use postgres::tls::{ChannelBinding, MakeTlsConnect, TlsConnect, TlsStream as PostgresTlsStream};
+use postgres::Client as PostgresClient;
+use reqwest::blocking::{Client as HttpClient, Response as HttpResponse};
+use reqwest::{StatusCode, Url};
+use rusqlite::{params, Connection as SqliteConnection, TransactionBehavior};
+use s3::bucket::Bucket as S3Bucket;
+use s3::error::S3Error;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_native_tls::{TlsConnector, TlsStream};
+
+use crate::deepcopy::DeepCopy;
+
+const TABLE_NAME: &str = "pathway_persistence";
+const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(60);
+const AZURE_API_VERSION: &str = "2021-08-06";
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    S3(#[from] S3Error),
+
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error(transparent)]
+    Postgres(#[from] postgres::Error),
+
+    #[error(transparent)]
+    Tls(#[from] native_tls::Error),
+
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error("{0} request to the object store failed with status {1}")]
+    UnexpectedStatus(&'static str, StatusCode),
+
+    #[error("malformed object store url: {0}")]
+    MalformedUrl(String),
+
+    #[error("malformed object store response: {0}")]
+    MalformedXml(String),
+}
+
+/// A flat storage of binary objects, addressed by string keys.
+///
+/// An object is always replaced atomically, so the concurrent readers, including the
+/// ones in other processes, see either its old or its new value.
+pub trait ObjectStore: Send + Sync + Debug {
+    /// Returns the keys of all objects that start with `prefix`.
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, Error>;
+    fn get_object(&self, key: &str) -> Result<Vec<u8>, Error>;
+    fn put_object(&self, key: &str, value: &[u8]) -> Result<(), Error>;
+    fn remove_object(&self, key: &str) -> Result<(), Error>;
+}
+
+/// The object stores that can keep the persisted state. All of them share the same
+/// layout of the metadata and the snapshots.
+#[derive(Debug, Clone)]
+pub enum ObjectStoreConfig {
+    /// S3 or an S3-compatible storage, such as Google Cloud Storage.
+    S3(S3Bucket),
+
+    /// Azure Blob Storage container, addressed by an URL with a SAS token.
+    Azure(String),
+
+    /// A table in the local `SQLite` database.
+    Sqlite(PathBuf),
+
+    /// A table in the Postgres database, addressed by a connection string.
+    Postgres(PostgresStoreConfig),
+}
+
+impl ObjectStoreConfig {
+    pub fn connect(&self) -> Result<Arc<dyn ObjectStore>, Error> {
+        match self {
+            Self::S3(bucket) => Ok(Arc::new(S3ObjectStore::new(bucket.deep_copy()))),
+            Self::Azure(container_sas_url) => {
+                Ok(Arc::new(AzureBlobObjectStore::new(container_sas_url)?))
+            }
+            Self::Sqlite(path) => Ok(Arc::new(SqliteObjectStore::new(path)?)),
+            Self::Postgres(config) => Ok(config.connect()?),
+        }
+    }
+}
+
+/// The Postgres store is connected on the first use and the connection is then
+/// shared by all clones of the config, so that the table is created only once.
+#[derive(Clone)]
+pub struct PostgresStoreConfig {
+    connection_string: String,
+    store: Arc<OnceCell<Arc<PostgresObjectStore>>>,
+}
+
+impl Debug for PostgresStoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresStoreConfig")
+            .finish_non_exhaustive()
+    }
+}
+
+impl PostgresStoreConfig {
+    pub fn new(connection_string: String) -> Self {
+        Self {
+            connection_string,
+            store: Arc::new(OnceCell::new()),
+        }
+    }
+
+    pub fn connect(&self) -> Result<Arc<PostgresObjectStore>, Error> {
+        self.store
+            .get_or_try_init(|| Ok(Arc::new(PostgresObjectStore::new(&self.connection_string)?)))
+            .cloned()
+    }
+}
+
+#[derive(Debug)]
+pub struct S3ObjectStore {
+    bucket: S3Bucket,
+}
+
+impl S3ObjectStore {
+    pub fn new(bucket: S3Bucket) -> Self {
+        Self { bucket }
+    }
+}
+
+impl ObjectStore for S3ObjectStore {
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let object_lists = self.bucket.list(prefix.to_string(), None)?;
+        Ok(object_lists
+            .into_iter()
+            .flat_map(|list| list.contents)
+            .map(|object| object.key)
+            .collect())
+    }
+
+    fn get_object(&self, key: &str) -> Result<Vec<u8>, Error> {
+        Ok(self.bucket.get_object(key)?.bytes().to_vec())
+    }
+
+    fn put_object(&self, key: &str, value: &[u8]) -> Result<(), Error> {
+        let _ = self.bucket.put_object(key, value)?;
+        Ok(())
+    }
+
+    fn remove_object(&self, key: &str) -> Result<(), Error> {
+        let _ = self.bucket.delete_object(key)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct AzureBlobObjectStore {
+    container_url: Url,
+    sas_token: String,
+    client: HttpClient,
+}
+
+impl AzureBlobObjectStore {
+    pub fn new(container_sas_url: &str) -> Result<Self, Error> {
+        let Some((container_url, sas_token)) = container_sas_url.split_once('?') else {
+            return Err(Error::MalformedUrl(
+                "the container url must contain a SAS token".to_string(),
+            ));
+        };
+        let container_url = Url::parse(container_url.trim_end_matches('/'))
+            .map_err(|e| Error::MalformedUrl(e.to_string()))?;
+        if container_url.cannot_be_a_base() {
+            return Err(Error::MalformedUrl(format!(
+                "{container_url} can't address the blobs"
+            )));
+        }
+        Ok(Self {
+            container_url,
+            sas_token: sas_token.to_string(),
+            client: HttpClient::new(),
+        })
+    }
+
+    fn container_url(&self) -> Url {
+        let mut url = self.container_url.clone();
+        url.set_query(Some(&self.sas_token));
+        url
+    }
+
+    // The parts of the key are percent-encoded separately, so that the slashes
+    // still separate the virtual directories.
+    fn blob_url(&self, key: &str) -> Url {
+        let mut url = self.container_url();
+        url.path_segments_mut()
+            .expect("the container url is checked to be a base")
+            .extend(key.split('/'));
+        url
+    }
+
+    fn check_status(
+        request_name: &'static str,
+        response: HttpResponse,
+    ) -> Result<HttpResponse, Error> {
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(Error::UnexpectedStatus(request_name, response.status()))
+        }
+    }
+}
+
+// The listing only has to extract the blob names and the continuation marker,
+// which are never nested in the response.
+fn xml_element_texts(document: &str, element: &str) -> Result<Vec<String>, Error> {
+    let opening_tag = format!("<{element}>");
+    let closing_tag = format!("</{element}>");
+    let mut texts = Vec::new();
+    let mut rest = document;
+    while let Some(start) = rest.find(&opening_tag) {
+        rest = &rest[start + opening_tag.len()..];
+        let Some(end) = rest.find(&closing_tag) else {
+            break;
+        };
+        texts.push(xml_unescape(&rest[..end])?);
+        rest = &rest[end + closing_tag.len()..];
+    }
+    Ok(texts)
+}
+
+fn xml_unescape(text: &str) -> Result<String, Error> {
+    let malformed = || Error::MalformedXml(text.to_string());
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let end = rest.find(';').ok_or_else(malformed)?;
+        let unescaped = match &rest[..end] {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            reference => {
+                let code = if let Some(hex) = reference.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16)
+                } else if let Some(decimal) = reference.strip_prefix('#') {
+                    decimal.parse()
+                } else {
+                    return Err(malformed());
+                };
+                code.ok().and_then(char::from_u32).ok_or_else(malformed)?
+            }
+        };
+        result.push(unescaped);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+impl ObjectStore for AzureBlobObjectStore {
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let mut keys = Vec::new();
+        let mut marker = String::new();
+        loop {
+            let mut request = self
+                .client
+                .get(self.container_url())
+                .header("x-ms-version", AZURE_API_VERSION)
+                .query(&[
+                    ("restype", "container"),
+                    ("comp", "list"),
+                    ("prefix", prefix),
+                ]);
+            if !marker.is_empty() {
+                request = request.query(&[("marker", marker.as_str())]);
+            }
+            let response = request.send()?;
+            let listing = Self::check_status("List Blobs", response)?.text()?;
+            keys.extend(xml_element_texts(&listing, "Name")?);
+            match xml_element_texts(&listing, "NextMarker")?.pop() {
+                Some(next_marker) if !next_marker.is_empty() => marker = next_marker,
+                _ => return Ok(keys),
+            }
+        }
+    }
+
+    fn get_object(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let response = self
+            .client
+            .get(self.blob_url(key))
+            .header("x-ms-version", AZURE_API_VERSION)
+            .send()?;
+        Ok(Self::check_status("Get Blob", response)?.bytes()?.to_vec())
+    }
+
+    fn put_object(&self, key: &str, value: &[u8]) -> Result<(), Error> {
+        let response = self
+            .client
+            .put(self.blob_url(key))
+            .header("x-ms-version", AZURE_API_VERSION)
+            .header("x-ms-blob-type", "BlockBlob")
+            .body(value.to_vec())
+            .send()?;
+        Self::check_status("Put Blob", response)?;
+        Ok(())
+    }
+
+    fn remove_object(&self, key: &str) -> Result<(), Error> {
+        let response = self
+            .client
+            .delete(self.blob_url(key))
+            .header("x-ms-version", AZURE_API_VERSION)
+            .send()?;
+        Self::check_status("Delete Blob", response)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct SqliteObjectStore {
+    connection: Mutex<SqliteConnection>,
+}
+
+impl SqliteObjectStore {
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        let connection = SqliteConnection::open(path)?;
+        // several processes may access the database concurrently
+        connection.busy_timeout(SQLITE_BUSY_TIMEOUT)?;
+        connection.query_row("PRAGMA journal_mode=WAL", [], |_row| Ok(()))?;
+        connection.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {TABLE_NAME} (key TEXT PRIMARY KEY, value BLOB NOT NULL)"
+        ))?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+impl ObjectStore for SqliteObjectStore {
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(&format!(
+            "SELECT key FROM {TABLE_NAME} WHERE substr(key, 1, length(?1)) = ?1"
+        ))?;
+        let keys = statement
+            .query_map([prefix], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(keys)
+    }
+
+    fn get_object(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let connection = self.connection.lock().unwrap();
+        let value = connection.query_row(
+            &format!("SELECT value FROM {TABLE_NAME} WHERE key = ?1"),
+            [key],
+            |row| row.get(0),
+        )?;
+        Ok(value)
+    }
+
+    fn put_object(&self, key: &str, value: &[u8]) -> Result<(), Error> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        transaction.execute(
+            &format!(
+                "INSERT INTO {TABLE_NAME} (key, value) VALUES (?1, ?2)
+                ON CONFLICT (key) DO UPDATE SET value = excluded.value"
+            ),
+            params![key, value],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn remove_object(&self, key: &str) -> Result<(), Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(&format!("DELETE FROM {TABLE_NAME} WHERE key = ?1"), [key])?;
+        Ok(())
+    }
+}
+
+pub struct PostgresObjectStore {
+    connection_string: String,
+    tls: MakeNativeTlsConnect,
+    client: Mutex<PostgresClient>,
+}
+
+impl Debug for PostgresObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresObjectStore")
+            .finish_non_exhaustive()
+    }
+}
+
+impl PostgresObjectStore {
+    pub fn new(connection_string: &str) -> Result<Self, Error> {
+        let tls = MakeNativeTlsConnect::new()?;
+        let mut client = PostgresClient::connect(connection_string, tls.clone())?;
+        client.batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {TABLE_NAME} (key TEXT PRIMARY KEY, value BYTEA NOT NULL)"
+        ))?;
+        Ok(Self {
+            connection_string: connection_string.to_string(),
+            tls,
+            client: Mutex::new(client),
+        })
+    }
+
+    // All queries are idempotent, so the one that fails because the connection
+    // is lost is retried once on a new connection.
+    fn run<T>(
+        &self,
+        query: impl Fn(&mut PostgresClient) -> Result<T, postgres::Error>,
+    ) -> Result<T, Error> {
+        let mut client = self.client.lock().unwrap();
+        if client.is_closed() {
+            *client = PostgresClient::connect(&self.connection_string, self.tls.clone())?;
+        }
+        match query(&mut client) {
+            Err(e) if e.is_closed() || client.is_closed() => {
+                warn!("The connection to the Postgres object store is lost, reconnecting: {e}");
+                *client = PostgresClient::connect(&self.connection_string, self.tls.clone())?;
+                Ok(query(&mut client)?)
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
+impl ObjectStore for PostgresObjectStore {
+    fn list_objects(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let rows = self.run(|client| {
+            client.query(
+                &format!("SELECT key FROM {TABLE_NAME} WHERE left(key, length($1)) = $1"),
+                &[&prefix],
+            )
+        })?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    fn get_object(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let row = self.run(|client| {
+            client.query_one(
+                &format!("SELECT value FROM {TABLE_NAME} WHERE key = $1"),
+                &[&key],
+            )
+        })?;
+        Ok(row.get(0))
+    }
+
+    fn put_object(&self, key: &str, value: &[u8]) -> Result<(), Error> {
+        self.run(|client| {
+            let mut transaction = client.transaction()?;
+            transaction.execute(
+                &format!(
+                    "INSERT INTO {TABLE_NAME} (key, value) VALUES ($1, $2)
+                    ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value"
+                ),
+                &[&key, &value],
+            )?;
+            transaction.commit()
+        })
+    }
+
+    fn remove_object(&self, key: &str) -> Result<(), Error> {
+        self.run(|client| {
+            client.execute(&format!("DELETE FROM {TABLE_NAME} WHERE key = $1"), &[&key])
+        })?;
+        Ok(())
+    }
+}
+
+/// Wraps the connections to Postgres in TLS, using the system certificates.
+/// Whether TLS is used is decided by `sslmode` in the connection string.
+#[derive(Clone)]
+struct MakeNativeTlsConnect(TlsConnector);
+
+impl MakeNativeTlsConnect {
+    fn new() -> Result<Self, Error> {
+        Ok(Self(native_tls::TlsConnector::new()?.into()))
+    }
+}
+
+impl<S> MakeTlsConnect<S> for MakeNativeTlsConnect
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Stream = NativeTlsStream<S>;
+    type TlsConnect = NativeTlsConnect;
+    type Error = native_tls::Error;
+
+    fn make_tls_connect(&mut self, domain: &str) -> Result<NativeTlsConnect, native_tls::Error> {
+        Ok(NativeTlsConnect {
+            connector: self.0.clone(),
+            domain: domain.to_string(),
+        })
+    }
+}
+
+struct NativeTlsConnect {
+    connector: TlsConnector,
+    domain: String,
+}
+
+impl<S> TlsConnect<S> for NativeTlsConnect
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Stream = NativeTlsStream<S>;
+    type Error = native_tls::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<NativeTlsStream<S>, native_tls::Error>> + Send>>;
+
+    fn connect(self, stream: S) -> Self::Future {
+        Box::pin(async move {
+            let stream = self.connector.connect(&self.domain, stream).await?;
+            Ok(NativeTlsStream(stream))
+        })
+    }
+}
+
+struct NativeTlsStream<S>(TlsStream<S>);
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for NativeTlsStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for NativeTlsStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> PostgresTlsStream for NativeTlsStream<S> {
+    fn channel_binding(&self) -> ChannelBinding {
+        ChannelBinding::none()
+    }
+}
@@ -1,8 +1,9 @@
 // Copyright © 2024 Pathway
 
 use log::{error, info, warn};
-use std::cmp::max;
-use std::collections::HashMap;
+use std::cmp::{max, min};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::mem::swap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -16,10 +17,28 @@ use crate::persistence::PersistentId;
 
 const EXPECTED_KEY_PARTS: usize = 3;
 
+// Each worker of a running program keeps the key {RUN_LOCK_KEY_PREFIX}{worker_id},
+// which tells the offline tools that the persisted state is in use.
+const RUN_LOCK_KEY_PREFIX: &str = "run-lock-";
+
+/// A rollback of the persisted state to an earlier finalized time. It is applied once:
+/// the runs with the same rollback configured restart as of the latest finalized time.
+/// Rolling back to the same time again requires another generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollbackTarget {
+    pub timestamp: Timestamp,
+    pub generation: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StoredMetadata {
     storage_types: HashMap<PersistentId, StorageType>,
     last_advanced_timestamp: TotalFrontier<Timestamp>,
+    // the snapshots are compacted up to this time, so they can't be rolled back past it
+    #[serde(default)]
+    compaction_threshold: Option<TotalFrontier<Timestamp>>,
+    #[serde(default)]
+    applied_rollback: Option<RollbackTarget>,
 }
 
 #[derive(Debug)]
@@ -27,6 +46,8 @@ pub struct MetadataAccessor {
     backend: Box<dyn MetadataBackend>,
     internal_state: StoredMetadata,
     past_runs_threshold_times: HashMap<usize, TotalFrontier<Timestamp>>,
+    adopted_keys: Vec<String>,
+    run_lock_key: String,
 
     current_key_to_use: String,
     next_key_to_use: String,
@@ -37,6 +58,8 @@ impl StoredMetadata {
         Self {
             storage_types: HashMap::new(),
             last_advanced_timestamp: TotalFrontier::At(Timestamp(0)),
+            compaction_threshold: None,
+            applied_rollback: None,
         }
     }
 
@@ -50,8 +73,25 @@ impl StoredMetadata {
         serde_json::to_string(&self).unwrap()
     }
 
+    pub fn storage_types(&self) -> &HashMap<PersistentId, StorageType> {
+        &self.storage_types
+    }
+
+    pub fn last_advanced_timestamp(&self) -> TotalFrontier<Timestamp> {
+        self.last_advanced_timestamp
+    }
+
+    pub fn compaction_threshold(&self) -> Option<TotalFrontier<Timestamp>> {
+        self.compaction_threshold
+    }
+
+    pub fn applied_rollback(&self) -> Option<RollbackTarget> {
+        self.applied_rollback
+    }
+
     pub fn merge(&mut self, other: &StoredMetadata) {
         self.storage_types.extend(other.storage_types.iter());
+        self.compaction_threshold = max(self.compaction_threshold, other.compaction_threshold);
     }
 }
 
@@ -69,6 +109,9 @@ struct MetadataKey {
 
 impl MetadataKey {
     fn from_str(key: &str) -> Option<Self> {
+        if key.starts_with(RUN_LOCK_KEY_PREFIX) {
+            return None;
+        }
         let key_parts: Vec<&str> = key.split('-').collect();
         if key_parts.len() != EXPECTED_KEY_PARTS {
             error!("Wrong format of persistent entry key: {key}");
@@ -117,10 +160,16 @@ impl Display for MetadataKey {
 }
 
 impl MetadataAccessor {
-    pub fn new(backend: Box<dyn MetadataBackend>, worker_id: usize) -> Result<Self, Error> {
-        let (internal_state, past_runs_threshold_times) = {
+    pub fn new(
+        mut backend: Box<dyn MetadataBackend>,
+        worker_id: usize,
+        total_workers: usize,
+    ) -> Result<Self, Error> {
+        let (internal_state, past_runs_threshold_times, adopted_keys) = {
             let mut internal_state = StoredMetadata::new();
-            let mut past_runs_threshold_times = HashMap::new();
+            let mut former_threshold_times = HashMap::new();
+            let mut adopted_keys = Vec::new();
+            let mut applied_rollbacks = Vec::new();
             let mut last_version_per_worker = HashMap::new();
 
             let keys = backend.list_keys()?;
@@ -156,11 +205,20 @@ impl MetadataAccessor {
                 }
 
                 let other_worker_id = metadata_key.worker_id;
+                if other_worker_id >= total_workers && other_worker_id % total_workers == worker_id
+                {
+                    // The former worker doesn't exist in the current run. Its snapshots
+                    // are read by this worker, so it also takes over its metadata block
+                    // which is removed once this worker saves its own state.
+                    info!("Worker {worker_id} adopts the metadata block {metadata_key} of the former worker {other_worker_id}");
+                    adopted_keys.push(key.clone());
+                }
+
                 let raw_block = backend.get_value(&key)?;
                 let block_result = StoredMetadata::parse(&raw_block);
                 match block_result {
                     Ok(block) => {
-                        past_runs_threshold_times
+                        former_threshold_times
                             .entry(other_worker_id)
                             .and_modify(|timestamp: &mut TotalFrontier<Timestamp>| {
                                 *timestamp = max(*timestamp, block.last_advanced_timestamp);
@@ -168,6 +226,7 @@ impl MetadataAccessor {
                             .or_insert(block.last_advanced_timestamp);
                         info!("Merge the current state with block: {block:?}");
                         internal_state.merge(&block);
+                        applied_rollbacks.push(block.applied_rollback);
                     }
                     Err(e) => {
                         warn!("Broken offsets block with key {key}. Error: {e}");
@@ -175,7 +234,32 @@ impl MetadataAccessor {
                 };
             }
 
-            (internal_state, past_runs_threshold_times)
+            // With fewer workers than before, the former workers are merged into the
+            // current ones in the same way as their snapshots are: the worker that reads
+            // the snapshots can only rewind up to the time finalized by all of their writers.
+            // With more workers than before, the new workers have no state of their own yet.
+            let mut past_runs_threshold_times = HashMap::new();
+            for (other_worker_id, threshold_time) in former_threshold_times {
+                past_runs_threshold_times
+                    .entry(other_worker_id % total_workers)
+                    .and_modify(|timestamp: &mut TotalFrontier<Timestamp>| {
+                        *timestamp = min(*timestamp, threshold_time);
+                    })
+                    .or_insert(threshold_time);
+            }
+
+            // The rollback counts as applied only if all workers have saved it. Otherwise
+            // it is applied again by all of them, so that their finalized times stay consistent.
+            if let Some(first_rollback) = applied_rollbacks.first() {
+                if applied_rollbacks
+                    .iter()
+                    .all(|rollback| rollback == first_rollback)
+                {
+                    internal_state.applied_rollback = *first_rollback;
+                }
+            }
+
+            (internal_state, past_runs_threshold_times, adopted_keys)
         };
 
         let current_timestamp = {
@@ -188,11 +272,15 @@ impl MetadataAccessor {
             MetadataKey::from_components(current_timestamp, worker_id, 0).to_string();
         let next_key_to_use =
             MetadataKey::from_components(current_timestamp, worker_id, 1).to_string();
+        let run_lock_key = format!("{RUN_LOCK_KEY_PREFIX}{worker_id}");
+        backend.put_value(&run_lock_key, &current_timestamp.to_string())?;
 
         Ok(Self {
             backend,
             internal_state,
             past_runs_threshold_times,
+            adopted_keys,
+            run_lock_key,
             current_key_to_use,
             next_key_to_use,
         })
@@ -220,11 +308,177 @@ impl MetadataAccessor {
         self.internal_state.last_advanced_timestamp
     }
 
+    /// Records that the snapshots are going to be compacted up to `threshold`.
+    /// It has to be saved before the compaction starts, as the history before
+    /// the threshold is lost and the rollbacks to it are no longer possible.
+    pub fn accept_compaction_threshold(&mut self, threshold: TotalFrontier<Timestamp>) {
+        self.internal_state.compaction_threshold =
+            max(self.internal_state.compaction_threshold, Some(threshold));
+    }
+
+    /// Makes the run restart as of the earlier finalized time of `target`: the snapshots
+    /// are rewound only up to it, so the data and the outputs following it are processed
+    /// again. The rolled back state is saved right away along with `target`, so that
+    /// the snapshot tails truncated by this run are never read against the former finalized
+    /// time and that the next runs with the same `target` don't roll back again.
+    pub fn rollback_to(&mut self, target: RollbackTarget) -> Result<(), Error> {
+        if self.internal_state.applied_rollback == Some(target) {
+            info!("The rollback to {target:?} is already applied");
+            return Ok(());
+        }
+        let timestamp = target.timestamp;
+        let requested = TotalFrontier::At(timestamp);
+        let finalized = self
+            .past_runs_threshold_times
+            .values()
+            .min()
+            .copied()
+            .unwrap_or(TotalFrontier::At(Timestamp(0)));
+        if requested > finalized {
+            return Err(Error::RollbackTimeNotFinalized {
+                requested: timestamp,
+                finalized,
+            });
+        }
+        if let Some(compaction_threshold) = self.internal_state.compaction_threshold {
+            if requested < compaction_threshold {
+                return Err(Error::RollbackTimeCompacted {
+                    requested: timestamp,
+                    compaction_threshold,
+                });
+            }
+        }
+
+        info!("Rolling back the persisted state from {finalized:?} to {timestamp}");
+        for threshold_time in self.past_runs_threshold_times.values_mut() {
+            *threshold_time = min(*threshold_time, requested);
+        }
+        self.internal_state.last_advanced_timestamp = requested;
+        self.internal_state.applied_rollback = Some(target);
+        self.save_current_state()
+    }
+
+    /// Returns the latest metadata block of each worker, without modifying the storage.
+    /// Of the two rotated blocks of the same version, the one with the greater time is taken.
+    pub fn latest_blocks(
+        backend: &dyn MetadataBackend,
+    ) -> Result<BTreeMap<usize, StoredMetadata>, Error> {
+        let mut latest_per_worker: BTreeMap<usize, (u128, StoredMetadata)> = BTreeMap::new();
+        for key in backend.list_keys()? {
+            let Some(metadata_key) = MetadataKey::from_str(&key) else {
+                continue;
+            };
+            let block = match StoredMetadata::parse(&backend.get_value(&key)?) {
+                Ok(block) => block,
+                Err(e) => {
+                    warn!("Broken offsets block with key {key}. Error: {e}");
+                    continue;
+                }
+            };
+            match latest_per_worker.entry(metadata_key.worker_id) {
+                Entry::Occupied(mut entry) => {
+                    let (version, latest_block) = entry.get();
+                    if metadata_key.version > *version
+                        || (metadata_key.version == *version
+                            && block.last_advanced_timestamp > latest_block.last_advanced_timestamp)
+                    {
+                        entry.insert((metadata_key.version, block));
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((metadata_key.version, block));
+                }
+            }
+        }
+        Ok(latest_per_worker
+            .into_iter()
+            .map(|(worker_id, (_version, block))| (worker_id, block))
+            .collect())
+    }
+
+    /// Returns the ids of the workers whose run locks are present, that is, the workers of
+    /// the running programs and the ones of the programs that have crashed.
+    pub fn locked_workers(backend: &dyn MetadataBackend) -> Result<Vec<usize>, Error> {
+        Ok(backend
+            .list_keys()?
+            .iter()
+            .filter_map(|key| key.strip_prefix(RUN_LOCK_KEY_PREFIX)?.parse().ok())
+            .collect())
+    }
+
+    /// Rolls the latest metadata blocks of all workers back to `timestamp`, so that the
+    /// next run rewinds the snapshots only up to it. Nothing is changed if the snapshots
+    /// are compacted past `timestamp`.
+    pub fn roll_back_blocks(
+        backend: &mut dyn MetadataBackend,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let requested = TotalFrontier::At(timestamp);
+        let mut rolled_back_blocks = Vec::new();
+        for key in backend.list_keys()? {
+            if MetadataKey::from_str(&key).is_none() {
+                continue;
+            }
+            let mut block = match StoredMetadata::parse(&backend.get_value(&key)?) {
+                Ok(block) => block,
+                Err(e) => {
+                    warn!("Broken offsets block with key {key}. Error: {e}");
+                    continue;
+                }
+            };
+            if let Some(compaction_threshold) = block.compaction_threshold {
+                if requested < compaction_threshold {
+                    return Err(Error::RollbackTimeCompacted {
+                        requested: timestamp,
+                        compaction_threshold,
+                    });
+                }
+            }
+            if block.last_advanced_timestamp > requested {
+                block.last_advanced_timestamp = requested;
+                rolled_back_blocks.push((key, block));
+            }
+        }
+        for (key, block) in rolled_back_blocks {
+            info!("Rolling back the metadata block {key} to {timestamp}");
+            backend.put_value(&key, &block.serialize())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the time finalized by all workers, according to the latest metadata blocks.
+    /// The snapshots are never rewound past this time, so they can be compacted up to it.
+    pub fn globally_finalized_timestamp(&self) -> Result<TotalFrontier<Timestamp>, Error> {
+        Ok(Self::latest_blocks(self.backend.as_ref())?
+            .into_values()
+            .map(|block| block.last_advanced_timestamp)
+            .min()
+            .unwrap_or(TotalFrontier::At(Timestamp(0))))
+    }
+
     pub fn save_current_state(&mut self) -> Result<(), Error> {
         let serialized_state = self.internal_state.serialize();
         self.backend
             .put_value(&self.current_key_to_use, &serialized_state)?;
         swap(&mut self.current_key_to_use, &mut self.next_key_to_use);
+
+        // The saved block supersedes the blocks of the adopted former workers:
+        // if they stayed, their finalized times would hold back the threshold
+        // of all subsequent runs.
+        for key in self.adopted_keys.drain(..) {
+            info!("Removing the adopted metadata block: {key}");
+            if let Err(e) = self.backend.remove_key(&key) {
+                error!("Failed to remove the adopted metadata block: {e}");
+            }
+        }
         Ok(())
     }
 }
+
+impl Drop for MetadataAccessor {
+    fn drop(&mut self) {
+        if let Err(e) = self.backend.remove_key(&self.run_lock_key) {
+            error!("Failed to remove the run lock {}: {e}", self.run_lock_key);
+        }
+    }
+}
@@ -1,19 +1,25 @@
 // Copyright © 2024 Pathway
 
 use log::{error, warn};
+use opentelemetry::KeyValue;
 use std::collections::{HashMap, HashSet};
 use std::mem::take;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::connectors::data_storage::{ReadError, StorageType, WriteError};
 use crate::connectors::snapshot::{SnapshotMode, SnapshotReader, SnapshotWriterFlushFuture};
-use crate::connectors::PersistenceMode;
+use crate::connectors::{PersistenceMode, SnapshotAccess};
+use crate::engine::telemetry::{timestamp_attribute, SpanGuard};
 use crate::engine::{Timestamp, TotalFrontier};
 use crate::persistence::config::{PersistenceManagerConfig, ReadersQueryPurpose};
 use crate::persistence::metadata_backends::Error as MetadataBackendError;
 use crate::persistence::state::MetadataAccessor;
 use crate::persistence::{PersistentId, SharedSnapshotWriter};
 
+// The checkpoints of the operator states are compacted with this interval
+// if the compaction of the snapshots isn't configured.
+const DEFAULT_CHECKPOINT_COMPACTION_INTERVAL: Duration = Duration::from_secs(60);
+
 /// The main coordinator for state persistence within a worker
 /// It tracks logical time and snapshots and commits them when
 /// the processing time advances.
@@ -22,9 +28,11 @@ pub struct WorkerPersistentStorage {
     config: PersistenceManagerConfig,
 
     snapshot_writers: HashMap<PersistentId, SharedSnapshotWriter>,
+    checkpoint_persistent_ids: HashSet<PersistentId>,
     sink_threshold_times: Vec<TotalFrontier<Timestamp>>,
     registered_persistent_ids: HashSet<PersistentId>,
     last_commit_at: Instant,
+    last_compaction_at: Instant,
 }
 
 /// The information from the first phase of time finalization commit.
@@ -73,9 +81,11 @@ impl WorkerPersistentStorage {
             config,
 
             snapshot_writers: HashMap::new(),
+            checkpoint_persistent_ids: HashSet::new(),
             sink_threshold_times: Vec::new(),
             registered_persistent_ids: HashSet::new(),
             last_commit_at: Instant::now(),
+            last_compaction_at: Instant::now(),
         })
     }
 
@@ -90,6 +100,31 @@ impl WorkerPersistentStorage {
         matches!(self.config.persistence_mode, PersistenceMode::Persisting)
     }
 
+    pub fn operator_state_checkpointing_enabled(&self) -> bool {
+        self.config.checkpoint_operator_state && self.persistent_id_generation_enabled()
+    }
+
+    /// With operator state checkpointing the stateful operators restore their own state,
+    /// so the connectors only store offsets and don't replay the data they have read.
+    pub fn effective_snapshot_access(&self, snapshot_access: SnapshotAccess) -> SnapshotAccess {
+        match snapshot_access {
+            SnapshotAccess::Full if self.operator_state_checkpointing_enabled() => {
+                SnapshotAccess::OffsetsOnly
+            }
+            snapshot_access => snapshot_access,
+        }
+    }
+
+    /// Tells whether some time was finalized in the previous runs, so that the persisted
+    /// state is restored from them.
+    pub fn is_restored_from_past_runs(&self) -> bool {
+        self.metadata_storage
+            .past_runs_threshold_times()
+            .values()
+            .min()
+            .is_some_and(|threshold_time| *threshold_time > TotalFrontier::At(Timestamp(0)))
+    }
+
     pub fn last_finalized_timestamp(&self) -> TotalFrontier<Timestamp> {
         self.metadata_storage.last_advanced_timestamp()
     }
@@ -132,6 +167,16 @@ impl WorkerPersistentStorage {
             || self.last_commit_at.elapsed() >= self.config.snapshot_interval;
 
         if timestamp_updated && commit_interval_passed {
+            let _span = SpanGuard::start("connector.commit", || {
+                let mut attributes = vec![KeyValue::new(
+                    "snapshot_writers",
+                    i64::try_from(self.snapshot_writers.len()).unwrap_or(i64::MAX),
+                )];
+                if let TotalFrontier::At(timestamp) = worker_finalized_timestamp {
+                    attributes.push(timestamp_attribute(timestamp));
+                }
+                attributes
+            });
             let mut commit_data = self.accept_finalized_timestamp(worker_finalized_timestamp);
             if !commit_data.prepare() {
                 warn!("Failed to prepare commit data, logical time {worker_finalized_timestamp:?} won't be committed");
@@ -139,6 +184,46 @@ impl WorkerPersistentStorage {
             }
             self.commit_finalized_timestamp(&commit_data);
             self.last_commit_at = Instant::now();
+            self.maybe_compact_snapshots();
+        }
+    }
+
+    fn maybe_compact_snapshots(&mut self) {
+        let compaction_interval = match self.config.snapshot_compaction_interval {
+            Some(interval) => interval,
+            // a checkpoint is a log of all changes of an operator state, it has to be compacted
+            // so that it doesn't outgrow the state
+            None if !self.checkpoint_persistent_ids.is_empty() => {
+                DEFAULT_CHECKPOINT_COMPACTION_INTERVAL
+            }
+            None => return,
+        };
+        if self.last_compaction_at.elapsed() < compaction_interval {
+            return;
+        }
+        self.last_compaction_at = Instant::now();
+
+        // other workers may be behind, the snapshots are rewound to the time finalized by all
+        let threshold = match self.metadata_storage.globally_finalized_timestamp() {
+            Ok(threshold) => threshold,
+            Err(e) => {
+                error!("Failed to determine the time the snapshots can be compacted up to: {e}");
+                return;
+            }
+        };
+        self.metadata_storage.accept_compaction_threshold(threshold);
+        if let Err(e) = self.metadata_storage.save_current_state() {
+            error!(
+                "Failed to save the compaction threshold, the snapshots won't be compacted: {e}"
+            );
+            return;
+        }
+        for (persistent_id, snapshot_writer) in &self.snapshot_writers {
+            if self.config.snapshot_compaction_interval.is_some()
+                || self.checkpoint_persistent_ids.contains(persistent_id)
+            {
+                snapshot_writer.lock().unwrap().compact(threshold);
+            }
         }
     }
 
@@ -204,4 +289,14 @@ impl WorkerPersistentStorage {
             Ok(writer)
         }
     }
+
+    /// Creates the writer of the checkpoint of an operator state, which is compacted
+    /// periodically even if the compaction of the snapshots isn't enabled.
+    pub fn create_checkpoint_writer(
+        &mut self,
+        persistent_id: PersistentId,
+    ) -> Result<SharedSnapshotWriter, WriteError> {
+        self.checkpoint_persistent_ids.insert(persistent_id);
+        self.create_snapshot_writer(persistent_id, SnapshotMode::Full)
+    }
 }
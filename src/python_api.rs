@@ -9,7 +9,7 @@ use crate::engine::graph::{
 use crate::engine::license::{Error as LicenseError, License};
 use crate::engine::{
     Computer as EngineComputer, Expressions, PyObjectWrapper as InternalPyObjectWrapper,
-    ShardPolicy, TotalFrontier,
+    ShardPolicy, TotalFrontier, Window, WindowBehavior,
 };
 use crate::persistence::frontier::OffsetAntichain;
 use csv::ReaderBuilder as CsvReaderBuilder;
@@ -49,17 +49,19 @@ use serde_json::Value as JsonValue;
 use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufWriter, Read};
 use std::mem::take;
 use std::os::unix::prelude::*;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
 
 use self::external_index_wrappers::{
     PyBruteForceKnnMetricKind, PyExternalIndexData, PyExternalIndexQuery, PyUSearchMetricKind,
+    PyUSearchScalarKind,
 };
 use self::threads::PythonThreadState;
 
@@ -75,13 +77,20 @@ use crate::connectors::data_storage::{
     PsqlWriter, PythonConnectorEventType, PythonReaderBuilder, ReadError, ReadMethod,
     ReaderBuilder, S3CsvReader, S3GenericReader, S3Scanner, SqliteReader, Writer,
 };
+use crate::connectors::dead_letter::{process_file_path, DeadLetterQueue};
+use crate::connectors::monitoring::{ErrorBudget, ErrorBudgetAction};
 use crate::connectors::snapshot::Event as SnapshotEvent;
 use crate::connectors::{PersistenceMode, SessionType, SnapshotAccess};
+use crate::engine::dataflow::arrangement_stats::{
+    ArrangementStats, ArrangementStatsRegistry, OperatorArrangementStats,
+};
 use crate::engine::dataflow::Config;
 use crate::engine::error::{DataError, DynError, DynResult, Trace as EngineTrace};
 use crate::engine::graph::ScopedContext;
 use crate::engine::progress_reporter::MonitoringLevel;
-use crate::engine::reduce::StatefulCombineFn;
+use crate::engine::reduce::{
+    CountDistinctApproxReducer, QuantilesReducer, StatefulCombineFn, TopKOrdering,
+};
 use crate::engine::time::DateTime;
 use crate::engine::Config as EngineTelemetryConfig;
 use crate::engine::Timestamp;
@@ -102,6 +111,9 @@ use crate::engine::{LegacyTable as EngineLegacyTable, StringExpression};
 use crate::persistence::config::{
     ConnectorWorkerPair, MetadataStorageConfig, PersistenceManagerOuterConfig, StreamStorageConfig,
 };
+use crate::persistence::encryption::Keyring;
+use crate::persistence::inspector::{event_to_json, PersistenceInspector};
+use crate::persistence::object_store::{ObjectStoreConfig, PostgresStoreConfig};
 use crate::persistence::{ExternalPersistentId, IntoPersistentId, PersistentId};
 use crate::pipe::{pipe, ReaderType, WriterType};
 use crate::python_api::external_index_wrappers::PyExternalIndexFactory;
@@ -875,6 +887,55 @@ impl PyReducer {
 
     #[classattr]
     pub const EARLIEST: Reducer = Reducer::Earliest;
+
+    #[classattr]
+    pub const MEAN: Reducer = Reducer::Mean;
+
+    #[staticmethod]
+    fn variance(sample: bool) -> Reducer {
+        Reducer::Variance { sample }
+    }
+
+    #[staticmethod]
+    fn stddev(sample: bool) -> Reducer {
+        Reducer::StdDev { sample }
+    }
+
+    #[classattr]
+    pub const COUNT_DISTINCT: Reducer = Reducer::CountDistinct;
+
+    #[staticmethod]
+    fn count_distinct_approx(precision: u8) -> PyResult<Reducer> {
+        CountDistinctApproxReducer::new(precision).map_err(EngineError::from)?;
+        Ok(Reducer::CountDistinctApprox { precision })
+    }
+
+    #[staticmethod]
+    fn quantile(quantile: f64) -> PyResult<Reducer> {
+        QuantilesReducer::new_single(quantile).map_err(EngineError::from)?;
+        Ok(Reducer::Quantile { quantile })
+    }
+
+    #[staticmethod]
+    fn quantiles(quantiles: Vec<f64>) -> PyResult<Reducer> {
+        QuantilesReducer::new_multiple(quantiles.clone()).map_err(EngineError::from)?;
+        Ok(Reducer::Quantiles { quantiles })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (k, descending = false, order_column = None))]
+    fn top_k(k: usize, descending: bool, order_column: Option<usize>) -> PyResult<Reducer> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k must be positive"));
+        }
+        Ok(Reducer::TopK {
+            k,
+            order: TopKOrdering {
+                descending,
+                order_column,
+            },
+        })
+    }
 }
 
 fn wrap_stateful_combine(combine: Py<PyAny>) -> StatefulCombineFn {
@@ -883,6 +944,45 @@ fn wrap_stateful_combine(combine: Py<PyAny>) -> StatefulCombineFn {
     })
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "Window")]
+pub struct PyWindow(Window);
+
+#[pymethods]
+impl PyWindow {
+    #[staticmethod]
+    #[pyo3(signature = (duration, origin = None))]
+    fn tumbling(duration: Value, origin: Option<Value>) -> Window {
+        Window::Tumbling { duration, origin }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (hop, duration, origin = None))]
+    fn sliding(hop: Value, duration: Value, origin: Option<Value>) -> Window {
+        Window::Sliding {
+            hop,
+            duration,
+            origin,
+        }
+    }
+
+    #[staticmethod]
+    fn session(max_gap: Value) -> Window {
+        Window::Session { max_gap }
+    }
+}
+
+impl<'py> FromPyObject<'py> for Window {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<PyRef<PyWindow>>()?.0.clone())
+    }
+}
+
+impl IntoPy<PyObject> for Window {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        PyWindow(self).into_py(py)
+    }
+}
+
 #[pyclass(module = "pathway.engine", frozen, name = "ReducerData")]
 struct PyReducerData(ReducerData);
 
@@ -2344,6 +2444,7 @@ impl Scope {
             properties
                 .commit_duration_ms
                 .map(time::Duration::from_millis),
+            properties.error_budget.map(|error_budget| error_budget.0),
             parallel_readers,
             Arc::new(EngineTableProperties::flat(column_properties)),
             persistent_id.as_ref(),
@@ -2632,6 +2733,34 @@ impl Scope {
         Table::new(self_, new_table_handle)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (table, time_column_path, instance_column_path, window, table_properties, *, delay = None, cutoff = None, keep_results = true))]
+    pub fn window_table(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        time_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        window: Window,
+        table_properties: TableProperties,
+        delay: Option<Value>,
+        cutoff: Option<Value>,
+        keep_results: bool,
+    ) -> PyResult<Py<Table>> {
+        let new_table_handle = self_.borrow().graph.window_table(
+            table.handle,
+            time_column_path,
+            instance_column_path,
+            window,
+            WindowBehavior {
+                delay,
+                cutoff,
+                keep_results,
+            },
+            table_properties.0,
+        )?;
+        Table::new(self_, new_table_handle)
+    }
+
     pub fn forget_immediately(
         self_: &Bound<Self>,
         table: PyRef<Table>,
@@ -2796,6 +2925,33 @@ impl Scope {
         Table::new(self_, new_table_handle)
     }
 
+    #[pyo3(signature = (table, key_column_path, instance_column_path, n, table_properties, *, offset = 0, descending = false))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn top_n_table(
+        self_: &Bound<Self>,
+        table: PyRef<Table>,
+        key_column_path: ColumnPath,
+        instance_column_path: ColumnPath,
+        n: usize,
+        table_properties: TableProperties,
+        offset: usize,
+        descending: bool,
+    ) -> PyResult<Py<Table>> {
+        if n == 0 {
+            return Err(PyValueError::new_err("n has to be positive"));
+        }
+        let new_table_handle = self_.borrow().graph.top_n_table(
+            table.handle,
+            key_column_path,
+            instance_column_path,
+            n,
+            offset,
+            descending,
+            table_properties.0,
+        )?;
+        Table::new(self_, new_table_handle)
+    }
+
     pub fn reindex_table(
         self_: &Bound<Self>,
         table: PyRef<Table>,
@@ -2935,7 +3091,7 @@ impl Scope {
         Table::new(self_, result_table_handle)
     }
 
-    #[pyo3(signature = (left_table, right_table, left_column_paths, right_column_paths, *, last_column_is_instance, table_properties, assign_id = false, left_ear = false, right_ear = false))]
+    #[pyo3(signature = (left_table, right_table, left_column_paths, right_column_paths, *, last_column_is_instance, table_properties, assign_id = false, left_ear = false, right_ear = false, semi = false, anti = false, left_time_column_path = None, right_time_column_path = None, asof_direction = None, tolerance = None, interval = None, cutoff = None))]
     #[allow(clippy::too_many_arguments)]
     #[allow(clippy::fn_params_excessive_bools)]
     pub fn join_tables(
@@ -2949,11 +3105,65 @@ impl Scope {
         assign_id: bool,
         left_ear: bool,
         right_ear: bool,
+        semi: bool,
+        anti: bool,
+        left_time_column_path: Option<ColumnPath>,
+        right_time_column_path: Option<ColumnPath>,
+        asof_direction: Option<&str>,
+        tolerance: Option<Value>,
+        interval: Option<(Value, Value)>,
+        cutoff: Option<Value>,
     ) -> PyResult<Py<Table>> {
-        let join_type = JoinType::from_assign_left_right(assign_id, left_ear, right_ear)?;
+        if (asof_direction.is_some() || interval.is_some()) && (assign_id || left_ear || right_ear)
+        {
+            return Err(PyValueError::new_err(
+                "assign_id, left_ear and right_ear can't be used in asof and interval joins",
+            ));
+        }
+        if tolerance.is_some() && asof_direction.is_none() {
+            return Err(PyValueError::new_err(
+                "tolerance can only be used in asof joins",
+            ));
+        }
+        if cutoff.is_some() && interval.is_none() {
+            return Err(PyValueError::new_err(
+                "cutoff can only be used in interval joins",
+            ));
+        }
+        let join_type = match (asof_direction, interval) {
+            (None, None) => match (semi, anti) {
+                (false, false) => JoinType::from_assign_left_right(assign_id, left_ear, right_ear)?,
+                (true, false) if !(assign_id || left_ear || right_ear) => JoinType::LeftSemi,
+                (false, true) if !(assign_id || left_ear || right_ear) => JoinType::LeftAnti,
+                _ => return Err(EngineError::BadJoinType.into()),
+            },
+            _ if semi || anti => return Err(EngineError::BadJoinType.into()),
+            (Some("backward"), None) => JoinType::AsofBackward { tolerance },
+            (Some("forward"), None) => JoinType::AsofForward { tolerance },
+            (Some("nearest"), None) => JoinType::AsofNearest { tolerance },
+            (Some(direction), None) => {
+                return Err(PyValueError::new_err(format!(
+                "asof_direction has to be one of backward, forward, nearest but is {direction:?}"
+            )))
+            }
+            (None, Some((lower, upper))) => JoinType::Interval {
+                lower,
+                upper,
+                cutoff,
+            },
+            (Some(_), Some(_)) => return Err(EngineError::BadJoinType.into()),
+        };
+        let mut left_data = JoinData::new(left_table.handle, left_column_paths);
+        if let Some(path) = left_time_column_path {
+            left_data = left_data.with_time_column_path(path);
+        }
+        let mut right_data = JoinData::new(right_table.handle, right_column_paths);
+        if let Some(path) = right_time_column_path {
+            right_data = right_data.with_time_column_path(path);
+        }
         let table_handle = self_.borrow().graph.join_tables(
-            JoinData::new(left_table.handle, left_column_paths),
-            JoinData::new(right_table.handle, right_column_paths),
+            left_data,
+            right_data,
             ShardPolicy::from_last_column_is_instance(last_column_is_instance),
             join_type,
             table_properties.0,
@@ -3106,10 +3316,12 @@ impl Scope {
         Ok(())
     }
 
+    #[pyo3(signature = (operator_id, depends_on_error_log, trace=None))]
     pub fn set_operator_properties(
         self_: &Bound<Self>,
         operator_id: usize,
         depends_on_error_log: bool,
+        trace: Option<EngineTrace>,
     ) -> PyResult<()> {
         Ok(self_
             .borrow()
@@ -3117,6 +3329,7 @@ impl Scope {
             .set_operator_properties(OperatorProperties {
                 id: operator_id,
                 depends_on_error_log,
+                trace: trace.unwrap_or(EngineTrace::Empty),
             })?)
     }
 
@@ -3157,6 +3370,11 @@ impl Scope {
         Ok(())
     }
 
+    pub fn arrangement_stats(self_: &Bound<Self>) -> PyResult<PyArrangementStatsRegistry> {
+        let registry = self_.borrow().graph.arrangement_stats()?;
+        Ok(PyArrangementStatsRegistry::new(registry))
+    }
+
     pub fn export_table(
         self_: &Bound<Self>,
         table: PyRef<Table>,
@@ -3234,12 +3452,15 @@ pub fn make_captured_table(table_data: Vec<CapturedTableData>) -> PyResult<Vec<D
     ignore_asserts = false,
     monitoring_level = MonitoringLevel::None,
     with_http_server = false,
+    monitoring_http_host = None,
+    monitoring_http_port = None,
     persistence_config = None,
     license_key = None,
     monitoring_server = None,
     trace_parent = None,
     run_id = None,
     terminate_on_error = true,
+    dead_letter_sink = None,
 ))]
 pub fn run_with_new_graph(
     py: Python,
@@ -3249,12 +3470,15 @@ pub fn run_with_new_graph(
     ignore_asserts: bool,
     monitoring_level: MonitoringLevel,
     with_http_server: bool,
+    monitoring_http_host: Option<String>,
+    monitoring_http_port: Option<u16>,
     persistence_config: Option<PersistenceConfig>,
     license_key: Option<String>,
     monitoring_server: Option<String>,
     trace_parent: Option<String>,
     run_id: Option<String>,
     terminate_on_error: bool,
+    dead_letter_sink: Option<(PyRef<DataStorage>, PyRef<DataFormat>)>,
 ) -> PyResult<Vec<Vec<DataRow>>> {
     LOGGING_RESET_HANDLE.reset();
     defer! {
@@ -3270,6 +3494,18 @@ pub fn run_with_new_graph(
             None
         }
     };
+    let dead_letter_queue = dead_letter_sink
+        .map(|(data_sink, data_format)| -> PyResult<_> {
+            Ok(DeadLetterQueue::new(
+                data_sink.construct_dead_letter_writer(
+                    py,
+                    &data_format,
+                    (config.processes() > 1).then(|| config.process_id()),
+                )?,
+                data_format.construct_formatter(py)?,
+            ))
+        })
+        .transpose()?;
     let license = License::new(license_key);
     let telemetry_config =
         EngineTelemetryConfig::create(&license, run_id, monitoring_server, trace_parent)?;
@@ -3300,10 +3536,13 @@ pub fn run_with_new_graph(
                 ignore_asserts,
                 monitoring_level,
                 with_http_server,
+                monitoring_http_host.as_deref(),
+                monitoring_http_port,
                 persistence_config,
                 &license,
                 telemetry_config,
                 terminate_on_error,
+                dead_letter_queue,
             )
         })
     })??;
@@ -3724,6 +3963,11 @@ pub struct PersistenceConfig {
     snapshot_access: SnapshotAccess,
     persistence_mode: PersistenceMode,
     continue_after_replay: bool,
+    snapshot_compaction_interval: Option<::std::time::Duration>,
+    checkpoint_operator_state: bool,
+    encryption_key_file: Option<PathBuf>,
+    rollback_to_timestamp: Option<Timestamp>,
+    rollback_generation: u64,
 }
 
 #[pymethods]
@@ -3737,7 +3981,13 @@ impl PersistenceConfig {
         snapshot_access = SnapshotAccess::Full,
         persistence_mode = PersistenceMode::Batch,
         continue_after_replay = true,
+        snapshot_compaction_interval_ms = None,
+        checkpoint_operator_state = false,
+        encryption_key_file = None,
+        rollback_to_timestamp = None,
+        rollback_generation = 0,
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         snapshot_interval_ms: u64,
         metadata_storage: DataStorage,
@@ -3745,6 +3995,11 @@ impl PersistenceConfig {
         snapshot_access: SnapshotAccess,
         persistence_mode: PersistenceMode,
         continue_after_replay: bool,
+        snapshot_compaction_interval_ms: Option<u64>,
+        checkpoint_operator_state: bool,
+        encryption_key_file: Option<PathBuf>,
+        rollback_to_timestamp: Option<Timestamp>,
+        rollback_generation: u64,
     ) -> Self {
         Self {
             snapshot_interval: ::std::time::Duration::from_millis(snapshot_interval_ms),
@@ -3753,13 +4008,27 @@ impl PersistenceConfig {
             snapshot_access,
             persistence_mode,
             continue_after_replay,
+            snapshot_compaction_interval: snapshot_compaction_interval_ms
+                .map(::std::time::Duration::from_millis),
+            checkpoint_operator_state,
+            encryption_key_file,
+            rollback_to_timestamp,
+            rollback_generation,
         }
     }
 }
 
+fn load_encryption_keyring(key_file: Option<&Path>) -> PyResult<Option<Arc<Keyring>>> {
+    Keyring::load(key_file)
+        .map(|keyring| keyring.map(Arc::new))
+        .map_err(|e| {
+            PyValueError::new_err(format!("Failed to load persistence encryption keys: {e}"))
+        })
+}
+
 impl PersistenceConfig {
     fn prepare(self, py: pyo3::Python) -> PyResult<PersistenceManagerOuterConfig> {
-        Ok(PersistenceManagerOuterConfig::new(
+        let mut config = PersistenceManagerOuterConfig::new(
             self.snapshot_interval,
             self.metadata_storage
                 .construct_metadata_storage_config(py)?,
@@ -3767,8 +4036,106 @@ impl PersistenceConfig {
             self.snapshot_access,
             self.persistence_mode,
             self.continue_after_replay,
+        )
+        .with_operator_state_checkpointing(self.checkpoint_operator_state);
+        if let Some(encryption) = load_encryption_keyring(self.encryption_key_file.as_deref())? {
+            config = config.with_encryption(encryption);
+        }
+        if let Some(timestamp) = self.rollback_to_timestamp {
+            config = config.with_rollback_to(timestamp, self.rollback_generation);
+        }
+        Ok(match self.snapshot_compaction_interval {
+            Some(interval) => config.with_snapshot_compaction_interval(interval),
+            None => config,
+        })
+    }
+}
+
+#[pyclass(module = "pathway.engine", name = "PersistenceInspector")]
+pub struct PyPersistenceInspector(PersistenceInspector);
+
+#[pymethods]
+impl PyPersistenceInspector {
+    #[new]
+    #[pyo3(signature = (*, metadata_storage, stream_storage, encryption_key_file = None, force = false))]
+    fn new(
+        py: pyo3::Python,
+        metadata_storage: DataStorage,
+        stream_storage: DataStorage,
+        encryption_key_file: Option<PathBuf>,
+        force: bool,
+    ) -> PyResult<Self> {
+        Ok(Self(
+            PersistenceInspector::new(
+                metadata_storage.construct_metadata_storage_config(py)?,
+                stream_storage.construct_stream_storage_config(py)?,
+            )
+            .with_encryption(load_encryption_keyring(encryption_key_file.as_deref())?)
+            .with_force(force),
         ))
     }
+
+    #[staticmethod]
+    fn internal_persistent_id(external_persistent_id: ExternalPersistentId) -> PersistentId {
+        external_persistent_id.into_persistent_id()
+    }
+
+    fn snapshot_streams(&self) -> PyResult<Vec<ConnectorWorkerPair>> {
+        self.0
+            .snapshot_streams()
+            .map_err(|e| PyIOError::new_err(format!("Failed to list snapshot streams: {e}")))
+    }
+
+    fn metadata(&self) -> PyResult<BTreeMap<usize, String>> {
+        let blocks = self
+            .0
+            .metadata_blocks()
+            .map_err(|e| PyIOError::new_err(format!("Failed to read metadata: {e}")))?;
+        Ok(blocks
+            .into_iter()
+            .map(|(worker_id, block)| (worker_id, block.serialize()))
+            .collect())
+    }
+
+    fn finalized_timestamps(&self) -> PyResult<BTreeMap<usize, TotalFrontier<Timestamp>>> {
+        self.0
+            .finalized_timestamps()
+            .map_err(|e| PyIOError::new_err(format!("Failed to read metadata: {e}")))
+    }
+
+    fn dump_events(&self, persistent_id: PersistentId, worker_id: usize) -> PyResult<Vec<String>> {
+        let events = self
+            .0
+            .read_events(persistent_id, worker_id)
+            .map_err(|e| PyIOError::new_err(format!("Failed to read snapshot stream: {e}")))?;
+        Ok(events
+            .iter()
+            .map(|event| event_to_json(event).to_string())
+            .collect())
+    }
+
+    fn truncate_stream(
+        &self,
+        persistent_id: PersistentId,
+        worker_id: usize,
+        time: u64,
+    ) -> PyResult<usize> {
+        self.0
+            .truncate_stream(persistent_id, worker_id, Timestamp(time))
+            .map_err(|e| PyIOError::new_err(format!("Failed to truncate snapshot stream: {e}")))
+    }
+
+    fn migrate_stream(&self, persistent_id: PersistentId, worker_id: usize) -> PyResult<usize> {
+        self.0
+            .migrate_stream(persistent_id, worker_id)
+            .map_err(|e| PyIOError::new_err(format!("Failed to migrate snapshot stream: {e}")))
+    }
+
+    fn migrate_metadata(&self) -> PyResult<usize> {
+        self.0
+            .migrate_metadata()
+            .map_err(|e| PyIOError::new_err(format!("Failed to migrate metadata: {e}")))
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -4451,17 +4818,37 @@ impl DataStorage {
         }
     }
 
+    fn object_store_config(&self, py: pyo3::Python) -> PyResult<ObjectStoreConfig> {
+        match self.storage_type.as_ref() {
+            "s3" | "gcs" => Ok(ObjectStoreConfig::S3(self.s3_bucket(py)?)),
+            "azure" => Ok(ObjectStoreConfig::Azure(
+                self.connection_string()?.to_string(),
+            )),
+            "sqlite" => Ok(ObjectStoreConfig::Sqlite(self.path()?.into())),
+            "postgres" => Ok(ObjectStoreConfig::Postgres(PostgresStoreConfig::new(
+                self.connection_string()?.to_string(),
+            ))),
+            other => Err(PyValueError::new_err(format!(
+                "Unsupported object store: {other:?}"
+            ))),
+        }
+    }
+
+    fn object_store_root_path(&self) -> String {
+        match self.storage_type.as_ref() {
+            // the path points to the database file
+            "sqlite" => String::new(),
+            _ => self.path.clone().unwrap_or_default(),
+        }
+    }
+
     fn construct_stream_storage_config(&self, py: pyo3::Python) -> PyResult<StreamStorageConfig> {
         match self.storage_type.as_ref() {
             "fs" => Ok(StreamStorageConfig::Filesystem(self.path()?.into())),
-            "s3" => {
-                let bucket = self.s3_bucket(py)?;
-                let path = self.path()?;
-                Ok(StreamStorageConfig::S3 {
-                    bucket,
-                    root_path: path.into(),
-                })
-            }
+            "s3" | "gcs" | "azure" | "sqlite" | "postgres" => Ok(StreamStorageConfig::ObjectStore {
+                store: self.object_store_config(py)?,
+                root_path: self.object_store_root_path(),
+            }),
             "mock" => {
                 let mut events = HashMap::<ConnectorWorkerPair, Vec<SnapshotEvent>>::new();
                 for ((external_persistent_id, worker_id), es) in self.mock_events.as_ref().unwrap()
@@ -4484,14 +4871,10 @@ impl DataStorage {
     ) -> PyResult<MetadataStorageConfig> {
         match self.storage_type.as_ref() {
             "fs" => Ok(MetadataStorageConfig::Filesystem(self.path()?.into())),
-            "s3" => {
-                let bucket = self.s3_bucket(py)?;
-                let path = self.path()?;
-                Ok(MetadataStorageConfig::S3 {
-                    bucket,
-                    root_path: path.into(),
-                })
-            }
+            "s3" | "gcs" | "azure" | "sqlite" | "postgres" => Ok(MetadataStorageConfig::ObjectStore {
+                store: self.object_store_config(py)?,
+                root_path: self.object_store_root_path(),
+            }),
             "mock" => Ok(MetadataStorageConfig::Mock),
             other => Err(PyValueError::new_err(format!(
                 "Unsupported metadata storage format: {other:?}"
@@ -4603,6 +4986,25 @@ impl DataStorage {
             ))),
         }
     }
+
+    /// Constructs the writer of the dead-letter queue. With multiple processes,
+    /// each of them writes to its own file.
+    fn construct_dead_letter_writer(
+        &self,
+        py: pyo3::Python,
+        data_format: &DataFormat,
+        process_id: Option<usize>,
+    ) -> PyResult<Box<dyn Writer>> {
+        match (self.storage_type.as_ref(), process_id) {
+            ("fs", Some(process_id)) => {
+                let path = process_file_path(Path::new(self.path()?), process_id);
+                let file = File::create(path)
+                    .map_err(|_| PyIOError::new_err("Filesystem operation (create) failed"))?;
+                Ok(Box::new(FileWriter::new(BufWriter::new(file))))
+            }
+            _ => self.construct_writer(py, data_format),
+        }
+    }
 }
 
 impl DataFormat {
@@ -4839,6 +5241,56 @@ impl TableProperties {
     }
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "ErrorBudget")]
+#[derive(Clone)]
+pub struct PyErrorBudget(ErrorBudget);
+
+#[pymethods]
+impl PyErrorBudget {
+    #[new]
+    #[pyo3(signature = (
+        max_error_rate,
+        window_ms = 60_000,
+        min_entries = 100,
+        action = "fail",
+        initial_backoff_ms = 1_000,
+        max_backoff_ms = 60_000
+    ))]
+    fn new(
+        max_error_rate: f64,
+        window_ms: u64,
+        min_entries: usize,
+        action: &str,
+        initial_backoff_ms: u64,
+        max_backoff_ms: u64,
+    ) -> PyResult<Self> {
+        if !(0.0..=1.0).contains(&max_error_rate) {
+            return Err(PyValueError::new_err(format!(
+                "max_error_rate should be between 0 and 1, got {max_error_rate}"
+            )));
+        }
+        let action = match action {
+            "fail" => ErrorBudgetAction::Fail,
+            "pause" => ErrorBudgetAction::Pause {
+                initial_backoff: time::Duration::from_millis(initial_backoff_ms),
+                max_backoff: time::Duration::from_millis(max_backoff_ms),
+            },
+            "skip" => ErrorBudgetAction::SkipAndAlert,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown error budget action: {action:?}"
+                )))
+            }
+        };
+        Ok(Self(ErrorBudget {
+            max_error_rate,
+            min_entries,
+            window: time::Duration::from_millis(window_ms),
+            action,
+        }))
+    }
+}
+
 #[pyclass(module = "pathway.engine", frozen)]
 #[derive(Clone)]
 pub struct ConnectorProperties {
@@ -4849,6 +5301,8 @@ pub struct ConnectorProperties {
     unsafe_trusted_ids: bool,
     #[pyo3(get)]
     column_properties: Vec<ColumnProperties>,
+    #[pyo3(get)]
+    error_budget: Option<PyErrorBudget>,
 }
 
 #[pymethods]
@@ -4857,17 +5311,20 @@ impl ConnectorProperties {
     #[pyo3(signature = (
         commit_duration_ms = None,
         unsafe_trusted_ids = false,
-        column_properties = vec![]
+        column_properties = vec![],
+        error_budget = None
     ))]
     fn new(
         commit_duration_ms: Option<u64>,
         unsafe_trusted_ids: bool,
         #[pyo3(from_py_with = "from_py_iterable")] column_properties: Vec<ColumnProperties>,
+        error_budget: Option<PyErrorBudget>,
     ) -> Self {
         Self {
             commit_duration_ms,
             unsafe_trusted_ids,
             column_properties,
+            error_budget,
         }
     }
 }
@@ -5052,6 +5509,33 @@ impl PyExportedTable {
     }
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "ArrangementStatsRegistry")]
+pub struct PyArrangementStatsRegistry {
+    inner: Arc<ArrangementStatsRegistry>,
+}
+
+impl PyArrangementStatsRegistry {
+    fn new(inner: Arc<ArrangementStatsRegistry>) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyArrangementStatsRegistry {
+    fn per_worker(&self) -> BTreeMap<usize, ArrangementStats> {
+        self.inner.per_worker()
+    }
+
+    #[pyo3(signature = (limit=None))]
+    fn per_operator(&self, limit: Option<usize>) -> Vec<OperatorArrangementStats> {
+        let mut stats = self.inner.per_operator();
+        if let Some(limit) = limit {
+            stats.truncate(limit);
+        }
+        stats
+    }
+}
+
 #[allow(clippy::struct_field_names)]
 struct WakeupHandler<'py> {
     _fd: OwnedFd,
@@ -5154,6 +5638,7 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<Pointer>()?;
     m.add_class::<PyObjectWrapper>()?;
     m.add_class::<PyReducer>()?;
+    m.add_class::<PyWindow>()?;
     m.add_class::<PyReducerData>()?;
     m.add_class::<PyUnaryOperator>()?;
     m.add_class::<PyBinaryOperator>()?;
@@ -5183,6 +5668,7 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<DataStorage>()?;
     m.add_class::<DataFormat>()?;
     m.add_class::<PersistenceConfig>()?;
+    m.add_class::<PyPersistenceInspector>()?;
     m.add_class::<PythonSubject>()?;
     m.add_class::<PyPersistenceMode>()?;
     m.add_class::<PySnapshotAccess>()?;
@@ -5190,17 +5676,20 @@ fn engine(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<TelemetryConfig>()?;
 
     m.add_class::<ConnectorProperties>()?;
+    m.add_class::<PyErrorBudget>()?;
     m.add_class::<ColumnProperties>()?;
     m.add_class::<TableProperties>()?;
     m.add_class::<Trace>()?;
     m.add_class::<Done>()?;
     m.add_class::<PyExportedTable>()?;
+    m.add_class::<PyArrangementStatsRegistry>()?;
     m.add_class::<Error>()?;
 
     m.add_class::<PyExternalIndexFactory>()?;
     m.add_class::<PyExternalIndexData>()?;
     m.add_class::<PyExternalIndexQuery>()?;
     m.add_class::<PyUSearchMetricKind>()?;
+    m.add_class::<PyUSearchScalarKind>()?;
     m.add_class::<PyBruteForceKnnMetricKind>()?;
 
     m.add_function(wrap_pyfunction!(run_with_new_graph, m)?)?;
@@ -4,14 +4,16 @@ use pyo3::prelude::*;
 
 use std::sync::Arc;
 
-use usearch::ffi::MetricKind;
+use usearch::ffi::{MetricKind, ScalarKind};
 
 use crate::engine::external_index_wrappers::{ExternalIndexData, ExternalIndexQuery};
 use crate::external_integration::brute_force_knn_integration::{
     BruteForceKNNIndexFactory, BruteForceKnnMetricKind,
 };
 use crate::external_integration::tantivy_integration::TantivyIndexFactory;
-use crate::external_integration::usearch_integration::{USearchKNNIndexFactory, USearchMetricKind};
+use crate::external_integration::usearch_integration::{
+    USearchKNNIndexFactory, USearchMetricKind, USearchScalarKind,
+};
 use crate::external_integration::ExternalIndexFactory;
 use crate::{engine::ColumnPath, python_api::Table};
 
@@ -25,6 +27,17 @@ pub struct PyExternalIndexFactory {
 #[pymethods]
 impl PyExternalIndexFactory {
     #[staticmethod]
+    #[pyo3(signature = (
+        dimensions,
+        reserved_space,
+        metric,
+        connectivity,
+        expansion_add,
+        expansion_search,
+        quantization = USearchScalarKind(ScalarKind::F16),
+        multi = false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn usearch_knn_factory(
         dimensions: usize,
         reserved_space: usize,
@@ -32,6 +45,8 @@ impl PyExternalIndexFactory {
         connectivity: usize,
         expansion_add: usize,
         expansion_search: usize,
+        quantization: USearchScalarKind,
+        multi: bool,
     ) -> PyExternalIndexFactory {
         PyExternalIndexFactory {
             inner: Arc::new(USearchKNNIndexFactory::new(
@@ -41,6 +56,8 @@ impl PyExternalIndexFactory {
                 connectivity,
                 expansion_add,
                 expansion_search,
+                quantization.0,
+                multi,
             )),
         }
     }
@@ -177,6 +194,35 @@ impl IntoPy<PyObject> for USearchMetricKind {
     }
 }
 
+#[pyclass(module = "pathway.engine", frozen, name = "USearchScalarKind")]
+pub struct PyUSearchScalarKind(USearchScalarKind);
+
+#[pymethods]
+impl PyUSearchScalarKind {
+    #[classattr]
+    pub const F64: USearchScalarKind = USearchScalarKind(ScalarKind::F64);
+    #[classattr]
+    pub const F32: USearchScalarKind = USearchScalarKind(ScalarKind::F32);
+    #[classattr]
+    pub const F16: USearchScalarKind = USearchScalarKind(ScalarKind::F16);
+    #[classattr]
+    pub const I8: USearchScalarKind = USearchScalarKind(ScalarKind::I8);
+    #[classattr]
+    pub const B1: USearchScalarKind = USearchScalarKind(ScalarKind::B1);
+}
+
+impl<'py> FromPyObject<'py> for USearchScalarKind {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<PyRef<PyUSearchScalarKind>>()?.0)
+    }
+}
+
+impl IntoPy<PyObject> for USearchScalarKind {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        PyUSearchScalarKind(self).into_py(py)
+    }
+}
+
 #[pyclass(module = "pathway.engine", frozen, name = "BruteForceKnnMetricKind")]
 pub struct PyBruteForceKnnMetricKind(BruteForceKnnMetricKind);
 
@@ -186,6 +232,12 @@ impl PyBruteForceKnnMetricKind {
     pub const L2SQ: BruteForceKnnMetricKind = BruteForceKnnMetricKind::L2sq;
     #[classattr]
     pub const COS: BruteForceKnnMetricKind = BruteForceKnnMetricKind::Cos;
+    #[classattr]
+    pub const IP: BruteForceKnnMetricKind = BruteForceKnnMetricKind::Ip;
+    #[classattr]
+    pub const L1: BruteForceKnnMetricKind = BruteForceKnnMetricKind::L1;
+    #[classattr]
+    pub const HAMMING: BruteForceKnnMetricKind = BruteForceKnnMetricKind::Hamming;
 }
 
 impl<'source> FromPyObject<'source> for BruteForceKnnMetricKind {
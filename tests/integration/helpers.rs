@@ -19,6 +19,7 @@ use pathway_engine::connectors::data_format::{
 use pathway_engine::connectors::data_storage::{
     DataEventType, ReadResult, Reader, ReaderBuilder, ReaderContext,
 };
+use pathway_engine::connectors::monitoring::ErrorBudgetTracker;
 use pathway_engine::connectors::snapshot::Event as SnapshotEvent;
 use pathway_engine::connectors::{Connector, Entry, PersistenceMode, SnapshotAccess};
 use pathway_engine::engine::{Key, Timestamp, TotalFrontier, Value};
@@ -78,7 +79,14 @@ pub fn full_cycle_read(
     .unwrap();
 
     let reporter = PanicErrorReporter::default();
-    Connector::read_realtime_updates(&mut *reader, &sender, &main_thread, &reporter);
+    Connector::read_realtime_updates(
+        &mut *reader,
+        &sender,
+        &main_thread,
+        &reporter,
+        "test-reader",
+        &ErrorBudgetTracker::new(None),
+    );
     let result = get_entries_in_receiver(receiver);
 
     let has_persistent_storage = persistent_storage.is_some();
@@ -220,6 +228,14 @@ pub fn create_persistence_manager(
         let _ = std::fs::remove_dir_all(fs_path);
     }
 
+    create_worker_persistence_manager(fs_path, 0, 1)
+}
+
+pub fn create_worker_persistence_manager(
+    fs_path: &Path,
+    worker_id: usize,
+    total_workers: usize,
+) -> Arc<Mutex<WorkerPersistentStorage>> {
     Arc::new(Mutex::new(
         WorkerPersistentStorage::new(
             PersistenceManagerOuterConfig::new(
@@ -230,7 +246,7 @@ pub fn create_persistence_manager(
                 PersistenceMode::Batch,
                 true,
             )
-            .into_inner(0, 1),
+            .into_inner(worker_id, total_workers),
         )
         .expect("Failed to create persistence manager"),
     ))
@@ -6,15 +6,20 @@ mod operator_test_utils;
 mod test_bytes;
 mod test_connector_field_defaults;
 mod test_dd_distinct_total;
+mod test_dead_letter;
 mod test_debezium;
 mod test_deltalake;
 mod test_dsv;
 mod test_dsv_dir;
 mod test_dsv_output;
+mod test_error_budget;
+mod test_external_index;
 mod test_file_kv;
 mod test_json_output;
 mod test_jsonlines;
+mod test_mat_mul;
 mod test_metadata;
+mod test_monitoring;
 mod test_null_writer;
 mod test_offsets_storage;
 mod test_parser;
@@ -22,11 +27,16 @@ mod test_parser_errors;
 mod test_prev_next;
 mod test_psql_output;
 mod test_psql_snapshot;
+mod test_reducers;
 mod test_seek;
 mod test_sqlite;
 mod test_stream_snapshot;
+mod test_telemetry;
+mod test_temporal_join;
 mod test_time;
 mod test_time_column;
+mod test_top_k;
 mod test_types;
 mod test_upsert_session;
 mod test_value_to_sql;
+mod test_window;
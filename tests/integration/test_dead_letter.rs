@@ -0,0 +1,142 @@
+// Copyright © 2024 Pathway
+
+use std::fs::{read_to_string, File};
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::{json, Value as JsonValue};
+use tempfile::tempdir;
+
+use pathway_engine::connectors::data_format::JsonLinesFormatter;
+use pathway_engine::connectors::data_storage::{DataEventType, FileWriter, ReaderContext};
+use pathway_engine::connectors::dead_letter::{
+    process_file_path, DeadLetterQueue, DeadLetterRecord, RejectedInput, DEAD_LETTER_COLUMNS,
+};
+use pathway_engine::connectors::{OffsetKey, OffsetValue};
+
+#[test]
+fn test_dead_letter_records_are_written() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let output_path = test_storage.path().join("dead_letters.jsonl");
+    let writer = FileWriter::new(BufWriter::new(File::create(&output_path)?));
+    let formatter = JsonLinesFormatter::new(
+        DEAD_LETTER_COLUMNS
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    );
+    let queue = Arc::new(DeadLetterQueue::new(Box::new(writer), Box::new(formatter)));
+
+    let offset = (
+        OffsetKey::Kafka(Arc::new("topic".to_string()), 3),
+        OffsetValue::KafkaOffset(42),
+    );
+    let rejected_input = RejectedInput::new(
+        queue.clone(),
+        "KafkaReader-0".to_string(),
+        Some(&ReaderContext::from_raw_bytes(
+            DataEventType::Insert,
+            b"{\"a\":".to_vec(),
+        )),
+        Some(&offset),
+    );
+    rejected_input.reject("received message is not json");
+    queue.send(DeadLetterRecord::data_error(
+        Some(7),
+        Some("Occurred here:\n\tLine: x = 1 / 0".to_string()),
+        "division by zero".to_string(),
+    ));
+    queue.close();
+
+    let records: Vec<JsonValue> = read_to_string(&output_path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 2);
+
+    assert_eq!(records[0]["source"], json!("KafkaReader-0"));
+    assert_eq!(
+        records[0]["offset"],
+        json!([{"Kafka": ["topic", 3]}, {"KafkaOffset": 42}])
+    );
+    assert_eq!(records[0]["payload"], json!(b"{\"a\":".to_vec()));
+    assert_eq!(records[0]["operator_id"], JsonValue::Null);
+    assert_eq!(records[0]["message"], json!("received message is not json"));
+
+    assert_eq!(records[1]["source"], JsonValue::Null);
+    assert_eq!(records[1]["payload"], JsonValue::Null);
+    assert_eq!(records[1]["operator_id"], json!(7));
+    assert_eq!(
+        records[1]["trace"],
+        json!("Occurred here:\n\tLine: x = 1 / 0")
+    );
+    assert_eq!(records[1]["message"], json!("division by zero"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dead_letter_queue_ignores_records_after_close() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let output_path = test_storage.path().join("dead_letters.jsonl");
+    let writer = FileWriter::new(BufWriter::new(File::create(&output_path)?));
+    let formatter = JsonLinesFormatter::new(
+        DEAD_LETTER_COLUMNS
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    );
+    let queue = DeadLetterQueue::new(Box::new(writer), Box::new(formatter));
+    queue.close();
+    queue.send(DeadLetterRecord::data_error(
+        Some(1),
+        None,
+        "division by zero".to_string(),
+    ));
+
+    assert_eq!(read_to_string(&output_path)?, "");
+
+    Ok(())
+}
+
+#[test]
+fn test_rejected_input_joins_all_messages_into_one_record() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let output_path = test_storage.path().join("dead_letters.jsonl");
+    let writer = FileWriter::new(BufWriter::new(File::create(&output_path)?));
+    let formatter = JsonLinesFormatter::new(
+        DEAD_LETTER_COLUMNS
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    );
+    let queue = Arc::new(DeadLetterQueue::new(Box::new(writer), Box::new(formatter)));
+    let rejected_input = RejectedInput::new(queue.clone(), "FileReader-0".to_string(), None, None);
+    rejected_input.reject_all(["field a is missing", "field b is not an int"]);
+    queue.close();
+
+    let records: Vec<JsonValue> = read_to_string(&output_path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(
+        records[0]["message"],
+        json!("field a is missing\nfield b is not an int")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_process_file_path() {
+    assert_eq!(
+        process_file_path(Path::new("/tmp/errors.jsonl"), 1),
+        Path::new("/tmp/errors-1.jsonl")
+    );
+    assert_eq!(
+        process_file_path(Path::new("/tmp/errors"), 2),
+        Path::new("/tmp/errors-2")
+    );
+}
@@ -0,0 +1,234 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pathway_engine::connectors::monitoring::{
+    ConnectorMonitor, ErrorBudget, ErrorBudgetAction, ErrorBudgetDecision, ErrorBudgetTracker,
+};
+use pathway_engine::engine::Error;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+fn budget(action: ErrorBudgetAction) -> ErrorBudget {
+    ErrorBudget {
+        max_error_rate: 0.5,
+        min_entries: 4,
+        window: WINDOW,
+        action,
+    }
+}
+
+#[test]
+fn test_error_budget_unlimited() {
+    let tracker = ErrorBudgetTracker::new(None);
+    let start = Instant::now();
+    for i in 0..10 {
+        assert_eq!(
+            tracker.record_error_at(start + Duration::from_millis(i)),
+            ErrorBudgetDecision::Unlimited
+        );
+    }
+    assert_eq!(tracker.remaining_pause(), None);
+}
+
+#[test]
+fn test_error_budget_fail() {
+    let tracker = ErrorBudgetTracker::new(Some(budget(ErrorBudgetAction::Fail)));
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    tracker.record_accepted_entries_at(at(0), 2);
+    // below the minimum number of entries
+    assert_eq!(
+        tracker.record_error_at(at(1)),
+        ErrorBudgetDecision::Continue
+    );
+    // 2 of 4 entries, the rate is not above the maximum
+    assert_eq!(
+        tracker.record_error_at(at(2)),
+        ErrorBudgetDecision::Continue
+    );
+    assert_eq!(
+        tracker.record_error_at(at(3)),
+        ErrorBudgetDecision::Fail {
+            num_errors: 3,
+            num_entries: 5
+        }
+    );
+
+    let error = tracker.exceeded_error("KafkaReader-0", 3, 5);
+    assert!(matches!(
+        error,
+        Error::ErrorBudgetExceeded {
+            num_errors: 3,
+            num_entries: 5,
+            window: WINDOW,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_error_budget_is_a_rate() {
+    let tracker = ErrorBudgetTracker::new(Some(budget(ErrorBudgetAction::Fail)));
+    let start = Instant::now();
+    tracker.record_accepted_entries_at(start, 100);
+    for i in 0..100 {
+        assert_eq!(
+            tracker.record_error_at(start + Duration::from_millis(i)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(
+        tracker.record_error_at(start + Duration::from_millis(100)),
+        ErrorBudgetDecision::Fail {
+            num_errors: 101,
+            num_entries: 201
+        }
+    );
+}
+
+#[test]
+fn test_error_budget_entries_expire_after_window() {
+    let tracker = ErrorBudgetTracker::new(Some(budget(ErrorBudgetAction::Fail)));
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    for secs in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(at(secs)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    // the accepted entries expire as well
+    tracker.record_accepted_entries_at(at(3), 10);
+    let later = at(3) + WINDOW;
+    for secs in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(later + Duration::from_secs(secs)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(
+        tracker.record_error_at(later + Duration::from_secs(3)),
+        ErrorBudgetDecision::Fail {
+            num_errors: 4,
+            num_entries: 4
+        }
+    );
+}
+
+#[test]
+fn test_error_budget_pause_with_backoff() {
+    let tracker = ErrorBudgetTracker::new(Some(budget(ErrorBudgetAction::Pause {
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(3),
+    })));
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    for _ in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(at(0)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(
+        tracker.record_error_at(at(0)),
+        ErrorBudgetDecision::Pause(Duration::from_secs(1))
+    );
+
+    // the errors received during the pause don't extend it
+    assert_eq!(
+        tracker.record_error_at(start + Duration::from_millis(500)),
+        ErrorBudgetDecision::Continue
+    );
+
+    assert_eq!(
+        tracker.record_error_at(at(2)),
+        ErrorBudgetDecision::Pause(Duration::from_secs(2))
+    );
+    assert_eq!(
+        tracker.record_error_at(at(5)),
+        ErrorBudgetDecision::Pause(Duration::from_secs(3))
+    );
+    assert_eq!(
+        tracker.record_error_at(at(10)),
+        ErrorBudgetDecision::Pause(Duration::from_secs(3))
+    );
+
+    // once the window passes without exceeding the budget, the backoff is reset
+    let later = at(10) + WINDOW;
+    for _ in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(later),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(
+        tracker.record_error_at(later),
+        ErrorBudgetDecision::Pause(Duration::from_secs(1))
+    );
+}
+
+#[test]
+fn test_error_budget_skip_and_alert() {
+    let tracker = ErrorBudgetTracker::new(Some(budget(ErrorBudgetAction::SkipAndAlert)));
+    let start = Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    for secs in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(at(secs)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(tracker.record_error_at(at(3)), ErrorBudgetDecision::Alert);
+    assert_eq!(
+        tracker.record_error_at(at(4)),
+        ErrorBudgetDecision::Continue
+    );
+    assert_eq!(tracker.remaining_pause(), None);
+
+    let later = at(4) + WINDOW;
+    for secs in 0..3 {
+        assert_eq!(
+            tracker.record_error_at(later + Duration::from_secs(secs)),
+            ErrorBudgetDecision::Continue
+        );
+    }
+    assert_eq!(
+        tracker.record_error_at(later + Duration::from_secs(3)),
+        ErrorBudgetDecision::Alert
+    );
+}
+
+#[test]
+fn test_error_budget_in_connector_stats() {
+    let tracker = Arc::new(ErrorBudgetTracker::new(Some(budget(
+        ErrorBudgetAction::Pause {
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(60),
+        },
+    ))));
+    let monitor =
+        ConnectorMonitor::new("test-reader".to_string()).with_error_budget(tracker.clone());
+
+    let stats = monitor.get_stats();
+    assert_eq!(stats.num_errors_from_start, 0);
+    assert!(!stats.error_budget_exceeded);
+    assert!(!stats.paused);
+
+    let now = Instant::now();
+    tracker.record_accepted_entries_at(now, 2);
+    for _ in 0..3 {
+        tracker.record_error_at(now);
+    }
+
+    let stats = monitor.get_stats();
+    assert_eq!(stats.num_errors_from_start, 3);
+    assert_eq!(stats.num_entries_in_window, 5);
+    assert_eq!(stats.num_errors_in_window, 3);
+    assert!(stats.error_budget_exceeded);
+    assert!(stats.paused);
+    assert!(tracker
+        .remaining_pause()
+        .is_some_and(|remaining| remaining <= Duration::from_secs(30)));
+}
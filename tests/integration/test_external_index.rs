@@ -0,0 +1,323 @@
+// Copyright © 2024 Pathway
+
+use usearch::ffi::{MetricKind, ScalarKind};
+
+use pathway_engine::engine::{Key, Value};
+use pathway_engine::external_integration::brute_force_knn_integration::{
+    BruteForceKNNIndex, BruteForceKnnMetricKind,
+};
+use pathway_engine::external_integration::usearch_integration::USearchKNNIndex;
+use pathway_engine::external_integration::{
+    KeyFilter, KeyScoreMatch, NonFilteringExternalIndex, Unpack, VectorGroup,
+};
+
+fn point_key(i: usize) -> Key {
+    Key::for_value(&i64::try_from(i).unwrap().into())
+}
+
+// points (0, 0), (1, 0), ..., (n - 1, 0), so the i-th point is the i-th nearest to the origin
+fn points_on_line(n: usize) -> Vec<(Key, Vec<f64>)> {
+    (0..n)
+        .map(|i| (point_key(i), vec![i as f64, 0.0]))
+        .collect()
+}
+
+fn match_keys(matches: &[KeyScoreMatch]) -> Vec<Key> {
+    matches.iter().map(KeyScoreMatch::key).collect()
+}
+
+fn usearch_index(metric: MetricKind, quantization: ScalarKind, multi: bool) -> USearchKNNIndex {
+    USearchKNNIndex::new(2, 16, metric, 0, 0, 0, quantization, multi)
+        .expect("index creation should succeed")
+}
+
+fn search_keys<DataType>(
+    index: &dyn NonFilteringExternalIndex<DataType, Vec<f64>>,
+    query: Vec<f64>,
+    limit: usize,
+) -> Vec<Key> {
+    let query_key = Key::random();
+    let mut results = index.search(&[(query_key, query, limit)]);
+    assert_eq!(results.len(), 1);
+    let (key, matches) = results.pop().unwrap();
+    assert_eq!(key, query_key);
+    match_keys(&matches.expect("search should succeed"))
+}
+
+fn filtered_search_odd_points<DataType>(
+    index: &dyn NonFilteringExternalIndex<DataType, Vec<f64>>,
+) -> Vec<Key> {
+    let odd_keys: Vec<Key> = (0..10).filter(|i| i % 2 == 1).map(point_key).collect();
+    let predicate = |key: Key| odd_keys.contains(&key);
+    let query_key = Key::random();
+    let mut results = index
+        .filtered_search(&[(query_key, vec![0.0, 0.0], 3, &predicate as KeyFilter)])
+        .expect("filters should be pushed down");
+    assert_eq!(results.len(), 1);
+    let (key, matches) = results.pop().unwrap();
+    assert_eq!(key, query_key);
+    match_keys(&matches.expect("search should succeed"))
+}
+
+#[test]
+fn test_usearch_filtered_search_returns_limit_matches() {
+    let mut index = usearch_index(MetricKind::L2sq, ScalarKind::F32, false);
+    for (_key, result) in
+        NonFilteringExternalIndex::<Vec<f64>, _>::add(&mut index, points_on_line(10))
+    {
+        result.expect("add should succeed");
+    }
+
+    // the three nearest points (0, 1 and 2) are mostly rejected by the filter,
+    // but the index still returns three matches
+    assert_eq!(
+        filtered_search_odd_points::<Vec<f64>>(&index),
+        vec![point_key(1), point_key(3), point_key(5)]
+    );
+}
+
+#[test]
+fn test_brute_force_filtered_search_returns_limit_matches() {
+    let mut index = BruteForceKNNIndex::new(2, 16, 1024, BruteForceKnnMetricKind::L2sq)
+        .expect("index creation should succeed");
+    for (_key, result) in index.add(points_on_line(10)) {
+        result.expect("add should succeed");
+    }
+
+    assert_eq!(
+        filtered_search_odd_points(&index),
+        vec![point_key(1), point_key(3), point_key(5)]
+    );
+}
+
+#[test]
+fn test_brute_force_filtered_search_with_fewer_accepted_entries_than_limit() {
+    let mut index = BruteForceKNNIndex::new(2, 16, 1024, BruteForceKnnMetricKind::L2sq)
+        .expect("index creation should succeed");
+    for (_key, result) in index.add(points_on_line(10)) {
+        result.expect("add should succeed");
+    }
+
+    let accepted = point_key(7);
+    let predicate = |key: Key| key == accepted;
+    let results = index
+        .filtered_search(&[(Key::random(), vec![0.0, 0.0], 3, &predicate as KeyFilter)])
+        .expect("filters should be pushed down");
+    assert_eq!(
+        match_keys(results[0].1.as_ref().expect("search should succeed")),
+        vec![accepted]
+    );
+}
+
+fn brute_force_index_with_points(metric: BruteForceKnnMetricKind) -> BruteForceKNNIndex {
+    let mut index =
+        BruteForceKNNIndex::new(2, 16, 1024, metric).expect("index creation should succeed");
+    for (_key, result) in index.add(points_on_line(10)) {
+        result.expect("add should succeed");
+    }
+    index
+}
+
+#[test]
+fn test_brute_force_inner_product() {
+    let index = brute_force_index_with_points(BruteForceKnnMetricKind::Ip);
+    // the larger the inner product, the nearer the point
+    assert_eq!(
+        search_keys(&index, vec![1.0, 0.0], 3),
+        vec![point_key(9), point_key(8), point_key(7)]
+    );
+}
+
+#[test]
+fn test_brute_force_l1() {
+    let index = brute_force_index_with_points(BruteForceKnnMetricKind::L1);
+    assert_eq!(
+        search_keys(&index, vec![4.2, 1.0], 3),
+        vec![point_key(4), point_key(5), point_key(3)]
+    );
+}
+
+// byte strings with i leading bits set, so the i-th one is the i-th nearest to zero bytes
+fn binary_embeddings(n: usize) -> Vec<(Key, Vec<f64>)> {
+    (0..n)
+        .map(|i| {
+            let bits = u16::MAX << (16 - i);
+            let bytes = Value::Bytes(bits.to_be_bytes().into());
+            (point_key(i), bytes.unpack().expect("bytes should unpack"))
+        })
+        .collect()
+}
+
+fn zero_bytes_query() -> Vec<f64> {
+    Value::Bytes([0_u8, 0].into())
+        .unpack()
+        .expect("bytes should unpack")
+}
+
+#[test]
+fn test_brute_force_hamming_on_bytes() {
+    let mut index = BruteForceKNNIndex::new(16, 16, 1024, BruteForceKnnMetricKind::Hamming)
+        .expect("index creation should succeed");
+    for (_key, result) in index.add(binary_embeddings(10)) {
+        result.expect("add should succeed");
+    }
+
+    let query_key = Key::random();
+    let results = index.search(&[(query_key, zero_bytes_query(), 3)]);
+    let matches = results[0].1.as_ref().expect("search should succeed");
+    assert_eq!(
+        match_keys(matches),
+        vec![point_key(0), point_key(1), point_key(2)]
+    );
+    let scores: Vec<f64> = matches.iter().map(KeyScoreMatch::score).collect();
+    assert_eq!(scores, vec![0.0, -1.0, -2.0]);
+}
+
+#[test]
+fn test_usearch_quantization() {
+    for quantization in [ScalarKind::F64, ScalarKind::F16, ScalarKind::I8] {
+        let mut index = usearch_index(MetricKind::L2sq, quantization, false);
+        for (_key, result) in
+            NonFilteringExternalIndex::<Vec<f64>, _>::add(&mut index, points_on_line(10))
+        {
+            result.expect("add should succeed");
+        }
+        assert_eq!(
+            search_keys::<Vec<f64>>(&index, vec![0.0, 0.0], 3),
+            vec![point_key(0), point_key(1), point_key(2)]
+        );
+    }
+}
+
+#[test]
+fn test_usearch_binary_quantization_with_hamming() {
+    let mut index =
+        USearchKNNIndex::new(16, 16, MetricKind::Hamming, 0, 0, 0, ScalarKind::B1, false)
+            .expect("index creation should succeed");
+    for (_key, result) in
+        NonFilteringExternalIndex::<Vec<f64>, _>::add(&mut index, binary_embeddings(10))
+    {
+        result.expect("add should succeed");
+    }
+    assert_eq!(
+        search_keys::<Vec<f64>>(&index, zero_bytes_query(), 3),
+        vec![point_key(0), point_key(1), point_key(2)]
+    );
+}
+
+// key 0 has many vectors near the origin, the other keys have one vector each
+fn multi_vector_index() -> USearchKNNIndex {
+    let mut index = usearch_index(MetricKind::L2sq, ScalarKind::F32, true);
+    let near_origin = (0..20).map(|j| vec![0.0, f64::from(j) * 0.01]).collect();
+    let mut data = vec![(point_key(0), VectorGroup(near_origin))];
+    data.extend(
+        points_on_line(10)
+            .into_iter()
+            .skip(1)
+            .map(|(key, point)| (key, VectorGroup(vec![point]))),
+    );
+    for (_key, result) in NonFilteringExternalIndex::<VectorGroup, _>::add(&mut index, data) {
+        result.expect("add should succeed");
+    }
+    index
+}
+
+#[test]
+fn test_usearch_multi_vector_search_returns_distinct_keys() {
+    let index = multi_vector_index();
+    // the nearest 20 vectors all belong to key 0, but there are still 3 distinct matches
+    assert_eq!(
+        search_keys::<VectorGroup>(&index, vec![0.0, 0.0], 3),
+        vec![point_key(0), point_key(1), point_key(2)]
+    );
+}
+
+#[test]
+fn test_usearch_multi_vector_filtered_search() {
+    let index = multi_vector_index();
+    assert_eq!(
+        filtered_search_odd_points::<VectorGroup>(&index),
+        vec![point_key(1), point_key(3), point_key(5)]
+    );
+}
+
+#[test]
+fn test_usearch_multi_vector_limit_larger_than_number_of_keys() {
+    let index = multi_vector_index();
+    assert_eq!(
+        search_keys::<VectorGroup>(&index, vec![0.0, 0.0], 20),
+        (0..10).map(point_key).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_vector_group_unpacking() {
+    let vector = |coordinates: &[f64]| {
+        Value::Tuple(
+            coordinates
+                .iter()
+                .map(|x| Value::Float((*x).into()))
+                .collect(),
+        )
+    };
+
+    let VectorGroup(vectors) = Value::Tuple([vector(&[1.0, 2.0])].into())
+        .unpack()
+        .expect("a tuple of vectors should unpack");
+    assert_eq!(vectors, vec![vec![1.0, 2.0]]);
+
+    // a single vector is not a group
+    let group: Result<VectorGroup, _> = vector(&[1.0, 2.0]).unpack();
+    assert!(group.is_err());
+
+    // an empty tuple is an empty group, which can't be added to the index
+    let empty: VectorGroup = Value::Tuple([].into())
+        .unpack()
+        .expect("an empty tuple should unpack");
+    assert!(empty.0.is_empty());
+    let mut index = usearch_index(MetricKind::L2sq, ScalarKind::F32, true);
+    let results =
+        NonFilteringExternalIndex::<VectorGroup, _>::add(&mut index, vec![(point_key(0), empty)]);
+    assert!(results[0].1.is_err());
+}
+
+#[test]
+fn test_brute_force_batch_search_within_auxiliary_space() {
+    let points: Vec<(Key, Vec<f64>)> = (0..50)
+        .map(|i| (point_key(i), vec![(i % 7) as f64, (i / 7) as f64]))
+        .collect();
+    let queries: Vec<(Key, Vec<f64>, usize)> = (0..300)
+        .map(|i| {
+            let query = vec![f64::from(i % 13) * 0.5, f64::from(i % 11) * 0.7];
+            (Key::random(), query, 5)
+        })
+        .collect();
+
+    let mut reference_index =
+        BruteForceKNNIndex::new(2, 64, 1_000_000, BruteForceKnnMetricKind::L2sq)
+            .expect("index creation should succeed");
+    for (_key, result) in reference_index.add(points.clone()) {
+        result.expect("add should succeed");
+    }
+    let expected: Vec<Vec<Key>> = queries
+        .iter()
+        .map(|(_key, query, limit)| search_keys(&reference_index, query.clone(), *limit))
+        .collect();
+
+    // a small auxiliary space splits both the index and the queries into blocks,
+    // the results don't depend on the block sizes
+    for auxiliary_space in [1, 10, 49, 50, 51, 1000, 20_000] {
+        let mut index =
+            BruteForceKNNIndex::new(2, 64, auxiliary_space, BruteForceKnnMetricKind::L2sq)
+                .expect("index creation should succeed");
+        for (_key, result) in index.add(points.clone()) {
+            result.expect("add should succeed");
+        }
+        let results: Vec<Vec<Key>> = index
+            .search(&queries)
+            .into_iter()
+            .map(|(_key, matches)| match_keys(&matches.expect("search should succeed")))
+            .collect();
+        assert_eq!(results, expected, "auxiliary_space = {auxiliary_space}");
+    }
+}
@@ -1,9 +1,14 @@
 // Copyright © 2024 Pathway
 
+use std::sync::Arc;
+
 use tempfile::tempdir;
 
+use assert_matches::assert_matches;
+
+use pathway_engine::persistence::encryption::{Error as EncryptionError, Keyring};
 use pathway_engine::persistence::metadata_backends::file::FilesystemKVStorage;
-use pathway_engine::persistence::metadata_backends::MetadataBackend;
+use pathway_engine::persistence::metadata_backends::{EncryptedKVStorage, Error, MetadataBackend};
 
 #[test]
 fn test_simple_kv_operations() -> eyre::Result<()> {
@@ -28,3 +33,48 @@ fn test_simple_kv_operations() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_encrypted_kv_operations() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let old_key = [1; 32];
+    let new_key = [2; 32];
+
+    let mut plain_storage = FilesystemKVStorage::new(test_storage_path)?;
+    plain_storage.put_value("1", "one")?;
+
+    let mut storage = EncryptedKVStorage::new(
+        Box::new(FilesystemKVStorage::new(test_storage_path)?),
+        Arc::new(Keyring::new(old_key, &[])),
+    );
+    // the values stored before the encryption was enabled are rejected until migrated
+    assert_matches!(
+        storage.get_value("1"),
+        Err(Error::Encryption(EncryptionError::Unencrypted))
+    );
+    assert_eq!(storage.encrypt_plaintext_values()?, 1);
+    assert_eq!(storage.get_value("1")?, "one");
+    assert_ne!(plain_storage.get_value("1")?, "one");
+    assert_eq!(storage.encrypt_plaintext_values()?, 0);
+    storage.put_value("2", "two")?;
+    assert_eq!(storage.get_value("2")?, "two");
+    assert_ne!(plain_storage.get_value("2")?, "two");
+
+    let mut rotated_storage = EncryptedKVStorage::new(
+        Box::new(FilesystemKVStorage::new(test_storage_path)?),
+        Arc::new(Keyring::new(new_key, &[old_key])),
+    );
+    assert_eq!(rotated_storage.get_value("2")?, "two");
+    rotated_storage.put_value("2", "three")?;
+
+    let new_key_storage = EncryptedKVStorage::new(
+        Box::new(FilesystemKVStorage::new(test_storage_path)?),
+        Arc::new(Keyring::new(new_key, &[])),
+    );
+    assert_eq!(new_key_storage.get_value("2")?, "three");
+    assert_eq!(new_key_storage.list_keys()?, vec!["1", "2"]);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+// Copyright © 2024 Pathway
+
+use std::collections::HashSet;
+
+use pathway_engine::mat_mul::product_blocks;
+
+fn check_product_blocks(rows: usize, columns: usize, max_block_size: usize) {
+    let mut covered = HashSet::new();
+    let mut finished_columns = HashSet::new();
+    let mut current_columns = None;
+    for (block_rows, block_columns) in product_blocks(rows, columns, max_block_size) {
+        assert!(!block_rows.is_empty() && !block_columns.is_empty());
+        // the memory needed for a block is bounded, unless a single column doesn't fit
+        assert!(
+            block_rows.len() * block_columns.len() <= max_block_size.max(block_rows.len()),
+            "block {block_rows:?} x {block_columns:?} exceeds {max_block_size}"
+        );
+
+        // blocks sharing the columns are consecutive, so the columns are prepared only once
+        if current_columns.as_ref() != Some(&block_columns) {
+            if let Some(previous) = current_columns.replace(block_columns.clone()) {
+                finished_columns.insert(previous);
+            }
+            assert!(!finished_columns.contains(&block_columns));
+        }
+
+        for row in block_rows {
+            for column in block_columns.clone() {
+                assert!(
+                    covered.insert((row, column)),
+                    "({row}, {column}) covered twice"
+                );
+            }
+        }
+    }
+    assert_eq!(covered.len(), rows * columns);
+}
+
+#[test]
+fn test_product_blocks_cover_product_within_bound() {
+    for (rows, columns) in [(1, 1), (10, 1), (1, 10), (100, 1000), (1000, 3), (257, 513)] {
+        for max_block_size in [1, 7, 100, 256, 1000, 100_000] {
+            check_product_blocks(rows, columns, max_block_size);
+        }
+    }
+}
+
+#[test]
+fn test_product_blocks_empty() {
+    assert_eq!(product_blocks(0, 10, 100).count(), 0);
+    assert_eq!(product_blocks(10, 0, 100).count(), 0);
+}
@@ -0,0 +1,195 @@
+// Copyright © 2024 Pathway
+
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use differential_dataflow::input::InputSession;
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use timely::dataflow::operators::Probe;
+use timely::dataflow::ProbeHandle;
+use timely::worker::AsWorker;
+
+use pathway_engine::connectors::monitoring::OutputConnectorStats;
+use pathway_engine::engine::dataflow::arrangement_stats::{
+    arrange_deferred, ArrangementStatsRegistry,
+};
+use pathway_engine::engine::error::Trace;
+use pathway_engine::engine::health::HealthState;
+use pathway_engine::engine::http_server::monitoring_http_address;
+use pathway_engine::engine::Error;
+
+#[test]
+fn test_sink_stats_are_shared() {
+    let mut stats = OutputConnectorStats::new("test_sink".to_string());
+    let shared_stats = stats.shared_stats();
+
+    stats.on_batch_started();
+    stats.on_batch_entry_written();
+    stats.on_batch_entry_written();
+    stats.on_batch_finished();
+    stats.on_time_committed(Some(2));
+
+    let sink_stats = *shared_stats.lock().unwrap();
+    assert_eq!(sink_stats.num_messages_from_start, 2);
+    assert_eq!(sink_stats.num_batches_from_start, 1);
+    assert_eq!(sink_stats.last_committed_time, Some(2));
+    assert!(!sink_stats.finished);
+
+    stats.on_batch_started();
+    stats.on_batch_entry_written();
+    stats.on_batch_finished();
+    stats.on_time_committed(None);
+
+    let sink_stats = *shared_stats.lock().unwrap();
+    assert_eq!(sink_stats.num_messages_from_start, 3);
+    assert_eq!(sink_stats.num_batches_from_start, 2);
+    assert_eq!(sink_stats.last_committed_time, Some(2));
+    assert!(sink_stats.finished);
+}
+
+#[test]
+fn test_arrangement_stats_are_attributed_to_operators() {
+    let registry = Arc::new(ArrangementStatsRegistry::default());
+    let (per_worker, per_operator) = timely::execute_directly(move |worker| {
+        registry.attach(worker);
+        let mut first_input = InputSession::<u64, (u64, u64), isize>::new();
+        let mut second_input = InputSession::<u64, (u64, u64), isize>::new();
+        let probe = ProbeHandle::new();
+        let worker_index = worker.index();
+        worker.dataflow(|scope| {
+            registry.register_operator(worker_index, scope.new_identifier(), 1, Trace::Empty);
+            first_input
+                .to_collection(scope)
+                .arrange_by_key()
+                .stream
+                .probe_with(&probe);
+            registry.register_operator(worker_index, scope.new_identifier(), 2, Trace::Empty);
+            second_input
+                .to_collection(scope)
+                .arrange_by_key()
+                .stream
+                .probe_with(&probe);
+        });
+
+        for i in 0..3 {
+            first_input.insert((i, i));
+        }
+        second_input.insert((0, 0));
+        first_input.advance_to(1);
+        second_input.advance_to(1);
+        first_input.flush();
+        second_input.flush();
+        while probe.less_than(&1) {
+            worker.step();
+        }
+        worker.step();
+
+        (registry.per_worker(), registry.per_operator())
+    });
+
+    assert_eq!(per_worker[&0].num_records, 4);
+    assert_eq!(per_operator.len(), 2);
+    assert_eq!(per_operator[0].operator_id, Some(1));
+    assert_eq!(per_operator[0].num_arrangements, 1);
+    assert_eq!(per_operator[0].stats.num_records, 3);
+    assert_eq!(per_operator[1].operator_id, Some(2));
+    assert_eq!(per_operator[1].stats.num_records, 1);
+    assert!(per_operator[0].stats.approximate_bytes > per_operator[1].stats.approximate_bytes);
+}
+
+#[test]
+fn test_health_state() {
+    let health = HealthState::new(2);
+    let first_worker = health.register_worker(0);
+    first_worker.heartbeat(true);
+    assert!(!health.is_healthy());
+    assert!(!health.is_ready());
+
+    let second_worker = health.register_worker(1);
+    second_worker.heartbeat(false);
+    assert!(health.is_healthy());
+    assert!(!health.is_ready());
+
+    second_worker.heartbeat(true);
+    assert!(health.is_ready());
+
+    first_worker.finish();
+    assert!(health.is_healthy());
+    assert!(health.is_ready());
+
+    second_worker.fail();
+    assert!(!health.is_healthy());
+}
+
+#[test]
+fn test_health_state_keeps_last_error() {
+    let health = HealthState::new(1);
+    assert!(health.last_error().is_none());
+
+    health.record_error(1, "division by zero".to_string(), String::new());
+    health.record_error(2, "value error".to_string(), "Occurred here".to_string());
+
+    let last_error = health.last_error().unwrap();
+    assert_eq!(last_error.operator_id, 2);
+    assert_eq!(last_error.message, "value error");
+    assert_eq!(last_error.trace, "Occurred here");
+}
+
+#[test]
+fn test_deferred_arrangements_are_attributed_to_data_creators() {
+    let registry = Arc::new(ArrangementStatsRegistry::default());
+    let per_operator = timely::execute_directly(move |worker| {
+        registry.attach(worker);
+        let mut input = InputSession::<u64, (u64, u64), isize>::new();
+        let probe = ProbeHandle::new();
+        let worker_index = worker.index();
+        worker.dataflow(|scope| {
+            registry.register_operator(worker_index, scope.new_identifier(), 1, Trace::Empty);
+            let collection = input.to_collection(scope);
+            let created_at = scope.new_identifier();
+            registry.register_operator(worker_index, scope.new_identifier(), 2, Trace::Empty);
+            arrange_deferred(scope, created_at, || collection.arrange_by_key())
+                .stream
+                .probe_with(&probe);
+            registry.register_operator(worker_index, scope.new_identifier(), 3, Trace::Empty);
+            collection.arrange_by_key().stream.probe_with(&probe);
+        });
+
+        for i in 0..3 {
+            input.insert((i, i));
+        }
+        input.advance_to(1);
+        input.flush();
+        while probe.less_than(&1) {
+            worker.step();
+        }
+        worker.step();
+
+        registry.per_operator()
+    });
+
+    let operator_ids: Vec<_> = per_operator.iter().map(|entry| entry.operator_id).collect();
+    assert_eq!(operator_ids, vec![Some(1), Some(3)]);
+    assert_eq!(per_operator[0].num_arrangements, 1);
+    assert_eq!(per_operator[0].stats.num_records, 3);
+}
+
+#[test]
+fn test_monitoring_http_address() -> eyre::Result<()> {
+    assert_eq!(
+        monitoring_http_address(Some("0.0.0.0"), Some(30000), 2)?,
+        "0.0.0.0:30002".parse()?
+    );
+    assert_matches!(
+        monitoring_http_address(Some("localhost:30000"), Some(30000), 0),
+        Err(Error::InvalidMonitoringHttpHost(host)) if host == "localhost:30000"
+    );
+    assert_matches!(
+        monitoring_http_address(Some("127.0.0.1"), Some(u16::MAX), 1),
+        Err(Error::MonitoringHttpPortOutOfRange {
+            base_port: u16::MAX,
+            process_id: 1
+        })
+    );
+    Ok(())
+}
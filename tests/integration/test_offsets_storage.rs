@@ -1,6 +1,7 @@
 // Copyright © 2024 Pathway
 
 use super::helpers::create_persistence_manager;
+use super::helpers::create_worker_persistence_manager;
 use super::helpers::get_entries_in_receiver;
 
 use std::sync::mpsc;
@@ -10,11 +11,16 @@ use std::time::Duration;
 use tempfile::tempdir;
 
 use pathway_engine::connectors::data_storage::StorageType;
-use pathway_engine::connectors::{Connector, Entry, PersistenceMode};
+use pathway_engine::connectors::snapshot::Event as SnapshotEvent;
+use pathway_engine::connectors::{Connector, Entry, PersistenceMode, SnapshotMode};
 use pathway_engine::connectors::{OffsetKey, OffsetValue};
-use pathway_engine::engine::{Timestamp, TotalFrontier};
+use pathway_engine::engine::{Key, Timestamp, TotalFrontier, Value};
+use pathway_engine::persistence::config::ReadersQueryPurpose;
 use pathway_engine::persistence::frontier::OffsetAntichain;
-use pathway_engine::persistence::metadata_backends::FilesystemKVStorage;
+use pathway_engine::persistence::metadata_backends::{
+    FilesystemKVStorage, MetadataBackend, ObjectStoreKVStorage,
+};
+use pathway_engine::persistence::object_store::ObjectStoreConfig;
 use pathway_engine::persistence::state::MetadataAccessor;
 
 fn assert_frontiers_equal(
@@ -309,7 +315,8 @@ fn test_metadata_files_versioning() -> eyre::Result<()> {
     }
 
     {
-        let ms = MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0)?;
+        let ms =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
         assert_eq!(
             ms.past_runs_threshold_times().values().min().unwrap(),
             &TotalFrontier::At(Timestamp(100))
@@ -328,7 +335,8 @@ fn test_metadata_files_versioning() -> eyre::Result<()> {
             .update_sink_finalized_time(sink_id, None);
     }
     {
-        let ms = MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0)?;
+        let ms =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
         assert_eq!(
             ms.past_runs_threshold_times().values().min().unwrap(),
             &TotalFrontier::Done
@@ -346,7 +354,8 @@ fn test_metadata_files_versioning() -> eyre::Result<()> {
             .update_sink_finalized_time(sink_id, Some(Timestamp(100)));
     }
     {
-        let ms = MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0)?;
+        let ms =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
         assert_eq!(
             ms.past_runs_threshold_times().values().min().unwrap(),
             &TotalFrontier::At(Timestamp(100))
@@ -355,3 +364,218 @@ fn test_metadata_files_versioning() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_metadata_of_former_workers_is_adopted() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    // the first run has two workers
+    for (worker_id, finalized_time) in [(0, 100), (1, 50)] {
+        let tracker = create_worker_persistence_manager(test_storage_path, worker_id, 2);
+        let sink_id = tracker.lock().unwrap().register_sink();
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(sink_id, Some(Timestamp(finalized_time)));
+    }
+
+    // the second run has a single worker, which takes over the state of the former one
+    sleep(Duration::from_millis(10));
+    {
+        let ms =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
+        assert_eq!(
+            ms.past_runs_threshold_times().values().min().unwrap(),
+            &TotalFrontier::At(Timestamp(50))
+        );
+    }
+    {
+        let tracker = create_worker_persistence_manager(test_storage_path, 0, 1);
+        let sink_id = tracker.lock().unwrap().register_sink();
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(sink_id, Some(Timestamp(200)));
+    }
+
+    // the threshold is no longer held back by the worker that doesn't exist
+    {
+        let ms =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
+        assert_eq!(ms.past_runs_threshold_times().len(), 1);
+        assert_eq!(
+            ms.past_runs_threshold_times().values().min().unwrap(),
+            &TotalFrontier::At(Timestamp(200))
+        );
+    }
+
+    Ok(())
+}
+
+fn write_worker_rows(
+    path: &std::path::Path,
+    worker_id: usize,
+    total_workers: usize,
+    rows: &[i64],
+    time: u64,
+    finalized_time: u64,
+) -> eyre::Result<()> {
+    let tracker = create_worker_persistence_manager(path, worker_id, total_workers);
+    let snapshot_writer = tracker
+        .lock()
+        .unwrap()
+        .create_snapshot_writer(42, SnapshotMode::Full)?;
+    let mut frontier = OffsetAntichain::new();
+    frontier.advance_offset(
+        OffsetKey::Kafka("test".to_string().into(), i32::try_from(worker_id)?),
+        OffsetValue::KafkaOffset(i64::try_from(time)?),
+    );
+    {
+        let mut snapshot_writer = snapshot_writer.lock().unwrap();
+        for row in rows {
+            snapshot_writer.write(&SnapshotEvent::Insert(
+                Key::random(),
+                vec![Value::Int(*row)],
+            ))?;
+        }
+        snapshot_writer.write(&SnapshotEvent::AdvanceTime(Timestamp(time), frontier))?;
+    }
+    let sink_id = tracker.lock().unwrap().register_sink();
+    tracker
+        .lock()
+        .unwrap()
+        .update_sink_finalized_time(sink_id, Some(Timestamp(finalized_time)));
+    Ok(())
+}
+
+fn rewind_all_workers(
+    path: &std::path::Path,
+    total_workers: usize,
+) -> (Vec<i64>, Vec<(OffsetKey, OffsetValue)>) {
+    let mut rows = Vec::new();
+    let mut frontier = OffsetAntichain::new();
+    for worker_id in 0..total_workers {
+        let tracker = create_worker_persistence_manager(path, worker_id, total_workers);
+        let (sender, receiver) = mpsc::channel();
+        Connector::rewind_from_disk_snapshot(42, &tracker, &sender, PersistenceMode::Batch);
+        drop(sender);
+        for entry in get_entries_in_receiver(receiver) {
+            if let Entry::Snapshot(SnapshotEvent::Insert(_, values)) = entry {
+                let [Value::Int(row)] = values.as_slice() else {
+                    panic!("unexpected values: {values:?}");
+                };
+                rows.push(*row);
+            }
+        }
+
+        let snapshot_readers = tracker
+            .lock()
+            .unwrap()
+            .create_snapshot_readers(42, ReadersQueryPurpose::ReconstructFrontier)
+            .expect("failed to create snapshot readers");
+        let mut worker_frontier = OffsetAntichain::new();
+        for mut snapshot_reader in snapshot_readers {
+            while !matches!(snapshot_reader.read(), Ok(SnapshotEvent::Finished) | Err(_)) {}
+            worker_frontier = StorageType::Kafka
+                .merge_two_frontiers(&worker_frontier, snapshot_reader.last_frontier());
+        }
+        if worker_id == 0 {
+            frontier = worker_frontier;
+        } else {
+            // all workers see the same frontier, as it is merged from every former worker
+            assert_frontiers_equal(frontier.as_vec(), worker_frontier.as_vec());
+        }
+    }
+    rows.sort_unstable();
+    (rows, frontier.as_vec())
+}
+
+#[test]
+fn test_rescaling_workers_keeps_all_rows() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    // the first run has four workers
+    for worker_id in 0..4 {
+        let first_row = i64::try_from(worker_id)? * 10;
+        let rows: Vec<i64> = (first_row..first_row + 3).collect();
+        write_worker_rows(test_storage_path, worker_id, 4, &rows, 2, 10)?;
+    }
+    let first_run_rows: Vec<i64> = (0..4)
+        .flat_map(|worker_id| (0..3).map(move |i| worker_id * 10 + i))
+        .collect();
+
+    // the second run has eight workers, each row is rewound exactly once
+    sleep(Duration::from_millis(10));
+    let (rows, frontier) = rewind_all_workers(test_storage_path, 8);
+    assert_eq!(rows, first_run_rows);
+    assert_frontiers_equal(
+        frontier,
+        (0..4)
+            .map(|partition| {
+                (
+                    OffsetKey::Kafka("test".to_string().into(), partition),
+                    OffsetValue::KafkaOffset(2),
+                )
+            })
+            .collect(),
+    );
+    for worker_id in 0..8 {
+        let row = 100 + i64::try_from(worker_id)?;
+        write_worker_rows(test_storage_path, worker_id, 8, &[row], 12, 20)?;
+    }
+
+    // the third run has four workers again, the snapshots of the former ones are adopted
+    sleep(Duration::from_millis(10));
+    let (rows, frontier) = rewind_all_workers(test_storage_path, 4);
+    let mut all_rows: Vec<i64> = first_run_rows.into_iter().chain(100..108).collect();
+    all_rows.sort_unstable();
+    assert_eq!(rows, all_rows);
+    assert_frontiers_equal(
+        frontier,
+        (0..8)
+            .map(|partition| {
+                (
+                    OffsetKey::Kafka("test".to_string().into(), partition),
+                    OffsetValue::KafkaOffset(12),
+                )
+            })
+            .collect(),
+    );
+    for worker_id in 0..4 {
+        write_worker_rows(test_storage_path, worker_id, 4, &[], 22, 30)?;
+    }
+
+    // the metadata blocks of the former workers are gone after the adoption
+    sleep(Duration::from_millis(10));
+    let ms = MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 4)?;
+    assert_eq!(ms.past_runs_threshold_times().len(), 4);
+    assert_eq!(
+        ms.past_runs_threshold_times().values().min().unwrap(),
+        &TotalFrontier::At(Timestamp(30))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_object_store_metadata_backend() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let store = ObjectStoreConfig::Sqlite(test_storage.path().join("persistence.db")).connect()?;
+    store.put_object("root/streams/0/1/100", b"snapshot chunk")?;
+
+    let mut backend = ObjectStoreKVStorage::new(store, "root");
+    backend.put_value("1-0-0", "first")?;
+    backend.put_value("1-0-0", "second")?;
+    backend.put_value("1-1-0", "other")?;
+    let mut keys = backend.list_keys()?;
+    keys.sort();
+    assert_eq!(keys, vec!["1-0-0".to_string(), "1-1-0".to_string()]);
+    assert_eq!(backend.get_value("1-0-0")?, "second");
+
+    backend.remove_key("1-1-0")?;
+    assert_eq!(backend.list_keys()?, vec!["1-0-0".to_string()]);
+
+    Ok(())
+}
@@ -0,0 +1,499 @@
+// Copyright © 2024 Pathway
+
+use std::num::NonZeroUsize;
+
+use differential_dataflow::difference::{Multiply, Semigroup};
+
+use pathway_engine::engine::reduce::{
+    CountDistinctApproxReducer, CountDistinctReducer, MomentsReducer, MomentsState,
+    MomentsStatistic, QuantilesReducer, QuantilesState, ReducerImpl, SemigroupReducerImpl,
+    TopKOrdering, TopKReducer,
+};
+use pathway_engine::engine::{Key, KeyImpl, Value};
+
+fn semigroup_reduce<R: SemigroupReducerImpl>(reducer: &R, updates: &[(Value, isize)]) -> Value {
+    let state = updates
+        .iter()
+        .map(|(value, diff)| {
+            reducer
+                .init(&Key::random(), value)
+                .expect("init should succeed")
+                .multiply(diff)
+        })
+        .reduce(|mut lhs, rhs| {
+            lhs.plus_equals(&rhs);
+            lhs
+        })
+        .expect("updates should not be empty");
+    reducer.finish(state)
+}
+
+fn assert_float_eq(value: &Value, expected: f64) {
+    let actual = value.as_float().expect("result should be a float");
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn test_mean() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Mean);
+    let result = semigroup_reduce(
+        &reducer,
+        &[
+            (Value::Int(1), 1),
+            (Value::Float(2.0.into()), 1),
+            (Value::Int(6), 1),
+        ],
+    );
+    assert_float_eq(&result, 3.0);
+}
+
+#[test]
+fn test_variance_population_and_sample() {
+    let updates = [
+        (Value::Int(2), 1),
+        (Value::Int(4), 1),
+        (Value::Int(4), 1),
+        (Value::Int(4), 1),
+        (Value::Int(5), 1),
+        (Value::Int(5), 1),
+        (Value::Int(7), 1),
+        (Value::Int(9), 1),
+    ];
+    let population = MomentsReducer::new(MomentsStatistic::Variance { sample: false });
+    assert_float_eq(&semigroup_reduce(&population, &updates), 4.0);
+    let sample = MomentsReducer::new(MomentsStatistic::Variance { sample: true });
+    assert_float_eq(&semigroup_reduce(&sample, &updates), 32.0 / 7.0);
+    let stddev = MomentsReducer::new(MomentsStatistic::StdDev { sample: false });
+    assert_float_eq(&semigroup_reduce(&stddev, &updates), 2.0);
+}
+
+#[test]
+fn test_variance_with_retractions() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Variance { sample: false });
+    let result = semigroup_reduce(
+        &reducer,
+        &[
+            (Value::Int(1), 1),
+            (Value::Int(100), 1),
+            (Value::Int(3), 2),
+            (Value::Int(100), -1),
+            (Value::Int(3), -1),
+        ],
+    );
+    assert_float_eq(&result, 1.0);
+}
+
+#[test]
+fn test_sample_variance_of_single_element() {
+    let reducer = MomentsReducer::new(MomentsStatistic::StdDev { sample: true });
+    let result = semigroup_reduce(
+        &reducer,
+        &[(Value::Int(1), 1), (Value::Int(5), 1), (Value::Int(5), -1)],
+    );
+    assert_eq!(result, Value::None);
+}
+
+#[test]
+fn test_moments_fully_retracted_state_is_zero() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Mean);
+    let mut state = reducer
+        .init(&Key::random(), &Value::Float(0.1.into()))
+        .unwrap();
+    state.plus_equals(
+        &reducer
+            .init(&Key::random(), &Value::Float(0.7.into()))
+            .unwrap(),
+    );
+    state.plus_equals(
+        &reducer
+            .init(&Key::random(), &Value::Float(0.1.into()))
+            .unwrap()
+            .multiply(&-1),
+    );
+    state.plus_equals(
+        &reducer
+            .init(&Key::random(), &Value::Float(0.7.into()))
+            .unwrap()
+            .multiply(&-1),
+    );
+    assert!(state.is_zero());
+}
+
+fn moments_states(reducer: &MomentsReducer, values: &[f64]) -> Vec<MomentsState> {
+    values
+        .iter()
+        .map(|value| {
+            reducer
+                .init(&Key::random(), &Value::Float((*value).into()))
+                .expect("init should succeed")
+        })
+        .collect()
+}
+
+fn merge_states(states: impl IntoIterator<Item = MomentsState>) -> MomentsState {
+    states
+        .into_iter()
+        .reduce(|mut lhs, rhs| {
+            lhs.plus_equals(&rhs);
+            lhs
+        })
+        .expect("states should not be empty")
+}
+
+#[test]
+fn test_moments_state_merge_order() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Variance { sample: true });
+    let values = [3.5, -1.0, 12.25, 0.0, 7.0, 7.0, -4.5, 100.0];
+    let states = moments_states(&reducer, &values);
+    let expected = reducer.finish(merge_states(states.clone()));
+    assert_float_eq(&expected, 1_193.114_955_357_143);
+
+    let reversed = merge_states(states.iter().rev().cloned());
+    assert_eq!(reversed, merge_states(states.clone()));
+
+    // partial aggregates merged together, as done by the arrangements
+    let (left, right) = states.split_at(3);
+    let mut tree = merge_states(right.iter().cloned());
+    tree.plus_equals(&merge_states(left.iter().cloned()));
+    assert_eq!(tree, merge_states(states));
+}
+
+#[test]
+fn test_moments_state_multiplied_and_retracted() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Variance { sample: false });
+    let states = moments_states(&reducer, &[1.0, 2.0, 6.0]);
+    let group = merge_states(states.clone());
+
+    // three copies of a group have the same variance as the group
+    let tripled = group.clone().multiply(&3);
+    assert_float_eq(&reducer.finish(tripled.clone()), 14.0 / 3.0);
+
+    // retracting a merged group from a larger aggregate leaves the rest
+    let mut state = tripled;
+    state.plus_equals(&states[2].clone().multiply(&2));
+    state.plus_equals(&group.clone().multiply(&-3));
+    assert_float_eq(&reducer.finish(state.clone()), 0.0);
+    state.plus_equals(&states[2].clone().multiply(&-2));
+    assert!(state.is_zero());
+}
+
+#[test]
+fn test_moments_in_place_update() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Mean);
+    let states = moments_states(&reducer, &[0.1, 0.2, 0.3]);
+
+    // an update replacing 0.1 with 0.3, with both changes merged before the group
+    let mut update = states[0].clone().multiply(&-1);
+    update.plus_equals(&states[2]);
+    assert!(!update.is_zero());
+
+    let mut state = merge_states(states[..2].iter().cloned());
+    state.plus_equals(&update);
+    assert_eq!(state, merge_states(states[1..].iter().cloned()));
+    assert_float_eq(&reducer.finish(state), 0.25);
+
+    // an update that doesn't change the value cancels out
+    let mut update = states[0].clone().multiply(&-1);
+    update.plus_equals(&states[0]);
+    assert!(update.is_zero());
+}
+
+#[test]
+fn test_moments_states_with_opposite_counts_merged_in_both_orders() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Variance { sample: false });
+    let group = merge_states(moments_states(&reducer, &[1.5, -2.0, 10.0]));
+    let retraction = merge_states(moments_states(&reducer, &[1.5, 7.25])).multiply(&-1);
+
+    let mut left = group.clone();
+    left.plus_equals(&retraction);
+    let mut right = retraction;
+    right.plus_equals(&group);
+    assert_eq!(left, right);
+
+    // the retracted value that was never inserted cancels out once it arrives
+    left.plus_equals(&moments_states(&reducer, &[7.25])[0]);
+    assert_eq!(left, merge_states(moments_states(&reducer, &[-2.0, 10.0])));
+    assert_float_eq(&reducer.finish(left), 36.0);
+}
+
+#[test]
+fn test_moments_rejects_non_finite_values() {
+    let reducer = MomentsReducer::new(MomentsStatistic::Mean);
+    for value in [f64::NAN, f64::INFINITY, 1e200] {
+        assert!(reducer
+            .init(&Key::random(), &Value::Float(value.into()))
+            .is_err());
+    }
+}
+
+#[test]
+fn test_count_distinct() {
+    let result = semigroup_reduce(
+        &CountDistinctReducer,
+        &[
+            (Value::from("a"), 1),
+            (Value::from("b"), 2),
+            (Value::from("a"), 1),
+            (Value::from("c"), 1),
+            (Value::from("b"), -2),
+        ],
+    );
+    assert_eq!(result, Value::Int(2));
+}
+
+#[test]
+fn test_count_distinct_approx() {
+    let reducer = CountDistinctApproxReducer::new(12).unwrap();
+    let updates: Vec<_> = (0..10_000)
+        .map(|i| (Value::Int(i % 5_000), 1))
+        .chain((0..1_000).map(|i| (Value::Int(i), -2)))
+        .collect();
+    let result = semigroup_reduce(&reducer, &updates).as_int().unwrap();
+    assert!(
+        (3_800..=4_200).contains(&result),
+        "estimate {result} is too far from 4000"
+    );
+}
+
+#[test]
+fn test_count_distinct_approx_small_cardinality() {
+    let reducer = CountDistinctApproxReducer::new(14).unwrap();
+    let updates: Vec<_> = (0..10).map(|i| (Value::Int(i), 1)).collect();
+    assert_eq!(semigroup_reduce(&reducer, &updates), Value::Int(10));
+}
+
+#[test]
+fn test_count_distinct_approx_precision_validation() {
+    for precision in [0, 3, 19, u8::MAX] {
+        assert!(CountDistinctApproxReducer::new(precision).is_err());
+    }
+    assert!(CountDistinctApproxReducer::new(CountDistinctApproxReducer::MIN_PRECISION).is_ok());
+    assert!(CountDistinctApproxReducer::new(CountDistinctApproxReducer::MAX_PRECISION).is_ok());
+}
+
+#[test]
+fn test_quantile_exact() {
+    let updates: Vec<_> = [1, 2, 3, 4, 100]
+        .into_iter()
+        .map(|i| (Value::Int(i), 1))
+        .chain([(Value::Int(100), -1)])
+        .collect();
+    let median = QuantilesReducer::new_single(0.5).unwrap();
+    assert_float_eq(&semigroup_reduce(&median, &updates), 2.5);
+    let quantiles = QuantilesReducer::new_multiple(vec![0.0, 1.0]).unwrap();
+    let result = semigroup_reduce(&quantiles, &updates);
+    let result = result.as_tuple().unwrap();
+    assert_float_eq(&result[0], 1.0);
+    assert_float_eq(&result[1], 4.0);
+}
+
+#[test]
+fn test_quantile_sketch_with_retractions() {
+    let reducer = QuantilesReducer::new_multiple(vec![0.5, 0.99]).unwrap();
+    let updates: Vec<_> = (1..=20_000)
+        .map(|i| (Value::Int(i), 1))
+        .chain((10_001..=20_000).map(|i| (Value::Int(i), -1)))
+        .collect();
+    let result = semigroup_reduce(&reducer, &updates);
+    let result = result.as_tuple().unwrap();
+    for (value, expected) in result.iter().zip([5_000.0, 9_900.0]) {
+        let value = value.as_float().unwrap();
+        assert!(
+            (value - expected).abs() <= 0.02 * expected,
+            "expected about {expected}, got {value}"
+        );
+    }
+}
+
+#[test]
+fn test_quantile_out_of_range() {
+    for quantile in [-0.1, 1.5, f64::NAN] {
+        assert!(QuantilesReducer::new_single(quantile).is_err());
+        assert!(QuantilesReducer::new_multiple(vec![0.5, quantile]).is_err());
+    }
+    assert!(QuantilesReducer::new_multiple(Vec::new()).is_err());
+}
+
+#[test]
+fn test_quantile_exact_for_close_values() {
+    // the values are closer than the accuracy of the sketch
+    let reducer = QuantilesReducer::new_multiple(vec![0.25, 0.5]).unwrap();
+    let values = [100.0, 100.25, 100.5, 101.0, 100.75];
+    let states: Vec<_> = values
+        .iter()
+        .map(|value| {
+            reducer
+                .init(&Key::random(), &Value::Float((*value).into()))
+                .unwrap()
+        })
+        .collect();
+
+    // 100.75 is replaced with 100.5, merged before and after the rest of the group
+    let mut update = states[4].clone().multiply(&-1);
+    update.plus_equals(&states[2]);
+    let mut update_last = sum_quantiles_states(states.iter().cloned());
+    update_last.plus_equals(&update);
+    let mut update_first = update;
+    update_first.plus_equals(&sum_quantiles_states(states.iter().cloned()));
+    assert_eq!(update_first, update_last);
+
+    let result = reducer.finish(update_first);
+    let result = result.as_tuple().unwrap();
+    assert_eq!(result[0], Value::Float(100.25.into()));
+    assert_eq!(result[1], Value::Float(100.5.into()));
+}
+
+fn quantiles_states(reducer: &QuantilesReducer, values: &[(i64, isize)]) -> Vec<QuantilesState> {
+    values
+        .iter()
+        .map(|(value, diff)| {
+            reducer
+                .init(&Key::random(), &Value::Int(*value))
+                .expect("init should succeed")
+                .multiply(diff)
+        })
+        .collect()
+}
+
+fn sum_quantiles_states(states: impl IntoIterator<Item = QuantilesState>) -> QuantilesState {
+    states
+        .into_iter()
+        .reduce(|mut lhs, rhs| {
+            lhs.plus_equals(&rhs);
+            lhs
+        })
+        .expect("states should not be empty")
+}
+
+#[test]
+fn test_quantiles_state_merge_order() {
+    let reducer = QuantilesReducer::new_single(0.9).unwrap();
+    let updates: Vec<_> = (0..10_000)
+        .map(|i| ((i * 7_919) % 10_007 - 5_000, 1))
+        .chain((0..3_000).map(|i| ((i * 7_919) % 10_007 - 5_000, -1)))
+        .collect();
+    let states = quantiles_states(&reducer, &updates);
+
+    let in_order = sum_quantiles_states(states.clone());
+    let reversed = sum_quantiles_states(states.iter().rev().cloned());
+    // retractions merged before the values they retract
+    let (inserted, retracted) = states.split_at(10_000);
+    let mut retractions_first = sum_quantiles_states(retracted.iter().cloned());
+    for chunk in inserted.chunks(1_000) {
+        retractions_first.plus_equals(&sum_quantiles_states(chunk.iter().cloned()));
+    }
+
+    assert_eq!(in_order, reversed);
+    assert_eq!(in_order, retractions_first);
+    assert_eq!(reducer.finish(in_order), reducer.finish(retractions_first));
+}
+
+#[test]
+fn test_quantiles_exact_after_retracting_large_group() {
+    let reducer = QuantilesReducer::new_multiple(vec![0.0, 0.5, 1.0]).unwrap();
+    let remaining = [(-20, 1), (3, 2), (1_000, 1)];
+    let updates: Vec<_> = (1..=20_000)
+        .map(|i| (i, 1))
+        .chain(remaining)
+        .chain((1..=20_000).map(|i| (i, -1)))
+        .collect();
+    let state = sum_quantiles_states(quantiles_states(&reducer, &updates));
+
+    // once the large group is retracted, the state is the same as if it was never there
+    assert_eq!(
+        state,
+        sum_quantiles_states(quantiles_states(&reducer, &remaining))
+    );
+    assert_eq!(
+        reducer.finish(state),
+        Value::from(
+            [
+                Value::Float((-20.0).into()),
+                Value::Float(3.0.into()),
+                Value::Float(1_000.0.into())
+            ]
+            .as_slice()
+        )
+    );
+}
+
+#[test]
+fn test_quantiles_state_fully_retracted_is_zero() {
+    let reducer = QuantilesReducer::new_single(0.5).unwrap();
+    let updates: Vec<_> = [0, -5, 17, 17]
+        .into_iter()
+        .map(|i| (i, 1))
+        .chain([17, 0, 17, -5].into_iter().map(|i| (i, -1)))
+        .collect();
+    assert!(sum_quantiles_states(quantiles_states(&reducer, &updates)).is_zero());
+}
+
+fn top_k(reducer: &TopKReducer, rows: &[(Key, Vec<Value>)]) -> Value {
+    let states = rows
+        .iter()
+        .map(|(key, values)| reducer.init(key, values).expect("init should succeed"))
+        .collect::<Vec<_>>();
+    let combined = reducer
+        .combine(states.iter().map(|state| (state, NonZeroUsize::MIN)))
+        .expect("combine should succeed");
+    reducer.finish(combined)
+}
+
+fn top_k_pair(value: &str, key: KeyImpl) -> Value {
+    Value::from([Value::from(value), Value::Pointer(Key(key))].as_slice())
+}
+
+fn top_k_rows() -> Vec<(Key, Vec<Value>)> {
+    vec![
+        (Key(3), vec![Value::from("c"), Value::Int(5)]),
+        (Key(1), vec![Value::from("a"), Value::Int(7)]),
+        (Key(4), vec![Value::from("d"), Value::Int(1)]),
+        (Key(2), vec![Value::from("b"), Value::Int(5)]),
+    ]
+}
+
+#[test]
+fn test_top_k_with_ties() {
+    let reducer = TopKReducer::new(
+        3,
+        TopKOrdering {
+            descending: true,
+            order_column: Some(1),
+        },
+    );
+    assert_eq!(
+        top_k(&reducer, &top_k_rows()),
+        Value::from([top_k_pair("a", 1), top_k_pair("b", 2), top_k_pair("c", 3)].as_slice())
+    );
+}
+
+#[test]
+fn test_top_k_ordered_by_reduced_value() {
+    // the second argument is not used for ordering unless it is the order column
+    let reducer = TopKReducer::new(
+        2,
+        TopKOrdering {
+            descending: true,
+            order_column: None,
+        },
+    );
+    assert_eq!(
+        top_k(&reducer, &top_k_rows()),
+        Value::from([top_k_pair("d", 4), top_k_pair("c", 3)].as_slice())
+    );
+}
+
+#[test]
+fn test_top_k_missing_order_column() {
+    let reducer = TopKReducer::new(
+        2,
+        TopKOrdering {
+            descending: false,
+            order_column: Some(1),
+        },
+    );
+    assert!(reducer.init(&Key(1), &[Value::from("a")]).is_err());
+}
@@ -4,21 +4,38 @@ use super::helpers::create_persistence_manager;
 use super::helpers::get_entries_in_receiver;
 
 use assert_matches::assert_matches;
-use pathway_engine::engine::Timestamp;
-use std::fs::File;
+use pathway_engine::engine::{Timestamp, TotalFrontier};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use std::path::Path;
-use std::sync::{mpsc, Arc};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
 
 use tempfile::tempdir;
 
+use pathway_engine::connectors::data_storage::ReadError;
 use pathway_engine::connectors::snapshot::Event as SnapshotEvent;
 use pathway_engine::connectors::snapshot::{
-    LocalBinarySnapshotReader, LocalBinarySnapshotWriter, ReadSnapshotEvent, WriteSnapshotEvent,
+    compact_events, migrate_local_chunks, LocalBinarySnapshotReader, LocalBinarySnapshotWriter,
+    ObjectStoreSnapshotReader, ObjectStoreSnapshotWriter, ReadSnapshotEvent, WriteSnapshotEvent,
+};
+use pathway_engine::connectors::{
+    Connector, Entry, OffsetKey, OffsetValue, PersistenceMode, SnapshotAccess, SnapshotMode,
 };
-use pathway_engine::connectors::{Connector, Entry, PersistenceMode, SnapshotMode};
 use pathway_engine::engine::{Key, Value};
+use pathway_engine::persistence::config::{
+    MetadataStorageConfig, PersistenceManagerOuterConfig, StreamStorageConfig,
+};
+use pathway_engine::persistence::encryption::{Error as EncryptionError, Keyring};
 use pathway_engine::persistence::frontier::OffsetAntichain;
+use pathway_engine::persistence::inspector::{Error as InspectorError, PersistenceInspector};
+use pathway_engine::persistence::metadata_backends::Error as MetadataBackendError;
+use pathway_engine::persistence::metadata_backends::FilesystemKVStorage;
+use pathway_engine::persistence::object_store::ObjectStoreConfig;
+use pathway_engine::persistence::state::MetadataAccessor;
+use pathway_engine::persistence::tracker::WorkerPersistentStorage;
 use pathway_engine::persistence::PersistentId;
 
 fn get_snapshot_reader_entries(
@@ -127,6 +144,394 @@ fn test_stream_snapshot_io_broken_format() -> eyre::Result<()> {
     Ok(())
 }
 
+fn write_local_snapshot(root_path: &Path, events: &[SnapshotEvent]) -> eyre::Result<PathBuf> {
+    {
+        let mut snapshot_writer = LocalBinarySnapshotWriter::new(root_path, SnapshotMode::Full)?;
+        for event in events {
+            snapshot_writer.write(event)?;
+        }
+    }
+    let chunk = fs::read_dir(root_path)?
+        .next()
+        .expect("snapshot chunk must be written")?;
+    Ok(chunk.path())
+}
+
+#[test]
+fn test_stream_snapshot_truncated_tail_is_repaired() -> eyre::Result<()> {
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = write_local_snapshot(test_storage_path, &[event1.clone(), event2])?;
+
+    // the program stopped in the middle of writing the second entry
+    let full_len = fs::metadata(&chunk_path)?.len();
+    OpenOptions::new()
+        .write(true)
+        .open(&chunk_path)?
+        .set_len(full_len - 3)?;
+
+    assert_eq!(
+        read_persistent_buffer(test_storage_path),
+        vec![event1.clone()]
+    );
+    // the incomplete entry is cut off, so that the new chunks can follow
+    assert!(fs::metadata(&chunk_path)?.len() < full_len - 3);
+    assert_eq!(read_persistent_buffer(test_storage_path), vec![event1]);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_snapshot_checksum_mismatch() -> eyre::Result<()> {
+    let event = SnapshotEvent::Insert(Key::random(), vec![Value::String("test".into())]);
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = write_local_snapshot(test_storage_path, &[event])?;
+
+    let mut contents = fs::read(&chunk_path)?;
+    *contents.last_mut().unwrap() ^= 0xff;
+    fs::write(&chunk_path, contents)?;
+
+    let mut snapshot_reader = LocalBinarySnapshotReader::new(test_storage_path.to_path_buf())?;
+    assert_matches!(
+        snapshot_reader.read(),
+        Err(ReadError::SnapshotChecksumMismatch)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_snapshot_malformed_block_length() -> eyre::Result<()> {
+    let event = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = write_local_snapshot(test_storage_path, &[event])?;
+    // the length of the first block follows the 16 bytes of the chunk header
+    let block_len_range = 16..20;
+
+    // longer than any block a writer produces
+    let mut contents = fs::read(&chunk_path)?;
+    contents[block_len_range.clone()].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&chunk_path, &contents)?;
+    let mut snapshot_reader = LocalBinarySnapshotReader::new(test_storage_path.to_path_buf())?;
+    assert_matches!(snapshot_reader.read(), Err(ReadError::MalformedData));
+
+    // longer than the rest of the chunk, which is cut off as an incomplete block
+    contents[block_len_range].copy_from_slice(&(1_u32 << 29).to_le_bytes());
+    fs::write(&chunk_path, &contents)?;
+    assert_eq!(read_persistent_buffer(test_storage_path), Vec::new());
+    assert_eq!(fs::metadata(&chunk_path)?.len(), 16);
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_snapshot_migration() -> eyre::Result<()> {
+    let mut frontier = OffsetAntichain::new();
+    frontier.advance_offset(OffsetKey::Empty, OffsetValue::Empty);
+    let events = vec![
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]),
+        SnapshotEvent::Upsert(Key::random(), None),
+        SnapshotEvent::AdvanceTime(Timestamp(2), frontier),
+    ];
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = test_storage_path.join("1");
+    let mut legacy_chunk = Vec::new();
+    for event in &events {
+        legacy_chunk.extend(bincode::serialize(event)?);
+    }
+    fs::write(&chunk_path, &legacy_chunk)?;
+
+    assert_eq!(read_persistent_buffer(test_storage_path), events);
+
+    assert_eq!(migrate_local_chunks(test_storage_path, None)?, 1);
+    assert_ne!(fs::read(&chunk_path)?, legacy_chunk);
+    assert_eq!(read_persistent_buffer(test_storage_path), events);
+
+    // the chunks in the current format are left as they are
+    assert_eq!(migrate_local_chunks(test_storage_path, None)?, 0);
+
+    Ok(())
+}
+
+fn read_encrypted_persistent_buffer(
+    chunks_root: &Path,
+    encryption: &Arc<Keyring>,
+) -> Result<Vec<SnapshotEvent>, ReadError> {
+    let mut snapshot_reader = LocalBinarySnapshotReader::new(chunks_root.to_path_buf())?
+        .with_encryption(Some(encryption.clone()));
+    let mut entries = Vec::new();
+    loop {
+        match snapshot_reader.read()? {
+            SnapshotEvent::Finished => break,
+            entry => entries.push(entry),
+        }
+    }
+    Ok(entries)
+}
+
+#[test]
+fn test_encrypted_snapshot_io() -> eyre::Result<()> {
+    let events = vec![
+        SnapshotEvent::Insert(Key::random(), vec![Value::String("secret".into())]),
+        SnapshotEvent::Delete(Key::random(), vec![Value::Int(1)]),
+    ];
+    let encryption = Arc::new(Keyring::new([7; 32], &[]));
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = {
+        let mut snapshot_writer =
+            LocalBinarySnapshotWriter::new(test_storage_path, SnapshotMode::Full)?
+                .with_encryption(Some(encryption.clone()));
+        for event in &events {
+            snapshot_writer.write(event)?;
+        }
+        fs::read_dir(test_storage_path)?
+            .next()
+            .expect("snapshot chunk must be written")?
+            .path()
+    };
+
+    let contents = fs::read(&chunk_path)?;
+    assert!(!contents.windows(6).any(|window| window == b"secret"));
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption)?,
+        events
+    );
+
+    let mut snapshot_reader = LocalBinarySnapshotReader::new(test_storage_path.to_path_buf())?;
+    assert_matches!(
+        snapshot_reader.read(),
+        Err(ReadError::Encryption(EncryptionError::MissingKey))
+    );
+
+    let other_encryption = Arc::new(Keyring::new([8; 32], &[]));
+    assert_matches!(
+        read_encrypted_persistent_buffer(test_storage_path, &other_encryption),
+        Err(ReadError::Encryption(EncryptionError::UnknownKey(_)))
+    );
+
+    Ok(())
+}
+
+fn write_encrypted_local_snapshot(
+    root_path: &Path,
+    events: &[SnapshotEvent],
+    encryption: &Arc<Keyring>,
+) -> eyre::Result<()> {
+    let mut snapshot_writer = LocalBinarySnapshotWriter::new(root_path, SnapshotMode::Full)?
+        .with_encryption(Some(encryption.clone()));
+    for event in events {
+        snapshot_writer.write(event)?;
+    }
+    // the chunks are named after their creation times in milliseconds
+    sleep(Duration::from_millis(2));
+    Ok(())
+}
+
+fn sorted_chunk_paths(root_path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let mut chunk_paths = fs::read_dir(root_path)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    chunk_paths.sort();
+    Ok(chunk_paths)
+}
+
+#[test]
+fn test_encrypted_snapshot_tampering() -> eyre::Result<()> {
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+    let encryption = Arc::new(Keyring::new([7; 32], &[]));
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    write_encrypted_local_snapshot(test_storage_path, &[event1.clone()], &encryption)?;
+    write_encrypted_local_snapshot(test_storage_path, &[event2.clone()], &encryption)?;
+    let [first_chunk_path, second_chunk_path] = &sorted_chunk_paths(test_storage_path)?[..] else {
+        panic!("two snapshot chunks must be written");
+    };
+    let first_chunk = fs::read(first_chunk_path)?;
+    let second_chunk = fs::read(second_chunk_path)?;
+
+    // the blocks are bound to their chunk
+    fs::write(first_chunk_path, &second_chunk)?;
+    assert_matches!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption),
+        Err(ReadError::Encryption(EncryptionError::Decryption))
+    );
+
+    // a chunk, which isn't the last one, can't lose its end
+    fs::write(first_chunk_path, &first_chunk[..first_chunk.len() - 3])?;
+    assert_matches!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption),
+        Err(ReadError::TruncatedSnapshot)
+    );
+    fs::write(first_chunk_path, &first_chunk)?;
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption)?,
+        vec![event1.clone(), event2.clone()]
+    );
+
+    // the last chunk may be left without its end when the program stops,
+    // it is closed when it's read; the end marker is a block with an empty payload,
+    // so it consists of the block header and the authentication tag
+    let end_marker_len = 12 + 16;
+    fs::write(
+        second_chunk_path,
+        &second_chunk[..second_chunk.len() - end_marker_len],
+    )?;
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption)?,
+        vec![event1, event2]
+    );
+    assert_eq!(fs::read(second_chunk_path)?, second_chunk);
+
+    Ok(())
+}
+
+#[test]
+fn test_encryption_key_rotation() -> eyre::Result<()> {
+    let events = vec![
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]),
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]),
+    ];
+    let old_key = [1; 32];
+    let new_key = [2; 32];
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    write_local_snapshot(test_storage_path, &events)?;
+
+    // the unencrypted chunks are rejected until they get encrypted by the migration
+    let old_encryption = Arc::new(Keyring::new(old_key, &[]));
+    assert_matches!(
+        read_encrypted_persistent_buffer(test_storage_path, &old_encryption),
+        Err(ReadError::Encryption(EncryptionError::Unencrypted))
+    );
+    assert_eq!(
+        migrate_local_chunks(test_storage_path, Some(&old_encryption))?,
+        1
+    );
+    assert_matches!(
+        LocalBinarySnapshotReader::new(test_storage_path.to_path_buf())?.read(),
+        Err(ReadError::Encryption(EncryptionError::MissingKey))
+    );
+
+    // after the rotation, the data is re-encrypted with the new key
+    let rotated_encryption = Arc::new(Keyring::new(new_key, &[old_key]));
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &rotated_encryption)?,
+        events
+    );
+    assert_eq!(
+        migrate_local_chunks(test_storage_path, Some(&rotated_encryption))?,
+        1
+    );
+    assert_eq!(
+        migrate_local_chunks(test_storage_path, Some(&rotated_encryption))?,
+        0
+    );
+
+    let new_encryption = Arc::new(Keyring::new(new_key, &[]));
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &new_encryption)?,
+        events
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_snapshot_rejected_with_encryption() -> eyre::Result<()> {
+    let events = vec![SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)])];
+    let encryption = Arc::new(Keyring::new([7; 32], &[]));
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    fs::write(test_storage_path.join("1"), bincode::serialize(&events[0])?)?;
+
+    assert_matches!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption),
+        Err(ReadError::Encryption(EncryptionError::Unencrypted))
+    );
+    assert_eq!(
+        migrate_local_chunks(test_storage_path, Some(&encryption))?,
+        1
+    );
+    assert_eq!(
+        read_encrypted_persistent_buffer(test_storage_path, &encryption)?,
+        events
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_keyring_parse() {
+    let key = "00".repeat(32);
+    let other_key = "ff".repeat(32);
+    let keyring = Keyring::parse(&format!("{key}, {other_key}\n")).unwrap();
+    assert_eq!(
+        keyring
+            .open(b"key", &keyring.seal(b"key", b"test"))
+            .unwrap(),
+        b"test"
+    );
+    // the sealed value is bound to its context
+    assert_matches!(
+        keyring.open(b"other key", &keyring.seal(b"key", b"test")),
+        Err(EncryptionError::Decryption)
+    );
+
+    assert_matches!(Keyring::parse(""), Err(EncryptionError::NoKeys));
+    assert_matches!(Keyring::parse("abc"), Err(EncryptionError::MalformedKey));
+    assert_matches!(
+        Keyring::parse(&"zz".repeat(32)),
+        Err(EncryptionError::MalformedKey)
+    );
+}
+
+#[test]
+fn test_legacy_snapshot_truncated_tail() -> eyre::Result<()> {
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::String("test".into())]);
+
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+    let chunk_path = test_storage_path.join("1");
+    let mut legacy_chunk = bincode::serialize(&event1)?;
+    let complete_len = legacy_chunk.len() as u64;
+    legacy_chunk.extend(bincode::serialize(&event2)?);
+    legacy_chunk.truncate(legacy_chunk.len() - 3);
+    fs::write(&chunk_path, &legacy_chunk)?;
+
+    // the migration doesn't drop the incomplete entry silently
+    assert_matches!(
+        migrate_local_chunks(test_storage_path, None),
+        Err(ReadError::TruncatedSnapshot)
+    );
+
+    // the reader cuts it off like in a chunk in the current format
+    assert_eq!(
+        read_persistent_buffer(test_storage_path),
+        vec![event1.clone()]
+    );
+    assert_eq!(fs::metadata(&chunk_path)?.len(), complete_len);
+    assert_eq!(migrate_local_chunks(test_storage_path, None)?, 1);
+    assert_eq!(read_persistent_buffer(test_storage_path), vec![event1]);
+
+    Ok(())
+}
+
 #[test]
 fn test_stream_empty() -> eyre::Result<()> {
     let test_storage = tempdir()?;
@@ -406,3 +811,588 @@ fn test_stream_snapshot_stateless() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_compact_events() {
+    let key = Key::random();
+    let events = vec![
+        SnapshotEvent::Upsert(key, Some(vec![Value::Int(1)])),
+        SnapshotEvent::Upsert(key, Some(vec![Value::Int(2)])),
+        SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+        SnapshotEvent::Upsert(key, Some(vec![Value::Int(3)])),
+        SnapshotEvent::AdvanceTime(Timestamp(4), OffsetAntichain::new()),
+        SnapshotEvent::Upsert(key, None),
+    ];
+    assert_eq!(
+        compact_events(events.clone(), TotalFrontier::At(Timestamp(4))),
+        Some(vec![
+            SnapshotEvent::Upsert(key, Some(vec![Value::Int(2)])),
+            SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+            SnapshotEvent::Upsert(key, Some(vec![Value::Int(3)])),
+            SnapshotEvent::AdvanceTime(Timestamp(4), OffsetAntichain::new()),
+            SnapshotEvent::Upsert(key, None),
+        ])
+    );
+    assert_eq!(
+        compact_events(events, TotalFrontier::At(Timestamp(2))),
+        None
+    );
+}
+
+#[test]
+fn test_compact_events_keeps_order_of_mixed_events() {
+    let row = |i| vec![Value::Int(i)];
+    let (key1, key2, key3) = (Key(1), Key(2), Key(3));
+    let events = vec![
+        SnapshotEvent::Insert(key1, row(1)),
+        SnapshotEvent::Upsert(key2, Some(row(5))),
+        SnapshotEvent::Upsert(key1, Some(row(2))),
+        SnapshotEvent::Delete(key1, row(1)),
+        SnapshotEvent::Insert(key1, row(3)),
+        SnapshotEvent::Upsert(key3, Some(row(7))),
+        SnapshotEvent::Delete(key1, row(3)),
+        SnapshotEvent::Insert(key2, row(6)),
+        SnapshotEvent::Insert(key3, row(8)),
+        SnapshotEvent::Upsert(key1, Some(row(4))),
+        SnapshotEvent::Delete(key3, row(8)),
+        SnapshotEvent::Upsert(key3, None),
+        SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+    ];
+    assert_eq!(
+        compact_events(events, TotalFrontier::At(Timestamp(4))),
+        Some(vec![
+            SnapshotEvent::Insert(key1, row(1)),
+            SnapshotEvent::Upsert(key1, Some(row(2))),
+            SnapshotEvent::Delete(key1, row(1)),
+            SnapshotEvent::Upsert(key1, Some(row(4))),
+            SnapshotEvent::Upsert(key2, Some(row(5))),
+            SnapshotEvent::Insert(key2, row(6)),
+            // the insertion and deletion between the upserts cancel out
+            SnapshotEvent::Upsert(key3, None),
+            SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+        ])
+    );
+}
+
+#[test]
+fn test_stream_snapshot_compaction() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+    let event3 = SnapshotEvent::Delete(Key::random(), vec![Value::Int(3)]);
+    let event4 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(4)]);
+    let SnapshotEvent::Insert(key1, values1) = event1.clone() else {
+        unreachable!()
+    };
+    {
+        let mut writer = LocalBinarySnapshotWriter::new(test_storage_path, SnapshotMode::Full)?;
+        writer.write(&event1)?;
+        writer.write(&event2)?;
+        writer.write(&SnapshotEvent::Delete(key1, values1))?;
+        writer.write(&SnapshotEvent::AdvanceTime(
+            Timestamp(2),
+            OffsetAntichain::new(),
+        ))?;
+        writer.write(&event3)?;
+        writer.write(&SnapshotEvent::AdvanceTime(
+            Timestamp(10),
+            OffsetAntichain::new(),
+        ))?;
+        writer.write(&event4)?;
+        writer.compact(TotalFrontier::At(Timestamp(5)));
+        // dropping the writer waits for the compaction
+    }
+
+    assert_eq!(std::fs::read_dir(test_storage_path)?.count(), 1);
+    assert_eq!(
+        read_persistent_buffer(test_storage_path),
+        vec![
+            event2,
+            SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+            event3,
+            SnapshotEvent::AdvanceTime(Timestamp(10), OffsetAntichain::new()),
+            event4,
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_restored_from_past_runs() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let tracker = create_persistence_manager(test_storage_path, true);
+    assert!(!tracker.lock().unwrap().is_restored_from_past_runs());
+    let mock_sink_id = tracker.lock().unwrap().register_sink();
+    tracker
+        .lock()
+        .unwrap()
+        .update_sink_finalized_time(mock_sink_id, Some(Timestamp(2)));
+
+    let tracker = create_persistence_manager(test_storage_path, false);
+    assert!(tracker.lock().unwrap().is_restored_from_past_runs());
+    assert!(!tracker
+        .lock()
+        .unwrap()
+        .operator_state_checkpointing_enabled());
+
+    Ok(())
+}
+
+fn create_checkpointing_persistence_manager(
+    fs_path: &Path,
+) -> eyre::Result<Arc<Mutex<WorkerPersistentStorage>>> {
+    Ok(Arc::new(Mutex::new(WorkerPersistentStorage::new(
+        PersistenceManagerOuterConfig::new(
+            Duration::ZERO,
+            MetadataStorageConfig::Filesystem(fs_path.to_path_buf()),
+            StreamStorageConfig::Filesystem(fs_path.to_path_buf()),
+            SnapshotAccess::Full,
+            PersistenceMode::Persisting,
+            true,
+        )
+        .with_operator_state_checkpointing(true)
+        .into_inner(0, 1),
+    )?)))
+}
+
+#[test]
+fn test_operator_state_checkpoint_is_compacted() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let (key1, key2) = (Key::random(), Key::random());
+    {
+        let tracker = WorkerPersistentStorage::new(
+            PersistenceManagerOuterConfig::new(
+                Duration::ZERO,
+                MetadataStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+                StreamStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+                SnapshotAccess::Full,
+                PersistenceMode::Persisting,
+                true,
+            )
+            .with_operator_state_checkpointing(true)
+            .with_snapshot_compaction_interval(Duration::ZERO)
+            .into_inner(0, 1),
+        )?;
+        let tracker = Arc::new(Mutex::new(tracker));
+        let checkpoint = tracker.lock().unwrap().create_checkpoint_writer(1)?;
+        let mock_sink_id = tracker.lock().unwrap().register_sink();
+        {
+            let mut writer = checkpoint.lock().unwrap();
+            writer.write(&SnapshotEvent::Insert(key1, vec![Value::Int(1)]))?;
+            writer.write(&SnapshotEvent::Insert(key2, vec![Value::Int(2)]))?;
+            writer.write(&SnapshotEvent::AdvanceTime(
+                Timestamp(2),
+                OffsetAntichain::new(),
+            ))?;
+            writer.write(&SnapshotEvent::Delete(key1, vec![Value::Int(1)]))?;
+            writer.write(&SnapshotEvent::AdvanceTime(
+                Timestamp(4),
+                OffsetAntichain::new(),
+            ))?;
+        }
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(mock_sink_id, Some(Timestamp(6)));
+        // dropping the writer waits for the compaction
+    }
+
+    // the log of the changes is compacted into the state at the finalized time
+    let checkpoint_path = test_storage_path.join("streams").join("0").join("1");
+    assert_eq!(std::fs::read_dir(&checkpoint_path)?.count(), 1);
+    assert_eq!(
+        read_persistent_buffer(&checkpoint_path),
+        vec![
+            SnapshotEvent::Insert(key2, vec![Value::Int(2)]),
+            SnapshotEvent::AdvanceTime(Timestamp(4), OffsetAntichain::new()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_operator_state_checkpointing_skips_input_replay() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let row = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    {
+        let tracker = create_checkpointing_persistence_manager(test_storage_path)?;
+        assert_matches!(
+            tracker
+                .lock()
+                .unwrap()
+                .effective_snapshot_access(SnapshotAccess::Full),
+            SnapshotAccess::OffsetsOnly
+        );
+        assert_matches!(
+            tracker
+                .lock()
+                .unwrap()
+                .effective_snapshot_access(SnapshotAccess::Record),
+            SnapshotAccess::Record
+        );
+        let input = tracker
+            .lock()
+            .unwrap()
+            .create_snapshot_writer(1, SnapshotMode::OffsetsOnly)?;
+        let operator_state = tracker
+            .lock()
+            .unwrap()
+            .create_snapshot_writer(2, SnapshotMode::Full)?;
+        let mock_sink_id = tracker.lock().unwrap().register_sink();
+        for writer in [&input, &operator_state] {
+            let mut writer = writer.lock().unwrap();
+            writer.write(&row)?;
+            writer.write(&SnapshotEvent::AdvanceTime(
+                Timestamp(2),
+                OffsetAntichain::new(),
+            ))?;
+        }
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(mock_sink_id, Some(Timestamp(4)));
+    }
+
+    // after the restart the input is not replayed, only the operator state is restored
+    let tracker = create_checkpointing_persistence_manager(test_storage_path)?;
+    assert!(tracker.lock().unwrap().is_restored_from_past_runs());
+    for (persistent_id, expected) in [(1, Vec::new()), (2, vec![row])] {
+        let (sender, receiver) = mpsc::channel();
+        Connector::rewind_from_disk_snapshot(
+            persistent_id,
+            &tracker,
+            &sender,
+            PersistenceMode::Persisting,
+        );
+        let events: Vec<SnapshotEvent> = get_entries_in_receiver(receiver)
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::Snapshot(event) => event,
+                _ => unreachable!("only snapshot entries are sent on rewind"),
+            })
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_object_store_snapshot_io() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let store = ObjectStoreConfig::Sqlite(test_storage.path().join("persistence.db")).connect()?;
+
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Delete(Key::random(), vec![Value::String("a".into())]);
+    let event3 = SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new());
+    let event4 = SnapshotEvent::Insert(Key::random(), vec![Value::Bool(true)]);
+    {
+        let mut snapshot_writer =
+            ObjectStoreSnapshotWriter::new(store.clone(), "streams/0/1", SnapshotMode::Full);
+        snapshot_writer.write(&event1)?;
+        snapshot_writer.write(&event2)?;
+        futures::executor::block_on(snapshot_writer.flush())??;
+        snapshot_writer.write(&event3)?;
+        snapshot_writer.write(&event4)?;
+        futures::executor::block_on(snapshot_writer.flush())??;
+    }
+
+    let snapshot_reader = ObjectStoreSnapshotReader::new(store.clone(), "streams/0/1")?;
+    assert_eq!(
+        get_snapshot_reader_entries(Box::new(snapshot_reader)),
+        vec![event1.clone(), event2.clone(), event3.clone(), event4]
+    );
+
+    // the unread tail of the chunk is dropped
+    let mut snapshot_reader = ObjectStoreSnapshotReader::new(store.clone(), "streams/0/1")?;
+    for _ in 0..3 {
+        snapshot_reader.read()?;
+    }
+    snapshot_reader.truncate()?;
+    let snapshot_reader = ObjectStoreSnapshotReader::new(store, "streams/0/1")?;
+    assert_eq!(
+        get_snapshot_reader_entries(Box::new(snapshot_reader)),
+        vec![event1, event2, event3]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_persistence_inspector() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let events = vec![
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]),
+        SnapshotEvent::AdvanceTime(Timestamp(2), OffsetAntichain::new()),
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]),
+        SnapshotEvent::AdvanceTime(Timestamp(4), OffsetAntichain::new()),
+        SnapshotEvent::Insert(Key::random(), vec![Value::Int(3)]),
+    ];
+    std::fs::create_dir_all(test_storage_path.join("streams/0"))?;
+    {
+        let mut snapshot_writer = LocalBinarySnapshotWriter::new(
+            &test_storage_path.join("streams/0/42"),
+            SnapshotMode::Full,
+        )?;
+        for event in &events {
+            snapshot_writer.write(event)?;
+        }
+    }
+
+    {
+        let tracker = create_persistence_manager(test_storage_path, false);
+        let sink_id = tracker.lock().unwrap().register_sink();
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(sink_id, Some(Timestamp(10)));
+    }
+
+    let inspector = PersistenceInspector::new(
+        MetadataStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+        StreamStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+    );
+    assert_eq!(inspector.snapshot_streams()?, vec![(42, 0)]);
+    assert_eq!(inspector.read_events(42, 0)?, events);
+
+    // the state isn't modified while a program uses it, unless it's forced
+    {
+        let _running_worker =
+            MetadataAccessor::new(Box::new(FilesystemKVStorage::new(test_storage_path)?), 0, 1)?;
+        assert_matches!(
+            inspector.truncate_stream(42, 0, Timestamp(3)),
+            Err(InspectorError::StateInUse(workers)) if workers == vec![0]
+        );
+        assert_eq!(inspector.read_events(42, 0)?, events);
+        assert_eq!(
+            inspector
+                .with_force(true)
+                .truncate_stream(42, 0, Timestamp(10))?,
+            5
+        );
+    }
+
+    // the events following the advancement to the time 4 are removed and
+    // the next run is rewound only up to the time 3
+    let inspector = PersistenceInspector::new(
+        MetadataStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+        StreamStorageConfig::Filesystem(test_storage_path.to_path_buf()),
+    );
+    assert_eq!(inspector.truncate_stream(42, 0, Timestamp(3))?, 4);
+    assert_eq!(inspector.read_events(42, 0)?, events[..4]);
+    assert_eq!(
+        inspector.finalized_timestamps()?,
+        BTreeMap::from([(0, TotalFrontier::At(Timestamp(3)))])
+    );
+
+    Ok(())
+}
+
+fn create_rolled_back_persistence_manager(
+    fs_path: &Path,
+    timestamp: Timestamp,
+    generation: u64,
+) -> Result<WorkerPersistentStorage, MetadataBackendError> {
+    // the metadata versions are the times of the runs in milliseconds
+    sleep(Duration::from_millis(2));
+    WorkerPersistentStorage::new(
+        PersistenceManagerOuterConfig::new(
+            Duration::ZERO,
+            MetadataStorageConfig::Filesystem(fs_path.to_path_buf()),
+            StreamStorageConfig::Filesystem(fs_path.to_path_buf()),
+            SnapshotAccess::Full,
+            PersistenceMode::Batch,
+            true,
+        )
+        .with_rollback_to(timestamp, generation)
+        .into_inner(0, 1),
+    )
+}
+
+#[test]
+fn test_rollback_to_finalized_time() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+    let event3 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(3)]);
+    {
+        let tracker = create_persistence_manager(test_storage_path, true);
+        let buffer = tracker
+            .lock()
+            .unwrap()
+            .create_snapshot_writer(42, SnapshotMode::Full)?;
+        let mock_sink_id = tracker.lock().unwrap().register_sink();
+        for (event, time) in [(&event1, 2), (&event2, 5), (&event3, 8)] {
+            let mut buffer = buffer.lock().unwrap();
+            buffer.write(event)?;
+            buffer.write(&SnapshotEvent::AdvanceTime(
+                Timestamp(time),
+                OffsetAntichain::new(),
+            ))?;
+        }
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(mock_sink_id, Some(Timestamp(10)));
+    }
+    assert_eq!(
+        read_persistent_buffer_full(test_storage_path, 42, PersistenceMode::Batch),
+        vec![event1.clone(), event2.clone(), event3.clone()]
+    );
+
+    // the times that haven't been finalized can't be rolled back to
+    assert_matches!(
+        create_rolled_back_persistence_manager(test_storage_path, Timestamp(11), 0).err(),
+        Some(MetadataBackendError::RollbackTimeNotFinalized { .. })
+    );
+
+    let tracker = create_rolled_back_persistence_manager(test_storage_path, Timestamp(5), 0)?;
+    assert_eq!(
+        tracker.last_finalized_timestamp(),
+        TotalFrontier::At(Timestamp(5))
+    );
+    let tracker = Arc::new(Mutex::new(tracker));
+    let (sender, receiver) = mpsc::channel();
+    Connector::rewind_from_disk_snapshot(42, &tracker, &sender, PersistenceMode::Batch);
+    let entries: Vec<Entry> = get_entries_in_receiver(receiver);
+    assert_eq!(entries.len(), 2);
+    drop(tracker);
+
+    // the rollback is persisted and the snapshot tail is truncated
+    sleep(Duration::from_millis(2));
+    assert_eq!(
+        read_persistent_buffer_full(test_storage_path, 42, PersistenceMode::Batch),
+        vec![event1, event2]
+    );
+
+    Ok(())
+}
+
+fn write_finalized_events(
+    tracker: &Arc<Mutex<WorkerPersistentStorage>>,
+    events: &[(&SnapshotEvent, u64)],
+) -> eyre::Result<()> {
+    let buffer = tracker
+        .lock()
+        .unwrap()
+        .create_snapshot_writer(42, SnapshotMode::Full)?;
+    let mock_sink_id = tracker.lock().unwrap().register_sink();
+    for (event, time) in events {
+        let mut buffer = buffer.lock().unwrap();
+        buffer.write(event)?;
+        buffer.write(&SnapshotEvent::AdvanceTime(
+            Timestamp(*time),
+            OffsetAntichain::new(),
+        ))?;
+    }
+    tracker
+        .lock()
+        .unwrap()
+        .update_sink_finalized_time(mock_sink_id, Some(Timestamp(10)));
+    Ok(())
+}
+
+#[test]
+fn test_restart_after_rollback() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    let event1 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(1)]);
+    let event2 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(2)]);
+    let event3 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(3)]);
+    let event4 = SnapshotEvent::Insert(Key::random(), vec![Value::Int(4)]);
+    write_finalized_events(
+        &create_persistence_manager(test_storage_path, true),
+        &[(&event1, 2), (&event2, 5), (&event3, 8)],
+    )?;
+
+    // the run following the rollback processes new data up to the time 10
+    {
+        let tracker = create_rolled_back_persistence_manager(test_storage_path, Timestamp(5), 0)?;
+        assert_eq!(
+            tracker.last_finalized_timestamp(),
+            TotalFrontier::At(Timestamp(5))
+        );
+        write_finalized_events(&Arc::new(Mutex::new(tracker)), &[(&event4, 7)])?;
+    }
+
+    // a restart with the same setting doesn't roll back again
+    {
+        let tracker = create_rolled_back_persistence_manager(test_storage_path, Timestamp(5), 0)?;
+        assert_eq!(
+            tracker.last_finalized_timestamp(),
+            TotalFrontier::At(Timestamp(10))
+        );
+    }
+    sleep(Duration::from_millis(2));
+    assert_eq!(
+        read_persistent_buffer_full(test_storage_path, 42, PersistenceMode::Batch),
+        vec![event1.clone(), event2.clone(), event4]
+    );
+
+    // a new generation rolls back to the same time once more
+    {
+        let tracker = create_rolled_back_persistence_manager(test_storage_path, Timestamp(5), 1)?;
+        assert_eq!(
+            tracker.last_finalized_timestamp(),
+            TotalFrontier::At(Timestamp(5))
+        );
+    }
+    sleep(Duration::from_millis(2));
+    assert_eq!(
+        read_persistent_buffer_full(test_storage_path, 42, PersistenceMode::Batch),
+        vec![event1, event2]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rollback_before_compaction_is_rejected() -> eyre::Result<()> {
+    let test_storage = tempdir()?;
+    let test_storage_path = test_storage.path();
+
+    {
+        let tracker = create_persistence_manager(test_storage_path, true);
+        let mock_sink_id = tracker.lock().unwrap().register_sink();
+        tracker
+            .lock()
+            .unwrap()
+            .update_sink_finalized_time(mock_sink_id, Some(Timestamp(10)));
+    }
+
+    let metadata_storage = MetadataStorageConfig::Filesystem(test_storage_path.to_path_buf());
+    sleep(Duration::from_millis(2));
+    {
+        let mut metadata = MetadataAccessor::new(metadata_storage.create_backend(None)?, 0, 1)?;
+        metadata.accept_finalized_timestamp(TotalFrontier::At(Timestamp(10)));
+        metadata.accept_compaction_threshold(TotalFrontier::At(Timestamp(6)));
+        metadata.save_current_state()?;
+    }
+
+    assert_matches!(
+        create_rolled_back_persistence_manager(test_storage_path, Timestamp(4), 0).err(),
+        Some(MetadataBackendError::RollbackTimeCompacted { .. })
+    );
+    let tracker = create_rolled_back_persistence_manager(test_storage_path, Timestamp(6), 0)?;
+    assert_eq!(
+        tracker.last_finalized_timestamp(),
+        TotalFrontier::At(Timestamp(6))
+    );
+
+    Ok(())
+}
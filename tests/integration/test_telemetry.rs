@@ -0,0 +1,74 @@
+// Copyright © 2024 Pathway
+
+use pathway_engine::engine::telemetry::{SpanGuard, TimestampSpans};
+use pathway_engine::engine::{Timestamp, TotalFrontier};
+
+#[test]
+fn test_span_attributes_are_not_computed_when_traces_are_not_exported() {
+    let mut span = SpanGuard::start("test", || panic!("attributes computed"));
+    span.set_attribute(opentelemetry::KeyValue::new("key", "value"));
+}
+
+#[test]
+fn test_timestamp_spans_end_when_output() {
+    let mut spans = TimestampSpans::new(0);
+    spans.update(
+        TotalFrontier::At(Timestamp(2)),
+        TotalFrontier::At(Timestamp(0)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![]);
+
+    spans.update(
+        TotalFrontier::At(Timestamp(4)),
+        TotalFrontier::At(Timestamp(2)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(2)]);
+
+    spans.update(
+        TotalFrontier::At(Timestamp(6)),
+        TotalFrontier::At(Timestamp(2)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(2), Timestamp(4)]);
+
+    // the range closed at 6 is output only when the output reaches 6
+    spans.update(
+        TotalFrontier::At(Timestamp(6)),
+        TotalFrontier::At(Timestamp(5)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(4)]);
+
+    spans.update(TotalFrontier::Done, TotalFrontier::At(Timestamp(6)));
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(6)]);
+
+    spans.update(TotalFrontier::Done, TotalFrontier::Done);
+    assert_eq!(spans.pending_timestamps(), vec![]);
+}
+
+#[test]
+fn test_timestamp_spans_cover_timestamps_closed_between_updates() {
+    let mut spans = TimestampSpans::new(0);
+    spans.update(
+        TotalFrontier::At(Timestamp(2)),
+        TotalFrontier::At(Timestamp(0)),
+    );
+
+    // the times 2 and 4 were closed between the updates and are traced together
+    spans.update(
+        TotalFrontier::At(Timestamp(6)),
+        TotalFrontier::At(Timestamp(0)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(2)]);
+
+    // the time 4 hasn't reached the outputs yet
+    spans.update(
+        TotalFrontier::At(Timestamp(6)),
+        TotalFrontier::At(Timestamp(4)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![Timestamp(2)]);
+
+    spans.update(
+        TotalFrontier::At(Timestamp(6)),
+        TotalFrontier::At(Timestamp(6)),
+    );
+    assert_eq!(spans.pending_timestamps(), vec![]);
+}
@@ -0,0 +1,155 @@
+// Copyright © 2024 Pathway
+
+use super::helpers::PanicErrorReporter;
+use super::operator_test_utils::run_test;
+
+use std::rc::Rc;
+
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::AsCollection;
+use timely::dataflow::operators::Map;
+
+use pathway_engine::engine::dataflow::operators::temporal_join::{
+    AsofDirection, JoinSide, TemporalJoin,
+};
+use pathway_engine::engine::{Key, Timestamp, Value};
+
+const JOIN_KEY: Key = Key(1);
+
+type JoinInput = (Key, (Value, JoinSide, Key, Value));
+type JoinOutput = ((Key, Value), Timestamp, i32);
+
+fn row_values(key: u64) -> Value {
+    Value::from(format!("row {key}").as_str())
+}
+
+fn join_input(
+    side: JoinSide,
+    time: i64,
+    key: u64,
+    timestamp: u64,
+    diff: i32,
+) -> (JoinInput, Timestamp, i32) {
+    (
+        (
+            JOIN_KEY,
+            (Value::Int(time), side, Key(key.into()), row_values(key)),
+        ),
+        Timestamp(timestamp),
+        diff,
+    )
+}
+
+fn join_output(left: u64, right: Option<u64>, timestamp: u64, diff: i32) -> JoinOutput {
+    let left_key = Key(left.into());
+    let (right_id, right_pointer, right_values) = match right {
+        Some(right) => {
+            let right_key = Key(right.into());
+            (
+                Value::from(right_key),
+                Value::Pointer(right_key),
+                row_values(right),
+            )
+        }
+        None => (Value::None, Value::None, Value::None),
+    };
+    let key = Key::for_values(&[Value::from(left_key), right_id]).with_shard_of(JOIN_KEY);
+    let values = Value::from(
+        [
+            Value::Pointer(left_key),
+            row_values(left),
+            right_pointer,
+            right_values,
+        ]
+        .as_slice(),
+    );
+    ((key, values), Timestamp(timestamp), diff)
+}
+
+#[test]
+fn test_asof_join_backward() {
+    let input = vec![
+        vec![
+            join_input(JoinSide::Right, 1, 10, 0, 1),
+            join_input(JoinSide::Right, 5, 11, 0, 1),
+            join_input(JoinSide::Left, 3, 20, 0, 1),
+            join_input(JoinSide::Left, 6, 21, 0, 1),
+            join_input(JoinSide::Left, 0, 22, 0, 1),
+        ],
+        vec![join_input(JoinSide::Right, 2, 12, 2, 1)],
+        vec![join_input(JoinSide::Right, 5, 11, 4, -1)],
+    ];
+    let expected = vec![
+        vec![
+            join_output(20, Some(10), 0, 1),
+            join_output(21, Some(11), 0, 1),
+            join_output(22, None, 0, 1),
+        ],
+        vec![
+            join_output(20, Some(10), 2, -1),
+            join_output(20, Some(12), 2, 1),
+        ],
+        vec![
+            join_output(21, Some(11), 4, -1),
+            join_output(21, Some(12), 4, 1),
+        ],
+    ];
+    run_test(input, expected, |collection| {
+        collection
+            .inner
+            .map(|(data, time, diff)| (data, time, isize::try_from(diff).unwrap()))
+            .as_collection()
+            .asof_join(
+                AsofDirection::Backward,
+                None,
+                Rc::new(PanicErrorReporter::default()),
+            )
+            .inner
+            .map(|(data, time, diff)| (data, time, i32::try_from(diff).unwrap()))
+            .as_collection()
+            .arrange_by_key()
+    });
+}
+
+#[test]
+fn test_interval_join_forgets_rows_after_cutoff() {
+    let input = vec![
+        vec![
+            join_input(JoinSide::Left, 5, 20, 0, 1),
+            join_input(JoinSide::Right, 4, 10, 0, 1),
+            join_input(JoinSide::Right, 7, 11, 0, 1),
+        ],
+        vec![
+            join_input(JoinSide::Right, 6, 12, 2, 1),
+            // older than the largest time by more than the cutoff
+            join_input(JoinSide::Left, 2, 21, 2, 1),
+            join_input(JoinSide::Right, 20, 13, 2, 1),
+        ],
+        vec![
+            // the row was forgotten, so its results stay in the output
+            join_input(JoinSide::Left, 5, 20, 4, -1),
+            join_input(JoinSide::Left, 19, 22, 4, 1),
+        ],
+    ];
+    let expected = vec![
+        vec![join_output(20, Some(10), 0, 1)],
+        vec![join_output(20, Some(12), 2, 1)],
+        vec![join_output(22, Some(13), 4, 1)],
+    ];
+    run_test(input, expected, |collection| {
+        collection
+            .inner
+            .map(|(data, time, diff)| (data, time, isize::try_from(diff).unwrap()))
+            .as_collection()
+            .interval_join(
+                Value::Int(-1),
+                Value::Int(1),
+                Some(Value::Int(2)),
+                Rc::new(PanicErrorReporter::default()),
+            )
+            .inner
+            .map(|(data, time, diff)| (data, time, i32::try_from(diff).unwrap()))
+            .as_collection()
+            .arrange_by_key()
+    });
+}
@@ -0,0 +1,206 @@
+// Copyright © 2024 Pathway
+
+use differential_dataflow::consolidation::consolidate_updates;
+use differential_dataflow::AsCollection;
+use timely::dataflow::operators::capture::{Capture, Extract};
+use timely::dataflow::operators::ToStream;
+
+use pathway_engine::engine::dataflow::operators::top_k::TopK;
+
+type Update = ((i32, i32), u64, isize);
+type OutputUpdate = ((i32, Vec<i32>), u64, isize);
+
+fn run_top_k(k: usize, input: Vec<Update>) -> Vec<OutputUpdate> {
+    let captured = timely::example(move |scope| {
+        input
+            .to_stream(scope)
+            .as_collection()
+            .top_k(k)
+            .inner
+            .capture()
+    });
+    let mut output: Vec<_> = captured
+        .extract()
+        .into_iter()
+        .flat_map(|(_time, updates)| updates)
+        .collect();
+    consolidate_updates(&mut output);
+    output.sort_by(|(lhs_data, lhs_time, _), (rhs_data, rhs_time, _)| {
+        (lhs_time, lhs_data).cmp(&(rhs_time, rhs_data))
+    });
+    output
+}
+
+#[test]
+fn test_top_k_insertions() {
+    let output = run_top_k(
+        2,
+        vec![
+            ((1, 5), 0, 1),
+            ((1, 3), 0, 1),
+            ((2, 4), 0, 1),
+            ((1, 8), 1, 1),
+            ((1, 1), 2, 1),
+        ],
+    );
+    assert_eq!(
+        output,
+        vec![
+            ((1, vec![3, 5]), 0, 1),
+            ((2, vec![4]), 0, 1),
+            ((1, vec![1, 3]), 2, 1),
+            ((1, vec![3, 5]), 2, -1),
+        ]
+    );
+}
+
+#[test]
+fn test_top_k_retraction_falls_back_to_arrangement() {
+    let output = run_top_k(
+        2,
+        vec![
+            ((1, 5), 0, 1),
+            ((1, 3), 0, 1),
+            ((1, 8), 0, 1),
+            ((1, 1), 0, 1),
+            // removes a stored element, 5 has to be read back from the arrangement
+            ((1, 1), 1, -1),
+            // removes both stored elements, only 8 remains
+            ((1, 3), 2, -1),
+            ((1, 5), 2, -1),
+            ((1, 2), 3, 1),
+            // the group becomes empty
+            ((1, 2), 4, -1),
+            ((1, 8), 4, -1),
+        ],
+    );
+    assert_eq!(
+        output,
+        vec![
+            ((1, vec![1, 3]), 0, 1),
+            ((1, vec![1, 3]), 1, -1),
+            ((1, vec![3, 5]), 1, 1),
+            ((1, vec![3, 5]), 2, -1),
+            ((1, vec![8]), 2, 1),
+            ((1, vec![2, 8]), 3, 1),
+            ((1, vec![8]), 3, -1),
+            ((1, vec![2, 8]), 4, -1),
+        ]
+    );
+}
+
+#[test]
+fn test_top_k_duplicates_and_retractions_outside_top() {
+    let output = run_top_k(
+        2,
+        vec![
+            ((1, 4), 0, 3),
+            ((1, 9), 0, 1),
+            // not in the top, the result doesn't change
+            ((1, 9), 1, -1),
+            // one of the copies is retracted, another one takes its place
+            ((1, 4), 2, -1),
+            ((1, 4), 3, -1),
+            ((1, 7), 3, 1),
+        ],
+    );
+    assert_eq!(
+        output,
+        vec![
+            ((1, vec![4, 4]), 0, 1),
+            ((1, vec![4, 4]), 3, -1),
+            ((1, vec![4, 7]), 3, 1),
+        ]
+    );
+}
+
+type RowUpdate = ((i32, i32), u64, isize);
+
+fn run_top_k_rows(k: usize, offset: usize, input: Vec<Update>) -> Vec<RowUpdate> {
+    let captured = timely::example(move |scope| {
+        input
+            .to_stream(scope)
+            .as_collection()
+            .top_k_rows(k, offset)
+            .inner
+            .capture()
+    });
+    // not consolidated, rows that stay in the top must not be emitted at all
+    let mut output: Vec<_> = captured
+        .extract()
+        .into_iter()
+        .flat_map(|(_time, updates)| updates)
+        .collect();
+    output.sort_by(|(lhs_data, lhs_time, _), (rhs_data, rhs_time, _)| {
+        (lhs_time, lhs_data).cmp(&(rhs_time, rhs_data))
+    });
+    output
+}
+
+#[test]
+fn test_top_k_rows_emits_only_changed_rows() {
+    let output = run_top_k_rows(
+        3,
+        0,
+        vec![
+            ((1, 5), 0, 1),
+            ((1, 3), 0, 1),
+            ((1, 8), 0, 1),
+            ((1, 9), 0, 1),
+            // 1 enters, 8 leaves, 3 and 5 stay
+            ((1, 1), 1, 1),
+            // 9 is read back from the arrangement
+            ((1, 3), 2, -1),
+            ((1, 8), 2, -1),
+            // the group becomes empty
+            ((1, 1), 3, -1),
+            ((1, 5), 3, -1),
+            ((1, 9), 3, -1),
+        ],
+    );
+    assert_eq!(
+        output,
+        vec![
+            ((1, 3), 0, 1),
+            ((1, 5), 0, 1),
+            ((1, 8), 0, 1),
+            ((1, 1), 1, 1),
+            ((1, 8), 1, -1),
+            ((1, 3), 2, -1),
+            ((1, 9), 2, 1),
+            ((1, 1), 3, -1),
+            ((1, 5), 3, -1),
+            ((1, 9), 3, -1),
+        ]
+    );
+}
+
+#[test]
+fn test_top_k_rows_with_offset() {
+    let output = run_top_k_rows(
+        2,
+        1,
+        vec![
+            ((1, 5), 0, 1),
+            ((1, 3), 0, 1),
+            ((2, 7), 0, 1),
+            ((1, 8), 0, 1),
+            ((1, 9), 0, 1),
+            // 1 becomes skipped, 3 moves into the range, 8 leaves it
+            ((1, 1), 1, 1),
+            // a duplicate of the skipped value pushes 3 out of the range
+            ((1, 1), 2, 1),
+        ],
+    );
+    assert_eq!(
+        output,
+        vec![
+            ((1, 5), 0, 1),
+            ((1, 8), 0, 1),
+            ((1, 3), 1, 1),
+            ((1, 8), 1, -1),
+            ((1, 1), 2, 1),
+            ((1, 5), 2, -1),
+        ]
+    );
+}
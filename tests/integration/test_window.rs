@@ -0,0 +1,322 @@
+// Copyright © 2024 Pathway
+
+use super::helpers::PanicErrorReporter;
+use super::operator_test_utils::run_test;
+
+use std::rc::Rc;
+
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::AsCollection;
+use timely::dataflow::operators::Map;
+
+use pathway_engine::engine::dataflow::operators::session_window::SessionWindows;
+use pathway_engine::engine::window::{shift, shift_back, time_difference, within_session_gap};
+use pathway_engine::engine::{DateTimeNaive, Duration, Key, Timestamp, Value, Window};
+
+#[test]
+fn test_tumbling_window_assignment() {
+    let window = Window::Tumbling {
+        duration: Value::Int(10),
+        origin: Some(Value::Int(3)),
+    };
+    assert_eq!(
+        window.assign(&Value::Int(25)).unwrap(),
+        vec![(Value::Int(23), Value::Int(33))]
+    );
+    assert_eq!(
+        window.assign(&Value::Int(-8)).unwrap(),
+        vec![(Value::Int(-17), Value::Int(-7))]
+    );
+}
+
+#[test]
+fn test_sliding_window_assignment() {
+    let window = Window::Sliding {
+        hop: Value::Int(2),
+        duration: Value::Int(5),
+        origin: None,
+    };
+    assert_eq!(
+        window.assign(&Value::Int(7)).unwrap(),
+        vec![
+            (Value::Int(4), Value::Int(9)),
+            (Value::Int(6), Value::Int(11))
+        ]
+    );
+    let window = Window::Sliding {
+        hop: Value::Float(0.5.into()),
+        duration: Value::Float(1.0.into()),
+        origin: None,
+    };
+    assert_eq!(
+        window.assign(&Value::Float(1.25.into())).unwrap(),
+        vec![
+            (Value::Float(0.5.into()), Value::Float(1.5.into())),
+            (Value::Float(1.0.into()), Value::Float(2.0.into()))
+        ]
+    );
+}
+
+#[test]
+fn test_datetime_window_assignment() {
+    let window = Window::Tumbling {
+        duration: Value::Duration(Duration::new_with_unit(3600, "s").unwrap()),
+        origin: None,
+    };
+    let time = DateTimeNaive::strptime("2024-01-01T10:30:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    let start = DateTimeNaive::strptime("2024-01-01T10:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    let end = DateTimeNaive::strptime("2024-01-01T11:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    assert_eq!(
+        window.assign(&Value::DateTimeNaive(time)).unwrap(),
+        vec![(Value::DateTimeNaive(start), Value::DateTimeNaive(end))]
+    );
+}
+
+#[test]
+fn test_window_assignment_overflow() {
+    let window = Window::Tumbling {
+        duration: Value::Int(10),
+        origin: None,
+    };
+    assert!(window.assign(&Value::Int(i64::MIN)).is_err());
+    let window = Window::Tumbling {
+        duration: Value::Int(10),
+        origin: Some(Value::Int(-1)),
+    };
+    assert!(window.assign(&Value::Int(i64::MAX)).is_err());
+    let window = Window::Sliding {
+        hop: Value::Int(2),
+        duration: Value::Int(5),
+        origin: None,
+    };
+    assert_eq!(
+        window.assign(&Value::Int(i64::MIN + 1)).unwrap(),
+        vec![(Value::Int(i64::MIN), Value::Int(i64::MIN + 5))]
+    );
+}
+
+#[test]
+fn test_window_assignment_rejects_too_many_windows() {
+    let window = Window::Sliding {
+        hop: Value::Int(1),
+        duration: Value::Int(i64::MAX),
+        origin: None,
+    };
+    assert!(window.assign(&Value::Int(0)).is_err());
+    let window = Window::Sliding {
+        hop: Value::Float(1e-300.into()),
+        duration: Value::Float(1.0.into()),
+        origin: None,
+    };
+    assert!(window.assign(&Value::Float(0.5.into())).is_err());
+    let window = Window::Sliding {
+        hop: Value::Duration(Duration::new_with_unit(1, "ns").unwrap()),
+        duration: Value::Duration(Duration::new_with_unit(3600, "s").unwrap()),
+        origin: None,
+    };
+    let time = DateTimeNaive::strptime("2024-01-01T10:30:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    assert!(window.assign(&Value::DateTimeNaive(time)).is_err());
+    let window = Window::Sliding {
+        hop: Value::Int(1),
+        duration: Value::Int(1000),
+        origin: None,
+    };
+    assert_eq!(window.assign(&Value::Int(0)).unwrap().len(), 1000);
+}
+
+#[test]
+fn test_window_assignment_rejects_non_finite_times() {
+    let window = Window::Sliding {
+        hop: Value::Float(0.5.into()),
+        duration: Value::Float(1.0.into()),
+        origin: None,
+    };
+    for time in [f64::NEG_INFINITY, f64::INFINITY, f64::NAN] {
+        assert!(window.assign(&Value::Float(time.into())).is_err());
+    }
+    let window = Window::Tumbling {
+        duration: Value::Float(1.0.into()),
+        origin: Some(Value::Float(f64::NEG_INFINITY.into())),
+    };
+    assert!(window.assign(&Value::Float(0.0.into())).is_err());
+    let window = Window::Tumbling {
+        duration: Value::Float(1e-300.into()),
+        origin: None,
+    };
+    assert!(window.assign(&Value::Float(1e300.into())).is_err());
+}
+
+#[test]
+fn test_window_parameters_mismatch() {
+    let window = Window::Tumbling {
+        duration: Value::Int(0),
+        origin: None,
+    };
+    assert!(window.assign(&Value::Int(1)).is_err());
+    let window = Window::Tumbling {
+        duration: Value::Int(10),
+        origin: None,
+    };
+    assert!(window.assign(&Value::from("abc")).is_err());
+}
+
+#[test]
+fn test_session_gap() {
+    assert!(within_session_gap(&Value::Int(1), &Value::Int(4), &Value::Int(3)).unwrap());
+    assert!(!within_session_gap(&Value::Int(1), &Value::Int(5), &Value::Int(3)).unwrap());
+    assert_eq!(
+        shift(&Value::Float(1.5.into()), &Value::Int(2)).unwrap(),
+        Value::Float(3.5.into())
+    );
+    assert!(shift(&Value::Int(1), &Value::Float(2.0.into())).is_err());
+}
+
+#[test]
+fn test_time_difference() {
+    assert_eq!(
+        shift_back(&Value::Int(10), &Value::Int(3)).unwrap(),
+        Value::Int(7)
+    );
+    assert_eq!(
+        time_difference(&Value::Int(10), &Value::Int(3)).unwrap(),
+        Value::Int(7)
+    );
+    assert_eq!(
+        time_difference(&Value::Float(2.5.into()), &Value::Int(1)).unwrap(),
+        Value::Float(1.5.into())
+    );
+    assert!(time_difference(&Value::Int(1), &Value::from("abc")).is_err());
+}
+
+type SessionInput = (Key, (Value, Key, Value));
+type SessionOutput = ((Key, (Value, Value)), Timestamp, i32);
+
+fn session_input(
+    instance: u64,
+    time: i64,
+    key: u64,
+    timestamp: u64,
+) -> (SessionInput, Timestamp, i32) {
+    (
+        (
+            Key(instance.into()),
+            (
+                Value::Int(time),
+                Key(key.into()),
+                Value::from(format!("row {key}").as_str()),
+            ),
+        ),
+        Timestamp(timestamp),
+        1,
+    )
+}
+
+fn session_output(
+    time: i64,
+    key: u64,
+    start: i64,
+    end: i64,
+    timestamp: u64,
+    diff: i32,
+) -> SessionOutput {
+    let key = Key(key.into());
+    let output_key = Key::for_values(&[Value::from(key), Value::Int(start)]).with_shard_of(key);
+    let values = Value::Tuple(
+        [
+            Value::from(format!("row {}", key.0).as_str()),
+            Value::Int(start),
+            Value::Int(end),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    (
+        (output_key, (values, Value::Int(time))),
+        Timestamp(timestamp),
+        diff,
+    )
+}
+
+fn run_session_test(
+    input: Vec<Vec<(SessionInput, Timestamp, i32)>>,
+    expected: Vec<Vec<SessionOutput>>,
+    cutoff: Option<Value>,
+) {
+    run_test(input, expected, move |collection| {
+        collection
+            .inner
+            .map(|(data, time, diff)| (data, time, isize::try_from(diff).unwrap()))
+            .as_collection()
+            .session_windows(
+                Value::Int(2),
+                cutoff.clone(),
+                true,
+                Rc::new(PanicErrorReporter::default()),
+            )
+            .inner
+            .map(|(data, time, diff)| (data, time, i32::try_from(diff).unwrap()))
+            .as_collection()
+            .arrange_by_key()
+    });
+}
+
+#[test]
+fn test_session_windows_merge_only_affected_sessions() {
+    let input = vec![
+        vec![
+            session_input(1, 1, 1, 0),
+            session_input(1, 2, 2, 0),
+            session_input(1, 10, 3, 0),
+        ],
+        vec![session_input(1, 5, 4, 2)],
+        vec![session_input(1, 4, 5, 4)],
+    ];
+    let expected = vec![
+        vec![
+            session_output(1, 1, 1, 2, 0, 1),
+            session_output(2, 2, 1, 2, 0, 1),
+            session_output(10, 3, 10, 10, 0, 1),
+        ],
+        vec![session_output(5, 4, 5, 5, 2, 1)],
+        vec![
+            session_output(1, 1, 1, 2, 4, -1),
+            session_output(2, 2, 1, 2, 4, -1),
+            session_output(5, 4, 5, 5, 4, -1),
+            session_output(1, 1, 1, 5, 4, 1),
+            session_output(2, 2, 1, 5, 4, 1),
+            session_output(4, 5, 1, 5, 4, 1),
+            session_output(5, 4, 1, 5, 4, 1),
+        ],
+    ];
+    run_session_test(input, expected, None);
+}
+
+#[test]
+fn test_session_windows_cutoff_from_session_end_per_instance() {
+    let input = vec![
+        vec![session_input(1, 1, 1, 0), session_input(1, 10, 2, 0)],
+        vec![
+            // joins the session ending at 10, so it is not late
+            session_input(1, 9, 3, 2),
+            // the session it would start ends more than the cutoff before 10
+            session_input(1, 4, 4, 2),
+            // the largest time of the other instance doesn't make it late
+            session_input(2, 0, 5, 2),
+        ],
+    ];
+    let expected = vec![
+        vec![
+            session_output(1, 1, 1, 1, 0, 1),
+            session_output(10, 2, 10, 10, 0, 1),
+        ],
+        // the session of the row at 1 is closed and retracted at the neu time
+        vec![session_output(1, 1, 1, 1, 1, -1)],
+        vec![
+            session_output(10, 2, 10, 10, 2, -1),
+            session_output(9, 3, 9, 10, 2, 1),
+            session_output(10, 2, 9, 10, 2, 1),
+            session_output(0, 5, 0, 0, 2, 1),
+        ],
+    ];
+    run_session_test(input, expected, Some(Value::Int(3)));
+}